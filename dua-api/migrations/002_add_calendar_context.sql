@@ -0,0 +1,6 @@
+-- Seasonal/calendar context for duas, used by the `calendar` filter.
+-- Values are lowercase occasion slugs such as 'ramadan', 'laylat_al_qadr',
+-- 'hajj', 'arafah', 'eid' and 'jumuah'.
+ALTER TABLE dua_context ADD COLUMN IF NOT EXISTS calendar_context TEXT[];
+
+CREATE INDEX IF NOT EXISTS idx_dua_context_calendar ON dua_context USING GIN(calendar_context);
@@ -0,0 +1,6 @@
+-- Distinguishes supplications (dua) from remembrance (dhikr), ruqyah
+-- recitations and salawat upon the Prophet. Used by the `dua_type` filter.
+ALTER TABLE duas ADD COLUMN IF NOT EXISTS dua_type VARCHAR(20) NOT NULL DEFAULT 'dua'
+    CHECK (dua_type IN ('dua', 'dhikr', 'ruqyah', 'salawat'));
+
+CREATE INDEX IF NOT EXISTS idx_duas_dua_type ON duas(dua_type);
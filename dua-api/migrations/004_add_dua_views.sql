@@ -0,0 +1,8 @@
+-- View counts behind popularity_score. Rows are only written when
+-- POPULARITY__TRACK_VIEWS is on; popularity_score is recomputed from them
+-- periodically, so duas that were never viewed keep their seeded score.
+CREATE TABLE IF NOT EXISTS dua_view_counts (
+    dua_id UUID PRIMARY KEY REFERENCES duas(id) ON DELETE CASCADE,
+    view_count BIGINT NOT NULL DEFAULT 0,
+    last_viewed_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
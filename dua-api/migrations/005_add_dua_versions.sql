@@ -0,0 +1,44 @@
+-- Content history for editorial review. Whenever a dua's text or metadata
+-- changes, the row as it was is kept here and `duas.version` is bumped, so
+-- every version but the current one can be read back. Popularity updates
+-- don't touch content and don't create versions.
+CREATE TABLE IF NOT EXISTS dua_versions (
+    dua_id UUID NOT NULL REFERENCES duas(id) ON DELETE CASCADE,
+    version INTEGER NOT NULL,
+    title VARCHAR(500) NOT NULL,
+    arabic_text TEXT NOT NULL,
+    transliteration TEXT,
+    translation TEXT NOT NULL,
+    slug VARCHAR(500) NOT NULL,
+    status VARCHAR(20) NOT NULL,
+    dua_type VARCHAR(20) NOT NULL,
+    -- When this version was written, and when the next one replaced it
+    updated_at TIMESTAMPTZ,
+    superseded_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (dua_id, version)
+);
+
+CREATE OR REPLACE FUNCTION record_dua_version() RETURNS TRIGGER AS $$
+BEGIN
+    IF (NEW.title, NEW.arabic_text, NEW.transliteration, NEW.translation, NEW.slug, NEW.status, NEW.dua_type)
+        IS NOT DISTINCT FROM
+       (OLD.title, OLD.arabic_text, OLD.transliteration, OLD.translation, OLD.slug, OLD.status, OLD.dua_type)
+    THEN
+        RETURN NEW;
+    END IF;
+
+    INSERT INTO dua_versions (dua_id, version, title, arabic_text, transliteration, translation, slug, status, dua_type, updated_at)
+    VALUES (OLD.id, COALESCE(OLD.version, 1), OLD.title, OLD.arabic_text, OLD.transliteration, OLD.translation,
+            OLD.slug, COALESCE(OLD.status, 'active'), OLD.dua_type, OLD.updated_at)
+    ON CONFLICT (dua_id, version) DO NOTHING;
+
+    NEW.version := COALESCE(OLD.version, 1) + 1;
+    NEW.updated_at := NOW();
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS duas_record_version ON duas;
+CREATE TRIGGER duas_record_version
+    BEFORE UPDATE ON duas
+    FOR EACH ROW EXECUTE FUNCTION record_dua_version();
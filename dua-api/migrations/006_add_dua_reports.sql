@@ -0,0 +1,17 @@
+-- Content corrections flagged by readers: a wrong word in the Arabic text,
+-- a bad reference and so on. Editors triage open reports through the admin
+-- endpoint and mark them resolved or dismissed.
+CREATE TABLE IF NOT EXISTS dua_reports (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    dua_id UUID NOT NULL REFERENCES duas(id) ON DELETE CASCADE,
+    reporter VARCHAR(200),
+    field VARCHAR(30) NOT NULL
+        CHECK (field IN ('title', 'arabic_text', 'transliteration', 'translation', 'sources', 'context', 'other')),
+    message TEXT NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'open'
+        CHECK (status IN ('open', 'resolved', 'dismissed')),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_dua_reports_status ON dua_reports(status, created_at);
@@ -0,0 +1,84 @@
+-- Running totals behind /v1/stats, so reading them doesn't count whole
+-- tables. Triggers keep the counters in step with inserts, deletes and
+-- status changes; each one updates the single row in the writing
+-- transaction, so concurrent writers queue on its lock instead of losing
+-- increments. most_popular_category and recent_additions depend on joins
+-- and the clock, so the service recounts them, and corrects any drift, on
+-- a schedule.
+CREATE TABLE IF NOT EXISTS dua_stats (
+    id BOOLEAN PRIMARY KEY DEFAULT TRUE CHECK (id),
+    total_duas BIGINT NOT NULL DEFAULT 0,
+    verified_duas BIGINT NOT NULL DEFAULT 0,
+    total_categories BIGINT NOT NULL DEFAULT 0,
+    total_tags BIGINT NOT NULL DEFAULT 0,
+    total_bundles BIGINT NOT NULL DEFAULT 0,
+    most_popular_category VARCHAR(200),
+    recent_additions BIGINT NOT NULL DEFAULT 0,
+    refreshed_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+INSERT INTO dua_stats (id) VALUES (TRUE) ON CONFLICT (id) DO NOTHING;
+
+-- Published duas; 'verified' is the legacy spelling of 'active'
+CREATE OR REPLACE FUNCTION dua_is_published(status VARCHAR) RETURNS INTEGER AS $$
+    SELECT CASE WHEN status IN ('active', 'verified') THEN 1 ELSE 0 END;
+$$ LANGUAGE sql IMMUTABLE;
+
+CREATE OR REPLACE FUNCTION count_dua_stats() RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP = 'INSERT' THEN
+        UPDATE dua_stats
+        SET total_duas = total_duas + 1,
+            verified_duas = verified_duas + dua_is_published(NEW.status),
+            recent_additions = recent_additions + 1;
+    ELSIF TG_OP = 'DELETE' THEN
+        UPDATE dua_stats
+        SET total_duas = total_duas - 1,
+            verified_duas = verified_duas - dua_is_published(OLD.status);
+    ELSIF dua_is_published(NEW.status) <> dua_is_published(OLD.status) THEN
+        UPDATE dua_stats
+        SET verified_duas = verified_duas + dua_is_published(NEW.status) - dua_is_published(OLD.status);
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS duas_count_stats ON duas;
+CREATE TRIGGER duas_count_stats
+    AFTER INSERT OR DELETE OR UPDATE OF status ON duas
+    FOR EACH ROW EXECUTE FUNCTION count_dua_stats();
+
+-- Categories, tags and bundles only need a row count; the column to bump
+-- is passed as the trigger argument
+CREATE OR REPLACE FUNCTION count_dua_label_stats() RETURNS TRIGGER AS $$
+DECLARE
+    delta INTEGER := CASE WHEN TG_OP = 'INSERT' THEN 1 ELSE -1 END;
+BEGIN
+    EXECUTE format('UPDATE dua_stats SET %1$I = %1$I + $1', TG_ARGV[0]) USING delta;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS dua_categories_count_stats ON dua_categories;
+CREATE TRIGGER dua_categories_count_stats
+    AFTER INSERT OR DELETE ON dua_categories
+    FOR EACH ROW EXECUTE FUNCTION count_dua_label_stats('total_categories');
+
+DROP TRIGGER IF EXISTS dua_tags_count_stats ON dua_tags;
+CREATE TRIGGER dua_tags_count_stats
+    AFTER INSERT OR DELETE ON dua_tags
+    FOR EACH ROW EXECUTE FUNCTION count_dua_label_stats('total_tags');
+
+DROP TRIGGER IF EXISTS dua_bundles_count_stats ON dua_bundles;
+CREATE TRIGGER dua_bundles_count_stats
+    AFTER INSERT OR DELETE ON dua_bundles
+    FOR EACH ROW EXECUTE FUNCTION count_dua_label_stats('total_bundles');
+
+-- Start from the current totals; the service's first refresh fills in the
+-- rest
+UPDATE dua_stats SET
+    total_duas = (SELECT COUNT(*) FROM duas),
+    verified_duas = (SELECT COALESCE(SUM(dua_is_published(status)), 0) FROM duas),
+    total_categories = (SELECT COUNT(*) FROM dua_categories),
+    total_tags = (SELECT COUNT(*) FROM dua_tags),
+    total_bundles = (SELECT COUNT(*) FROM dua_bundles);
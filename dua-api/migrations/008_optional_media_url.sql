@@ -0,0 +1,7 @@
+-- Media stored on this server has a file_path and no url, so url is only
+-- required when there is no file_path
+ALTER TABLE dua_media ALTER COLUMN url DROP NOT NULL;
+
+ALTER TABLE dua_media DROP CONSTRAINT IF EXISTS dua_media_location_check;
+ALTER TABLE dua_media ADD CONSTRAINT dua_media_location_check
+    CHECK (url IS NOT NULL OR file_path IS NOT NULL);
@@ -1,21 +1,35 @@
 use axum::{
+    body::StreamBody,
     extract::{Path, Query},
+    http::{header, HeaderMap, HeaderName, StatusCode},
+    response::{IntoResponse, Response},
     Extension, Json,
 };
 use shared::{
-    cache::Cache,
+    admin::AdminToken,
+    cache::{Cache, CachedJson},
+    config::{MediaConfig, PaginationConfig, PopularityConfig, SearchConfig},
     database::Database,
     error::{ApiError, ApiResult},
+    middleware::ClientIp,
 };
-use std::collections::HashMap;
-use tracing::info;
+use std::{
+    collections::{HashMap, HashSet},
+    time::Duration,
+};
+use tokio::io::AsyncReadExt;
+use tracing::{info, warn};
 use uuid::Uuid;
 use validator::Validate;
 
 use crate::{
+    jobs::{SearchJob, SearchJobs},
     models::*,
+    occasions::{find_occasion, occasion_on_date},
+    outbound,
     repository::DuaRepository,
     services::DuaService,
+    suggestions::{suggest_labels, DuaText, Label},
 };
 
 // ============= DUA ENDPOINTS =============
@@ -23,59 +37,282 @@ use crate::{
 pub async fn list_duas(
     Extension(database): Extension<Database>,
     Extension(cache): Extension<Cache>,
-    Query(params): Query<DuaQueryParams>,
-) -> ApiResult<Json<serde_json::Value>> {
+    Extension(pagination): Extension<PaginationConfig>,
+    Extension(admin): Extension<AdminToken>,
+    headers: HeaderMap,
+    Query(mut params): Query<DuaQueryParams>,
+) -> ApiResult<Negotiated<CachedJson>> {
     info!("Listing duas with params: {:?}", params);
+    restrict_visibility(&mut params, &admin, &headers);
+    params.apply_pagination(&pagination);
+    params.check_include().map_err(ApiError::validation)?;
     
     let repository = DuaRepository::new(database);
     let service = DuaService::new(repository, cache);
+    params.lang = service.resolve_lang(params.lang.take(), accept_language(&headers)).await?;
     
-    let response = service.list_duas_with_filters(params).await?;
-    Ok(Json(serde_json::to_value(response)?))
+    Ok(negotiated(service.list_duas_json(params).await?))
+}
+
+pub async fn export_duas_ndjson(
+    Extension(database): Extension<Database>,
+    Query(params): Query<HashMap<String, String>>,
+) -> ApiResult<impl IntoResponse> {
+    let include = params.get("include").cloned();
+    info!("Exporting duas as NDJSON (include: {:?})", include);
+    validate_include(include.as_deref(), strict_include(&params)).map_err(ApiError::validation)?;
+    
+    let repository = DuaRepository::new(database);
+    let lines = futures::stream::unfold(repository.export_ndjson(include), |mut rx| async move {
+        rx.recv().await.map(|line| (line, rx))
+    });
+    
+    Ok((
+        [(header::CONTENT_TYPE, "application/x-ndjson")],
+        StreamBody::new(lines),
+    ))
+}
+
+/// `status` and `include_drafts` expose unpublished duas, so only admin
+/// requests may set them
+fn restrict_visibility(params: &mut DuaQueryParams, admin: &AdminToken, headers: &HeaderMap) {
+    if !admin.authorizes(headers) {
+        params.clear_editor_overrides();
+    }
 }
 
+/// How a single dua is looked up
+enum DuaLookup {
+    Id(Uuid),
+    Slug(String),
+}
+
+/// `/v1/duas/:id` takes either; anything that parses as a UUID is treated
+/// as an id. Use the `by-id` and `by-slug` routes to be explicit.
 pub async fn get_dua(
+    database: Extension<Database>,
+    cache: Extension<Cache>,
+    popularity: Extension<PopularityConfig>,
+    client_ip: Option<Extension<ClientIp>>,
+    headers: HeaderMap,
+    Path(id_or_slug): Path<String>,
+    params: Query<HashMap<String, String>>,
+) -> ApiResult<Negotiated<Json<serde_json::Value>>> {
+    let lookup = match Uuid::parse_str(&id_or_slug) {
+        Ok(id) => DuaLookup::Id(id),
+        Err(_) => DuaLookup::Slug(id_or_slug),
+    };
+    serve_dua(database, cache, popularity, client_ip, headers, lookup, params).await
+}
+
+pub async fn get_dua_by_id(
+    database: Extension<Database>,
+    cache: Extension<Cache>,
+    popularity: Extension<PopularityConfig>,
+    client_ip: Option<Extension<ClientIp>>,
+    headers: HeaderMap,
+    Path(id): Path<Uuid>,
+    params: Query<HashMap<String, String>>,
+) -> ApiResult<Negotiated<Json<serde_json::Value>>> {
+    serve_dua(database, cache, popularity, client_ip, headers, DuaLookup::Id(id), params).await
+}
+
+pub async fn get_dua_by_slug(
+    database: Extension<Database>,
+    cache: Extension<Cache>,
+    popularity: Extension<PopularityConfig>,
+    client_ip: Option<Extension<ClientIp>>,
+    headers: HeaderMap,
+    Path(slug): Path<String>,
+    params: Query<HashMap<String, String>>,
+) -> ApiResult<Negotiated<Json<serde_json::Value>>> {
+    serve_dua(database, cache, popularity, client_ip, headers, DuaLookup::Slug(slug), params).await
+}
+
+async fn serve_dua(
     Extension(database): Extension<Database>,
     Extension(cache): Extension<Cache>,
-    Path(id_or_slug): Path<String>,
+    Extension(popularity): Extension<PopularityConfig>,
+    client_ip: Option<Extension<ClientIp>>,
+    headers: HeaderMap,
+    lookup: DuaLookup,
     Query(params): Query<HashMap<String, String>>,
-) -> ApiResult<Json<serde_json::Value>> {
-    info!("Getting dua: {}", id_or_slug);
+) -> ApiResult<Negotiated<Json<serde_json::Value>>> {
+    validate_include(params.get("include").map(String::as_str), strict_include(&params))
+        .map_err(ApiError::validation)?;
     
     let repository = DuaRepository::new(database);
     let service = DuaService::new(repository, cache);
     
-    // Check if it's a UUID or slug
-    let dua = if let Ok(id) = Uuid::parse_str(&id_or_slug) {
-        service.get_dua_by_id(id, params.get("include").cloned()).await?
-    } else {
-        service.get_dua_by_slug(&id_or_slug, params.get("include").cloned()).await?
+    let (dua, requested) = match lookup {
+        DuaLookup::Id(id) => {
+            info!("Getting dua by id: {}", id);
+            (service.get_dua_by_id(id, params.get("include").cloned()).await?, id.to_string())
+        }
+        DuaLookup::Slug(slug) => {
+            info!("Getting dua by slug: {}", slug);
+            (service.get_dua_by_slug(&slug, params.get("include").cloned()).await?, slug)
+        }
     };
     
     match dua {
-        Some(dua) => Ok(Json(serde_json::to_value(dua)?)),
-        None => Err(ApiError::NotFound(format!("Dua {} not found", id_or_slug))),
+        Some(mut dua) => {
+            let lang = service.resolve_lang(params.get("lang").cloned(), accept_language(&headers)).await?;
+            if let Some(ref lang) = lang {
+                service.localize(&mut dua, lang).await?;
+            }
+            if popularity.track_views {
+                let debounce = Duration::from_secs(popularity.view_debounce_secs);
+                let client = client_ip.map(|Extension(ClientIp(ip))| ip);
+                service.record_view(dua.dua.id, client.as_deref().unwrap_or("unknown"), debounce).await;
+            }
+            Ok(negotiated(Json(serde_json::to_value(dua)?)))
+        }
+        None => Err(ApiError::NotFound(format!("Dua {} not found", requested))),
     }
 }
 
+/// A response whose body depends on the language and credentials sent, so
+/// shared caches keep one copy per variant
+type Negotiated<T> = ([(HeaderName, &'static str); 1], T);
+
+fn negotiated<T>(body: T) -> Negotiated<T> {
+    ([(header::VARY, "Accept-Language, Authorization, X-API-Key")], body)
+}
+
+fn accept_language(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(header::ACCEPT_LANGUAGE)
+        .and_then(|value| value.to_str().ok())
+}
+
+/// `strict_include` for handlers that read raw query maps; on unless `false`
+fn strict_include(params: &HashMap<String, String>) -> bool {
+    params
+        .get("strict_include")
+        .is_none_or(|value| !matches!(value.trim(), "false" | "0"))
+}
+
 pub async fn get_random_dua(
     Extension(database): Extension<Database>,
     Extension(cache): Extension<Cache>,
-    Query(params): Query<DuaQueryParams>,
-) -> ApiResult<Json<serde_json::Value>> {
+    Extension(admin): Extension<AdminToken>,
+    headers: HeaderMap,
+    Query(mut params): Query<DuaQueryParams>,
+) -> ApiResult<Negotiated<Json<serde_json::Value>>> {
     info!("Getting random dua with filters");
+    restrict_visibility(&mut params, &admin, &headers);
+    params.check_include().map_err(ApiError::validation)?;
     
     let repository = DuaRepository::new(database);
     let service = DuaService::new(repository, cache);
+    params.lang = service.resolve_lang(params.lang.take(), accept_language(&headers)).await?;
     
     let dua = service.get_random_dua(params).await?;
     
     match dua {
-        Some(dua) => Ok(Json(serde_json::to_value(dua)?)),
+        Some(dua) => Ok(negotiated(Json(serde_json::to_value(dua)?))),
         None => Err(ApiError::NotFound("No duas found matching criteria".to_string())),
     }
 }
 
+const MAX_BULK_IMPORT_SIZE: usize = 500;
+
+pub async fn bulk_import_duas(
+    Extension(database): Extension<Database>,
+    Extension(cache): Extension<Cache>,
+    Json(entries): Json<Vec<serde_json::Value>>,
+) -> ApiResult<Json<serde_json::Value>> {
+    info!("Bulk importing {} duas", entries.len());
+    
+    if entries.is_empty() || entries.len() > MAX_BULK_IMPORT_SIZE {
+        return Err(ApiError::validation(format!(
+            "Bulk import accepts between 1 and {} duas",
+            MAX_BULK_IMPORT_SIZE
+        )));
+    }
+    
+    let repository = DuaRepository::new(database);
+    let service = DuaService::new(repository, cache);
+    
+    let report = service.bulk_import(entries).await?;
+    Ok(Json(serde_json::to_value(report)?))
+}
+
+// ============= VERSION HISTORY ENDPOINTS =============
+
+pub async fn get_dua_versions(
+    Extension(database): Extension<Database>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Json<serde_json::Value>> {
+    info!("Getting version history for dua: {}", id);
+    
+    let repository = DuaRepository::new(database);
+    let dua = repository.require_dua(id).await?;
+    let versions = repository.list_dua_versions(id).await?;
+    
+    Ok(Json(serde_json::json!({
+        "dua_id": id,
+        "current_version": dua.version,
+        "versions": versions,
+        "total": versions.len()
+    })))
+}
+
+pub async fn get_dua_version(
+    Extension(database): Extension<Database>,
+    Path((id, version)): Path<(Uuid, i32)>,
+) -> ApiResult<Json<DuaVersion>> {
+    info!("Getting version {} of dua: {}", version, id);
+    
+    let repository = DuaRepository::new(database);
+    repository.require_dua(id).await?;
+    let version_row = repository.get_dua_version(id, version).await?;
+    
+    version_row
+        .map(Json)
+        .ok_or_else(|| ApiError::not_found(format!("Version {} of dua {}", version, id)))
+}
+
+// ============= CONTENT REPORT ENDPOINTS =============
+
+pub async fn report_dua(
+    Extension(database): Extension<Database>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<CreateReportRequest>,
+) -> ApiResult<(StatusCode, Json<DuaReport>)> {
+    info!("Reporting {} issue on dua: {}", request.field, id);
+    
+    let field = request.check().map_err(ApiError::validation)?;
+    
+    let repository = DuaRepository::new(database);
+    repository.require_dua(id).await?;
+    
+    let report = repository.create_report(id, field, &request).await?;
+    Ok((StatusCode::CREATED, Json(report)))
+}
+
+/// Admin triage list; mounted behind the admin token
+pub async fn list_reports(
+    Extension(database): Extension<Database>,
+    Query(params): Query<ReportQueryParams>,
+) -> ApiResult<Json<serde_json::Value>> {
+    let status = match params.status {
+        Some(ref status) => status.parse().map_err(ApiError::validation)?,
+        None => ReportStatus::Open,
+    };
+    info!("Listing {} reports", status.as_str());
+    
+    let repository = DuaRepository::new(database);
+    let reports = repository.list_reports(status).await?;
+    
+    Ok(Json(serde_json::json!({
+        "status": status.as_str(),
+        "reports": reports,
+        "total": reports.len()
+    })))
+}
+
 // ============= TRANSLATION ENDPOINTS =============
 
 pub async fn get_dua_translations(
@@ -85,6 +322,7 @@ pub async fn get_dua_translations(
     info!("Getting translations for dua: {}", id);
     
     let repository = DuaRepository::new(database);
+    repository.require_dua(id).await?;
     let translations = repository.get_dua_translations(id).await?;
     
     Ok(Json(serde_json::json!({
@@ -94,6 +332,98 @@ pub async fn get_dua_translations(
     })))
 }
 
+/// Adds or replaces a batch of a dua's translations, keyed by language, and
+/// returns all of its translations
+pub async fn upsert_dua_translations(
+    Extension(database): Extension<Database>,
+    Extension(cache): Extension<Cache>,
+    Path(id): Path<Uuid>,
+    Json(translations): Json<Vec<UpsertTranslationRequest>>,
+) -> ApiResult<Json<serde_json::Value>> {
+    info!("Upserting {} translations for dua: {}", translations.len(), id);
+    
+    check_translation_batch(&translations).map_err(ApiError::validation)?;
+    
+    let repository = DuaRepository::new(database);
+    if repository.get_any_dua_by_id(id).await?.is_none() {
+        return Err(ApiError::not_found(format!("Dua {}", id)));
+    }
+    
+    let service = DuaService::new(repository, cache);
+    let translations = service.upsert_translations(id, &translations).await?;
+    Ok(Json(serde_json::json!({
+        "dua_id": id,
+        "translations": translations,
+        "total": translations.len()
+    })))
+}
+
+/// The dua's own text next to each requested translation, for editors
+/// checking them against each other
+pub async fn compare_translations(
+    Extension(database): Extension<Database>,
+    Path(id): Path<Uuid>,
+    Query(params): Query<CompareTranslationsParams>,
+) -> ApiResult<Json<TranslationComparison>> {
+    let languages = params.languages().map_err(ApiError::validation)?;
+    info!("Comparing {:?} translations for dua: {}", languages, id);
+    
+    let repository = DuaRepository::new(database);
+    let dua = repository.require_dua(id).await?;
+    let translations = repository.get_dua_translations(id).await?;
+    
+    Ok(Json(TranslationComparison::new(dua, &translations, &languages)))
+}
+
+pub async fn get_dua_translation(
+    Extension(database): Extension<Database>,
+    Path((id, lang)): Path<(Uuid, String)>,
+) -> ApiResult<Json<LocalizedTranslation>> {
+    info!("Getting {} translation for dua: {}", lang, id);
+    
+    let repository = DuaRepository::new(database);
+    let translation = repository.get_dua_translation(id, &lang).await?;
+    
+    translation
+        .map(Json)
+        .ok_or_else(|| ApiError::not_found(format!("Translation '{}' for dua {}", lang, id)))
+}
+
+/// Suggests tags and categories for a dua from its text without assigning them
+pub async fn suggest_dua_tags(
+    Extension(database): Extension<Database>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Json<TagSuggestionResponse>> {
+    info!("Suggesting tags for dua: {}", id);
+    
+    let repository = DuaRepository::new(database);
+    let dua = repository.require_dua(id).await?;
+    
+    let (translations, tags, categories, assigned_tags, assigned_categories) = tokio::try_join!(
+        repository.get_dua_translations(id),
+        repository.list_tags(),
+        repository.list_categories(),
+        repository.get_dua_tags(id),
+        repository.get_dua_categories(id),
+    )?;
+    
+    let mut body = vec![dua.translation.as_str()];
+    body.extend(dua.transliteration.as_deref());
+    body.extend(translations.iter().filter_map(|t| t.translation.as_deref()));
+    let text = DuaText { title: &dua.title, body };
+    
+    let tag_labels: Vec<Label> = tags.iter().map(|t| Label { slug: &t.slug, name: &t.name }).collect();
+    let category_labels: Vec<Label> = categories.iter().map(|c| Label { slug: &c.slug, name: &c.name }).collect();
+    let assigned_tags: HashSet<&str> = assigned_tags.iter().map(|t| t.slug.as_str()).collect();
+    let assigned_categories: HashSet<&str> = assigned_categories.iter().map(|c| c.slug.as_str()).collect();
+    
+    Ok(Json(TagSuggestionResponse {
+        dua_id: id,
+        tags: suggest_labels(&text, &tag_labels, &assigned_tags),
+        categories: suggest_labels(&text, &category_labels, &assigned_categories),
+    }))
+}
+
 pub async fn list_all_translations(
     Extension(database): Extension<Database>,
 ) -> ApiResult<Json<serde_json::Value>> {
@@ -139,15 +469,22 @@ pub async fn get_category(
 pub async fn get_category_duas(
     Extension(database): Extension<Database>,
     Extension(cache): Extension<Cache>,
+    Extension(pagination): Extension<PaginationConfig>,
+    Extension(admin): Extension<AdminToken>,
+    headers: HeaderMap,
     Path(slug): Path<String>,
     Query(mut params): Query<DuaQueryParams>,
 ) -> ApiResult<Json<serde_json::Value>> {
     info!("Getting duas for category: {}", slug);
+    restrict_visibility(&mut params, &admin, &headers);
     
     // Set the category filter
     params.category = Some(slug.clone());
+    params.apply_pagination(&pagination);
+    params.check_include().map_err(ApiError::validation)?;
     
     let repository = DuaRepository::new(database);
+    repository.require_category(&slug).await?;
     let service = DuaService::new(repository, cache);
     
     let response = service.list_duas_with_filters(params).await?;
@@ -172,15 +509,22 @@ pub async fn list_tags(
 pub async fn get_tag_duas(
     Extension(database): Extension<Database>,
     Extension(cache): Extension<Cache>,
+    Extension(pagination): Extension<PaginationConfig>,
+    Extension(admin): Extension<AdminToken>,
+    headers: HeaderMap,
     Path(slug): Path<String>,
     Query(mut params): Query<DuaQueryParams>,
 ) -> ApiResult<Json<serde_json::Value>> {
     info!("Getting duas for tag: {}", slug);
+    restrict_visibility(&mut params, &admin, &headers);
     
     // Set the tag filter
     params.tag = Some(slug.clone());
+    params.apply_pagination(&pagination);
+    params.check_include().map_err(ApiError::validation)?;
     
     let repository = DuaRepository::new(database);
+    repository.require_tag(&slug).await?;
     let service = DuaService::new(repository, cache);
     
     let response = service.list_duas_with_filters(params).await?;
@@ -229,20 +573,59 @@ pub async fn get_bundle_items(
     Ok(Json(serde_json::to_value(response)?))
 }
 
+// ============= OCCASION ENDPOINTS =============
+
+/// Duas for a Hijri occasion such as `ramadan` or `arafah`. The slug
+/// `current` resolves to today's occasion, if any.
+pub async fn get_occasion_duas(
+    Extension(database): Extension<Database>,
+    Extension(cache): Extension<Cache>,
+    Extension(pagination): Extension<PaginationConfig>,
+    Extension(admin): Extension<AdminToken>,
+    headers: HeaderMap,
+    Path(slug): Path<String>,
+    Query(mut params): Query<DuaQueryParams>,
+) -> ApiResult<Json<serde_json::Value>> {
+    info!("Getting duas for occasion: {}", slug);
+    restrict_visibility(&mut params, &admin, &headers);
+    
+    let occasion = if slug == "current" {
+        occasion_on_date(chrono::Utc::now().date_naive())
+            .ok_or_else(|| ApiError::not_found("Occasion for today"))?
+    } else {
+        find_occasion(&slug).ok_or_else(|| ApiError::not_found(format!("Occasion {}", slug)))?
+    };
+    
+    params.calendar = Some(occasion.slug.to_string());
+    params.apply_pagination(&pagination);
+    params.check_include().map_err(ApiError::validation)?;
+    
+    let repository = DuaRepository::new(database);
+    let service = DuaService::new(repository, cache);
+    
+    let list = service.list_duas_with_filters(params).await?;
+    Ok(Json(serde_json::to_value(OccasionDuasResponse { occasion, list })?))
+}
+
 // ============= SOURCE ENDPOINTS =============
 
 pub async fn list_sources(
     Extension(database): Extension<Database>,
-    Query(params): Query<SourceQueryParams>,
+    Extension(pagination): Extension<PaginationConfig>,
+    Query(mut params): Query<SourceQueryParams>,
 ) -> ApiResult<Json<serde_json::Value>> {
     info!("Listing sources");
+    params.apply_pagination(&pagination);
     
     let repository = DuaRepository::new(database);
     let sources = repository.list_sources(&params).await?;
+    let (page, per_page, _) = params.page_window();
     
     Ok(Json(serde_json::json!({
         "sources": sources,
-        "total": sources.len()
+        "total": sources.len(),
+        "page": page,
+        "per_page": per_page
     })))
 }
 
@@ -266,6 +649,7 @@ pub async fn get_source_duas(
     info!("Getting duas for source: {}", id);
     
     let repository = DuaRepository::new(database);
+    repository.require_source(id).await?;
     let duas = repository.get_duas_by_source(id).await?;
     
     Ok(Json(serde_json::json!({
@@ -284,6 +668,7 @@ pub async fn get_dua_media(
     info!("Getting media for dua: {}", id);
     
     let repository = DuaRepository::new(database);
+    repository.require_dua(id).await?;
     let media = repository.get_dua_media(id).await?;
     
     Ok(Json(serde_json::json!({
@@ -293,18 +678,111 @@ pub async fn get_dua_media(
     })))
 }
 
+pub async fn create_dua_media(
+    Extension(database): Extension<Database>,
+    Extension(media): Extension<MediaConfig>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<CreateMediaRequest>,
+) -> ApiResult<Json<serde_json::Value>> {
+    info!("Creating {} media for dua: {}", request.media_type, id);
+    
+    request.check().map_err(ApiError::validation)?;
+    
+    let repository = DuaRepository::new(database);
+    if repository.get_any_dua_by_id(id).await?.is_none() {
+        return Err(ApiError::not_found(format!("Dua {}", id)));
+    }
+    
+    let duration = match request.duration {
+        Some(duration) => Some(duration),
+        None => probe_wav_duration(&request, &media).await,
+    };
+    
+    let media = repository.create_dua_media(id, &request, duration).await?;
+    Ok(Json(serde_json::to_value(media)?))
+}
+
+const MEDIA_PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Reads a WAV's duration from its header: from `file_path` when a media
+/// directory is configured, otherwise from `url`
+async fn probe_wav_duration(request: &CreateMediaRequest, media: &MediaConfig) -> Option<i32> {
+    if let (Some(root), Some(file_path)) = (&media.local_root, &request.file_path) {
+        return probe_local_wav_duration(root, file_path).await;
+    }
+    probe_remote_wav_duration(request.url.as_deref()?).await
+}
+
+/// Reads the header of a `.wav` file under `root`. Paths that resolve
+/// outside it, including through symlinks, are not opened.
+async fn probe_local_wav_duration(root: &str, file_path: &str) -> Option<i32> {
+    if !file_path.to_ascii_lowercase().ends_with(".wav") {
+        return None;
+    }
+    
+    let root = tokio::fs::canonicalize(root).await.ok()?;
+    let path = tokio::fs::canonicalize(root.join(file_path.trim_start_matches(['/', '\\'])))
+        .await
+        .ok()?;
+    if !path.starts_with(&root) {
+        warn!("Not probing {}: it resolves outside the media directory", file_path);
+        return None;
+    }
+    
+    let mut header = [0u8; WAV_HEADER_LEN];
+    let mut file = tokio::fs::File::open(&path).await.ok()?;
+    file.read_exact(&mut header).await.ok()?;
+    wav_header_duration(&header)
+}
+
+/// Fetches just the header of a `.wav` URL and reads its duration. The host
+/// must resolve to a public address; see `outbound`.
+async fn probe_remote_wav_duration(url: &str) -> Option<i32> {
+    let path = url.split(['?', '#']).next()?;
+    if !path.to_ascii_lowercase().ends_with(".wav") {
+        return None;
+    }
+    
+    let client = match outbound::public_client(url, "url", MEDIA_PROBE_TIMEOUT).await {
+        Ok(client) => client,
+        Err(e) => {
+            warn!("Not probing media: {}", e);
+            return None;
+        }
+    };
+    let mut response = client
+        .get(url)
+        .header(header::RANGE, format!("bytes=0-{}", WAV_HEADER_LEN - 1))
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status)
+        .ok()?;
+    
+    // Servers that ignore Range send the whole file; stop after the header
+    let mut bytes = Vec::with_capacity(WAV_HEADER_LEN);
+    while bytes.len() < WAV_HEADER_LEN {
+        bytes.extend_from_slice(&response.chunk().await.ok()??);
+    }
+    wav_header_duration(&bytes)
+}
+
 pub async fn search_media(
     Extension(database): Extension<Database>,
-    Query(params): Query<MediaQueryParams>,
+    Extension(pagination): Extension<PaginationConfig>,
+    Query(mut params): Query<MediaQueryParams>,
 ) -> ApiResult<Json<serde_json::Value>> {
     info!("Searching media");
+    params.apply_pagination(&pagination);
     
     let repository = DuaRepository::new(database);
     let media = repository.search_media(&params).await?;
+    let (page, per_page, _) = params.page_window();
     
     Ok(Json(serde_json::json!({
         "media": media,
-        "total": media.len()
+        "total": media.len(),
+        "page": page,
+        "per_page": per_page
     })))
 }
 
@@ -313,19 +791,32 @@ pub async fn search_media(
 pub async fn keyword_search(
     Extension(database): Extension<Database>,
     Extension(cache): Extension<Cache>,
+    Extension(search): Extension<SearchConfig>,
     Query(params): Query<HashMap<String, String>>,
 ) -> ApiResult<Json<serde_json::Value>> {
-    let query = params.get("q").cloned().unwrap_or_default();
-    let limit = params.get("limit")
-        .and_then(|l| l.parse::<u32>().ok())
-        .unwrap_or(20);
+    let Some(query) = search_query(params.get("q").map(String::as_str), search.empty_query)
+        .map_err(ApiError::validation)?
+    else {
+        return Ok(Json(serde_json::json!({
+            "query": "",
+            "results": [],
+            "total": 0
+        })));
+    };
+    let limit = search_limit(params.get("limit").map(String::as_str), 20, &search)
+        .map_err(ApiError::validation)?;
+    let verified_only = params.get("verified_only")
+        .map(|v| matches!(v.to_lowercase().as_str(), "true" | "1" | "yes"))
+        .unwrap_or(false);
+    let filters = SearchFilters::parse(params.get("min_authenticity").map(String::as_str), verified_only)
+        .map_err(ApiError::validation)?;
     
     info!("Keyword search for: {}", query);
     
     let repository = DuaRepository::new(database);
     let service = DuaService::new(repository, cache);
     
-    let results = service.keyword_search(&query, limit).await?;
+    let results = service.keyword_search(&query, limit, &filters).await?;
     
     Ok(Json(serde_json::json!({
         "query": query,
@@ -337,38 +828,102 @@ pub async fn keyword_search(
 pub async fn semantic_search(
     Extension(database): Extension<Database>,
     Extension(cache): Extension<Cache>,
-    Json(request): Json<SemanticSearchRequest>,
-) -> ApiResult<Json<serde_json::Value>> {
+    Extension(jobs): Extension<SearchJobs>,
+    Extension(search): Extension<SearchConfig>,
+    Json(mut request): Json<SemanticSearchRequest>,
+) -> ApiResult<Response> {
     info!("Semantic search for: {}", request.query);
     
     request.validate()
         .map_err(|e| ApiError::Validation(format!("Validation failed: {}", e)))?;
+    request.limit = Some(search.limit(request.limit, 20));
     
     let repository = DuaRepository::new(database);
     let service = DuaService::new(repository, cache);
     
+    if let Some(callback_url) = request.callback_url.take() {
+        let query = request.query.clone();
+        let job = jobs
+            .enqueue(query, Some(callback_url), async move { service.semantic_search(request).await })
+            .await?;
+        info!("Queued semantic search job {}", job.id);
+        return Ok((StatusCode::ACCEPTED, Json(job)).into_response());
+    }
+    
     let results = service.semantic_search(request).await?;
-    Ok(Json(serde_json::to_value(results)?))
+    Ok(Json(serde_json::to_value(results)?).into_response())
+}
+
+pub async fn get_search_job(
+    Extension(jobs): Extension<SearchJobs>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Json<SearchJob>> {
+    jobs.get(id)
+        .await
+        .map(Json)
+        .ok_or_else(|| ApiError::not_found("Search job"))
 }
 
+pub async fn unified_search(
+    Extension(database): Extension<Database>,
+    Extension(search): Extension<SearchConfig>,
+    Query(params): Query<HashMap<String, String>>,
+) -> ApiResult<Json<UnifiedSearchResponse>> {
+    let query = params.get("q").map(|q| q.trim().to_string()).unwrap_or_default();
+    if query.is_empty() {
+        return Err(ApiError::validation("Query parameter 'q' is required"));
+    }
+    let limit = search_limit(params.get("limit").map(String::as_str), 10, &search)
+        .map_err(ApiError::validation)?;
+    
+    info!("Unified search for: {}", query);
+    
+    let repository = DuaRepository::new(database);
+    let results = repository.search_all(&query, limit).await?;
+    
+    Ok(Json(results))
+}
+
+/// Prefix suggestions. Prefixes shorter than the configured minimum get an
+/// empty list without a lookup; responses tell clients how long to cache
+/// them (`Cache-Control`) and how long to wait between keystrokes
+/// (`X-Debounce-Ms`).
 pub async fn autocomplete(
     Extension(database): Extension<Database>,
+    Extension(cache): Extension<Cache>,
+    Extension(search): Extension<SearchConfig>,
     Query(params): Query<HashMap<String, String>>,
-) -> ApiResult<Json<serde_json::Value>> {
-    let query = params.get("q").cloned().unwrap_or_default();
-    let limit = params.get("limit")
-        .and_then(|l| l.parse::<u32>().ok())
-        .unwrap_or(10);
+) -> ApiResult<impl IntoResponse> {
+    let headers = [
+        (header::CACHE_CONTROL, format!("public, max-age={}", search.autocomplete_cache_ttl_secs)),
+        (HeaderName::from_static("x-debounce-ms"), search.autocomplete_debounce_ms.to_string()),
+    ];
+    
+    let query = search_query(params.get("q").map(String::as_str), search.empty_query)
+        .map_err(ApiError::validation)?
+        .unwrap_or_default();
+    if !meets_min_length(&query, search.autocomplete_min_length) {
+        return Ok((headers, Json(serde_json::json!({
+            "query": query,
+            "suggestions": [],
+            "min_length": search.autocomplete_min_length
+        }))));
+    }
+    let limit = search_limit(params.get("limit").map(String::as_str), 10, &search)
+        .map_err(ApiError::validation)?;
     
     info!("Autocomplete for: {}", query);
     
     let repository = DuaRepository::new(database);
-    let suggestions = repository.get_suggestions(&query, limit).await?;
+    let service = DuaService::new(repository, cache);
+    let ttl = Duration::from_secs(search.autocomplete_cache_ttl_secs);
+    let suggestions = service.suggest(&query, limit, ttl).await?;
     
-    Ok(Json(serde_json::json!({
+    Ok((headers, Json(serde_json::json!({
         "query": query,
-        "suggestions": suggestions
-    })))
+        "suggestions": suggestions,
+        "min_length": search.autocomplete_min_length
+    }))))
 }
 
 // ============= STATS ENDPOINT =============
@@ -404,6 +959,256 @@ pub async fn health_check(
         "version": "1.0.0",
         "database": "connected",
         "cache": "connected",
-        "timestamp": chrono::Utc::now().to_rfc3339()
+        "timestamp": shared::time::now_rfc3339()
     })))
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The database and cache, or `None` when either isn't reachable
+    async fn connect() -> Option<(Database, Cache)> {
+        let url = std::env::var("DATABASE_URL").ok()?;
+        let cache = Cache::new(&shared::config::RedisConfig {
+            url: "redis://localhost:6379".to_string(),
+            pool_max_open: 10,
+            pool_max_idle: 5,
+            pool_timeout: 30,
+            pool_expire: 300,
+        })
+        .await
+        .ok()?;
+        let database = Database::new(&shared::config::DatabaseConfig {
+            url,
+            max_connections: 5,
+            min_connections: 1,
+            connect_timeout: 30,
+            acquire_timeout_ms: 5000,
+            idle_timeout: 600,
+            slow_query_threshold_ms: 500,
+        })
+        .await
+        .unwrap();
+        Some((database, cache))
+    }
+
+    #[tokio::test]
+    async fn test_by_slug_does_not_match_uuid_shaped_slug_as_id() {
+        let Some((database, cache)) = connect().await else {
+            return;
+        };
+
+        // The overloaded route would find this dua by id
+        let id = Uuid::new_v4();
+        sqlx::query(
+            "INSERT INTO duas (id, title, arabic_text, translation, slug) VALUES ($1, 'Test by-slug', 'نص', 'Text', $2)",
+        )
+        .bind(id)
+        .bind(format!("test-by-slug-{}", id))
+        .execute(&database.pool)
+        .await
+        .unwrap();
+
+        let popularity = PopularityConfig {
+            track_views: false,
+            view_debounce_secs: 3600,
+            recompute_interval_secs: 3600,
+        };
+        let by_slug = get_dua_by_slug(
+            Extension(database.clone()),
+            Extension(cache.clone()),
+            Extension(popularity.clone()),
+            None,
+            HeaderMap::new(),
+            Path(id.to_string()),
+            Query(HashMap::new()),
+        )
+        .await;
+        let overloaded = get_dua(
+            Extension(database.clone()),
+            Extension(cache),
+            Extension(popularity),
+            None,
+            HeaderMap::new(),
+            Path(id.to_string()),
+            Query(HashMap::new()),
+        )
+        .await;
+
+        sqlx::query("DELETE FROM duas WHERE id = $1")
+            .bind(id)
+            .execute(&database.pool)
+            .await
+            .unwrap();
+
+        assert!(matches!(by_slug, Err(ApiError::NotFound(_))));
+        assert!(overloaded.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_dua_list_varies_on_language_and_credentials() {
+        let Some((database, cache)) = connect().await else {
+            return;
+        };
+
+        let mut headers = HeaderMap::new();
+        headers.insert(header::ACCEPT_LANGUAGE, "ar, en;q=0.8".parse().unwrap());
+        let response = list_duas(
+            Extension(database),
+            Extension(cache),
+            Extension(PaginationConfig::default()),
+            Extension(AdminToken::default()),
+            headers,
+            Query(DuaQueryParams::default()),
+        )
+        .await
+        .unwrap()
+        .into_response();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()[header::VARY],
+            "Accept-Language, Authorization, X-API-Key"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_autocomplete_needs_min_length_prefix() {
+        use axum::body::HttpBody;
+
+        let Some((database, cache)) = connect().await else {
+            return;
+        };
+        let search = SearchConfig::default();
+
+        let id = Uuid::new_v4();
+        sqlx::query(
+            "INSERT INTO duas (id, title, arabic_text, translation, slug) VALUES ($1, 'Test autocomplete', 'نص', 'Text', $2)",
+        )
+        .bind(id)
+        .bind(format!("test-autocomplete-{}", id))
+        .execute(&database.pool)
+        .await
+        .unwrap();
+
+        let mut responses = Vec::new();
+        for prefix in ["T", "Te"] {
+            let params = HashMap::from([("q".to_string(), prefix.to_string())]);
+            let response = autocomplete(
+                Extension(database.clone()),
+                Extension(cache.clone()),
+                Extension(search.clone()),
+                Query(params),
+            )
+            .await
+            .map(IntoResponse::into_response);
+            responses.push(response);
+        }
+
+        sqlx::query("DELETE FROM duas WHERE id = $1")
+            .bind(id)
+            .execute(&database.pool)
+            .await
+            .unwrap();
+
+        let mut bodies = Vec::new();
+        for response in responses {
+            let mut body = response.unwrap().into_body();
+            let mut bytes = Vec::new();
+            while let Some(chunk) = body.data().await {
+                bytes.extend_from_slice(&chunk.unwrap());
+            }
+            bodies.push(serde_json::from_slice::<serde_json::Value>(&bytes).unwrap());
+        }
+
+        assert_eq!(bodies[0]["suggestions"], serde_json::json!([]));
+        assert_eq!(bodies[0]["min_length"], search.autocomplete_min_length);
+        assert!(!bodies[1]["suggestions"].as_array().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_only_admins_can_see_drafts() {
+        let admin = AdminToken::new(Some("s3cret".to_string()));
+        let params = || DuaQueryParams {
+            status: Some("draft".to_string()),
+            include_drafts: Some(true),
+            ..Default::default()
+        };
+
+        let mut anonymous = params();
+        restrict_visibility(&mut anonymous, &admin, &HeaderMap::new());
+        assert_eq!(anonymous.visible_statuses().unwrap(), Status::Active.db_values());
+
+        let mut headers = HeaderMap::new();
+        headers.insert(header::AUTHORIZATION, "Bearer s3cret".parse().unwrap());
+        let mut editor = params();
+        restrict_visibility(&mut editor, &admin, &headers);
+        assert_eq!(editor.visible_statuses().unwrap(), vec!["draft"]);
+    }
+
+    /// A 3 second PCM WAV header
+    fn wav_header() -> [u8; WAV_HEADER_LEN] {
+        let mut header = [0u8; WAV_HEADER_LEN];
+        header[0..4].copy_from_slice(b"RIFF");
+        header[8..12].copy_from_slice(b"WAVE");
+        header[28..32].copy_from_slice(&16_000u32.to_le_bytes());
+        header[40..44].copy_from_slice(&48_000u32.to_le_bytes());
+        header
+    }
+
+    #[tokio::test]
+    async fn test_local_probe_stays_inside_media_root() {
+        let base = std::env::temp_dir().join(format!("dua-media-{}", Uuid::new_v4()));
+        let root = base.join("media");
+        std::fs::create_dir_all(root.join("duas")).unwrap();
+        std::fs::write(root.join("duas/morning.wav"), wav_header()).unwrap();
+        std::fs::write(base.join("outside.wav"), wav_header()).unwrap();
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(base.join("outside.wav"), root.join("escape.wav")).unwrap();
+        let root = root.to_str().unwrap();
+
+        let inside = probe_local_wav_duration(root, "duas/morning.wav").await;
+        let absolute = probe_local_wav_duration(root, "/duas/morning.wav").await;
+        let escaped = probe_local_wav_duration(root, "escape.wav").await;
+        let missing = probe_local_wav_duration(root, "duas/evening.wav").await;
+        std::fs::remove_dir_all(&base).unwrap();
+
+        assert_eq!(inside, Some(3));
+        assert_eq!(absolute, Some(3));
+        assert_eq!(escaped, None);
+        assert_eq!(missing, None);
+    }
+
+    #[tokio::test]
+    async fn test_remote_probe_never_calls_private_hosts() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use tokio::io::AsyncWriteExt;
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/morning.wav", listener.local_addr().unwrap());
+        let hits = std::sync::Arc::new(AtomicUsize::new(0));
+        let counter = hits.clone();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                counter.fetch_add(1, Ordering::SeqCst);
+                let mut response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    WAV_HEADER_LEN
+                )
+                .into_bytes();
+                response.extend_from_slice(&wav_header());
+                let _ = stream.write_all(&response).await;
+            }
+        });
+
+        let request: CreateMediaRequest = serde_json::from_value(serde_json::json!({
+            "media_type": "audio",
+            "url": url
+        }))
+        .unwrap();
+        let duration = probe_wav_duration(&request, &MediaConfig::default()).await;
+
+        assert_eq!(duration, None);
+        assert_eq!(hits.load(Ordering::SeqCst), 0);
+    }
+}
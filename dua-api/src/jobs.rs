@@ -0,0 +1,301 @@
+// Background semantic search jobs, for clients that would rather be called
+// back (or poll) than hold a request open while embeddings are searched.
+// Jobs live in memory on the instance that accepted them.
+//
+// Callback URLs come from clients, so they are vetted and called through
+// `outbound`, which only reaches hosts that resolve to public addresses.
+
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use serde::{Deserialize, Serialize};
+use shared::{
+    error::{ApiError, ApiResult},
+    http::{retry, RetryPolicy},
+};
+use std::{
+    collections::HashMap,
+    future::Future,
+    sync::{Arc, Weak},
+    time::Duration,
+};
+use tokio::sync::RwLock;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::{models::SearchResponse, outbound};
+
+/// How long finished jobs stay available for polling
+const JOB_RETENTION_MINUTES: i64 = 60;
+/// How often finished jobs past their retention are dropped
+const SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+/// Jobs still running at once; further requests are turned away
+const MAX_PENDING_JOBS: usize = 64;
+const CALLBACK_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchJobStatus {
+    Pending,
+    Complete,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchJob {
+    pub id: Uuid,
+    pub status: SearchJobStatus,
+    pub query: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub callback_url: Option<String>,
+    #[serde(with = "shared::time::rfc3339")]
+    pub created_at: DateTime<Utc>,
+    #[serde(default, with = "shared::time::rfc3339::option")]
+    pub completed_at: Option<DateTime<Utc>>,
+    pub result: Option<SearchResponse>,
+    pub error: Option<String>,
+}
+
+type JobMap = Arc<RwLock<HashMap<Uuid, SearchJob>>>;
+
+#[derive(Clone)]
+pub struct SearchJobs {
+    jobs: JobMap,
+}
+
+impl SearchJobs {
+    /// Must be called inside a Tokio runtime: a sweeper task drops finished
+    /// jobs once they are past their retention, and stops with the store.
+    pub fn new() -> Self {
+        let jobs: JobMap = Arc::new(RwLock::new(HashMap::new()));
+
+        let sweep = Arc::downgrade(&jobs);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(SWEEP_INTERVAL);
+            loop {
+                interval.tick().await;
+                let Some(jobs) = Weak::upgrade(&sweep) else {
+                    break;
+                };
+                evict_expired(&mut *jobs.write().await, Utc::now());
+            }
+        });
+
+        Self { jobs }
+    }
+
+    /// Records a pending job and runs `search` in the background. When it
+    /// finishes the job is updated and, if a callback URL was given, the job
+    /// is POSTed to it. Fails if the callback URL is not public or too many
+    /// jobs are already running.
+    pub async fn enqueue<F>(
+        &self,
+        query: String,
+        callback_url: Option<String>,
+        search: F,
+    ) -> ApiResult<SearchJob>
+    where
+        F: Future<Output = ApiResult<SearchResponse>> + Send + 'static,
+    {
+        if let Some(url) = &callback_url {
+            outbound::resolve_public(url, "callback_url").await?;
+        }
+
+        let job = SearchJob {
+            id: Uuid::new_v4(),
+            status: SearchJobStatus::Pending,
+            query,
+            callback_url,
+            created_at: Utc::now(),
+            completed_at: None,
+            result: None,
+            error: None,
+        };
+
+        {
+            let mut jobs = self.jobs.write().await;
+            evict_expired(&mut jobs, Utc::now());
+            let pending = jobs.values().filter(|j| j.status == SearchJobStatus::Pending).count();
+            if pending >= MAX_PENDING_JOBS {
+                return Err(ApiError::ServiceUnavailable(
+                    "Too many search jobs in progress, try again later".to_string(),
+                ));
+            }
+            jobs.insert(job.id, job.clone());
+        }
+
+        let jobs = self.clone();
+        let id = job.id;
+        tokio::spawn(async move {
+            let outcome = search.await;
+            if let Some(finished) = jobs.finish(id, outcome).await {
+                notify(&finished).await;
+            }
+        });
+
+        Ok(job)
+    }
+
+    pub async fn get(&self, id: Uuid) -> Option<SearchJob> {
+        self.jobs.read().await.get(&id).cloned()
+    }
+
+    async fn finish(&self, id: Uuid, outcome: ApiResult<SearchResponse>) -> Option<SearchJob> {
+        let mut jobs = self.jobs.write().await;
+        let job = jobs.get_mut(&id)?;
+
+        match outcome {
+            Ok(result) => {
+                job.status = SearchJobStatus::Complete;
+                job.result = Some(result);
+            }
+            Err(e) => {
+                warn!("Semantic search job {} failed: {}", id, e);
+                job.status = SearchJobStatus::Failed;
+                job.error = Some(e.to_string());
+            }
+        }
+        job.completed_at = Some(Utc::now());
+
+        Some(job.clone())
+    }
+
+}
+
+fn evict_expired(jobs: &mut HashMap<Uuid, SearchJob>, now: DateTime<Utc>) {
+    let cutoff = now - ChronoDuration::minutes(JOB_RETENTION_MINUTES);
+    jobs.retain(|_, j| j.completed_at.is_none_or(|done| done > cutoff));
+}
+
+async fn notify(job: &SearchJob) {
+    let Some(ref url) = job.callback_url else {
+        return;
+    };
+
+    // Resolve again at delivery time and pin the client to that address
+    let client = match outbound::public_client(url, "callback_url", CALLBACK_TIMEOUT).await {
+        Ok(client) => client,
+        Err(e) => {
+            warn!("Refusing callback for search job {}: {}", job.id, e);
+            return;
+        }
+    };
+
+    let delivery = retry(&RetryPolicy::default(), "Search job callback", || async {
+        client.post(url).json(job).send().await?.error_for_status()
+    })
+    .await;
+
+    match delivery {
+        Ok(_) => info!("Delivered search job {} to callback", job.id),
+        Err(e) => warn!("Giving up on callback for search job {}: {}", job.id, e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::sync::oneshot;
+
+    #[tokio::test]
+    async fn test_job_transitions_from_pending_to_complete() {
+        let jobs = SearchJobs::new();
+        let (release, wait) = oneshot::channel::<()>();
+
+        let job = jobs
+            .enqueue("morning".to_string(), None, async move {
+                wait.await.ok();
+                Ok(SearchResponse {
+                    results: Vec::new(),
+                    total: 0,
+                    query: "morning".to_string(),
+                })
+            })
+            .await
+            .unwrap();
+        assert_eq!(job.status, SearchJobStatus::Pending);
+        assert_eq!(jobs.get(job.id).await.unwrap().status, SearchJobStatus::Pending);
+
+        release.send(()).unwrap();
+        let mut finished = None;
+        for _ in 0..100 {
+            let current = jobs.get(job.id).await.unwrap();
+            if current.status != SearchJobStatus::Pending {
+                finished = Some(current);
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+
+        let finished = finished.expect("job did not finish");
+        assert_eq!(finished.status, SearchJobStatus::Complete);
+        assert!(finished.completed_at.is_some());
+        assert_eq!(finished.result.unwrap().query, "morning");
+        assert!(jobs.get(Uuid::new_v4()).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_enqueue_rejects_internal_callback_urls() {
+        let jobs = SearchJobs::new();
+        for url in [
+            "http://127.0.0.1/hook",
+            "http://169.254.169.254/latest/meta-data",
+            "http://10.0.0.5:8080/hook",
+            "http://[::1]/hook",
+            "http://localhost/hook",
+            "file:///etc/passwd",
+        ] {
+            let result = jobs
+                .enqueue("morning".to_string(), Some(url.to_string()), async {
+                    Err(ApiError::Internal(anyhow::anyhow!("search should not run")))
+                })
+                .await;
+            assert!(matches!(result, Err(ApiError::Validation(_))), "{} was accepted", url);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_enqueue_caps_pending_jobs() {
+        let jobs = SearchJobs::new();
+        let (release, wait) = tokio::sync::broadcast::channel::<()>(1);
+
+        for _ in 0..MAX_PENDING_JOBS {
+            let mut wait = release.subscribe();
+            jobs.enqueue("morning".to_string(), None, async move {
+                wait.recv().await.ok();
+                Err(ApiError::Internal(anyhow::anyhow!("released")))
+            })
+            .await
+            .unwrap();
+        }
+        let overflow = jobs
+            .enqueue("morning".to_string(), None, async { Err(ApiError::Internal(anyhow::anyhow!("unused"))) })
+            .await;
+        assert!(matches!(overflow, Err(ApiError::ServiceUnavailable(_))));
+        drop(wait);
+        release.send(()).unwrap();
+    }
+
+    #[test]
+    fn test_evict_expired_drops_only_old_finished_jobs() {
+        let now = Utc::now();
+        let job = |completed_at: Option<DateTime<Utc>>| SearchJob {
+            id: Uuid::new_v4(),
+            status: SearchJobStatus::Complete,
+            query: "morning".to_string(),
+            callback_url: None,
+            created_at: now - ChronoDuration::hours(3),
+            completed_at,
+            result: None,
+            error: None,
+        };
+        let stale = job(Some(now - ChronoDuration::minutes(JOB_RETENTION_MINUTES + 1)));
+        let fresh = job(Some(now - ChronoDuration::minutes(1)));
+        let pending = job(None);
+
+        let mut jobs: HashMap<Uuid, SearchJob> =
+            [&stale, &fresh, &pending].into_iter().map(|j| (j.id, j.clone())).collect();
+        evict_expired(&mut jobs, now);
+
+        assert!(!jobs.contains_key(&stale.id));
+        assert!(jobs.contains_key(&fresh.id) && jobs.contains_key(&pending.id));
+    }
+}
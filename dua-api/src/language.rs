@@ -0,0 +1,65 @@
+// `Accept-Language` negotiation, used as the default for the `lang`
+// parameter when a client doesn't pass one explicitly.
+
+/// Language ranges from an `Accept-Language` header, most preferred first.
+/// Ranges with `q=0` are refused by the client and dropped.
+pub fn parse_accept_language(header: &str) -> Vec<String> {
+    let mut ranges: Vec<(String, f32)> = header
+        .split(',')
+        .filter_map(|part| {
+            let mut pieces = part.split(';');
+            let range = pieces.next()?.trim().to_lowercase();
+            if range.is_empty() {
+                return None;
+            }
+
+            let quality = pieces
+                .filter_map(|param| param.trim().strip_prefix("q="))
+                .find_map(|q| q.trim().parse::<f32>().ok())
+                .unwrap_or(1.0);
+            (quality > 0.0).then_some((range, quality))
+        })
+        .collect();
+
+    // Stable, so equally weighted ranges keep the client's order
+    ranges.sort_by(|a, b| b.1.total_cmp(&a.1));
+    ranges.into_iter().map(|(range, _)| range).collect()
+}
+
+/// The best available language for an `Accept-Language` header. A range
+/// matches a language exactly or by its primary subtag, so `ur-PK` picks
+/// `ur`. The `*` wildcard never picks a language: without a match the
+/// dua's own text is served.
+pub fn negotiate_language(header: &str, available: &[String]) -> Option<String> {
+    parse_accept_language(header).into_iter().find_map(|range| {
+        let primary = range.split('-').next().unwrap_or(&range);
+        available
+            .iter()
+            .find(|lang| lang.eq_ignore_ascii_case(&range))
+            .or_else(|| available.iter().find(|lang| lang.eq_ignore_ascii_case(primary)))
+            .cloned()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ranges_are_ordered_by_quality() {
+        assert_eq!(
+            parse_accept_language("en;q=0.5, ur-PK, ar;q=0.8, fr;q=0"),
+            vec!["ur-pk", "ar", "en"]
+        );
+        assert!(parse_accept_language("").is_empty());
+    }
+
+    #[test]
+    fn test_best_available_language_is_chosen() {
+        let available = vec!["ar".to_string(), "ur".to_string()];
+        assert_eq!(negotiate_language("ur", &available).as_deref(), Some("ur"));
+        assert_eq!(negotiate_language("de, ur-PK;q=0.9", &available).as_deref(), Some("ur"));
+        assert_eq!(negotiate_language("en;q=0.2, ar;q=0.7", &available).as_deref(), Some("ar"));
+        assert_eq!(negotiate_language("de, *", &available), None);
+    }
+}
@@ -1,27 +1,35 @@
 use axum::{
     middleware,
-    routing::{get, post},
+    routing::{get, post, put},
     Extension, Router, Server,
 };
 use dotenv::dotenv;
 use shared::{
+    admin::{admin_auth_middleware, admin_router, AdminToken},
     cache::Cache,
     config::AppConfig,
     database::Database,
+    latency::{latency_middleware, LatencyTracker},
     middleware::{
-        cors_layer, rate_limit_middleware, timeout_layer, trace_layer,
+        cors_layer, rate_limit_middleware, read_only_middleware, security_headers_middleware,
+        timeout_middleware, trace_layer, ReadOnlyMode, RouteTimeouts, SecurityHeaders,
     },
     SimpleRateLimiter,
     ApiResult,
 };
-use std::net::SocketAddr;
-use tracing::{info, level_filters::LevelFilter};
+use std::{net::SocketAddr, time::Duration};
+use tracing::{info, level_filters::LevelFilter, warn};
 use tracing_subscriber::{EnvFilter, FmtSubscriber};
 
 mod handlers;
+mod jobs;
+mod language;
 mod models;
+mod occasions;
+mod outbound;
 mod repository;
 mod services;
+mod suggestions;
 
 use handlers::*;
 
@@ -59,18 +67,78 @@ async fn main() -> ApiResult<()> {
     let cache = Cache::new(&config.redis).await?;
     info!("Cache connected successfully");
 
+    // Periodically fold view counts into popularity_score
+    if config.popularity.track_views {
+        let repository = repository::DuaRepository::new(database.clone());
+        let interval = Duration::from_secs(config.popularity.recompute_interval_secs.max(1));
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                match repository.recompute_popularity().await {
+                    Ok(updated) => info!("Recomputed popularity for {} duas", updated),
+                    Err(e) => warn!("Failed to recompute popularity: {}", e),
+                }
+            }
+        });
+    }
+
+    // Periodically recount dua_stats to correct drift and refresh the
+    // figures the triggers can't maintain
+    {
+        let repository = repository::DuaRepository::new(database.clone());
+        let interval = Duration::from_secs(config.stats.refresh_interval_secs.max(1));
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                if let Err(e) = repository.refresh_stats().await {
+                    warn!("Failed to refresh dua stats: {}", e);
+                }
+            }
+        });
+    }
+
     // Initialize rate limiter
     let rate_limiter = SimpleRateLimiter::new(cache.clone(), config.rate_limit.clone());
 
+    // Maintenance switch; semantic search and tag suggestions are POSTs but
+    // never write to the database
+    let read_only = ReadOnlyMode::new(config.server.read_only)
+        .exempt("/v1/search/semantic")
+        .exempt("/v1/duas/:id/suggest-tags");
+    if read_only.is_enabled() {
+        info!("Read-only mode enabled: write requests will be rejected");
+    }
+
+    let admin_token = AdminToken::new(config.server.admin_token.clone());
+
+    // Batch and semantic endpoints get longer to finish, health checks less
+    let long_timeout = Duration::from_secs(config.server.long_timeout_secs);
+    let short_timeout = Duration::from_secs(config.server.short_timeout_secs);
+    let timeouts = RouteTimeouts::from_config(&config.server)
+        .with_override("/v1/duas/bulk", long_timeout)
+        .with_override("/v1/search/semantic", long_timeout)
+        .with_override("/health", short_timeout);
+    let latency = LatencyTracker::default();
+
     // Build the application router
     let app = Router::new()
         // ===== DUA ENDPOINTS =====
         .route("/v1/duas", get(list_duas))
         .route("/v1/duas/random", get(get_random_dua))
+        .route("/v1/duas/by-id/:id", get(get_dua_by_id))
+        .route("/v1/duas/by-slug/:slug", get(get_dua_by_slug))
         .route("/v1/duas/:id", get(get_dua))
+        .route("/v1/duas/:id/suggest-tags", post(suggest_dua_tags))
+        .route("/v1/duas/:id/versions", get(get_dua_versions))
+        .route("/v1/duas/:id/versions/:version", get(get_dua_version))
+        .route("/v1/duas/:id/report", post(report_dua))
         
         // ===== TRANSLATION ENDPOINTS =====
         .route("/v1/duas/:id/translations", get(get_dua_translations))
+        .route("/v1/duas/:id/translations/compare", get(compare_translations))
+        .route("/v1/duas/:id/translations/:lang", get(get_dua_translation))
         .route("/v1/translations", get(list_all_translations))
         
         // ===== CATEGORY ENDPOINTS =====
@@ -87,6 +155,9 @@ async fn main() -> ApiResult<()> {
         .route("/v1/bundles/:slug", get(get_bundle))
         .route("/v1/bundles/:slug/items", get(get_bundle_items))
         
+        // ===== OCCASION ENDPOINTS =====
+        .route("/v1/occasions/:slug/duas", get(get_occasion_duas))
+        
         // ===== SOURCE ENDPOINTS =====
         .route("/v1/sources", get(list_sources))
         .route("/v1/sources/:id", get(get_source))
@@ -98,7 +169,9 @@ async fn main() -> ApiResult<()> {
         
         // ===== SEARCH ENDPOINTS =====
         .route("/v1/search", get(keyword_search))
+        .route("/v1/search/all", get(unified_search))
         .route("/v1/search/semantic", post(semantic_search))
+        .route("/v1/search/jobs/:id", get(get_search_job))
         .route("/v1/suggest", get(autocomplete))
         
         // ===== STATS ENDPOINT =====
@@ -107,16 +180,48 @@ async fn main() -> ApiResult<()> {
         // ===== HEALTH CHECK =====
         .route("/health", get(health_check))
         
+        // ===== ADMIN ENDPOINTS =====
+        .merge(admin_router(
+            rate_limiter.clone(),
+            latency.clone(),
+            read_only.clone(),
+            admin_token.clone(),
+        ))
+        .merge(
+            Router::new()
+                .route("/v1/duas/bulk", post(bulk_import_duas))
+                .route("/v1/duas/export.ndjson", get(export_duas_ndjson))
+                .route("/v1/duas/:id/media", post(create_dua_media))
+                .route("/v1/duas/:id/translations", put(upsert_dua_translations))
+                .route("/v1/reports", get(list_reports))
+                .layer(middleware::from_fn_with_state(admin_token.clone(), admin_auth_middleware)),
+        )
+        
         // Apply middleware layers
+        .layer(middleware::from_fn_with_state(
+            read_only.clone(),
+            read_only_middleware,
+        ))
         .layer(middleware::from_fn_with_state(
             rate_limiter.clone(),
             rate_limit_middleware,
         ))
-        .layer(timeout_layer())
-        .layer(cors_layer())
+        .layer(middleware::from_fn_with_state(timeouts, timeout_middleware))
+        .layer(middleware::from_fn_with_state(latency, latency_middleware))
+        .layer(middleware::from_fn_with_state(
+            SecurityHeaders::from_config(&config.server),
+            security_headers_middleware,
+        ))
+        .layer(cors_layer(&config.cors))
         .layer(trace_layer())
         .layer(Extension(database))
-        .layer(Extension(cache));
+        .layer(Extension(cache))
+        .layer(Extension(admin_token))
+        .layer(Extension(config.pagination.clone()))
+        .layer(Extension(config.popularity.clone()))
+        .layer(Extension(config.search.clone()))
+        .layer(Extension(config.media.clone()))
+        .layer(Extension(jobs::SearchJobs::new()));
 
     // Start the server
     let addr: SocketAddr = config.bind_address().parse()?;
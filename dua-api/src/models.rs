@@ -1,9 +1,16 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, TimeZone, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use uuid::Uuid;
 use validator::Validate;
+use std::collections::HashSet;
 use serde::de::{self, Deserializer};
+use shared::{
+    config::{EmptyQueryPolicy, PaginationConfig, SearchConfig},
+    pagination::{PageItem, Paginated},
+};
+
+use crate::occasions::Occasion;
 
 
 fn deserialize_bool_from_string<'de, D>(deserializer: D) -> Result<Option<bool>, D::Error>
@@ -48,6 +55,64 @@ pub enum Authenticity {
     Unclassified,
 }
 
+impl Authenticity {
+    const RANKED: [Authenticity; 5] = [
+        Authenticity::Quranic,
+        Authenticity::Sahih,
+        Authenticity::Hasan,
+        Authenticity::Daif,
+        Authenticity::Unclassified,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Authenticity::Quranic => "Quranic",
+            Authenticity::Sahih => "Sahih",
+            Authenticity::Hasan => "Hasan",
+            Authenticity::Daif => "Daif",
+            Authenticity::Unclassified => "Unclassified",
+        }
+    }
+
+    /// Quranic > Sahih > Hasan > Daif > Unclassified
+    pub fn rank(&self) -> u8 {
+        match self {
+            Authenticity::Quranic => 4,
+            Authenticity::Sahih => 3,
+            Authenticity::Hasan => 2,
+            Authenticity::Daif => 1,
+            Authenticity::Unclassified => 0,
+        }
+    }
+
+    /// Database values for every grade at or above this one
+    pub fn at_least(&self) -> Vec<String> {
+        Self::RANKED
+            .iter()
+            .filter(|a| a.rank() >= self.rank())
+            .map(|a| a.as_str().to_string())
+            .collect()
+    }
+}
+
+impl std::str::FromStr for Authenticity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "quranic" => Ok(Authenticity::Quranic),
+            "sahih" => Ok(Authenticity::Sahih),
+            "hasan" => Ok(Authenticity::Hasan),
+            "daif" => Ok(Authenticity::Daif),
+            "unclassified" => Ok(Authenticity::Unclassified),
+            _ => Err(format!(
+                "Unknown authenticity '{}'. Valid values: quranic, sahih, hasan, daif, unclassified",
+                s
+            )),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type)]
 #[sqlx(type_name = "invocation_time_enum", rename_all = "snake_case")]
 pub enum InvocationTime {
@@ -132,6 +197,23 @@ pub enum MediaType {
     Svg,
 }
 
+impl std::str::FromStr for MediaType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "audio" => Ok(MediaType::Audio),
+            "video" => Ok(MediaType::Video),
+            "image" => Ok(MediaType::Image),
+            "svg" => Ok(MediaType::Svg),
+            _ => Err(format!(
+                "Unknown media_type '{}'. Valid values: audio, video, image, svg",
+                s
+            )),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type)]
 #[sqlx(type_name = "reciter_style_enum", rename_all = "snake_case")]
 pub enum ReciterStyle {
@@ -140,6 +222,22 @@ pub enum ReciterStyle {
     Spoken,
 }
 
+impl std::str::FromStr for ReciterStyle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "mujawwad" => Ok(ReciterStyle::Mujawwad),
+            "murattal" => Ok(ReciterStyle::Murattal),
+            "spoken" => Ok(ReciterStyle::Spoken),
+            _ => Err(format!(
+                "Unknown reciter_style '{}'. Valid values: mujawwad, murattal, spoken",
+                s
+            )),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type)]
 #[sqlx(type_name = "license_enum")]
 pub enum License {
@@ -153,6 +251,34 @@ pub enum License {
     AllRightsReserved,
 }
 
+impl License {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            License::CC0 => "CC0",
+            License::CCBY => "CC-BY",
+            License::PublicDomain => "Public Domain",
+            License::AllRightsReserved => "All Rights Reserved",
+        }
+    }
+}
+
+impl std::str::FromStr for License {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "cc0" => Ok(License::CC0),
+            "cc-by" => Ok(License::CCBY),
+            "public domain" => Ok(License::PublicDomain),
+            "all rights reserved" => Ok(License::AllRightsReserved),
+            _ => Err(format!(
+                "Unknown license '{}'. Valid values: CC0, CC-BY, Public Domain, All Rights Reserved",
+                s
+            )),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type)]
 #[sqlx(type_name = "review_status_enum", rename_all = "snake_case")]
 pub enum ReviewStatus {
@@ -178,6 +304,72 @@ pub enum Status {
     Deprecated,
 }
 
+impl Status {
+    /// Stored `duas.status` values for this status. Older rows used
+    /// 'verified' for published duas, so it is treated as active.
+    pub fn db_values(&self) -> &'static [&'static str] {
+        match self {
+            Status::Active => &["active", "verified"],
+            Status::Draft => &["draft"],
+            Status::Deprecated => &["deprecated"],
+        }
+    }
+}
+
+impl std::str::FromStr for Status {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "active" | "verified" => Ok(Status::Active),
+            "draft" => Ok(Status::Draft),
+            "deprecated" => Ok(Status::Deprecated),
+            _ => Err(format!(
+                "Unknown status '{}'. Valid values: active, draft, deprecated",
+                s
+            )),
+        }
+    }
+}
+
+/// Kind of text stored in `duas.dua_type`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DuaType {
+    Dua,
+    Dhikr,
+    Ruqyah,
+    Salawat,
+}
+
+impl DuaType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DuaType::Dua => "dua",
+            DuaType::Dhikr => "dhikr",
+            DuaType::Ruqyah => "ruqyah",
+            DuaType::Salawat => "salawat",
+        }
+    }
+}
+
+impl std::str::FromStr for DuaType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "dua" => Ok(DuaType::Dua),
+            "dhikr" => Ok(DuaType::Dhikr),
+            "ruqyah" => Ok(DuaType::Ruqyah),
+            "salawat" => Ok(DuaType::Salawat),
+            _ => Err(format!(
+                "Unknown dua_type '{}'. Valid values: dua, dhikr, ruqyah, salawat",
+                s
+            )),
+        }
+    }
+}
+
 // ============= CORE MODELS =============
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
@@ -191,10 +383,31 @@ pub struct Dua {
     pub status: String,
     pub version: i32,
     pub popularity_score: f64,
+    pub dua_type: String,
+    #[serde(with = "shared::time::rfc3339")]
     pub created_at: DateTime<Utc>,
+    #[serde(with = "shared::time::rfc3339")]
     pub updated_at: DateTime<Utc>,
 }
 
+/// A dua's content as it was before an edit replaced it
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct DuaVersion {
+    pub dua_id: Uuid,
+    pub version: i32,
+    pub title: String,
+    pub arabic_text: String,
+    pub transliteration: Option<String>,
+    pub translation: String,
+    pub slug: String,
+    pub status: String,
+    pub dua_type: String,
+    #[serde(default, with = "shared::time::rfc3339::option")]
+    pub updated_at: Option<DateTime<Utc>>,
+    #[serde(with = "shared::time::rfc3339")]
+    pub superseded_at: DateTime<Utc>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct DuaTranslation {
     pub id: Uuid,
@@ -206,10 +419,174 @@ pub struct DuaTranslation {
     pub slug: Option<String>,
     pub seo_title: Option<String>,
     pub meta_description: Option<String>,
+    #[serde(with = "shared::time::rfc3339")]
     pub created_at: DateTime<Utc>,
+    #[serde(with = "shared::time::rfc3339")]
     pub updated_at: DateTime<Utc>,
 }
 
+/// A single translation with the slug a localized page should be served under
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct LocalizedTranslation {
+    #[serde(flatten)]
+    #[sqlx(flatten)]
+    pub translation: DuaTranslation,
+    /// The translation's own slug, falling back to the dua's slug
+    pub canonical_slug: String,
+}
+
+/// One entry of a `PUT /v1/duas/:id/translations` batch, keyed by language
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct UpsertTranslationRequest {
+    /// ISO 639-1 or 639-2 code, optionally with a region, e.g. `ur` or `pt-BR`
+    #[validate(custom = "validate_language_code")]
+    pub language_code: String,
+    #[validate(length(min = 1, max = 500))]
+    pub title: Option<String>,
+    #[validate(length(min = 1))]
+    pub translation: String,
+    pub transliteration: Option<String>,
+    #[validate(length(min = 1, max = 500), custom = "validate_slug")]
+    pub slug: Option<String>,
+    #[validate(length(max = 200))]
+    pub seo_title: Option<String>,
+    pub meta_description: Option<String>,
+}
+
+/// Largest translation batch accepted in one request
+pub const MAX_TRANSLATION_BATCH: usize = 100;
+
+fn validate_language_code(code: &str) -> Result<(), validator::ValidationError> {
+    let mut subtags = code.split('-');
+    let primary = subtags.next().unwrap_or_default();
+    let region = subtags.next();
+    
+    let valid = (2..=3).contains(&primary.len())
+        && primary.chars().all(|c| c.is_ascii_alphabetic())
+        && region.is_none_or(|r| (2..=3).contains(&r.len()) && r.chars().all(|c| c.is_ascii_alphanumeric()))
+        && subtags.next().is_none();
+    if !valid {
+        return Err(validator::ValidationError::new(
+            "language_code must be an ISO 639 code such as 'en' or 'pt-BR'",
+        ));
+    }
+    Ok(())
+}
+
+/// Validates a translation batch: every entry, the batch size, and that no
+/// language appears twice
+pub fn check_translation_batch(batch: &[UpsertTranslationRequest]) -> Result<(), String> {
+    if batch.is_empty() {
+        return Err("At least one translation is required".to_string());
+    }
+    if batch.len() > MAX_TRANSLATION_BATCH {
+        return Err(format!("At most {} translations can be sent at once", MAX_TRANSLATION_BATCH));
+    }
+    
+    let mut languages = HashSet::new();
+    for (index, entry) in batch.iter().enumerate() {
+        entry
+            .validate()
+            .map_err(|e| format!("Translation {}: validation failed: {}", index, e))?;
+        if entry.translation.trim().is_empty() {
+            return Err(format!("Translation {}: translation must not be blank", index));
+        }
+        if !languages.insert(entry.language_code.to_lowercase()) {
+            return Err(format!("Language '{}' appears more than once", entry.language_code));
+        }
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CompareTranslationsParams {
+    /// Comma-separated language codes, e.g. `en,ur`
+    pub langs: String,
+}
+
+impl CompareTranslationsParams {
+    /// Requested languages, lowercased and without duplicates, in the order given
+    pub fn languages(&self) -> Result<Vec<String>, String> {
+        let mut languages: Vec<String> = Vec::new();
+        for lang in self.langs.split(',').map(|l| l.trim().to_lowercase()) {
+            if !lang.is_empty() && !languages.contains(&lang) {
+                languages.push(lang);
+            }
+        }
+        
+        if languages.is_empty() {
+            return Err("langs must name at least one language".to_string());
+        }
+        if languages.len() > 10 {
+            return Err("langs can name at most 10 languages".to_string());
+        }
+        Ok(languages)
+    }
+}
+
+/// The text fields that line up between a dua and its translations
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ComparedText {
+    pub title: Option<String>,
+    pub translation: Option<String>,
+    pub transliteration: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ComparedTranslation {
+    pub language_code: String,
+    /// No translation exists in this language; the text fields are null
+    pub missing: bool,
+    #[serde(flatten)]
+    pub text: ComparedText,
+}
+
+/// A dua's own text side by side with translations, for reviewing them
+#[derive(Debug, Clone, Serialize)]
+pub struct TranslationComparison {
+    pub dua_id: Uuid,
+    pub arabic_text: String,
+    pub base: ComparedText,
+    pub translations: Vec<ComparedTranslation>,
+}
+
+impl TranslationComparison {
+    pub fn new(dua: Dua, translations: &[DuaTranslation], languages: &[String]) -> Self {
+        let translations = languages
+            .iter()
+            .map(|lang| {
+                match translations.iter().find(|t| t.language_code.eq_ignore_ascii_case(lang)) {
+                    Some(t) => ComparedTranslation {
+                        language_code: lang.clone(),
+                        missing: false,
+                        text: ComparedText {
+                            title: t.title.clone(),
+                            translation: t.translation.clone(),
+                            transliteration: t.transliteration.clone(),
+                        },
+                    },
+                    None => ComparedTranslation {
+                        language_code: lang.clone(),
+                        missing: true,
+                        text: ComparedText::default(),
+                    },
+                }
+            })
+            .collect();
+        
+        Self {
+            dua_id: dua.id,
+            arabic_text: dua.arabic_text,
+            base: ComparedText {
+                title: Some(dua.title),
+                translation: Some(dua.translation),
+                transliteration: dua.transliteration,
+            },
+            translations,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct DuaCategory {
     pub id: Uuid,
@@ -218,6 +595,7 @@ pub struct DuaCategory {
     pub description: Option<String>,
     pub parent_id: Option<Uuid>,
     pub sort_order: i32,
+    #[serde(with = "shared::time::rfc3339")]
     pub created_at: DateTime<Utc>,
 }
 
@@ -226,6 +604,7 @@ pub struct DuaTag {
     pub id: Uuid,
     pub name: String,
     pub slug: String,
+    #[serde(with = "shared::time::rfc3339")]
     pub created_at: DateTime<Utc>,
 }
 
@@ -238,6 +617,7 @@ pub struct DuaVariant {
     pub transliteration: Option<String>,
     pub translation: Option<String>,
     pub notes: Option<String>,
+    #[serde(with = "shared::time::rfc3339")]
     pub created_at: DateTime<Utc>,
 }
 
@@ -254,6 +634,7 @@ pub struct DuaSource {
     pub takhrij: Option<String>,
     pub isnad: Option<String>,
     pub notes: Option<String>,
+    #[serde(with = "shared::time::rfc3339")]
     pub created_at: DateTime<Utc>,
 }
 
@@ -269,7 +650,10 @@ pub struct DuaContext {
     pub audible_mode: Option<String>,
     pub addressing_mode: Option<String>,
     pub etiquette_notes: Option<String>,
+    pub calendar_context: Option<Vec<String>>,
+    #[serde(with = "shared::time::rfc3339")]
     pub created_at: DateTime<Utc>,
+    #[serde(with = "shared::time::rfc3339")]
     pub updated_at: DateTime<Utc>,
 }
 
@@ -278,7 +662,7 @@ pub struct DuaMedia {
     pub id: Uuid,
     pub dua_id: Uuid,
     pub media_type: String,
-    pub url: String,
+    pub url: Option<String>,
     pub file_path: Option<String>,
     pub file_size: Option<i32>,
     pub duration: Option<i32>,
@@ -287,6 +671,7 @@ pub struct DuaMedia {
     pub language_code: Option<String>,
     pub license: Option<String>,
     pub metadata: Option<serde_json::Value>,
+    #[serde(with = "shared::time::rfc3339")]
     pub created_at: DateTime<Utc>,
 }
 
@@ -299,7 +684,9 @@ pub struct DuaBundle {
     pub bundle_type: Option<String>,
     pub is_ruqyah: bool,
     pub metadata: Option<serde_json::Value>,
+    #[serde(with = "shared::time::rfc3339")]
     pub created_at: DateTime<Utc>,
+    #[serde(with = "shared::time::rfc3339")]
     pub updated_at: DateTime<Utc>,
 }
 
@@ -320,6 +707,7 @@ pub struct DuaRelation {
     pub target_dua_id: Uuid,
     pub relation_type: String,
     pub notes: Option<String>,
+    #[serde(with = "shared::time::rfc3339")]
     pub created_at: DateTime<Utc>,
 }
 
@@ -336,11 +724,31 @@ pub struct DuaWithRelations {
     pub tags: Option<Vec<DuaTag>>,
     pub translations: Option<Vec<DuaTranslation>>,
     pub variants: Option<Vec<DuaVariant>>,
+    /// Language the title and translation were localized to, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lang: Option<String>,
+}
+
+impl DuaWithRelations {
+    /// Serves the fields a translation provides in its language; fields it
+    /// leaves empty keep the dua's own text.
+    pub fn localize(&mut self, translation: &DuaTranslation) {
+        if let Some(ref title) = translation.title {
+            self.dua.title = title.clone();
+        }
+        if let Some(ref text) = translation.translation {
+            self.dua.translation = text.clone();
+        }
+        if let Some(ref transliteration) = translation.transliteration {
+            self.dua.transliteration = Some(transliteration.clone());
+        }
+        self.lang = Some(translation.language_code.clone());
+    }
 }
 
 // ============= REQUEST/RESPONSE MODELS =============
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Clone, Default)]
 pub struct DuaQueryParams {
     // Pagination
     pub page: Option<u32>,
@@ -353,6 +761,9 @@ pub struct DuaQueryParams {
     // Field selection
     pub fields: Option<String>,
     pub include: Option<String>,
+    // Unknown include names are rejected unless this is false
+    #[serde(default, deserialize_with = "deserialize_bool_from_string")]
+    pub strict_include: Option<bool>,
     
     // Language
     pub lang: Option<String>,
@@ -375,7 +786,10 @@ pub struct DuaQueryParams {
     pub audible_mode: Option<String>,
     pub addressing_mode: Option<String>,
     pub repetitions: Option<String>,
+    // Seasonal context from dua_context.calendar_context, e.g. ramadan, hajj, arafah
     pub calendar: Option<String>,
+    // Hijri month (1-12); matches duas tagged with any occasion in that month
+    pub hijri_month: Option<u32>,
     pub bundle: Option<String>,
     
     #[serde(default, deserialize_with = "deserialize_bool_from_string")]
@@ -386,6 +800,73 @@ pub struct DuaQueryParams {
 
     pub reciter_style: Option<String>,
     pub popularity_min: Option<f64>,
+
+    // Date ranges, as RFC 3339 timestamps or YYYY-MM-DD dates (UTC)
+    pub created_after: Option<String>,
+    pub created_before: Option<String>,
+    pub updated_after: Option<String>,
+    pub updated_before: Option<String>,
+
+    // Deterministic selection for /duas/random
+    pub seed: Option<String>,
+
+    // Editor overrides, honoured only for admin requests; public queries
+    // only see active duas
+    pub status: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_bool_from_string")]
+    pub include_drafts: Option<bool>,
+
+    // Only run the count query; `duas` comes back empty
+    #[serde(default, deserialize_with = "deserialize_bool_from_string")]
+    pub count_only: Option<bool>,
+}
+
+/// Relations that can be requested with `include`
+pub const INCLUDE_RELATIONS: &[&str] = &[
+    "sources",
+    "context",
+    "media",
+    "categories",
+    "tags",
+    "translations",
+    "variants",
+];
+
+/// Checks a comma-separated `include` list against `INCLUDE_RELATIONS`.
+/// Lenient (non-strict) requests skip unknown names as before.
+pub fn validate_include(include: Option<&str>, strict: bool) -> Result<(), String> {
+    let Some(include) = include else {
+        return Ok(());
+    };
+    if !strict {
+        return Ok(());
+    }
+
+    let invalid: Vec<&str> = include
+        .split(',')
+        .map(str::trim)
+        .filter(|token| !token.is_empty() && !INCLUDE_RELATIONS.contains(token))
+        .collect();
+    if invalid.is_empty() {
+        return Ok(());
+    }
+
+    Err(format!(
+        "Unknown include value(s): {}. Valid values are: {}",
+        invalid.join(", "),
+        INCLUDE_RELATIONS.join(", ")
+    ))
+}
+
+/// Parses an RFC 3339 timestamp or a plain date, treated as midnight UTC.
+pub fn parse_date_param(name: &str, value: &str) -> Result<DateTime<Utc>, String> {
+    if let Ok(datetime) = DateTime::parse_from_rfc3339(value) {
+        return Ok(datetime.with_timezone(&Utc));
+    }
+
+    chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map(|date| Utc.from_utc_datetime(&date.and_hms_opt(0, 0, 0).unwrap()))
+        .map_err(|_| format!("{} must be an RFC 3339 timestamp or YYYY-MM-DD date", name))
 }
 
 impl DuaQueryParams {
@@ -393,22 +874,62 @@ impl DuaQueryParams {
         self.page.unwrap_or(1).max(1)
     }
 
+    /// Handlers clamp `per_page` to the configured maximum with
+    /// `apply_pagination` before this is read
     pub fn get_per_page(&self) -> u32 {
-        self.per_page.unwrap_or(20).min(100).max(1)
+        self.per_page.unwrap_or(20).max(1)
+    }
+
+    pub fn apply_pagination(&mut self, pagination: &PaginationConfig) {
+        self.per_page = Some(pagination.per_page(self.per_page));
     }
 
     pub fn get_offset(&self) -> u32 {
         (self.get_page() - 1) * self.get_per_page()
     }
+
+    pub fn check_include(&self) -> Result<(), String> {
+        validate_include(self.include.as_deref(), self.strict_include.unwrap_or(true))
+    }
+
+    /// `duas.status` values visible for this query: active duas by default,
+    /// an explicit `status`, or active plus drafts with `include_drafts`.
+    pub fn visible_statuses(&self) -> Result<Vec<&'static str>, String> {
+        let mut statuses = match self.status {
+            Some(ref status) => status.parse::<Status>()?.db_values().to_vec(),
+            None => Status::Active.db_values().to_vec(),
+        };
+
+        if self.include_drafts.unwrap_or(false) && !statuses.contains(&"draft") {
+            statuses.extend_from_slice(Status::Draft.db_values());
+        }
+
+        Ok(statuses)
+    }
+
+    /// Drops `status` and `include_drafts` so the query sees only active
+    /// duas. Applied to every request that isn't an admin's.
+    pub fn clear_editor_overrides(&mut self) {
+        self.status = None;
+        self.include_drafts = None;
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct DuaListResponse {
-    pub duas: Vec<DuaWithRelations>,
-    pub total: i64,
-    pub page: u32,
-    pub per_page: u32,
-    pub total_pages: u32,
+pub type DuaListResponse = Paginated<DuaWithRelations>;
+
+impl PageItem for Dua {
+    const FIELD: &'static str = "duas";
+}
+
+impl PageItem for DuaWithRelations {
+    const FIELD: &'static str = "duas";
+}
+
+#[derive(Debug, Serialize)]
+pub struct OccasionDuasResponse {
+    pub occasion: &'static Occasion,
+    #[serde(flatten)]
+    pub list: DuaListResponse,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -448,15 +969,371 @@ pub struct MediaListResponse {
     pub total: i64,
 }
 
+// ============= MEDIA CREATION =============
+
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct CreateMediaRequest {
+    pub media_type: String,
+    #[validate(url)]
+    pub url: Option<String>,
+    #[validate(length(min = 1, max = 1000))]
+    pub file_path: Option<String>,
+    #[validate(range(min = 0))]
+    pub file_size: Option<i32>,
+    // Seconds; read from the header of a WAV `file_path` (when a media
+    // directory is configured) or `url` when omitted
+    #[validate(range(min = 0))]
+    pub duration: Option<i32>,
+    #[validate(length(max = 200))]
+    pub reciter_name: Option<String>,
+    pub reciter_style: Option<String>,
+    #[validate(length(min = 2, max = 10))]
+    pub language_code: Option<String>,
+    pub license: Option<String>,
+    pub metadata: Option<serde_json::Value>,
+}
+
+impl CreateMediaRequest {
+    /// Field validation plus checks against the media enums.
+    pub fn check(&self) -> Result<(), String> {
+        self.validate().map_err(|e| format!("Validation failed: {}", e))?;
+
+        self.media_type.parse::<MediaType>()?;
+        if let Some(ref license) = self.license {
+            license.parse::<License>()?;
+        }
+        if let Some(ref reciter_style) = self.reciter_style {
+            reciter_style.parse::<ReciterStyle>()?;
+        }
+
+        if self.url.is_none() && self.file_path.is_none() {
+            return Err("Either url or file_path is required".to_string());
+        }
+        if let Some(ref file_path) = self.file_path {
+            if file_path.split(['/', '\\']).any(|part| part == "..") {
+                return Err("file_path must not contain '..'".to_string());
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Bytes of a canonical PCM WAV header, enough to work out the duration
+pub const WAV_HEADER_LEN: usize = 44;
+
+/// Reads the duration in whole seconds from the first bytes of a PCM WAV
+/// file. Other formats need a real decoder, so they are left for the
+/// caller to supply.
+pub fn wav_header_duration(header: &[u8]) -> Option<i32> {
+    let header = header.get(..WAV_HEADER_LEN)?;
+    if &header[0..4] != b"RIFF" || &header[8..12] != b"WAVE" {
+        return None;
+    }
+
+    let byte_rate = u32::from_le_bytes(header[28..32].try_into().ok()?);
+    let data_size = u32::from_le_bytes(header[40..44].try_into().ok()?);
+    if byte_rate == 0 {
+        return None;
+    }
+
+    Some((data_size / byte_rate) as i32)
+}
+
+// ============= CONTENT REPORTS =============
+
+/// The part of a dua a correction report is about
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportField {
+    Title,
+    ArabicText,
+    Transliteration,
+    Translation,
+    Sources,
+    Context,
+    Other,
+}
+
+impl ReportField {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReportField::Title => "title",
+            ReportField::ArabicText => "arabic_text",
+            ReportField::Transliteration => "transliteration",
+            ReportField::Translation => "translation",
+            ReportField::Sources => "sources",
+            ReportField::Context => "context",
+            ReportField::Other => "other",
+        }
+    }
+}
+
+impl std::str::FromStr for ReportField {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "title" => Ok(ReportField::Title),
+            "arabic_text" => Ok(ReportField::ArabicText),
+            "transliteration" => Ok(ReportField::Transliteration),
+            "translation" => Ok(ReportField::Translation),
+            "sources" => Ok(ReportField::Sources),
+            "context" => Ok(ReportField::Context),
+            "other" => Ok(ReportField::Other),
+            _ => Err(format!(
+                "Unknown field '{}'. Valid values: title, arabic_text, transliteration, translation, sources, context, other",
+                s
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportStatus {
+    Open,
+    Resolved,
+    Dismissed,
+}
+
+impl ReportStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReportStatus::Open => "open",
+            ReportStatus::Resolved => "resolved",
+            ReportStatus::Dismissed => "dismissed",
+        }
+    }
+}
+
+impl std::str::FromStr for ReportStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "open" => Ok(ReportStatus::Open),
+            "resolved" => Ok(ReportStatus::Resolved),
+            "dismissed" => Ok(ReportStatus::Dismissed),
+            _ => Err(format!(
+                "Unknown status '{}'. Valid values: open, resolved, dismissed",
+                s
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct CreateReportRequest {
+    /// Name or contact of whoever reported it; anonymous reports are fine
+    #[validate(length(max = 200))]
+    pub reporter: Option<String>,
+    pub field: String,
+    #[validate(length(min = 1, max = 5000))]
+    pub message: String,
+}
+
+impl CreateReportRequest {
+    /// Field validation plus the reported field name, returned parsed
+    pub fn check(&self) -> Result<ReportField, String> {
+        self.validate().map_err(|e| format!("Validation failed: {}", e))?;
+        if self.message.trim().is_empty() {
+            return Err("message must not be blank".to_string());
+        }
+        self.field.parse()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct DuaReport {
+    pub id: Uuid,
+    pub dua_id: Uuid,
+    pub reporter: Option<String>,
+    pub field: String,
+    pub message: String,
+    pub status: String,
+    #[serde(with = "shared::time::rfc3339")]
+    pub created_at: DateTime<Utc>,
+    #[serde(with = "shared::time::rfc3339")]
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReportQueryParams {
+    /// Defaults to open reports
+    pub status: Option<String>,
+}
+
+// ============= BULK IMPORT =============
+
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct DuaImportPayload {
+    #[validate(length(min = 1, max = 500))]
+    pub title: String,
+    #[validate(length(min = 1))]
+    pub arabic_text: String,
+    pub transliteration: Option<String>,
+    #[validate(length(min = 1))]
+    pub translation: String,
+    #[validate(length(min = 1, max = 500), custom = "validate_slug")]
+    pub slug: String,
+    pub dua_type: Option<String>,
+    pub status: Option<String>,
+    pub sources: Option<Vec<DuaSourcePayload>>,
+    pub context: Option<DuaContextPayload>,
+    // Slugs of existing tags
+    pub tags: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DuaSourcePayload {
+    pub source_type: SourceType,
+    pub reference_text: Option<String>,
+    pub book_name: Option<String>,
+    pub chapter: Option<String>,
+    pub hadith_number: Option<String>,
+    pub authenticity: Option<Authenticity>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DuaContextPayload {
+    pub invocation_time: Option<Vec<String>>,
+    pub event_trigger: Option<Vec<String>>,
+    pub posture: Option<Vec<String>>,
+    pub repetition_count: Option<i32>,
+    pub calendar_context: Option<Vec<String>>,
+    pub etiquette_notes: Option<String>,
+}
+
+fn validate_slug(slug: &str) -> Result<(), validator::ValidationError> {
+    let valid = slug
+        .chars()
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-');
+    if !valid || slug.starts_with('-') || slug.ends_with('-') {
+        return Err(validator::ValidationError::new(
+            "slug must contain only lowercase letters, digits and hyphens",
+        ));
+    }
+    Ok(())
+}
+
+impl DuaImportPayload {
+    /// Field validation plus checks on the free-text enum columns.
+    pub fn check(&self) -> Result<(), String> {
+        self.validate().map_err(|e| format!("Validation failed: {}", e))?;
+
+        if let Some(ref dua_type) = self.dua_type {
+            dua_type.parse::<DuaType>()?;
+        }
+        if let Some(ref status) = self.status {
+            status.parse::<Status>()?;
+        }
+        if let Some(count) = self.context.as_ref().and_then(|c| c.repetition_count) {
+            if count < 1 {
+                return Err("context.repetition_count must be a positive integer".to_string());
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BulkImportFailure {
+    pub index: usize,
+    pub error: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BulkImportResponse {
+    pub inserted: usize,
+    pub failed: Vec<BulkImportFailure>,
+}
+
 #[derive(Debug, Deserialize, Validate)]
 pub struct SemanticSearchRequest {
     #[validate(length(min = 1, max = 500))]
     pub query: String,
+    #[validate(range(min = 1))]
     pub limit: Option<u32>,
     pub threshold: Option<f64>,
+    pub min_authenticity: Option<String>,
+    pub verified_only: Option<bool>,
+    /// When set the search runs in the background and the results are
+    /// POSTed here; the response is a job that can also be polled
+    #[validate(url)]
+    pub callback_url: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Authenticity restrictions shared by keyword and semantic search
+#[derive(Debug, Clone, Default)]
+pub struct SearchFilters {
+    /// Keep duas with at least one source graded at or above this level
+    pub min_authenticity: Option<Authenticity>,
+    /// Keep duas with at least one graded (not Unclassified) source
+    pub verified_only: bool,
+}
+
+impl SearchFilters {
+    pub fn parse(min_authenticity: Option<&str>, verified_only: bool) -> Result<Self, String> {
+        let min_authenticity = min_authenticity
+            .filter(|s| !s.trim().is_empty())
+            .map(|s| s.trim().parse::<Authenticity>())
+            .transpose()?;
+
+        Ok(Self {
+            min_authenticity,
+            verified_only,
+        })
+    }
+
+    /// Source grades a dua must have one of, or `None` when unrestricted
+    pub fn allowed_authenticities(&self) -> Option<Vec<String>> {
+        let mut allowed = match &self.min_authenticity {
+            Some(min) => min.at_least(),
+            None if self.verified_only => Authenticity::Daif.at_least(),
+            None => return None,
+        };
+        if self.verified_only {
+            allowed.retain(|a| a != Authenticity::Unclassified.as_str());
+        }
+        Some(allowed)
+    }
+}
+
+/// The trimmed `q` of a keyword search or autocomplete. A blank query would
+/// match every row, so it's either refused or `None`, meaning the caller
+/// answers with no results without querying.
+pub fn search_query(q: Option<&str>, policy: EmptyQueryPolicy) -> Result<Option<String>, String> {
+    let query = q.map(str::trim).unwrap_or_default();
+    if !query.is_empty() {
+        return Ok(Some(query.to_string()));
+    }
+    
+    match policy {
+        EmptyQueryPolicy::Empty => Ok(None),
+        EmptyQueryPolicy::Reject => Err("Query parameter 'q' is required".to_string()),
+    }
+}
+
+/// A search's `limit` parameter: `default` when absent, clamped to the
+/// configured maximum, and rejected unless it's a positive number.
+pub fn search_limit(limit: Option<&str>, default: u32, search: &SearchConfig) -> Result<u32, String> {
+    let requested = match limit.map(str::trim) {
+        None => None,
+        Some(raw) => match raw.parse::<u32>() {
+            Ok(limit) if limit > 0 => Some(limit),
+            _ => return Err(format!("limit must be a positive number, got '{}'", raw)),
+        },
+    };
+    Ok(search.limit(requested, default))
+}
+
+/// Whether an autocomplete prefix is long enough to look up. Single
+/// characters match a large share of titles and are mostly noise.
+pub fn meets_min_length(query: &str, min_length: usize) -> bool {
+    query.chars().count() >= min_length
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SearchResponse {
     pub results: Vec<DuaWithRelations>,
     pub total: i64,
@@ -469,7 +1346,64 @@ pub struct SuggestResponse {
     pub suggestions: Vec<String>,
 }
 
+/// One typed group of results in a unified search
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SearchGroup<T> {
+    #[serde(rename = "type")]
+    pub result_type: String,
+    pub count: usize,
+    pub results: Vec<T>,
+}
+
+impl<T> SearchGroup<T> {
+    pub fn new(result_type: &str, results: Vec<T>) -> Self {
+        Self {
+            result_type: result_type.to_string(),
+            count: results.len(),
+            results,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UnifiedSearchResponse {
+    pub query: String,
+    pub total: usize,
+    pub duas: SearchGroup<Dua>,
+    pub categories: SearchGroup<DuaCategory>,
+    pub tags: SearchGroup<DuaTag>,
+    pub bundles: SearchGroup<DuaBundle>,
+}
+
+/// A tag or category an editor may want to assign, with why it was suggested
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LabelSuggestion {
+    pub slug: String,
+    pub name: String,
+    pub confidence: f64,
+    /// Words from the dua that triggered the suggestion
+    pub matched: Vec<String>,
+}
+
+impl LabelSuggestion {
+    pub fn new(label: &crate::suggestions::Label, confidence: f64, matched: Vec<String>) -> Self {
+        Self {
+            slug: label.slug.to_string(),
+            name: label.name.to_string(),
+            confidence,
+            matched,
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
+pub struct TagSuggestionResponse {
+    pub dua_id: Uuid,
+    pub tags: Vec<LabelSuggestion>,
+    pub categories: Vec<LabelSuggestion>,
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow)]
 pub struct StatsResponse {
     pub total_duas: i64,
     pub verified_duas: i64,
@@ -481,7 +1415,7 @@ pub struct StatsResponse {
 }
 
 // Media search parameters
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Default)]
 pub struct MediaQueryParams {
     pub media_type: Option<String>,
     pub license: Option<String>,
@@ -491,11 +1425,173 @@ pub struct MediaQueryParams {
 }
 
 // Source search parameters
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Default)]
 pub struct SourceQueryParams {
     pub source_type: Option<String>,
     pub authenticity: Option<String>,
     pub q: Option<String>,
     pub page: Option<u32>,
     pub per_page: Option<u32>,
-}
\ No newline at end of file
+}
+
+impl MediaQueryParams {
+    pub fn apply_pagination(&mut self, pagination: &PaginationConfig) {
+        self.per_page = Some(pagination.per_page(self.per_page));
+    }
+
+    /// (page, per_page, offset) after `apply_pagination`
+    pub fn page_window(&self) -> (u32, u32, u32) {
+        page_window(self.page, self.per_page)
+    }
+}
+
+impl SourceQueryParams {
+    pub fn apply_pagination(&mut self, pagination: &PaginationConfig) {
+        self.per_page = Some(pagination.per_page(self.per_page));
+    }
+
+    /// (page, per_page, offset) after `apply_pagination`
+    pub fn page_window(&self) -> (u32, u32, u32) {
+        page_window(self.page, self.per_page)
+    }
+}
+
+fn page_window(page: Option<u32>, per_page: Option<u32>) -> (u32, u32, u32) {
+    let page = page.unwrap_or(1).max(1);
+    let per_page = per_page.unwrap_or(20).max(1);
+    (page, per_page, (page - 1) * per_page)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_blank_search_query_is_short_circuited_or_rejected() {
+        assert_eq!(
+            search_query(Some("  morning "), EmptyQueryPolicy::Reject),
+            Ok(Some("morning".to_string()))
+        );
+        assert_eq!(search_query(Some(" \t"), EmptyQueryPolicy::Empty), Ok(None));
+        assert_eq!(search_query(None, EmptyQueryPolicy::Empty), Ok(None));
+        assert!(search_query(Some("   "), EmptyQueryPolicy::Reject).is_err());
+    }
+
+    #[test]
+    fn test_misspelled_include_is_rejected_with_valid_options() {
+        let error = validate_include(Some("souces"), true).unwrap_err();
+        assert!(error.contains("souces"), "{}", error);
+        assert!(error.contains("sources, context, media"), "{}", error);
+
+        assert!(validate_include(Some("sources, tags"), true).is_ok());
+        assert!(validate_include(Some("souces"), false).is_ok());
+
+        let params: DuaQueryParams = serde_json::from_value(serde_json::json!({
+            "include": "sources,souces",
+            "strict_include": "false"
+        }))
+        .unwrap();
+        assert!(params.check_include().is_ok());
+    }
+
+    #[test]
+    fn test_report_field_must_be_known() {
+        let mut request = CreateReportRequest {
+            reporter: None,
+            field: "arabic_text".to_string(),
+            message: "Missing shadda on the second word".to_string(),
+        };
+        assert_eq!(request.check().unwrap(), ReportField::ArabicText);
+
+        request.field = "arabic".to_string();
+        let error = request.check().unwrap_err();
+        assert!(error.contains("arabic_text"), "{}", error);
+    }
+
+    fn test_dua(title: &str) -> Dua {
+        Dua {
+            id: Uuid::new_v4(),
+            title: title.to_string(),
+            arabic_text: "نص".to_string(),
+            transliteration: None,
+            translation: "Text".to_string(),
+            slug: title.to_lowercase(),
+            status: "active".to_string(),
+            version: 1,
+            popularity_score: 0.5,
+            dua_type: "dua".to_string(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_paginated_duas_round_total_pages_up() {
+        let page = Paginated::new(vec![test_dua("Morning"), test_dua("Evening")], 21, 1, 20);
+        assert_eq!(page.total_pages, 2);
+
+        let value = serde_json::to_value(&page).unwrap();
+        assert_eq!(value["duas"].as_array().unwrap().len(), 2);
+        assert_eq!(value["total"], 21);
+        assert_eq!(value["per_page"], 20);
+        assert_eq!(value["total_pages"], 2);
+        assert!(value.get("items").is_none());
+        assert!(shared::time::is_rfc3339_utc(value["duas"][0]["created_at"].as_str().unwrap()));
+
+        assert_eq!(Paginated::<Dua>::new(Vec::new(), 40, 2, 20).total_pages, 2);
+    }
+
+    #[test]
+    fn test_search_limit_is_clamped_to_cap() {
+        let search = SearchConfig::default();
+
+        assert_eq!(search_limit(Some("100000"), 20, &search), Ok(100));
+        assert_eq!(search_limit(Some("15"), 20, &search), Ok(15));
+        assert_eq!(search_limit(None, 20, &search), Ok(20));
+        for invalid in ["0", "-5", "ten"] {
+            assert!(search_limit(Some(invalid), 20, &search).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_per_page_is_clamped_to_configured_maximum() {
+        let pagination = PaginationConfig::default();
+
+        let mut params = DuaQueryParams {
+            per_page: Some(1000),
+            ..Default::default()
+        };
+        params.apply_pagination(&pagination);
+        assert_eq!(params.get_per_page(), 100);
+
+        let mut sources = SourceQueryParams {
+            per_page: Some(1000),
+            page: Some(3),
+            ..Default::default()
+        };
+        sources.apply_pagination(&pagination);
+        assert_eq!(sources.page_window(), (3, 100, 200));
+
+        let mut media = MediaQueryParams::default();
+        media.apply_pagination(&PaginationConfig {
+            default_per_page: 20,
+            max_per_page: 10,
+        });
+        assert_eq!(media.page_window(), (1, 10, 0));
+    }
+
+    #[test]
+    fn test_wav_duration_is_read_from_header_bytes() {
+        let mut header = [0u8; WAV_HEADER_LEN];
+        header[0..4].copy_from_slice(b"RIFF");
+        header[8..12].copy_from_slice(b"WAVE");
+        header[28..32].copy_from_slice(&16_000u32.to_le_bytes());
+        header[40..44].copy_from_slice(&48_000u32.to_le_bytes());
+
+        assert_eq!(wav_header_duration(&header), Some(3));
+        assert_eq!(wav_header_duration(&header[..40]), None);
+
+        header[8..12].copy_from_slice(b"AVI ");
+        assert_eq!(wav_header_duration(&header), None);
+    }
+}
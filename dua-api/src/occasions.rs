@@ -0,0 +1,80 @@
+// Occasions in the Hijri calendar that duas are tagged with through
+// `dua_context.calendar_context`. The slugs here are the values stored in
+// that column, so the table only adds when each occasion falls.
+
+use chrono::{Datelike, NaiveDate};
+use hijri_date::HijriDate;
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Occasion {
+    pub slug: &'static str,
+    pub name: &'static str,
+    pub hijri_month: u32,
+    /// First and last day of the month the occasion covers, inclusive
+    pub first_day: u32,
+    pub last_day: u32,
+}
+
+pub const OCCASIONS: &[Occasion] = &[
+    Occasion { slug: "ashura", name: "Ashura", hijri_month: 1, first_day: 9, last_day: 10 },
+    Occasion { slug: "ramadan", name: "Ramadan", hijri_month: 9, first_day: 1, last_day: 30 },
+    Occasion { slug: "laylat_al_qadr", name: "Laylat al-Qadr", hijri_month: 9, first_day: 21, last_day: 30 },
+    Occasion { slug: "eid_al_fitr", name: "Eid al-Fitr", hijri_month: 10, first_day: 1, last_day: 3 },
+    Occasion { slug: "dhul_hijjah", name: "First ten days of Dhul-Hijjah", hijri_month: 12, first_day: 1, last_day: 10 },
+    Occasion { slug: "hajj", name: "Hajj", hijri_month: 12, first_day: 8, last_day: 13 },
+    Occasion { slug: "arafah", name: "Day of Arafah", hijri_month: 12, first_day: 9, last_day: 9 },
+    Occasion { slug: "eid_al_adha", name: "Eid al-Adha", hijri_month: 12, first_day: 10, last_day: 13 },
+];
+
+pub fn find_occasion(slug: &str) -> Option<&'static Occasion> {
+    OCCASIONS.iter().find(|o| o.slug.eq_ignore_ascii_case(slug))
+}
+
+/// Slugs of every occasion that falls in a Hijri month
+pub fn occasion_slugs_in_month(month: u32) -> Vec<String> {
+    OCCASIONS
+        .iter()
+        .filter(|o| o.hijri_month == month)
+        .map(|o| o.slug.to_string())
+        .collect()
+}
+
+/// The occasion on a Hijri day. When occasions overlap (Arafah falls during
+/// Hajj) the shortest one wins, as it is the more specific.
+pub fn occasion_on(hijri_month: u32, hijri_day: u32) -> Option<&'static Occasion> {
+    OCCASIONS
+        .iter()
+        .filter(|o| o.hijri_month == hijri_month && (o.first_day..=o.last_day).contains(&hijri_day))
+        .min_by_key(|o| o.last_day - o.first_day)
+}
+
+/// The occasion on a Gregorian date, using the tabular Hijri calendar. This
+/// can be a day off from local moon sighting.
+pub fn occasion_on_date(date: NaiveDate) -> Option<&'static Occasion> {
+    let hijri = HijriDate::from_gr(date.year() as usize, date.month() as usize, date.day() as usize).ok()?;
+    occasion_on(hijri.month() as u32, hijri.day() as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_most_specific_occasion_wins() {
+        assert_eq!(occasion_on(9, 5).unwrap().slug, "ramadan");
+        assert_eq!(occasion_on(9, 27).unwrap().slug, "laylat_al_qadr");
+        assert_eq!(occasion_on(12, 9).unwrap().slug, "arafah");
+        assert!(occasion_on(2, 1).is_none());
+
+        let date = NaiveDate::from_ymd_opt(2025, 3, 15).unwrap();
+        assert_eq!(occasion_on_date(date).unwrap().slug, "ramadan");
+    }
+
+    #[test]
+    fn test_occasion_slugs_in_month() {
+        assert_eq!(occasion_slugs_in_month(9), vec!["ramadan", "laylat_al_qadr"]);
+        assert!(occasion_slugs_in_month(2).is_empty());
+        assert_eq!(find_occasion("Ramadan").unwrap().hijri_month, 9);
+    }
+}
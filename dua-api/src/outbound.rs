@@ -0,0 +1,124 @@
+// Outbound HTTP to URLs that clients supply: search job callbacks and media
+// fetched to read their duration.
+//
+// A URL is only called when every address its host resolves to is publicly
+// routable, and the request is pinned to the vetted address so a second
+// lookup cannot swap it out. Redirects are not followed.
+
+use reqwest::{redirect, Url};
+use shared::error::{ApiError, ApiResult};
+use std::{
+    net::{IpAddr, SocketAddr},
+    time::Duration,
+};
+
+/// A client that can only reach `url`'s host, at a public address it
+/// resolved to. `field` names the URL in validation errors.
+pub async fn public_client(
+    url: &str,
+    field: &str,
+    timeout: Duration,
+) -> ApiResult<reqwest::Client> {
+    let (host, addr) = resolve_public(url, field).await?;
+    reqwest::Client::builder()
+        .timeout(timeout)
+        .redirect(redirect::Policy::none())
+        .resolve(&host, addr)
+        .build()
+        .map_err(|e| ApiError::internal(format!("Failed to build HTTP client: {}", e)))
+}
+
+/// Resolves a URL's host, returning it with the address to connect to.
+/// Every resolved address must be public, so private, loopback, link-local
+/// and cloud metadata endpoints are rejected.
+pub async fn resolve_public(url: &str, field: &str) -> ApiResult<(String, SocketAddr)> {
+    let rejected = |reason: &str| ApiError::validation(format!("Invalid {}: {}", field, reason));
+
+    let parsed = Url::parse(url).map_err(|_| rejected("not a valid URL"))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(rejected("only http and https are supported"));
+    }
+    let host = parsed.host_str().ok_or_else(|| rejected("missing host"))?;
+    let port = parsed
+        .port_or_known_default()
+        .ok_or_else(|| rejected("missing port"))?;
+
+    let lookup = host.trim_start_matches('[').trim_end_matches(']');
+    let addrs: Vec<SocketAddr> = tokio::net::lookup_host((lookup, port))
+        .await
+        .map_err(|_| rejected("host does not resolve"))?
+        .collect();
+
+    match addrs.first() {
+        None => Err(rejected("host does not resolve")),
+        Some(_) if !addrs.iter().all(|addr| is_public(addr.ip())) => {
+            Err(rejected("host must resolve to a public address"))
+        }
+        Some(&addr) => Ok((host.to_string(), addr)),
+    }
+}
+
+fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => {
+            let [a, b, ..] = v4.octets();
+            !(v4.is_private()
+                || v4.is_loopback()
+                || v4.is_link_local()
+                || v4.is_broadcast()
+                || v4.is_documentation()
+                || v4.is_unspecified()
+                || a == 0
+                // Carrier-grade NAT, 100.64.0.0/10
+                || (a == 100 && (b & 0xc0) == 64))
+        }
+        IpAddr::V6(v6) => match v6.to_ipv4_mapped() {
+            Some(v4) => is_public(IpAddr::V4(v4)),
+            None => {
+                !(v6.is_loopback()
+                    || v6.is_unspecified()
+                    || v6.is_unique_local()
+                    || v6.is_unicast_link_local())
+            }
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_public() {
+        for ip in ["8.8.8.8", "2606:4700::1111"] {
+            assert!(is_public(ip.parse().unwrap()), "{}", ip);
+        }
+        for ip in [
+            "192.168.1.1",
+            "172.16.0.1",
+            "100.64.0.1",
+            "0.0.0.0",
+            "::ffff:127.0.0.1",
+            "fd00:ec2::254",
+            "fe80::1",
+        ] {
+            assert!(!is_public(ip.parse().unwrap()), "{}", ip);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_internal_urls_are_rejected() {
+        for url in [
+            "http://127.0.0.1/a.wav",
+            "http://localhost/a.wav",
+            "ftp://example.com/a.wav",
+        ] {
+            let result = resolve_public(url, "url").await;
+            assert!(
+                matches!(result, Err(ApiError::Validation(ref m)) if m.starts_with("Invalid url")),
+                "{}",
+                url
+            );
+        }
+    }
+}
@@ -1,9 +1,92 @@
-use shared::{database::Database, error::ApiResult};
-use sqlx::QueryBuilder;
-use tracing::{debug, info};
+use futures::TryStreamExt;
+use shared::{database::Database, error::{ApiError, ApiResult}};
+use sqlx::{postgres::PgRow, FromRow, QueryBuilder};
+use std::collections::{HashMap, HashSet};
+use tokio::sync::mpsc;
+use tracing::{debug, info, warn};
 use uuid::Uuid;
 
-use crate::models::*;
+use crate::{models::*, occasions::occasion_slugs_in_month};
+
+/// Lines buffered between the export cursor and the response body
+const EXPORT_CHANNEL_CAPACITY: usize = 64;
+/// Duas read from the export cursor before their relations are loaded
+const EXPORT_PAGE_SIZE: usize = 200;
+
+/// A category row with the dua it is mapped to
+#[derive(FromRow)]
+struct MappedCategory {
+    dua_id: Uuid,
+    #[sqlx(flatten)]
+    category: DuaCategory,
+}
+
+/// A tag row with the dua it is mapped to
+#[derive(FromRow)]
+struct MappedTag {
+    dua_id: Uuid,
+    #[sqlx(flatten)]
+    tag: DuaTag,
+}
+
+/// Groups rows by their dua, keeping the query's order within each dua
+fn group_by_dua<T, V>(rows: Vec<T>, split: impl Fn(T) -> (Uuid, V)) -> HashMap<Uuid, Vec<V>> {
+    let mut grouped: HashMap<Uuid, Vec<V>> = HashMap::new();
+    for row in rows {
+        let (dua_id, value) = split(row);
+        grouped.entry(dua_id).or_default().push(value);
+    }
+    grouped
+}
+
+/// A dua's rows from a loaded relation, or `None` if it wasn't included
+fn take_for_dua<V>(grouped: &mut Option<HashMap<Uuid, Vec<V>>>, dua_id: &Uuid) -> Option<Vec<V>> {
+    grouped.as_mut().map(|grouped| grouped.remove(dua_id).unwrap_or_default())
+}
+
+/// Escapes `%`, `_` and `\` so user input matches literally in an
+/// `ILIKE ... ESCAPE '\'` pattern
+fn escape_like(input: &str) -> String {
+    let mut escaped = String::with_capacity(input.len());
+    for c in input.chars() {
+        if matches!(c, '%' | '_' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// `ILIKE` pattern matching `input` anywhere in the text
+fn contains_pattern(input: &str) -> String {
+    format!("%{}%", escape_like(input))
+}
+
+const STATS_QUERY: &str = r#"
+    SELECT total_duas, verified_duas, total_categories, total_tags, total_bundles,
+           most_popular_category, recent_additions
+    FROM dua_stats
+"#;
+
+/// Recounts `dua_stats` from the tables; `$1` is the published statuses
+const REFRESH_STATS_QUERY: &str = r#"
+    UPDATE dua_stats SET
+        total_duas = (SELECT COUNT(*) FROM duas),
+        verified_duas = (SELECT COUNT(*) FROM duas WHERE status = ANY($1)),
+        total_categories = (SELECT COUNT(*) FROM dua_categories),
+        total_tags = (SELECT COUNT(*) FROM dua_tags),
+        total_bundles = (SELECT COUNT(*) FROM dua_bundles),
+        most_popular_category = (
+            SELECT c.name
+            FROM dua_categories c
+            JOIN dua_category_map dcm ON c.id = dcm.category_id
+            GROUP BY c.id, c.name
+            ORDER BY COUNT(*) DESC
+            LIMIT 1
+        ),
+        recent_additions = (SELECT COUNT(*) FROM duas WHERE created_at > NOW() - INTERVAL '30 days'),
+        refreshed_at = NOW()
+"#;
 
 pub struct DuaRepository {
     db: Database,
@@ -23,9 +106,9 @@ impl DuaRepository {
             r#"
             INSERT INTO duas (
                 id, title, arabic_text, transliteration, translation, 
-                slug, status, version, popularity_score, created_at, updated_at
+                slug, status, version, popularity_score, dua_type, created_at, updated_at
             )
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
             RETURNING *
             "#,
         )
@@ -38,6 +121,7 @@ impl DuaRepository {
         .bind(dua.status)
         .bind(dua.version)
         .bind(dua.popularity_score)
+        .bind(dua.dua_type)
         .bind(dua.created_at)
         .bind(dua.updated_at)
         .fetch_one(&self.db.pool)
@@ -47,9 +131,29 @@ impl DuaRepository {
         Ok(result)
     }
 
+    /// A published dua by id; drafts and archived duas read as missing.
+    /// Admin writes use `get_any_dua_by_id` instead.
     pub async fn get_dua_by_id(&self, id: Uuid) -> ApiResult<Option<Dua>> {
         debug!("Fetching dua by ID: {}", id);
 
+        let result = sqlx::query_as::<_, Dua>("SELECT * FROM duas WHERE id = $1 AND status = ANY($2)")
+            .bind(id)
+            .bind(Status::Active.db_values())
+            .fetch_optional(&self.db.pool)
+            .await?;
+
+        Ok(result)
+    }
+
+    /// Like `get_dua_by_id`, but a missing or unpublished dua is `NotFound`
+    pub async fn require_dua(&self, id: Uuid) -> ApiResult<Dua> {
+        self.get_dua_by_id(id)
+            .await?
+            .ok_or_else(|| ApiError::not_found(format!("Dua {}", id)))
+    }
+
+    /// A dua by id whatever its status, for the admin endpoints
+    pub async fn get_any_dua_by_id(&self, id: Uuid) -> ApiResult<Option<Dua>> {
         let result = sqlx::query_as::<_, Dua>("SELECT * FROM duas WHERE id = $1")
             .bind(id)
             .fetch_optional(&self.db.pool)
@@ -61,14 +165,205 @@ impl DuaRepository {
     pub async fn get_dua_by_slug(&self, slug: &str) -> ApiResult<Option<Dua>> {
         debug!("Fetching dua by slug: {}", slug);
 
-        let result = sqlx::query_as::<_, Dua>("SELECT * FROM duas WHERE slug = $1")
+        let result = sqlx::query_as::<_, Dua>("SELECT * FROM duas WHERE slug = $1 AND status = ANY($2)")
             .bind(slug)
+            .bind(Status::Active.db_values())
             .fetch_optional(&self.db.pool)
             .await?;
 
         Ok(result)
     }
 
+    // ============= VERSION HISTORY =============
+
+    /// Earlier versions of a dua, newest first. Versions are recorded by a
+    /// trigger on `duas`, so every content edit is captured however it's made.
+    pub async fn list_dua_versions(&self, dua_id: Uuid) -> ApiResult<Vec<DuaVersion>> {
+        let versions = sqlx::query_as::<_, DuaVersion>(
+            "SELECT * FROM dua_versions WHERE dua_id = $1 ORDER BY version DESC"
+        )
+        .bind(dua_id)
+        .fetch_all(&self.db.pool)
+        .await?;
+        
+        Ok(versions)
+    }
+
+    pub async fn get_dua_version(&self, dua_id: Uuid, version: i32) -> ApiResult<Option<DuaVersion>> {
+        let result = sqlx::query_as::<_, DuaVersion>(
+            "SELECT * FROM dua_versions WHERE dua_id = $1 AND version = $2"
+        )
+        .bind(dua_id)
+        .bind(version)
+        .fetch_optional(&self.db.pool)
+        .await?;
+        
+        Ok(result)
+    }
+
+    // ============= CONTENT REPORTS =============
+
+    pub async fn create_report(
+        &self,
+        dua_id: Uuid,
+        field: ReportField,
+        request: &CreateReportRequest,
+    ) -> ApiResult<DuaReport> {
+        let report = sqlx::query_as::<_, DuaReport>(
+            r#"
+            INSERT INTO dua_reports (dua_id, reporter, field, message)
+            VALUES ($1, $2, $3, $4)
+            RETURNING *
+            "#
+        )
+        .bind(dua_id)
+        .bind(request.reporter.as_deref().map(str::trim).filter(|r| !r.is_empty()))
+        .bind(field.as_str())
+        .bind(request.message.trim())
+        .fetch_one(&self.db.pool)
+        .await?;
+        
+        Ok(report)
+    }
+
+    /// Reports in a status, oldest first so triage works through the backlog
+    pub async fn list_reports(&self, status: ReportStatus) -> ApiResult<Vec<DuaReport>> {
+        let reports = sqlx::query_as::<_, DuaReport>(
+            "SELECT * FROM dua_reports WHERE status = $1 ORDER BY created_at"
+        )
+        .bind(status.as_str())
+        .fetch_all(&self.db.pool)
+        .await?;
+        
+        Ok(reports)
+    }
+
+    // ============= BULK IMPORT =============
+
+    /// Imports each entry in its own transaction and reports the ones that fail.
+    pub async fn bulk_import(&self, entries: Vec<serde_json::Value>) -> ApiResult<BulkImportResponse> {
+        let mut seen_slugs = HashSet::new();
+        let mut inserted = 0;
+        let mut failed = Vec::new();
+        
+        for (index, entry) in entries.into_iter().enumerate() {
+            let result = match serde_json::from_value::<DuaImportPayload>(entry) {
+                Ok(payload) => self.import_dua(&payload, &mut seen_slugs).await,
+                Err(e) => Err(format!("Invalid payload: {}", e)),
+            };
+            
+            match result {
+                Ok(id) => {
+                    debug!("Imported dua {} at index {}", id, index);
+                    inserted += 1;
+                }
+                Err(error) => failed.push(BulkImportFailure { index, error }),
+            }
+        }
+        
+        info!("Bulk import finished: {} inserted, {} failed", inserted, failed.len());
+        Ok(BulkImportResponse { inserted, failed })
+    }
+
+    async fn import_dua(
+        &self,
+        payload: &DuaImportPayload,
+        seen_slugs: &mut HashSet<String>,
+    ) -> Result<Uuid, String> {
+        payload.check()?;
+        
+        if !seen_slugs.insert(payload.slug.clone()) {
+            return Err(format!("Duplicate slug '{}' within batch", payload.slug));
+        }
+        
+        let exists: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM duas WHERE slug = $1)")
+            .bind(&payload.slug)
+            .fetch_one(&self.db.pool)
+            .await
+            .map_err(|e| ApiError::from(e).to_string())?;
+        if exists {
+            return Err(format!("Slug '{}' already exists", payload.slug));
+        }
+        
+        self.insert_dua_with_relations(payload)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    async fn insert_dua_with_relations(&self, payload: &DuaImportPayload) -> ApiResult<Uuid> {
+        let mut tx = self.db.pool.begin().await?;
+        
+        let id: Uuid = sqlx::query_scalar(
+            r#"
+            INSERT INTO duas (title, arabic_text, transliteration, translation, slug, dua_type, status)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            RETURNING id
+            "#,
+        )
+        .bind(&payload.title)
+        .bind(&payload.arabic_text)
+        .bind(&payload.transliteration)
+        .bind(&payload.translation)
+        .bind(&payload.slug)
+        .bind(payload.dua_type.as_deref().unwrap_or("dua").to_lowercase())
+        .bind(payload.status.as_deref().unwrap_or("active").to_lowercase())
+        .fetch_one(&mut tx)
+        .await?;
+        
+        for source in payload.sources.iter().flatten() {
+            sqlx::query(
+                r#"
+                INSERT INTO dua_sources (dua_id, source_type, reference_text, book_name, chapter, hadith_number, authenticity)
+                VALUES ($1, $2, $3, $4, $5, $6, $7)
+                "#,
+            )
+            .bind(id)
+            .bind(&source.source_type)
+            .bind(&source.reference_text)
+            .bind(&source.book_name)
+            .bind(&source.chapter)
+            .bind(&source.hadith_number)
+            .bind(source.authenticity.clone().unwrap_or(Authenticity::Unclassified))
+            .execute(&mut tx)
+            .await?;
+        }
+        
+        if let Some(ref context) = payload.context {
+            sqlx::query(
+                r#"
+                INSERT INTO dua_context (dua_id, invocation_time, event_trigger, posture, repetition_count, calendar_context, etiquette_notes)
+                VALUES ($1, $2::text[]::invocation_time_enum[], $3::text[]::event_trigger_enum[], $4::text[]::posture_enum[], $5, $6, $7)
+                "#,
+            )
+            .bind(id)
+            .bind(&context.invocation_time)
+            .bind(&context.event_trigger)
+            .bind(&context.posture)
+            .bind(context.repetition_count)
+            .bind(&context.calendar_context)
+            .bind(&context.etiquette_notes)
+            .execute(&mut tx)
+            .await?;
+        }
+        
+        for tag in payload.tags.iter().flatten() {
+            let tag_id: Option<Uuid> = sqlx::query_scalar("SELECT id FROM dua_tags WHERE slug = $1")
+                .bind(tag)
+                .fetch_optional(&mut tx)
+                .await?;
+            let tag_id = tag_id.ok_or_else(|| ApiError::validation(format!("Unknown tag '{}'", tag)))?;
+            
+            sqlx::query("INSERT INTO dua_tag_map (dua_id, tag_id) VALUES ($1, $2) ON CONFLICT DO NOTHING")
+                .bind(id)
+                .bind(tag_id)
+                .execute(&mut tx)
+                .await?;
+        }
+        
+        tx.commit().await?;
+        Ok(id)
+    }
+
     // ============= LIST WITH FILTERS =============
 
     pub async fn list_with_filters(&self, params: &DuaQueryParams) -> ApiResult<(Vec<Dua>, i64)> {
@@ -92,7 +387,8 @@ impl DuaRepository {
         
         if params.invocation_time.is_some() || params.event_trigger.is_some() || 
            params.posture.is_some() || params.hands_raising_rule.is_some() ||
-           params.audible_mode.is_some() || params.addressing_mode.is_some() {
+           params.audible_mode.is_some() || params.addressing_mode.is_some() ||
+           params.calendar.is_some() || params.hijri_month.is_some() {
             query.push(" JOIN dua_context ctx ON d.id = ctx.dua_id");
             count_query.push(" JOIN dua_context ctx ON d.id = ctx.dua_id");
         }
@@ -117,13 +413,6 @@ impl DuaRepository {
         // Build WHERE clause
         let mut where_conditions = Vec::new();
         
-        if let Some(ref q) = params.q {
-            where_conditions.push(format!(
-                "(d.title ILIKE '%{}%' OR d.arabic_text ILIKE '%{}%' OR d.translation ILIKE '%{}%' OR d.transliteration ILIKE '%{}%')",
-                q, q, q, q
-            ));
-        }
-        
         if let Some(ref category) = params.category {
             where_conditions.push(format!("c.slug = '{}'", category));
         }
@@ -195,11 +484,19 @@ impl DuaRepository {
             count_query.push(&where_clause);
         }
         
+        Self::push_bound_filters(&mut query, params, !where_conditions.is_empty())?;
+        Self::push_bound_filters(&mut count_query, params, !where_conditions.is_empty())?;
+        
         // Get total count
-        let total: i64 = sqlx::query_scalar(&count_query.sql())
-            .fetch_one(&self.db.pool)
+        let count_sql = count_query.sql().to_string();
+        let (total,): (i64,) = self.db
+            .timed(&count_sql, count_query.build_query_as().fetch_one(&self.db.pool))
             .await?;
         
+        if params.count_only == Some(true) {
+            return Ok((Vec::new(), total));
+        }
+        
         // Apply sorting
         let sort_field = params.sort.as_deref().unwrap_or("created_at");
         let sort_order = params.order.as_deref().unwrap_or("desc");
@@ -211,45 +508,144 @@ impl DuaRepository {
         query.push(&format!(" LIMIT {} OFFSET {}", per_page, offset));
         
         // Execute query
-        let duas = query
-            .build_query_as::<Dua>()
-            .fetch_all(&self.db.pool)
+        let sql = query.sql().to_string();
+        let duas = self.db
+            .timed(&sql, query.build_query_as::<Dua>().fetch_all(&self.db.pool))
             .await?;
         
         Ok((duas, total))
     }
 
-    // ============= RANDOM DUA =============
-
-    pub async fn get_random_dua(&self, params: &DuaQueryParams) -> ApiResult<Option<Dua>> {
-        let mut query: QueryBuilder<'_, sqlx::Postgres> = QueryBuilder::new("SELECT d.* FROM duas d");
+    /// Appends filters whose values are bound as query parameters.
+    fn push_bound_filters(
+        builder: &mut QueryBuilder<'_, sqlx::Postgres>,
+        params: &DuaQueryParams,
+        mut has_where: bool,
+    ) -> ApiResult<()> {
+        let mut push_condition = |builder: &mut QueryBuilder<'_, sqlx::Postgres>| {
+            builder.push(if has_where { " AND " } else { " WHERE " });
+            has_where = true;
+        };
+        
+        let statuses = params.visible_statuses().map_err(ApiError::validation)?;
+        push_condition(builder);
+        builder.push("d.status = ANY(");
+        builder.push_bind(statuses);
+        builder.push(")");
         
-        // Apply filters (similar to list_with_filters but with RANDOM())
-        let mut where_conditions = Vec::new();
+        if let Some(ref q) = params.q {
+            let pattern = contains_pattern(q);
+            push_condition(builder);
+            builder.push("(");
+            for (i, column) in ["d.title", "d.arabic_text", "d.translation", "d.transliteration"]
+                .into_iter()
+                .enumerate()
+            {
+                if i > 0 {
+                    builder.push(" OR ");
+                }
+                builder
+                    .push(column)
+                    .push(" ILIKE ")
+                    .push_bind(pattern.clone())
+                    .push(r" ESCAPE '\'");
+            }
+            builder.push(")");
+        }
         
-        if let Some(ref invocation_time) = params.invocation_time {
-            query.push(" JOIN dua_context ctx ON d.id = ctx.dua_id");
-            where_conditions.push(format!("'{}' = ANY(ctx.invocation_time)", invocation_time));
+        if let Some(ref repetitions) = params.repetitions {
+            let repetitions: i32 = repetitions
+                .parse()
+                .ok()
+                .filter(|count| *count > 0)
+                .ok_or_else(|| ApiError::validation("repetitions must be a positive integer"))?;
+            
+            // Matches the context's recommended count or a bundle's repetition count
+            push_condition(builder);
+            builder.push("(EXISTS (SELECT 1 FROM dua_context rc WHERE rc.dua_id = d.id AND rc.repetition_count = ");
+            builder.push_bind(repetitions);
+            builder.push(") OR EXISTS (SELECT 1 FROM dua_bundle_items rbi WHERE rbi.dua_id = d.id AND rbi.repetitions = ");
+            builder.push_bind(repetitions);
+            builder.push("))");
         }
         
-        if let Some(ref event_trigger) = params.event_trigger {
-            if !params.invocation_time.is_some() {
-                query.push(" JOIN dua_context ctx ON d.id = ctx.dua_id");
+        if let Some(ref dua_type) = params.dua_type {
+            let dua_type: DuaType = dua_type.parse().map_err(ApiError::validation)?;
+            
+            push_condition(builder);
+            builder.push("d.dua_type = ");
+            builder.push_bind(dua_type.as_str());
+        }
+        
+        let date_ranges = [
+            ("created_after", &params.created_after, "d.created_at >= "),
+            ("created_before", &params.created_before, "d.created_at < "),
+            ("updated_after", &params.updated_after, "d.updated_at >= "),
+            ("updated_before", &params.updated_before, "d.updated_at < "),
+        ];
+        for (name, value, condition) in date_ranges {
+            if let Some(value) = value {
+                let date = parse_date_param(name, value).map_err(ApiError::validation)?;
+                
+                push_condition(builder);
+                builder.push(condition);
+                builder.push_bind(date);
             }
-            where_conditions.push(format!("'{}' = ANY(ctx.event_trigger)", event_trigger));
         }
         
-        if let Some(ref category) = params.category {
-            query.push(" JOIN dua_category_map dcm ON d.id = dcm.dua_id");
-            query.push(" JOIN dua_categories c ON dcm.category_id = c.id");
-            where_conditions.push(format!("c.slug = '{}'", category));
+        if let Some(ref calendar) = params.calendar {
+            push_condition(builder);
+            builder.push_bind(calendar.to_lowercase());
+            builder.push(" = ANY(ctx.calendar_context)");
         }
         
-        if !where_conditions.is_empty() {
-            query.push(&format!(" WHERE {}", where_conditions.join(" AND ")));
+        if let Some(month) = params.hijri_month {
+            if !(1..=12).contains(&month) {
+                return Err(ApiError::validation("hijri_month must be between 1 and 12"));
+            }
+            
+            push_condition(builder);
+            builder.push("ctx.calendar_context && ");
+            builder.push_bind(occasion_slugs_in_month(month));
+            builder.push("::text[]");
         }
         
-        query.push(" ORDER BY RANDOM() LIMIT 1");
+        Ok(())
+    }
+
+    // ============= RANDOM DUA =============
+
+    pub async fn get_random_dua(&self, params: &DuaQueryParams) -> ApiResult<Option<Dua>> {
+        // A seed gives a stable ordering so the same seed always returns the same dua
+        if let Some(ref seed) = params.seed {
+            let mut query: QueryBuilder<'_, sqlx::Postgres> = QueryBuilder::new("SELECT d.* FROM duas d");
+            Self::push_random_dua_filters(&mut query, params)?;
+            query.push(" ORDER BY md5(d.id::text || ");
+            query.push_bind(seed);
+            query.push("), d.id LIMIT 1");
+            
+            let dua = query
+                .build_query_as::<Dua>()
+                .fetch_optional(&self.db.pool)
+                .await?;
+            
+            return Ok(dua);
+        }
+        
+        // Pick a random offset over the filtered count instead of sorting the whole table
+        let mut offset_query: QueryBuilder<'_, sqlx::Postgres> =
+            QueryBuilder::new("SELECT FLOOR(RANDOM() * COUNT(*))::BIGINT FROM duas d");
+        Self::push_random_dua_filters(&mut offset_query, params)?;
+        let offset_sql = offset_query.sql().to_string();
+        let (offset,): (i64,) = self.db
+            .timed(&offset_sql, offset_query.build_query_as().fetch_one(&self.db.pool))
+            .await?;
+        
+        let mut query: QueryBuilder<'_, sqlx::Postgres> = QueryBuilder::new("SELECT d.* FROM duas d");
+        Self::push_random_dua_filters(&mut query, params)?;
+        query.push(" OFFSET ");
+        query.push_bind(offset);
+        query.push(" LIMIT 1");
         
         let dua = query
             .build_query_as::<Dua>()
@@ -259,8 +655,287 @@ impl DuaRepository {
         Ok(dua)
     }
 
+    /// Appends the joins and bound filters a random pick honours
+    fn push_random_dua_filters(
+        builder: &mut QueryBuilder<'_, sqlx::Postgres>,
+        params: &DuaQueryParams,
+    ) -> ApiResult<()> {
+        let statuses = params.visible_statuses().map_err(ApiError::validation)?;
+        
+        if params.invocation_time.is_some() || params.event_trigger.is_some() {
+            builder.push(" JOIN dua_context ctx ON d.id = ctx.dua_id");
+        }
+        
+        if params.category.is_some() {
+            builder.push(" JOIN dua_category_map dcm ON d.id = dcm.dua_id");
+            builder.push(" JOIN dua_categories c ON dcm.category_id = c.id");
+        }
+        
+        builder.push(" WHERE d.status = ANY(");
+        builder.push_bind(statuses);
+        builder.push(")");
+        
+        if let Some(ref invocation_time) = params.invocation_time {
+            builder.push(" AND ");
+            builder.push_bind(invocation_time.clone());
+            builder.push(" = ANY(ctx.invocation_time::text[])");
+        }
+        
+        if let Some(ref event_trigger) = params.event_trigger {
+            builder.push(" AND ");
+            builder.push_bind(event_trigger.clone());
+            builder.push(" = ANY(ctx.event_trigger::text[])");
+        }
+        
+        if let Some(ref category) = params.category {
+            builder.push(" AND c.slug = ");
+            builder.push_bind(category.clone());
+        }
+        
+        Ok(())
+    }
+
     // ============= RELATIONS LOADERS =============
 
+    /// Loads the relations named in a comma-separated `include` list
+    pub async fn load_relations(&self, dua: Dua, include: Option<&str>) -> ApiResult<DuaWithRelations> {
+        let mut dua_with_relations = DuaWithRelations {
+            dua: dua.clone(),
+            sources: None,
+            context: None,
+            media: None,
+            categories: None,
+            tags: None,
+            translations: None,
+            variants: None,
+            lang: None,
+        };
+        
+        if let Some(include) = include {
+            let includes: Vec<&str> = include.split(',').map(str::trim).collect();
+            
+            if includes.contains(&"sources") {
+                dua_with_relations.sources = Some(self.get_dua_sources(dua.id).await?);
+            }
+            
+            if includes.contains(&"context") {
+                dua_with_relations.context = self.get_dua_context(dua.id).await?;
+            }
+            
+            if includes.contains(&"media") {
+                dua_with_relations.media = Some(self.get_dua_media(dua.id).await?);
+            }
+            
+            if includes.contains(&"categories") {
+                dua_with_relations.categories = Some(self.get_dua_categories(dua.id).await?);
+            }
+            
+            if includes.contains(&"tags") {
+                dua_with_relations.tags = Some(self.get_dua_tags(dua.id).await?);
+            }
+            
+            if includes.contains(&"translations") {
+                dua_with_relations.translations = Some(self.get_dua_translations(dua.id).await?);
+            }
+            
+            if includes.contains(&"variants") {
+                dua_with_relations.variants = Some(self.get_dua_variants(dua.id).await?);
+            }
+        }
+        
+        Ok(dua_with_relations)
+    }
+
+    /// Like `load_relations` for a page of duas, with one query per
+    /// included relation rather than one per dua
+    pub async fn load_relations_batch(
+        &self,
+        duas: Vec<Dua>,
+        include: Option<&str>,
+    ) -> ApiResult<Vec<DuaWithRelations>> {
+        let includes: Vec<&str> = include
+            .map(|include| include.split(',').map(str::trim).collect())
+            .unwrap_or_default();
+        let ids: Vec<Uuid> = duas.iter().map(|d| d.id).collect();
+        let wanted = |name: &str| !ids.is_empty() && includes.contains(&name);
+        
+        let mut sources = if wanted("sources") {
+            Some(group_by_dua(
+                self.fetch_for_duas::<DuaSource>("SELECT * FROM dua_sources WHERE dua_id = ANY($1)", &ids).await?,
+                |s| (s.dua_id, s),
+            ))
+        } else {
+            None
+        };
+        let mut context = if wanted("context") {
+            Some(group_by_dua(
+                self.fetch_for_duas::<DuaContext>("SELECT * FROM dua_context WHERE dua_id = ANY($1)", &ids).await?,
+                |c| (c.dua_id, c),
+            ))
+        } else {
+            None
+        };
+        let mut media = if wanted("media") {
+            Some(group_by_dua(
+                self.fetch_for_duas::<DuaMedia>("SELECT * FROM dua_media WHERE dua_id = ANY($1)", &ids).await?,
+                |m| (m.dua_id, m),
+            ))
+        } else {
+            None
+        };
+        let mut categories = if wanted("categories") {
+            Some(group_by_dua(
+                self.fetch_for_duas::<MappedCategory>(
+                    r#"
+                    SELECT dcm.dua_id, c.* FROM dua_categories c
+                    JOIN dua_category_map dcm ON c.id = dcm.category_id
+                    WHERE dcm.dua_id = ANY($1)
+                    ORDER BY c.sort_order
+                    "#,
+                    &ids,
+                )
+                .await?,
+                |m| (m.dua_id, m.category),
+            ))
+        } else {
+            None
+        };
+        let mut tags = if wanted("tags") {
+            Some(group_by_dua(
+                self.fetch_for_duas::<MappedTag>(
+                    r#"
+                    SELECT dtm.dua_id, t.* FROM dua_tags t
+                    JOIN dua_tag_map dtm ON t.id = dtm.tag_id
+                    WHERE dtm.dua_id = ANY($1)
+                    ORDER BY t.name
+                    "#,
+                    &ids,
+                )
+                .await?,
+                |m| (m.dua_id, m.tag),
+            ))
+        } else {
+            None
+        };
+        let mut translations = if wanted("translations") {
+            Some(group_by_dua(
+                self.fetch_for_duas::<DuaTranslation>(
+                    "SELECT * FROM dua_translations WHERE dua_id = ANY($1) ORDER BY language_code",
+                    &ids,
+                )
+                .await?,
+                |t| (t.dua_id, t),
+            ))
+        } else {
+            None
+        };
+        let mut variants = if wanted("variants") {
+            Some(group_by_dua(
+                self.fetch_for_duas::<DuaVariant>(
+                    "SELECT * FROM dua_variants WHERE dua_id = ANY($1) ORDER BY variant_type",
+                    &ids,
+                )
+                .await?,
+                |v| (v.dua_id, v),
+            ))
+        } else {
+            None
+        };
+        
+        Ok(duas
+            .into_iter()
+            .map(|dua| {
+                let id = dua.id;
+                DuaWithRelations {
+                    sources: take_for_dua(&mut sources, &id),
+                    context: take_for_dua(&mut context, &id).and_then(|rows| rows.into_iter().next()),
+                    media: take_for_dua(&mut media, &id),
+                    categories: take_for_dua(&mut categories, &id),
+                    tags: take_for_dua(&mut tags, &id),
+                    translations: take_for_dua(&mut translations, &id),
+                    variants: take_for_dua(&mut variants, &id),
+                    dua,
+                    lang: None,
+                }
+            })
+            .collect())
+    }
+
+    async fn fetch_for_duas<T>(&self, sql: &str, ids: &[Uuid]) -> ApiResult<Vec<T>>
+    where
+        T: for<'r> FromRow<'r, PgRow> + Send + Unpin,
+    {
+        let rows = sqlx::query_as::<_, T>(sql)
+            .bind(ids)
+            .fetch_all(&self.db.pool)
+            .await?;
+        
+        Ok(rows)
+    }
+
+    // ============= EXPORT =============
+
+    /// Streams every dua, whatever its status, as NDJSON lines over a
+    /// bounded channel. Rows are read with a cursor and their relations
+    /// loaded a page at a time, so memory use does not grow with the table.
+    pub fn export_ndjson(self, include: Option<String>) -> mpsc::Receiver<ApiResult<String>> {
+        let (tx, rx) = mpsc::channel(EXPORT_CHANNEL_CAPACITY);
+        
+        tokio::spawn(async move {
+            let mut rows = sqlx::query_as::<_, Dua>("SELECT * FROM duas ORDER BY created_at, id")
+                .fetch(&self.db.pool);
+            let mut page = Vec::with_capacity(EXPORT_PAGE_SIZE);
+            
+            loop {
+                let done = match rows.try_next().await {
+                    Ok(Some(dua)) => {
+                        page.push(dua);
+                        if page.len() < EXPORT_PAGE_SIZE {
+                            continue;
+                        }
+                        false
+                    }
+                    Ok(None) => true,
+                    Err(e) => {
+                        warn!("Dua export aborted: {}", e);
+                        let _ = tx.send(Err(e.into())).await;
+                        break;
+                    }
+                };
+                
+                let lines = self
+                    .load_relations_batch(std::mem::take(&mut page), include.as_deref())
+                    .await
+                    .and_then(|duas| {
+                        duas.iter()
+                            .map(|dua| Ok(serde_json::to_string(dua)? + "\n"))
+                            .collect::<ApiResult<Vec<_>>>()
+                    });
+                match lines {
+                    Ok(lines) => {
+                        for line in lines {
+                            // The receiver is gone once the client disconnects
+                            if tx.send(Ok(line)).await.is_err() {
+                                return;
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        warn!("Dua export aborted: {}", e);
+                        let _ = tx.send(Err(e)).await;
+                        break;
+                    }
+                }
+                
+                if done {
+                    break;
+                }
+            }
+        });
+        
+        rx
+    }
+
     pub async fn get_dua_sources(&self, dua_id: Uuid) -> ApiResult<Vec<DuaSource>> {
         let sources = sqlx::query_as::<_, DuaSource>(
             "SELECT * FROM dua_sources WHERE dua_id = $1"
@@ -294,6 +969,51 @@ impl DuaRepository {
         Ok(media)
     }
 
+    pub async fn create_dua_media(
+        &self,
+        dua_id: Uuid,
+        request: &CreateMediaRequest,
+        duration: Option<i32>,
+    ) -> ApiResult<DuaMedia> {
+        let license = request
+            .license
+            .as_deref()
+            .map(|license| license.parse::<License>().map(|l| l.as_str()))
+            .transpose()
+            .map_err(ApiError::validation)?
+            .unwrap_or(License::AllRightsReserved.as_str());
+        
+        // Enum columns are cast to text so they decode into the String fields
+        let media = sqlx::query_as::<_, DuaMedia>(
+            r#"
+            INSERT INTO dua_media (
+                dua_id, media_type, url, file_path, file_size, duration,
+                reciter_name, reciter_style, language_code, license, metadata
+            )
+            VALUES ($1, $2::media_type_enum, $3, $4, $5, $6, $7, $8::reciter_style_enum, $9, $10::license_enum, $11)
+            RETURNING id, dua_id, media_type::text AS media_type, url, file_path, file_size, duration,
+                reciter_name, reciter_style::text AS reciter_style, language_code,
+                license::text AS license, metadata, created_at
+            "#,
+        )
+        .bind(dua_id)
+        .bind(request.media_type.to_lowercase())
+        .bind(&request.url)
+        .bind(&request.file_path)
+        .bind(request.file_size)
+        .bind(duration)
+        .bind(&request.reciter_name)
+        .bind(request.reciter_style.as_ref().map(|style| style.to_lowercase()))
+        .bind(&request.language_code)
+        .bind(license)
+        .bind(&request.metadata)
+        .fetch_one(&self.db.pool)
+        .await?;
+        
+        info!("Created {} media {} for dua {}", media.media_type, media.id, dua_id);
+        Ok(media)
+    }
+
     pub async fn get_dua_categories(&self, dua_id: Uuid) -> ApiResult<Vec<DuaCategory>> {
         let categories = sqlx::query_as::<_, DuaCategory>(
             r#"
@@ -337,6 +1057,101 @@ impl DuaRepository {
         Ok(translations)
     }
 
+    /// Inserts or replaces a dua's translations by language, all or nothing.
+    /// Language codes are stored lowercased so each language has one row.
+    pub async fn upsert_translations(
+        &self,
+        dua_id: Uuid,
+        translations: &[UpsertTranslationRequest],
+    ) -> ApiResult<Vec<DuaTranslation>> {
+        let mut tx = self.db.pool.begin().await?;
+        
+        for translation in translations {
+            sqlx::query(
+                r#"
+                INSERT INTO dua_translations (dua_id, language_code, title, translation, transliteration, slug, seo_title, meta_description)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+                ON CONFLICT (dua_id, language_code) DO UPDATE SET
+                    title = EXCLUDED.title,
+                    translation = EXCLUDED.translation,
+                    transliteration = EXCLUDED.transliteration,
+                    slug = EXCLUDED.slug,
+                    seo_title = EXCLUDED.seo_title,
+                    meta_description = EXCLUDED.meta_description,
+                    updated_at = NOW()
+                "#,
+            )
+            .bind(dua_id)
+            .bind(translation.language_code.to_lowercase())
+            .bind(&translation.title)
+            .bind(translation.translation.trim())
+            .bind(&translation.transliteration)
+            .bind(&translation.slug)
+            .bind(&translation.seo_title)
+            .bind(&translation.meta_description)
+            .execute(&mut tx)
+            .await?;
+        }
+        
+        tx.commit().await?;
+        self.get_dua_translations(dua_id).await
+    }
+
+    pub async fn get_dua_translation(&self, dua_id: Uuid, language_code: &str) -> ApiResult<Option<LocalizedTranslation>> {
+        let translation = sqlx::query_as::<_, LocalizedTranslation>(
+            r#"
+            SELECT t.*, COALESCE(NULLIF(t.slug, ''), d.slug) AS canonical_slug
+            FROM dua_translations t
+            JOIN duas d ON d.id = t.dua_id
+            WHERE t.dua_id = $1 AND LOWER(t.language_code) = LOWER($2) AND d.status = ANY($3)
+            "#
+        )
+        .bind(dua_id)
+        .bind(language_code)
+        .bind(Status::Active.db_values())
+        .fetch_optional(&self.db.pool)
+        .await?;
+        
+        Ok(translation)
+    }
+
+    /// Language codes that have at least one translation
+    pub async fn available_languages(&self) -> ApiResult<Vec<String>> {
+        let languages = sqlx::query_scalar::<_, String>(
+            "SELECT DISTINCT LOWER(language_code) FROM dua_translations ORDER BY 1"
+        )
+        .fetch_all(&self.db.pool)
+        .await?;
+        
+        Ok(languages)
+    }
+
+    /// Localizes each dua to `lang` when it has a translation in it, loading
+    /// the translations in one query
+    pub async fn localize(&self, duas: &mut [DuaWithRelations], lang: &str) -> ApiResult<()> {
+        if duas.is_empty() {
+            return Ok(());
+        }
+        
+        let ids: Vec<Uuid> = duas.iter().map(|d| d.dua.id).collect();
+        let translations = sqlx::query_as::<_, DuaTranslation>(
+            "SELECT * FROM dua_translations WHERE dua_id = ANY($1) AND LOWER(language_code) = LOWER($2)"
+        )
+        .bind(&ids)
+        .bind(lang)
+        .fetch_all(&self.db.pool)
+        .await?;
+        
+        let by_dua: HashMap<Uuid, DuaTranslation> =
+            translations.into_iter().map(|t| (t.dua_id, t)).collect();
+        for dua in duas {
+            if let Some(translation) = by_dua.get(&dua.dua.id) {
+                dua.localize(translation);
+            }
+        }
+        Ok(())
+    }
+
     pub async fn get_dua_variants(&self, dua_id: Uuid) -> ApiResult<Vec<DuaVariant>> {
         let variants = sqlx::query_as::<_, DuaVariant>(
             "SELECT * FROM dua_variants WHERE dua_id = $1 ORDER BY variant_type"
@@ -371,17 +1186,27 @@ impl DuaRepository {
         Ok(category)
     }
 
+    /// Like `get_category_by_slug`, but a missing category is `NotFound`.
+    /// Sub-resource listings check this first so an unknown slug is a 404
+    /// rather than an empty list.
+    pub async fn require_category(&self, slug: &str) -> ApiResult<DuaCategory> {
+        self.get_category_by_slug(slug)
+            .await?
+            .ok_or_else(|| ApiError::not_found(format!("Category {}", slug)))
+    }
+
     pub async fn get_duas_by_category(&self, category_slug: &str) -> ApiResult<Vec<Dua>> {
         let duas = sqlx::query_as::<_, Dua>(
             r#"
             SELECT d.* FROM duas d
             JOIN dua_category_map dcm ON d.id = dcm.dua_id
             JOIN dua_categories c ON dcm.category_id = c.id
-            WHERE c.slug = $1
+            WHERE c.slug = $1 AND d.status = ANY($2)
             ORDER BY d.popularity_score DESC, d.title
             "#
         )
         .bind(category_slug)
+        .bind(Status::Active.db_values())
         .fetch_all(&self.db.pool)
         .await?;
         
@@ -400,17 +1225,35 @@ impl DuaRepository {
         Ok(tags)
     }
 
+    pub async fn get_tag_by_slug(&self, slug: &str) -> ApiResult<Option<DuaTag>> {
+        let tag = sqlx::query_as::<_, DuaTag>(
+            "SELECT * FROM dua_tags WHERE slug = $1"
+        )
+        .bind(slug)
+        .fetch_optional(&self.db.pool)
+        .await?;
+        
+        Ok(tag)
+    }
+
+    pub async fn require_tag(&self, slug: &str) -> ApiResult<DuaTag> {
+        self.get_tag_by_slug(slug)
+            .await?
+            .ok_or_else(|| ApiError::not_found(format!("Tag {}", slug)))
+    }
+
     pub async fn get_duas_by_tag(&self, tag_slug: &str) -> ApiResult<Vec<Dua>> {
         let duas = sqlx::query_as::<_, Dua>(
             r#"
             SELECT d.* FROM duas d
             JOIN dua_tag_map dtm ON d.id = dtm.dua_id
             JOIN dua_tags t ON dtm.tag_id = t.id
-            WHERE t.slug = $1
+            WHERE t.slug = $1 AND d.status = ANY($2)
             ORDER BY d.popularity_score DESC, d.title
             "#
         )
         .bind(tag_slug)
+        .bind(Status::Active.db_values())
         .fetch_all(&self.db.pool)
         .await?;
         
@@ -440,17 +1283,24 @@ impl DuaRepository {
         Ok(bundle)
     }
 
+    pub async fn require_bundle(&self, slug: &str) -> ApiResult<DuaBundle> {
+        self.get_bundle_by_slug(slug)
+            .await?
+            .ok_or_else(|| ApiError::not_found(format!("Bundle {}", slug)))
+    }
+
     pub async fn get_bundle_items(&self, bundle_slug: &str) -> ApiResult<Vec<Dua>> {
         let duas = sqlx::query_as::<_, Dua>(
             r#"
             SELECT d.* FROM duas d
             JOIN dua_bundle_items dbi ON d.id = dbi.dua_id
             JOIN dua_bundles b ON dbi.bundle_id = b.id
-            WHERE b.slug = $1
+            WHERE b.slug = $1 AND d.status = ANY($2)
             ORDER BY dbi.sort_order
             "#
         )
         .bind(bundle_slug)
+        .bind(Status::Active.db_values())
         .fetch_all(&self.db.pool)
         .await?;
         
@@ -471,10 +1321,17 @@ impl DuaRepository {
         }
         
         if let Some(ref q) = params.q {
-            query.push(&format!(" AND (book_name ILIKE '%{}%' OR reference_text ILIKE '%{}%')", q, q));
+            let pattern = contains_pattern(q);
+            query.push(" AND (book_name ILIKE ").push_bind(pattern.clone());
+            query.push(r" ESCAPE '\' OR reference_text ILIKE ").push_bind(pattern);
+            query.push(r" ESCAPE '\')");
         }
         
-        query.push(" ORDER BY created_at DESC");
+        let (_, per_page, offset) = params.page_window();
+        query.push(" ORDER BY created_at DESC LIMIT ");
+        query.push_bind(per_page as i64);
+        query.push(" OFFSET ");
+        query.push_bind(offset as i64);
         
         let sources = query
             .build_query_as::<DuaSource>()
@@ -495,15 +1352,22 @@ impl DuaRepository {
         Ok(source)
     }
 
+    pub async fn require_source(&self, id: Uuid) -> ApiResult<DuaSource> {
+        self.get_source_by_id(id)
+            .await?
+            .ok_or_else(|| ApiError::not_found(format!("Source {}", id)))
+    }
+
     pub async fn get_duas_by_source(&self, source_id: Uuid) -> ApiResult<Vec<Dua>> {
         let duas = sqlx::query_as::<_, Dua>(
             r#"
             SELECT d.* FROM duas d
             JOIN dua_sources s ON d.id = s.dua_id
-            WHERE s.id = $1
+            WHERE s.id = $1 AND d.status = ANY($2)
             "#
         )
         .bind(source_id)
+        .bind(Status::Active.db_values())
         .fetch_all(&self.db.pool)
         .await?;
         
@@ -524,10 +1388,15 @@ impl DuaRepository {
         }
         
         if let Some(ref reciter) = params.reciter {
-            query.push(&format!(" AND reciter_name ILIKE '%{}%'", reciter));
+            query.push(" AND reciter_name ILIKE ").push_bind(contains_pattern(reciter));
+            query.push(r" ESCAPE '\'");
         }
         
-        query.push(" ORDER BY created_at DESC");
+        let (_, per_page, offset) = params.page_window();
+        query.push(" ORDER BY created_at DESC LIMIT ");
+        query.push_bind(per_page as i64);
+        query.push(" OFFSET ");
+        query.push_bind(offset as i64);
         
         let media = query
             .build_query_as::<DuaMedia>()
@@ -539,142 +1408,1302 @@ impl DuaRepository {
 
     // ============= SEARCH =============
 
-    pub async fn keyword_search(&self, query: &str, limit: u32) -> ApiResult<Vec<Dua>> {
-        let duas = sqlx::query_as::<_, Dua>(
-            r#"
+    /// Duas whose text contains `query`. A blank query matches nothing
+    /// rather than the whole table.
+    pub async fn keyword_search(&self, query: &str, limit: u32, filters: &SearchFilters) -> ApiResult<Vec<Dua>> {
+        let query = query.trim();
+        if query.is_empty() {
+            return Ok(Vec::new());
+        }
+        
+        let sql = r#"
             SELECT * FROM duas
-            WHERE title ILIKE $1 
-               OR arabic_text ILIKE $1
-               OR translation ILIKE $1
-               OR transliteration ILIKE $1
+            WHERE (title ILIKE $1 ESCAPE '\'
+               OR arabic_text ILIKE $1 ESCAPE '\'
+               OR translation ILIKE $1 ESCAPE '\'
+               OR transliteration ILIKE $1 ESCAPE '\')
+              AND status = ANY($3)
+              AND ($4::text[] IS NULL OR EXISTS (
+                  SELECT 1 FROM dua_sources ds
+                  WHERE ds.dua_id = duas.id AND ds.authenticity::text = ANY($4)
+              ))
             ORDER BY popularity_score DESC
             LIMIT $2
-            "#
-        )
-        .bind(format!("%{}%", query))
-        .bind(limit as i64)
-        .fetch_all(&self.db.pool)
-        .await?;
+        "#;
+        
+        let duas = self.db
+            .timed(
+                sql,
+                sqlx::query_as::<_, Dua>(sql)
+                    .bind(contains_pattern(query))
+                    .bind(limit as i64)
+                    .bind(Status::Active.db_values())
+                    .bind(filters.allowed_authenticities())
+                    .fetch_all(&self.db.pool),
+            )
+            .await?;
         
         Ok(duas)
     }
 
-    pub async fn semantic_search(&self, query: &str, limit: u32, _threshold: f64) -> ApiResult<Vec<Dua>> {
+    pub async fn semantic_search(&self, query: &str, limit: u32, _threshold: f64, filters: &SearchFilters) -> ApiResult<Vec<Dua>> {
         // This is a placeholder - in production, you'd integrate with a vector database
         // or embedding service for true semantic search
         
         let sql = r#"
             SELECT d.*
             FROM duas d
-            WHERE d.title ILIKE $1 
-               OR d.translation ILIKE $1
+            WHERE (d.title ILIKE $1 ESCAPE '\'
+               OR d.translation ILIKE $1 ESCAPE '\')
+              AND d.status = ANY($3)
+              AND ($4::text[] IS NULL OR EXISTS (
+                  SELECT 1 FROM dua_sources ds
+                  WHERE ds.dua_id = d.id AND ds.authenticity::text = ANY($4)
+              ))
             ORDER BY d.popularity_score DESC
             LIMIT $2
         "#;
         
-        let duas = sqlx::query_as::<_, Dua>(sql)
-            .bind(format!("%{}%", query))
-            .bind(limit as i64)
-            .fetch_all(&self.db.pool)
+        let duas = self.db
+            .timed(
+                sql,
+                sqlx::query_as::<_, Dua>(sql)
+                    .bind(contains_pattern(query))
+                    .bind(limit as i64)
+                    .bind(Status::Active.db_values())
+                    .bind(filters.allowed_authenticities())
+                    .fetch_all(&self.db.pool),
+            )
             .await?;
         
         Ok(duas)
     }
 
-    pub async fn get_suggestions(&self, query: &str, limit: u32) -> ApiResult<Vec<String>> {
-        let results: Vec<(String,)> = sqlx::query_as(
-            r#"
+    pub async fn search_categories(&self, query: &str, limit: u32) -> ApiResult<Vec<DuaCategory>> {
+        let categories = sqlx::query_as::<_, DuaCategory>(
+            "SELECT * FROM dua_categories WHERE name ILIKE $1 ESCAPE '\\' ORDER BY sort_order, name LIMIT $2"
+        )
+        .bind(contains_pattern(query))
+        .bind(limit as i64)
+        .fetch_all(&self.db.pool)
+        .await?;
+        
+        Ok(categories)
+    }
+
+    pub async fn search_tags(&self, query: &str, limit: u32) -> ApiResult<Vec<DuaTag>> {
+        let tags = sqlx::query_as::<_, DuaTag>(
+            "SELECT * FROM dua_tags WHERE name ILIKE $1 ESCAPE '\\' ORDER BY name LIMIT $2"
+        )
+        .bind(contains_pattern(query))
+        .bind(limit as i64)
+        .fetch_all(&self.db.pool)
+        .await?;
+        
+        Ok(tags)
+    }
+
+    pub async fn search_bundles(&self, query: &str, limit: u32) -> ApiResult<Vec<DuaBundle>> {
+        let bundles = sqlx::query_as::<_, DuaBundle>(
+            "SELECT * FROM dua_bundles WHERE name ILIKE $1 ESCAPE '\\' ORDER BY name LIMIT $2"
+        )
+        .bind(contains_pattern(query))
+        .bind(limit as i64)
+        .fetch_all(&self.db.pool)
+        .await?;
+        
+        Ok(bundles)
+    }
+
+    /// Searches duas, categories, tags and bundles concurrently, `limit` per group
+    pub async fn search_all(&self, query: &str, limit: u32) -> ApiResult<UnifiedSearchResponse> {
+        let no_filters = SearchFilters::default();
+        let (duas, categories, tags, bundles) = tokio::try_join!(
+            self.keyword_search(query, limit, &no_filters),
+            self.search_categories(query, limit),
+            self.search_tags(query, limit),
+            self.search_bundles(query, limit),
+        )?;
+        
+        let duas = SearchGroup::new("dua", duas);
+        let categories = SearchGroup::new("category", categories);
+        let tags = SearchGroup::new("tag", tags);
+        let bundles = SearchGroup::new("bundle", bundles);
+        
+        Ok(UnifiedSearchResponse {
+            query: query.to_string(),
+            total: duas.count + categories.count + tags.count + bundles.count,
+            duas,
+            categories,
+            tags,
+            bundles,
+        })
+    }
+
+    /// Titles, categories and tags starting with `query`; nothing for a
+    /// blank query
+    pub async fn get_suggestions(&self, query: &str, limit: u32) -> ApiResult<Vec<String>> {
+        let query = query.trim();
+        if query.is_empty() {
+            return Ok(Vec::new());
+        }
+        
+        let results: Vec<(String,)> = sqlx::query_as(
+            r#"
             SELECT DISTINCT title FROM duas
-            WHERE title ILIKE $1
+            WHERE title ILIKE $1 ESCAPE '\' AND status = ANY($3)
             UNION
             SELECT DISTINCT name FROM dua_categories
-            WHERE name ILIKE $1
+            WHERE name ILIKE $1 ESCAPE '\'
             UNION
             SELECT DISTINCT name FROM dua_tags
-            WHERE name ILIKE $1
+            WHERE name ILIKE $1 ESCAPE '\'
             ORDER BY 1
             LIMIT $2
             "#
         )
-        .bind(format!("{}%", query))
+        .bind(format!("{}%", escape_like(query)))
         .bind(limit as i64)
+        .bind(Status::Active.db_values())
         .fetch_all(&self.db.pool)
         .await?;
         
         Ok(results.into_iter().map(|(s,)| s).collect())
     }
 
-    // ============= STATISTICS =============
+    // ============= POPULARITY =============
 
-    pub async fn get_stats(&self) -> ApiResult<StatsResponse> {
-        let total_duas = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM duas")
-            .fetch_one(&self.db.pool)
-            .await?;
-
-        let verified_duas = sqlx::query_scalar::<_, i64>(
-            "SELECT COUNT(*) FROM duas WHERE status = 'verified'"
+    /// Counts a view of a dua, returning its new view count
+    pub async fn record_view(&self, dua_id: Uuid) -> ApiResult<i64> {
+        let (view_count,): (i64,) = sqlx::query_as(
+            r#"
+            INSERT INTO dua_view_counts (dua_id, view_count, last_viewed_at)
+            VALUES ($1, 1, NOW())
+            ON CONFLICT (dua_id) DO UPDATE
+            SET view_count = dua_view_counts.view_count + 1, last_viewed_at = NOW()
+            RETURNING view_count
+            "#
         )
+        .bind(dua_id)
         .fetch_one(&self.db.pool)
         .await?;
+        
+        Ok(view_count)
+    }
 
-        let total_categories = sqlx::query_scalar::<_, i64>(
-            "SELECT COUNT(*) FROM dua_categories"
+    /// Rescales `popularity_score` from view counts: the most viewed dua
+    /// scores 1.0 and the rest their share of its views. Duas that were
+    /// never viewed keep their current score.
+    pub async fn recompute_popularity(&self) -> ApiResult<u64> {
+        let result = sqlx::query(
+            r#"
+            UPDATE duas d
+            SET popularity_score = v.view_count::float8 / m.max_views
+            FROM dua_view_counts v,
+                 (SELECT MAX(view_count)::float8 AS max_views FROM dua_view_counts) m
+            WHERE d.id = v.dua_id AND m.max_views > 0
+            "#
         )
-        .fetch_one(&self.db.pool)
+        .execute(&self.db.pool)
         .await?;
+        
+        Ok(result.rows_affected())
+    }
 
-        let total_tags = sqlx::query_scalar::<_, i64>(
-            "SELECT COUNT(*) FROM dua_tags"
-        )
-        .fetch_one(&self.db.pool)
-        .await?;
+    // ============= STATISTICS =============
 
-        let total_bundles = sqlx::query_scalar::<_, i64>(
-            "SELECT COUNT(*) FROM dua_bundles"
-        )
-        .fetch_one(&self.db.pool)
-        .await?;
+    /// Reads the counters kept by the `dua_stats` triggers (migration 007)
+    pub async fn get_stats(&self) -> ApiResult<StatsResponse> {
+        let stats = sqlx::query_as::<_, StatsResponse>(STATS_QUERY)
+            .fetch_one(&self.db.pool)
+            .await?;
 
-        let most_popular_category = sqlx::query_scalar::<_, Option<String>>(
-            r#"
-            SELECT c.name 
-            FROM dua_categories c
-            JOIN dua_category_map dcm ON c.id = dcm.category_id
-            GROUP BY c.id, c.name
-            ORDER BY COUNT(*) DESC
-            LIMIT 1
-            "#
-        )
-        .fetch_optional(&self.db.pool)
-        .await?
-        .flatten();
+        Ok(stats)
+    }
 
-        let recent_additions = sqlx::query_scalar::<_, i64>(
-            "SELECT COUNT(*) FROM duas WHERE created_at > NOW() - INTERVAL '30 days'"
-        )
-        .fetch_one(&self.db.pool)
-        .await?;
+    /// Recounts `dua_stats` from the tables, correcting drift and refreshing
+    /// the most popular category and recent additions. The row is locked
+    /// first, so writes that land during the recount wait and then apply
+    /// their increments on top of it.
+    pub async fn refresh_stats(&self) -> ApiResult<()> {
+        let mut tx = self.db.pool.begin().await?;
+        sqlx::query("SELECT 1 FROM dua_stats FOR UPDATE")
+            .execute(&mut tx)
+            .await?;
+        sqlx::query(REFRESH_STATS_QUERY)
+            .bind(Status::Active.db_values())
+            .execute(&mut tx)
+            .await?;
+        tx.commit().await?;
 
-        Ok(StatsResponse {
-            total_duas,
-            verified_duas,
-            total_categories,
-            total_tags,
-            total_bundles,
-            most_popular_category,
-            recent_additions,
-        })
+        Ok(())
     }
 
     // ============= TRANSLATIONS =============
 
     pub async fn list_all_translations(&self) -> ApiResult<Vec<DuaTranslation>> {
         let translations = sqlx::query_as::<_, DuaTranslation>(
-            "SELECT * FROM dua_translations ORDER BY dua_id, language_code"
+            r#"
+            SELECT t.* FROM dua_translations t
+            JOIN duas d ON d.id = t.dua_id
+            WHERE d.status = ANY($1)
+            ORDER BY t.dua_id, t.language_code
+            "#
         )
+        .bind(Status::Active.db_values())
         .fetch_all(&self.db.pool)
         .await?;
         
         Ok(translations)
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn test_repository() -> Option<DuaRepository> {
+        let url = std::env::var("DATABASE_URL").ok()?;
+
+        let db = Database::new(&shared::config::DatabaseConfig {
+            url,
+            max_connections: 5,
+            min_connections: 1,
+            connect_timeout: 30,
+            acquire_timeout_ms: 5000,
+            idle_timeout: 600,
+            slow_query_threshold_ms: 500,
+        })
+        .await
+        .unwrap();
+
+        Some(DuaRepository::new(db))
+    }
+
+    async fn insert_test_dua(repo: &DuaRepository, label: &str) -> Uuid {
+        let id = Uuid::new_v4();
+        sqlx::query(
+            "INSERT INTO duas (id, title, arabic_text, translation, slug) VALUES ($1, $2, 'نص', 'Text', $3)",
+        )
+        .bind(id)
+        .bind(format!("Test {}", label))
+        .bind(format!("test-{}-{}", label, id))
+        .execute(&repo.db.pool)
+        .await
+        .unwrap();
+        id
+    }
+
+    async fn delete_test_dua(repo: &DuaRepository, id: Uuid) {
+        sqlx::query("DELETE FROM duas WHERE id = $1")
+            .bind(id)
+            .execute(&repo.db.pool)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_updates_keep_previous_versions() {
+        let Some(repo) = test_repository().await else {
+            return;
+        };
+
+        let id = insert_test_dua(&repo, "versioned").await;
+        for translation in ["Second text", "Third text"] {
+            sqlx::query("UPDATE duas SET translation = $2 WHERE id = $1")
+                .bind(id)
+                .bind(translation)
+                .execute(&repo.db.pool)
+                .await
+                .unwrap();
+        }
+        // Score updates aren't content edits
+        sqlx::query("UPDATE duas SET popularity_score = 0.9 WHERE id = $1")
+            .bind(id)
+            .execute(&repo.db.pool)
+            .await
+            .unwrap();
+
+        let versions = repo.list_dua_versions(id).await;
+        let first = repo.get_dua_version(id, 1).await;
+        let current = repo.get_dua_by_id(id).await;
+        delete_test_dua(&repo, id).await;
+
+        let versions = versions.unwrap();
+        assert_eq!(versions.iter().map(|v| v.version).collect::<Vec<_>>(), vec![2, 1]);
+        assert_eq!(versions[0].translation, "Second text");
+        assert_eq!(first.unwrap().unwrap().translation, "Text");
+        let current = current.unwrap().unwrap();
+        assert_eq!(current.version, 3);
+        assert_eq!(current.translation, "Third text");
+    }
+
+    #[tokio::test]
+    async fn test_creating_dua_increments_stats_without_recount() {
+        let Some(repo) = test_repository().await else {
+            return;
+        };
+
+        // Rolled back, and the lock keeps other tests' writes out of the row
+        let mut tx = repo.db.pool.begin().await.unwrap();
+        sqlx::query("SELECT 1 FROM dua_stats FOR UPDATE")
+            .execute(&mut tx)
+            .await
+            .unwrap();
+        // Totals no recount could produce
+        sqlx::query("UPDATE dua_stats SET total_duas = -100, verified_duas = -100")
+            .execute(&mut tx)
+            .await
+            .unwrap();
+        for status in ["active", "draft"] {
+            sqlx::query(
+                "INSERT INTO duas (title, arabic_text, translation, slug, status) VALUES ('Test stats', 'نص', 'Text', $1, $2)",
+            )
+            .bind(format!("test-stats-{}", Uuid::new_v4()))
+            .bind(status)
+            .execute(&mut tx)
+            .await
+            .unwrap();
+        }
+        let stats = sqlx::query_as::<_, StatsResponse>(STATS_QUERY)
+            .fetch_one(&mut tx)
+            .await
+            .unwrap();
+        tx.rollback().await.unwrap();
+
+        assert_eq!(stats.total_duas, -98);
+        assert_eq!(stats.verified_duas, -99);
+    }
+
+    #[tokio::test]
+    async fn test_refresh_corrects_drifted_stats() {
+        let Some(repo) = test_repository().await else {
+            return;
+        };
+
+        let mut tx = repo.db.pool.begin().await.unwrap();
+        sqlx::query("SELECT 1 FROM dua_stats FOR UPDATE")
+            .execute(&mut tx)
+            .await
+            .unwrap();
+        sqlx::query("UPDATE dua_stats SET total_duas = -100")
+            .execute(&mut tx)
+            .await
+            .unwrap();
+        sqlx::query(REFRESH_STATS_QUERY)
+            .bind(Status::Active.db_values())
+            .execute(&mut tx)
+            .await
+            .unwrap();
+        let stats = sqlx::query_as::<_, StatsResponse>(STATS_QUERY)
+            .fetch_one(&mut tx)
+            .await
+            .unwrap();
+        let total: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM duas")
+            .fetch_one(&mut tx)
+            .await
+            .unwrap();
+        tx.rollback().await.unwrap();
+
+        assert_eq!(stats.total_duas, total);
+    }
+
+    #[tokio::test]
+    async fn test_submitted_report_is_listed_as_open() {
+        let Some(repo) = test_repository().await else {
+            return;
+        };
+
+        let id = insert_test_dua(&repo, "reported").await;
+        let request = CreateReportRequest {
+            reporter: Some("  editor@example.org ".to_string()),
+            field: "translation".to_string(),
+            message: "Second sentence is missing".to_string(),
+        };
+        let field = request.check().unwrap();
+        let report = repo.create_report(id, field, &request).await;
+        let open = repo.list_reports(ReportStatus::Open).await;
+        let resolved = repo.list_reports(ReportStatus::Resolved).await;
+        delete_test_dua(&repo, id).await;
+
+        let report = report.unwrap();
+        assert_eq!(report.status, "open");
+        assert_eq!(report.reporter.as_deref(), Some("editor@example.org"));
+        assert!(open.unwrap().iter().any(|r| r.id == report.id && r.field == "translation"));
+        assert!(!resolved.unwrap().iter().any(|r| r.id == report.id));
+    }
+
+    #[tokio::test]
+    async fn test_search_all_groups_duas_and_categories() {
+        let Some(repo) = test_repository().await else {
+            return;
+        };
+
+        let dua_id = insert_test_dua(&repo, "unifiedzq").await;
+        let category_id = Uuid::new_v4();
+        sqlx::query("INSERT INTO dua_categories (id, name, slug) VALUES ($1, $2, $3)")
+            .bind(category_id)
+            .bind(format!("Unifiedzq {}", category_id))
+            .bind(format!("unifiedzq-{}", category_id))
+            .execute(&repo.db.pool)
+            .await
+            .unwrap();
+
+        let results = repo.search_all("unifiedzq", 10).await;
+
+        sqlx::query("DELETE FROM dua_categories WHERE id = $1")
+            .bind(category_id)
+            .execute(&repo.db.pool)
+            .await
+            .unwrap();
+        delete_test_dua(&repo, dua_id).await;
+
+        let results = results.unwrap();
+        assert!(results.duas.results.iter().any(|d| d.id == dua_id));
+        assert!(results.categories.results.iter().any(|c| c.id == category_id));
+        assert_eq!(results.duas.result_type, "dua");
+        assert_eq!(results.categories.count, results.categories.results.len());
+        assert_eq!(
+            results.total,
+            results.duas.count + results.categories.count + results.tags.count + results.bundles.count
+        );
+    }
+
+    #[tokio::test]
+    async fn test_blank_query_does_not_match_every_dua() {
+        let Some(repo) = test_repository().await else {
+            return;
+        };
+
+        let dua_id = insert_test_dua(&repo, "blankzq").await;
+
+        let searched = repo.keyword_search("   ", 50, &SearchFilters::default()).await;
+        let suggested = repo.get_suggestions("", 50).await;
+        let matched = repo.keyword_search(" blankzq ", 50, &SearchFilters::default()).await;
+
+        delete_test_dua(&repo, dua_id).await;
+
+        assert!(searched.unwrap().is_empty());
+        assert!(suggested.unwrap().is_empty());
+        assert!(matched.unwrap().iter().any(|d| d.id == dua_id));
+    }
+
+    async fn insert_test_source(repo: &DuaRepository, dua_id: Uuid, authenticity: &str) {
+        sqlx::query(
+            "INSERT INTO dua_sources (dua_id, source_type, authenticity) VALUES ($1, 'Hadith', $2::authenticity_enum)",
+        )
+        .bind(dua_id)
+        .bind(authenticity)
+        .execute(&repo.db.pool)
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_min_authenticity_excludes_daif_sources() {
+        let Some(repo) = test_repository().await else {
+            return;
+        };
+
+        let daif_id = insert_test_dua(&repo, "gradezq").await;
+        let sahih_id = insert_test_dua(&repo, "gradezq").await;
+        insert_test_source(&repo, daif_id, "Daif").await;
+        insert_test_source(&repo, sahih_id, "Sahih").await;
+
+        let unfiltered = repo.keyword_search("gradezq", 50, &SearchFilters::default()).await;
+        let sahih_only = SearchFilters::parse(Some("sahih"), false).unwrap();
+        let filtered = repo.keyword_search("gradezq", 50, &sahih_only).await;
+        let semantic = repo.semantic_search("gradezq", 50, 0.5, &sahih_only).await;
+
+        delete_test_dua(&repo, daif_id).await;
+        delete_test_dua(&repo, sahih_id).await;
+
+        let ids = |duas: Vec<Dua>| duas.into_iter().map(|d| d.id).collect::<Vec<_>>();
+        let unfiltered = ids(unfiltered.unwrap());
+        assert!(unfiltered.contains(&daif_id) && unfiltered.contains(&sahih_id));
+        for results in [ids(filtered.unwrap()), ids(semantic.unwrap())] {
+            assert!(!results.contains(&daif_id));
+            assert!(results.contains(&sahih_id));
+        }
+    }
+
+    #[test]
+    fn test_search_filters_allowed_authenticities() {
+        assert_eq!(SearchFilters::default().allowed_authenticities(), None);
+        assert_eq!(
+            SearchFilters::parse(Some("Hasan"), false).unwrap().allowed_authenticities(),
+            Some(vec!["Quranic".to_string(), "Sahih".to_string(), "Hasan".to_string()])
+        );
+        let verified = SearchFilters::parse(None, true).unwrap().allowed_authenticities().unwrap();
+        assert!(!verified.contains(&"Unclassified".to_string()));
+        assert!(verified.contains(&"Daif".to_string()));
+        assert!(SearchFilters::parse(Some("strong"), false).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_export_ndjson_yields_one_object_per_dua() {
+        let Some(repo) = test_repository().await else {
+            return;
+        };
+
+        let seeded = [
+            insert_test_dua(&repo, "exportzq").await,
+            insert_test_dua(&repo, "exportzq").await,
+        ];
+        let draft = insert_test_dua(&repo, "exportzq").await;
+        sqlx::query("UPDATE duas SET status = 'draft' WHERE id = $1")
+            .bind(draft)
+            .execute(&repo.db.pool)
+            .await
+            .unwrap();
+
+        let mut rx = test_repository().await.unwrap().export_ndjson(Some("sources,categories,tags".to_string()));
+        let mut lines = Vec::new();
+        while let Some(line) = rx.recv().await {
+            lines.push(line);
+        }
+
+        for id in seeded.into_iter().chain([draft]) {
+            delete_test_dua(&repo, id).await;
+        }
+
+        let exported: Vec<serde_json::Value> = lines
+            .into_iter()
+            .map(|line| {
+                let line = line.unwrap();
+                assert!(line.ends_with('\n') && !line.trim_end().contains('\n'));
+                serde_json::from_str(&line).unwrap()
+            })
+            .collect();
+        assert!(exported
+            .iter()
+            .all(|v| v["sources"].is_array() && v["categories"].is_array() && v["tags"].is_array()));
+        // A backup covers drafts as well as published duas
+        for id in seeded.into_iter().chain([draft]) {
+            let matches = exported
+                .iter()
+                .filter(|v| v["id"] == serde_json::json!(id))
+                .count();
+            assert_eq!(matches, 1);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_translation_by_language_code() {
+        let Some(repo) = test_repository().await else {
+            return;
+        };
+
+        let dua_id = insert_test_dua(&repo, "translationzq").await;
+        for (lang, slug) in [("ur", None), ("en", Some("morning-remembrance"))] {
+            sqlx::query(
+                "INSERT INTO dua_translations (dua_id, language_code, title, slug, seo_title) VALUES ($1, $2, 'Title', $3, 'SEO')",
+            )
+            .bind(dua_id)
+            .bind(lang)
+            .bind(slug)
+            .execute(&repo.db.pool)
+            .await
+            .unwrap();
+        }
+
+        let urdu = repo.get_dua_translation(dua_id, "UR").await;
+        let english = repo.get_dua_translation(dua_id, "en").await;
+        let french = repo.get_dua_translation(dua_id, "fr").await;
+        let dua = repo.get_dua_by_id(dua_id).await;
+
+        delete_test_dua(&repo, dua_id).await;
+
+        let urdu = urdu.unwrap().expect("urdu translation");
+        assert_eq!(urdu.translation.language_code, "ur");
+        assert_eq!(urdu.translation.seo_title.as_deref(), Some("SEO"));
+        assert_eq!(urdu.canonical_slug, dua.unwrap().unwrap().slug);
+        assert_eq!(english.unwrap().unwrap().canonical_slug, "morning-remembrance");
+        assert!(french.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_accept_language_localizes_without_lang_param() {
+        let Some(repo) = test_repository().await else {
+            return;
+        };
+
+        let dua_id = insert_test_dua(&repo, "acceptlangzq").await;
+        sqlx::query(
+            "INSERT INTO dua_translations (dua_id, language_code, title, translation) VALUES ($1, 'ur', 'صبح کی دعا', 'اردو ترجمہ')",
+        )
+        .bind(dua_id)
+        .execute(&repo.db.pool)
+        .await
+        .unwrap();
+
+        let available = repo.available_languages().await.unwrap();
+        let lang = crate::language::negotiate_language("ur", &available);
+        let dua = repo.get_dua_by_id(dua_id).await.unwrap().unwrap();
+        let mut localized = repo.load_relations(dua, None).await.unwrap();
+        let result = repo
+            .localize(std::slice::from_mut(&mut localized), lang.as_deref().unwrap())
+            .await;
+
+        delete_test_dua(&repo, dua_id).await;
+
+        result.unwrap();
+        assert_eq!(lang.as_deref(), Some("ur"));
+        assert_eq!(localized.lang.as_deref(), Some("ur"));
+        assert_eq!(localized.dua.title, "صبح کی دعا");
+        assert_eq!(localized.dua.translation, "اردو ترجمہ");
+    }
+
+    #[tokio::test]
+    async fn test_localize_batches_a_page_of_duas() {
+        let Some(repo) = test_repository().await else {
+            return;
+        };
+
+        let translated = insert_test_dua(&repo, "localizezq").await;
+        let untranslated = insert_test_dua(&repo, "localizezq").await;
+        sqlx::query(
+            "INSERT INTO dua_translations (dua_id, language_code, title, translation) VALUES ($1, 'ur', 'صبح کی دعا', 'اردو ترجمہ')",
+        )
+        .bind(translated)
+        .execute(&repo.db.pool)
+        .await
+        .unwrap();
+
+        let mut page = Vec::new();
+        for id in [translated, untranslated] {
+            let dua = repo.get_dua_by_id(id).await.unwrap().unwrap();
+            page.push(repo.load_relations(dua, None).await.unwrap());
+        }
+        let result = repo.localize(&mut page, "UR").await;
+
+        delete_test_dua(&repo, translated).await;
+        delete_test_dua(&repo, untranslated).await;
+
+        result.unwrap();
+        assert_eq!(page[0].lang.as_deref(), Some("ur"));
+        assert_eq!(page[0].dua.translation, "اردو ترجمہ");
+        assert!(page[1].lang.is_none());
+        assert_eq!(page[1].dua.translation, "Text");
+    }
+
+    #[tokio::test]
+    async fn test_compare_aligns_requested_translations() {
+        let Some(repo) = test_repository().await else {
+            return;
+        };
+
+        let dua_id = insert_test_dua(&repo, "comparezq").await;
+        sqlx::query(
+            "INSERT INTO dua_translations (dua_id, language_code, title, translation) VALUES ($1, 'en', 'Morning dua', 'English text'), ($1, 'ur', 'صبح کی دعا', 'اردو ترجمہ')",
+        )
+        .bind(dua_id)
+        .execute(&repo.db.pool)
+        .await
+        .unwrap();
+
+        let params = CompareTranslationsParams { langs: "en, UR,fr".to_string() };
+        let result = crate::handlers::compare_translations(
+            axum::Extension(repo.db.clone()),
+            axum::extract::Path(dua_id),
+            axum::extract::Query(params),
+        )
+        .await;
+
+        delete_test_dua(&repo, dua_id).await;
+
+        let axum::Json(comparison) = result.unwrap();
+        assert_eq!(comparison.base.translation.as_deref(), Some("Text"));
+        let [en, ur, fr] = &comparison.translations[..] else {
+            panic!("expected three languages: {:?}", comparison.translations);
+        };
+        assert_eq!((en.language_code.as_str(), en.missing), ("en", false));
+        assert_eq!(en.text.translation.as_deref(), Some("English text"));
+        assert_eq!((ur.language_code.as_str(), ur.missing), ("ur", false));
+        assert_eq!(ur.text.title.as_deref(), Some("صبح کی دعا"));
+        assert!(fr.missing && fr.text.translation.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_upsert_replaces_translation_in_same_language() {
+        let Some(repo) = test_repository().await else {
+            return;
+        };
+
+        let dua_id = insert_test_dua(&repo, "upsertzq").await;
+        let batch: Vec<UpsertTranslationRequest> = serde_json::from_value(serde_json::json!([
+            { "language_code": "en", "title": "Morning dua", "translation": "English text" },
+            { "language_code": "fr", "translation": "Texte français" }
+        ]))
+        .unwrap();
+        check_translation_batch(&batch).unwrap();
+        let first = repo.upsert_translations(dua_id, &batch).await;
+
+        let update: Vec<UpsertTranslationRequest> = serde_json::from_value(serde_json::json!([
+            { "language_code": "FR", "translation": "Texte révisé" }
+        ]))
+        .unwrap();
+        let second = repo.upsert_translations(dua_id, &update).await;
+
+        delete_test_dua(&repo, dua_id).await;
+
+        assert_eq!(first.unwrap().len(), 2);
+        let translations = second.unwrap();
+        let french: Vec<_> = translations.iter().filter(|t| t.language_code == "fr").collect();
+        assert_eq!(translations.len(), 2);
+        assert_eq!(french.len(), 1);
+        assert_eq!(french[0].translation.as_deref(), Some("Texte révisé"));
+    }
+
+    #[tokio::test]
+    async fn test_seeded_random_dua_is_deterministic() {
+        let Some(repo) = test_repository().await else {
+            return;
+        };
+
+        let params = DuaQueryParams {
+            seed: Some("daily-2024-01-01".to_string()),
+            ..Default::default()
+        };
+
+        let first = repo.get_random_dua(&params).await.unwrap().map(|d| d.id);
+        let second = repo.get_random_dua(&params).await.unwrap().map(|d| d.id);
+        assert_eq!(first, second);
+    }
+
+    #[tokio::test]
+    async fn test_filter_by_repetitions() {
+        let Some(repo) = test_repository().await else {
+            return;
+        };
+
+        let thrice = insert_test_dua(&repo, "thrice").await;
+        let once = insert_test_dua(&repo, "once").await;
+        for (id, count) in [(thrice, 3), (once, 1)] {
+            sqlx::query("INSERT INTO dua_context (dua_id, repetition_count) VALUES ($1, $2)")
+                .bind(id)
+                .bind(count)
+                .execute(&repo.db.pool)
+                .await
+                .unwrap();
+        }
+
+        let params = DuaQueryParams {
+            repetitions: Some("3".to_string()),
+            per_page: Some(100),
+            ..Default::default()
+        };
+        let (duas, total) = repo.list_with_filters(&params).await.unwrap();
+        let ids: Vec<Uuid> = duas.iter().map(|d| d.id).collect();
+
+        delete_test_dua(&repo, thrice).await;
+        delete_test_dua(&repo, once).await;
+
+        assert!(ids.contains(&thrice));
+        assert!(!ids.contains(&once));
+        assert_eq!(total as usize, ids.len());
+    }
+
+    #[tokio::test]
+    async fn test_filter_rejects_invalid_repetitions() {
+        let Some(repo) = test_repository().await else {
+            return;
+        };
+
+        let params = DuaQueryParams {
+            repetitions: Some("three".to_string()),
+            ..Default::default()
+        };
+        assert!(repo.list_with_filters(&params).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_filter_by_dua_type() {
+        let Some(repo) = test_repository().await else {
+            return;
+        };
+
+        let dhikr = insert_test_dua(&repo, "dhikr").await;
+        let plain = insert_test_dua(&repo, "plain").await;
+        sqlx::query("UPDATE duas SET dua_type = 'dhikr' WHERE id = $1")
+            .bind(dhikr)
+            .execute(&repo.db.pool)
+            .await
+            .unwrap();
+
+        let params = DuaQueryParams {
+            dua_type: Some("dhikr".to_string()),
+            per_page: Some(100),
+            ..Default::default()
+        };
+        let (duas, _) = repo.list_with_filters(&params).await.unwrap();
+
+        delete_test_dua(&repo, dhikr).await;
+        delete_test_dua(&repo, plain).await;
+
+        assert!(duas.iter().any(|d| d.id == dhikr));
+        assert!(duas.iter().all(|d| d.dua_type == "dhikr"));
+    }
+
+    #[tokio::test]
+    async fn test_filter_rejects_unknown_dua_type() {
+        let Some(repo) = test_repository().await else {
+            return;
+        };
+
+        let params = DuaQueryParams {
+            dua_type: Some("poem".to_string()),
+            ..Default::default()
+        };
+        let err = repo.list_with_filters(&params).await.unwrap_err();
+        assert!(matches!(err, ApiError::Validation(_)));
+    }
+
+    #[tokio::test]
+    async fn test_filter_by_updated_range() {
+        let Some(repo) = test_repository().await else {
+            return;
+        };
+
+        let recent = insert_test_dua(&repo, "recent").await;
+        let stale = insert_test_dua(&repo, "stale").await;
+        sqlx::query("UPDATE duas SET updated_at = NOW() - INTERVAL '30 days' WHERE id = $1")
+            .bind(stale)
+            .execute(&repo.db.pool)
+            .await
+            .unwrap();
+
+        let week_ago = chrono::Utc::now() - chrono::Duration::days(7);
+        let params = DuaQueryParams {
+            updated_after: Some(week_ago.to_rfc3339()),
+            per_page: Some(100),
+            ..Default::default()
+        };
+        let (duas, _) = repo.list_with_filters(&params).await.unwrap();
+
+        delete_test_dua(&repo, recent).await;
+        delete_test_dua(&repo, stale).await;
+
+        assert!(duas.iter().any(|d| d.id == recent));
+        assert!(!duas.iter().any(|d| d.id == stale));
+    }
+
+    #[tokio::test]
+    async fn test_filter_rejects_invalid_date() {
+        let Some(repo) = test_repository().await else {
+            return;
+        };
+
+        let params = DuaQueryParams {
+            created_before: Some("last tuesday".to_string()),
+            ..Default::default()
+        };
+        let err = repo.list_with_filters(&params).await.unwrap_err();
+        assert!(matches!(err, ApiError::Validation(_)));
+    }
+
+    #[tokio::test]
+    async fn test_drafts_hidden_unless_requested() {
+        let Some(repo) = test_repository().await else {
+            return;
+        };
+
+        let draft = insert_test_dua(&repo, "draft").await;
+        sqlx::query("UPDATE duas SET status = 'draft' WHERE id = $1")
+            .bind(draft)
+            .execute(&repo.db.pool)
+            .await
+            .unwrap();
+
+        let public = DuaQueryParams {
+            per_page: Some(100),
+            ..Default::default()
+        };
+        let (public_duas, _) = repo.list_with_filters(&public).await.unwrap();
+
+        let editor = DuaQueryParams {
+            status: Some("draft".to_string()),
+            per_page: Some(100),
+            ..Default::default()
+        };
+        let (draft_duas, _) = repo.list_with_filters(&editor).await.unwrap();
+
+        delete_test_dua(&repo, draft).await;
+
+        assert!(!public_duas.iter().any(|d| d.id == draft));
+        assert!(draft_duas.iter().any(|d| d.id == draft));
+        assert!(draft_duas.iter().all(|d| d.status == "draft"));
+    }
+
+    #[tokio::test]
+    async fn test_bulk_import_reports_failures() {
+        let Some(repo) = test_repository().await else {
+            return;
+        };
+
+        let prefix = format!("bulk-{}", Uuid::new_v4());
+        let entry = |suffix: &str| {
+            serde_json::json!({
+                "title": format!("Bulk {}", suffix),
+                "arabic_text": "نص",
+                "translation": "Text",
+                "slug": format!("{}-{}", prefix, suffix),
+                "sources": [{"source_type": "Hadith", "authenticity": "Sahih"}],
+                "context": {"repetition_count": 3, "invocation_time": ["morning"]},
+                "tags": ["sunnah"]
+            })
+        };
+
+        let mut invalid = entry("invalid");
+        invalid["arabic_text"] = serde_json::json!("");
+        let entries = vec![entry("a"), entry("b"), invalid, entry("c")];
+
+        let report = repo.bulk_import(entries).await.unwrap();
+
+        sqlx::query("DELETE FROM duas WHERE slug LIKE $1")
+            .bind(format!("{}%", prefix))
+            .execute(&repo.db.pool)
+            .await
+            .unwrap();
+
+        assert_eq!(report.inserted, 3);
+        assert_eq!(report.failed.len(), 1);
+        assert_eq!(report.failed[0].index, 2);
+        assert!(report.failed[0].error.contains("arabic_text"));
+    }
+
+    #[tokio::test]
+    async fn test_bulk_import_rejects_duplicate_slugs_in_batch() {
+        let Some(repo) = test_repository().await else {
+            return;
+        };
+
+        let slug = format!("bulk-dup-{}", Uuid::new_v4());
+        let entry = serde_json::json!({
+            "title": "Duplicate",
+            "arabic_text": "نص",
+            "translation": "Text",
+            "slug": slug
+        });
+
+        let report = repo.bulk_import(vec![entry.clone(), entry]).await.unwrap();
+
+        sqlx::query("DELETE FROM duas WHERE slug = $1")
+            .bind(&slug)
+            .execute(&repo.db.pool)
+            .await
+            .unwrap();
+
+        assert_eq!(report.inserted, 1);
+        assert_eq!(report.failed[0].index, 1);
+        assert!(report.failed[0].error.contains("Duplicate slug"));
+    }
+
+    #[tokio::test]
+    async fn test_create_dua_media() {
+        let Some(repo) = test_repository().await else {
+            return;
+        };
+
+        let dua_id = insert_test_dua(&repo, "media").await;
+        let request: CreateMediaRequest = serde_json::from_value(serde_json::json!({
+            "media_type": "audio",
+            "url": "https://cdn.example.com/duas/morning.mp3",
+            "duration": 42,
+            "reciter_name": "Test Reciter",
+            "reciter_style": "murattal",
+            "license": "CC-BY"
+        }))
+        .unwrap();
+        assert!(request.check().is_ok());
+
+        let media = repo.create_dua_media(dua_id, &request, request.duration).await;
+        let local: CreateMediaRequest = serde_json::from_value(serde_json::json!({
+            "media_type": "audio",
+            "file_path": "duas/morning.wav"
+        }))
+        .unwrap();
+        let local = repo.create_dua_media(dua_id, &local, None).await;
+        delete_test_dua(&repo, dua_id).await;
+
+        let media = media.unwrap();
+        assert_eq!(media.media_type, "audio");
+        assert_eq!(media.duration, Some(42));
+        assert_eq!(media.license.as_deref(), Some("CC-BY"));
+        assert_eq!(media.file_path, None);
+
+        // A file on this server keeps its path in file_path, not url
+        let local = local.unwrap();
+        assert_eq!(local.url, None);
+        assert_eq!(local.file_path.as_deref(), Some("duas/morning.wav"));
+    }
+
+    #[test]
+    fn test_create_media_rejects_invalid_values() {
+        let request: CreateMediaRequest = serde_json::from_value(serde_json::json!({
+            "media_type": "hologram",
+            "url": "https://cdn.example.com/duas/morning.mp3"
+        }))
+        .unwrap();
+        assert!(request.check().unwrap_err().contains("media_type"));
+
+        let request: CreateMediaRequest = serde_json::from_value(serde_json::json!({
+            "media_type": "audio",
+            "url": "https://cdn.example.com/duas/morning.mp3",
+            "reciter_style": "opera"
+        }))
+        .unwrap();
+        assert!(request.check().unwrap_err().contains("reciter_style"));
+
+        let request: CreateMediaRequest =
+            serde_json::from_value(serde_json::json!({ "media_type": "audio" })).unwrap();
+        assert!(request.check().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_unseeded_random_dua_returns_a_dua() {
+        let Some(repo) = test_repository().await else {
+            return;
+        };
+
+        // Same published statuses the random query filters on
+        let total: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM duas WHERE status = ANY($1)")
+            .bind(Status::Active.db_values())
+            .fetch_one(&repo.db.pool)
+            .await
+            .unwrap();
+
+        let dua = repo.get_random_dua(&DuaQueryParams::default()).await.unwrap();
+        assert_eq!(dua.is_some(), total > 0);
+    }
+
+    #[tokio::test]
+    async fn test_random_dua_binds_filter_values() {
+        let Some(repo) = test_repository().await else {
+            return;
+        };
+
+        // Spliced into the SQL, this would match every dua in any category
+        let injected = "x' OR '1'='1".to_string();
+        for seed in [None, Some("abc".to_string())] {
+            let params = DuaQueryParams {
+                category: Some(injected.clone()),
+                invocation_time: Some(injected.clone()),
+                event_trigger: Some(injected.clone()),
+                seed,
+                ..Default::default()
+            };
+            assert!(repo.get_random_dua(&params).await.unwrap().is_none());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_count_only_matches_full_total_without_rows() {
+        let Some(repo) = test_repository().await else {
+            return;
+        };
+
+        let seeded = [
+            insert_test_dua(&repo, "countzq").await,
+            insert_test_dua(&repo, "countzq").await,
+        ];
+
+        let mut params = DuaQueryParams {
+            q: Some("countzq".to_string()),
+            ..Default::default()
+        };
+        let full = repo.list_with_filters(&params).await;
+        params.count_only = Some(true);
+        let counted = repo.list_with_filters(&params).await;
+
+        for id in seeded {
+            delete_test_dua(&repo, id).await;
+        }
+
+        let (duas, total) = full.unwrap();
+        assert_eq!(total, 2);
+        assert_eq!(duas.len(), 2);
+        let (duas, count_only_total) = counted.unwrap();
+        assert_eq!(count_only_total, total);
+        assert!(duas.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_filter_by_hijri_month_finds_ramadan_duas() {
+        let Some(repo) = test_repository().await else {
+            return;
+        };
+
+        let ramadan = insert_test_dua(&repo, "ramadanzq").await;
+        let hajj = insert_test_dua(&repo, "ramadanzq").await;
+        for (id, occasion) in [(ramadan, "laylat_al_qadr"), (hajj, "hajj")] {
+            sqlx::query("INSERT INTO dua_context (dua_id, calendar_context) VALUES ($1, ARRAY[$2])")
+                .bind(id)
+                .bind(occasion)
+                .execute(&repo.db.pool)
+                .await
+                .unwrap();
+        }
+
+        let params = DuaQueryParams {
+            q: Some("ramadanzq".to_string()),
+            hijri_month: Some(9),
+            ..Default::default()
+        };
+        let listed = repo.list_with_filters(&params).await;
+        let invalid = repo
+            .list_with_filters(&DuaQueryParams {
+                hijri_month: Some(13),
+                ..Default::default()
+            })
+            .await;
+
+        delete_test_dua(&repo, ramadan).await;
+        delete_test_dua(&repo, hajj).await;
+
+        let ids: Vec<Uuid> = listed.unwrap().0.iter().map(|d| d.id).collect();
+        assert_eq!(ids, vec![ramadan]);
+        assert!(matches!(invalid, Err(ApiError::Validation(_))));
+    }
+
+    #[tokio::test]
+    async fn test_record_view_increments_counter() {
+        let Some(repo) = test_repository().await else {
+            return;
+        };
+
+        let dua_id = insert_test_dua(&repo, "viewzq").await;
+
+        let first = repo.record_view(dua_id).await;
+        let second = repo.record_view(dua_id).await;
+        let stored: Result<i64, _> = sqlx::query_scalar("SELECT view_count FROM dua_view_counts WHERE dua_id = $1")
+            .bind(dua_id)
+            .fetch_one(&repo.db.pool)
+            .await;
+        let recomputed = repo.recompute_popularity().await;
+        let dua = repo.get_dua_by_id(dua_id).await;
+
+        delete_test_dua(&repo, dua_id).await;
+
+        assert_eq!(first.unwrap(), 1);
+        assert_eq!(second.unwrap(), 2);
+        assert_eq!(stored.unwrap(), 2);
+        assert!(recomputed.unwrap() >= 1);
+        let score = dua.unwrap().unwrap().popularity_score;
+        assert!(score > 0.0 && score <= 1.0);
+    }
+
+    #[tokio::test]
+    async fn test_missing_category_is_not_found_but_empty_category_lists_nothing() {
+        let Some(repo) = test_repository().await else {
+            return;
+        };
+
+        let category_id = Uuid::new_v4();
+        let slug = format!("emptyzq-{}", category_id);
+        sqlx::query("INSERT INTO dua_categories (id, name, slug) VALUES ($1, $2, $3)")
+            .bind(category_id)
+            .bind(format!("Emptyzq {}", category_id))
+            .bind(&slug)
+            .execute(&repo.db.pool)
+            .await
+            .unwrap();
+
+        let existing = repo.require_category(&slug).await;
+        let params = DuaQueryParams {
+            category: Some(slug.clone()),
+            ..Default::default()
+        };
+        let listed = repo.list_with_filters(&params).await;
+        let missing = repo.require_category(&format!("missingzq-{}", category_id)).await;
+
+        sqlx::query("DELETE FROM dua_categories WHERE id = $1")
+            .bind(category_id)
+            .execute(&repo.db.pool)
+            .await
+            .unwrap();
+
+        assert_eq!(existing.unwrap().id, category_id);
+        let (duas, total) = listed.unwrap();
+        assert!(duas.is_empty());
+        assert_eq!(total, 0);
+        assert!(matches!(missing, Err(ApiError::NotFound(_))));
+    }
+
+    #[test]
+    fn test_like_patterns_escape_wildcards() {
+        assert_eq!(contains_pattern("dua"), "%dua%");
+        assert_eq!(contains_pattern("%"), "%\\%%");
+        assert_eq!(escape_like("a_b\\c"), "a\\_b\\\\c");
+    }
+
+    #[tokio::test]
+    async fn test_wildcards_in_search_terms_match_literally() {
+        let Some(repo) = test_repository().await else {
+            return;
+        };
+
+        let percent = insert_test_dua(&repo, "likezq 100%").await;
+        let digits = insert_test_dua(&repo, "likezq 1000").await;
+
+        let found = repo
+            .keyword_search("likezq 100%", 10, &SearchFilters::default())
+            .await
+            .unwrap();
+        let params = DuaQueryParams {
+            q: Some("likezq_1".to_string()),
+            ..Default::default()
+        };
+        let (listed, _) = repo.list_with_filters(&params).await.unwrap();
+
+        delete_test_dua(&repo, percent).await;
+        delete_test_dua(&repo, digits).await;
+
+        assert_eq!(found.iter().map(|d| d.id).collect::<Vec<_>>(), vec![percent]);
+        assert!(listed.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_drafts_are_hidden_from_public_lookups() {
+        let Some(repo) = test_repository().await else {
+            return;
+        };
+
+        let draft = insert_test_dua(&repo, "hiddenzq").await;
+        insert_test_source(&repo, draft, "Sahih").await;
+        sqlx::query("INSERT INTO dua_translations (dua_id, language_code, title, translation) VALUES ($1, 'ur', 'عنوان', 'ترجمہ')")
+            .bind(draft)
+            .execute(&repo.db.pool)
+            .await
+            .unwrap();
+        sqlx::query("UPDATE duas SET status = 'draft' WHERE id = $1")
+            .bind(draft)
+            .execute(&repo.db.pool)
+            .await
+            .unwrap();
+        let (slug, source_id): (String, Uuid) = sqlx::query_as(
+            "SELECT d.slug, s.id FROM duas d JOIN dua_sources s ON s.dua_id = d.id WHERE d.id = $1",
+        )
+        .bind(draft)
+        .fetch_one(&repo.db.pool)
+        .await
+        .unwrap();
+
+        let by_id = repo.get_dua_by_id(draft).await;
+        let required = repo.require_dua(draft).await;
+        let any = repo.get_any_dua_by_id(draft).await;
+        let by_slug = repo.get_dua_by_slug(&slug).await;
+        let by_source = repo.get_duas_by_source(source_id).await;
+        let translation = repo.get_dua_translation(draft, "ur").await;
+        let all_translations = repo.list_all_translations().await;
+
+        delete_test_dua(&repo, draft).await;
+
+        assert!(by_id.unwrap().is_none());
+        assert!(matches!(required, Err(ApiError::NotFound(_))));
+        assert_eq!(any.unwrap().unwrap().id, draft);
+        assert!(by_slug.unwrap().is_none());
+        assert!(by_source.unwrap().is_empty());
+        assert!(translation.unwrap().is_none());
+        assert!(all_translations.unwrap().iter().all(|t| t.dua_id != draft));
+    }
+}
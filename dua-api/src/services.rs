@@ -1,13 +1,17 @@
-use shared::{cache::Cache, error::ApiResult};
+use shared::{cache::{Cache, CachedJson}, error::{ApiError, ApiResult}};
 use std::time::Duration;
 use tracing::{debug, warn};
 use uuid::Uuid;
 
 use crate::{
+    language::negotiate_language,
     models::*,
     repository::DuaRepository,
 };
 
+/// Languages duas are translated into, for Accept-Language negotiation
+const LANGUAGES_CACHE_KEY: &str = "languages:all";
+
 pub struct DuaService {
     repository: DuaRepository,
     cache: Cache,
@@ -20,45 +24,45 @@ impl DuaService {
 
     // ============= DUA OPERATIONS =============
 
-    pub async fn list_duas_with_filters(&self, params: DuaQueryParams) -> ApiResult<DuaListResponse> {
+    /// Serialized dua list; simple queries are cached as response bytes
+    pub async fn list_duas_json(&self, params: DuaQueryParams) -> ApiResult<CachedJson> {
+        let cacheable = params.q.is_none() && params.category.is_none() && params.tag.is_none();
         let cache_key = self.create_search_cache_key(&params);
         
         // Try cache for simple queries
-        if params.q.is_none() && params.category.is_none() && params.tag.is_none() {
-            if let Ok(Some(cached_response)) = self.cache.get::<DuaListResponse>(&cache_key).await {
+        if cacheable {
+            if let Ok(Some(cached_response)) = self.cache.get_response(&cache_key).await {
                 debug!("Returning cached dua list");
                 return Ok(cached_response);
             }
         }
         
+        let response = CachedJson::from_value(&self.list_duas_with_filters(params).await?)?;
+        
+        // Cache simple queries for 10 minutes
+        if cacheable {
+            if let Err(e) = self.cache.set_response(&cache_key, &response, Some(Duration::from_secs(600))).await {
+                warn!("Failed to cache dua list: {}", e);
+            }
+        }
+        
+        Ok(response)
+    }
+
+    pub async fn list_duas_with_filters(&self, params: DuaQueryParams) -> ApiResult<DuaListResponse> {
         // Fetch from database
         let (duas, total) = self.repository.list_with_filters(&params).await?;
         
         // Load relations if requested
         let mut duas_with_relations = Vec::new();
         for dua in duas {
-            let dua_with_relations = self.load_dua_relations(dua, params.include.as_deref()).await?;
-            duas_with_relations.push(dua_with_relations);
+            duas_with_relations.push(self.load_dua_relations(dua, params.include.as_deref()).await?);
         }
-        
-        let total_pages = ((total as f64) / (params.get_per_page() as f64)).ceil() as u32;
-        
-        let response = DuaListResponse {
-            duas: duas_with_relations,
-            total,
-            page: params.get_page(),
-            per_page: params.get_per_page(),
-            total_pages,
-        };
-        
-        // Cache simple queries for 10 minutes
-        if params.q.is_none() && params.category.is_none() && params.tag.is_none() {
-            if let Err(e) = self.cache.set(&cache_key, &response, Some(Duration::from_secs(600))).await {
-                warn!("Failed to cache dua list: {}", e);
-            }
+        if let Some(ref lang) = params.lang {
+            self.repository.localize(&mut duas_with_relations, lang).await?;
         }
         
-        Ok(response)
+        Ok(DuaListResponse::new(duas_with_relations, total, params.get_page(), params.get_per_page()))
     }
 
     pub async fn get_dua_by_id(&self, id: Uuid, include: Option<String>) -> ApiResult<Option<DuaWithRelations>> {
@@ -100,58 +104,68 @@ impl DuaService {
 
     pub async fn get_random_dua(&self, params: DuaQueryParams) -> ApiResult<Option<DuaWithRelations>> {
         if let Some(dua) = self.repository.get_random_dua(&params).await? {
-            let dua_with_relations = self.load_dua_relations(dua, params.include.as_deref()).await?;
+            let mut dua_with_relations = self.load_dua_relations(dua, params.include.as_deref()).await?;
+            if let Some(ref lang) = params.lang {
+                self.localize(&mut dua_with_relations, lang).await?;
+            }
             Ok(Some(dua_with_relations))
         } else {
             Ok(None)
         }
     }
 
-    async fn load_dua_relations(&self, dua: Dua, include: Option<&str>) -> ApiResult<DuaWithRelations> {
-        let mut dua_with_relations = DuaWithRelations {
-            dua: dua.clone(),
-            sources: None,
-            context: None,
-            media: None,
-            categories: None,
-            tags: None,
-            translations: None,
-            variants: None,
-        };
+    pub async fn bulk_import(&self, entries: Vec<serde_json::Value>) -> ApiResult<BulkImportResponse> {
+        let report = self.repository.bulk_import(entries).await?;
         
-        if let Some(include) = include {
-            let includes: Vec<&str> = include.split(',').collect();
-            
-            if includes.contains(&"sources") {
-                dua_with_relations.sources = Some(self.repository.get_dua_sources(dua.id).await?);
-            }
-            
-            if includes.contains(&"context") {
-                dua_with_relations.context = self.repository.get_dua_context(dua.id).await?;
-            }
-            
-            if includes.contains(&"media") {
-                dua_with_relations.media = Some(self.repository.get_dua_media(dua.id).await?);
-            }
-            
-            if includes.contains(&"categories") {
-                dua_with_relations.categories = Some(self.repository.get_dua_categories(dua.id).await?);
-            }
-            
-            if includes.contains(&"tags") {
-                dua_with_relations.tags = Some(self.repository.get_dua_tags(dua.id).await?);
-            }
-            
-            if includes.contains(&"translations") {
-                dua_with_relations.translations = Some(self.repository.get_dua_translations(dua.id).await?);
-            }
-            
-            if includes.contains(&"variants") {
-                dua_with_relations.variants = Some(self.repository.get_dua_variants(dua.id).await?);
-            }
+        if report.inserted > 0 {
+            self.invalidate_list_caches().await;
+        }
+        
+        Ok(report)
+    }
+
+    /// Localizes a single dua; lists and random picks do this from `params.lang`
+    pub async fn localize(&self, dua: &mut DuaWithRelations, lang: &str) -> ApiResult<()> {
+        self.repository.localize(std::slice::from_mut(dua), lang).await
+    }
+
+    /// Adds or replaces a batch of a dua's translations, returning all of them
+    pub async fn upsert_translations(
+        &self,
+        dua_id: Uuid,
+        translations: &[UpsertTranslationRequest],
+    ) -> ApiResult<Vec<DuaTranslation>> {
+        let translations = self.repository.upsert_translations(dua_id, translations).await?;
+        
+        // A new language changes what Accept-Language can resolve to
+        if let Err(e) = self.cache.delete(LANGUAGES_CACHE_KEY).await {
+            warn!("Failed to invalidate available languages: {}", e);
         }
         
-        Ok(dua_with_relations)
+        Ok(translations)
+    }
+
+    /// `lang` if the client passed one, otherwise the best match for its
+    /// `Accept-Language` among the languages duas are translated into
+    pub async fn resolve_lang(&self, lang: Option<String>, accept_language: Option<&str>) -> ApiResult<Option<String>> {
+        if lang.is_some() {
+            return Ok(lang);
+        }
+        let Some(header) = accept_language else {
+            return Ok(None);
+        };
+        
+        let available: Vec<String> = self
+            .cache
+            .get_or_compute(LANGUAGES_CACHE_KEY, Some(Duration::from_secs(1800)), || {
+                self.repository.available_languages()
+            })
+            .await?;
+        Ok(negotiate_language(header, &available))
+    }
+
+    async fn load_dua_relations(&self, dua: Dua, include: Option<&str>) -> ApiResult<DuaWithRelations> {
+        self.repository.load_relations(dua, include).await
     }
 
     // ============= CATEGORIES =============
@@ -248,8 +262,7 @@ impl DuaService {
         }
         
         // Fetch bundle and its items
-        let bundle = self.repository.get_bundle_by_slug(bundle_slug).await?
-            .ok_or_else(|| shared::error::ApiError::NotFound(format!("Bundle {} not found", bundle_slug)))?;
+        let bundle = self.repository.require_bundle(bundle_slug).await?;
         
         let duas = self.repository.get_bundle_items(bundle_slug).await?;
         
@@ -278,8 +291,8 @@ impl DuaService {
 
     // ============= SEARCH =============
 
-    pub async fn keyword_search(&self, query: &str, limit: u32) -> ApiResult<Vec<DuaWithRelations>> {
-        let duas = self.repository.keyword_search(query, limit).await?;
+    pub async fn keyword_search(&self, query: &str, limit: u32, filters: &SearchFilters) -> ApiResult<Vec<DuaWithRelations>> {
+        let duas = self.repository.keyword_search(query, limit, filters).await?;
         
         let mut results = Vec::new();
         for dua in duas {
@@ -290,11 +303,36 @@ impl DuaService {
         Ok(results)
     }
 
+    /// Autocomplete suggestions for a prefix. The same prefixes come in over
+    /// and over as people type, so results are cached for `ttl`.
+    pub async fn suggest(&self, query: &str, limit: u32, ttl: Duration) -> ApiResult<Vec<String>> {
+        let cache_key = format!("suggest:{}:{}", limit, query.to_lowercase());
+        
+        if let Ok(Some(cached)) = self.cache.get::<Vec<String>>(&cache_key).await {
+            debug!("Returning cached suggestions for: {}", query);
+            return Ok(cached);
+        }
+        
+        let suggestions = self.repository.get_suggestions(query, limit).await?;
+        
+        if let Err(e) = self.cache.set(&cache_key, &suggestions, Some(ttl)).await {
+            warn!("Failed to cache suggestions for {}: {}", query, e);
+        }
+        
+        Ok(suggestions)
+    }
+
     pub async fn semantic_search(&self, request: SemanticSearchRequest) -> ApiResult<SearchResponse> {
         let limit = request.limit.unwrap_or(20);
         let threshold = request.threshold.unwrap_or(0.5);
         
-        let duas = self.repository.semantic_search(&request.query, limit, threshold).await?;
+        let filters = SearchFilters::parse(
+            request.min_authenticity.as_deref(),
+            request.verified_only.unwrap_or(false),
+        )
+        .map_err(ApiError::validation)?;
+        
+        let duas = self.repository.semantic_search(&request.query, limit, threshold, &filters).await?;
         
         let mut results = Vec::new();
         for dua in duas {
@@ -311,26 +349,30 @@ impl DuaService {
         })
     }
 
+    // ============= POPULARITY =============
+
+    /// Counts a view of a dua unless the same client viewed it within
+    /// `debounce`. Failures are only logged so they never fail the request.
+    pub async fn record_view(&self, dua_id: Uuid, client: &str, debounce: Duration) {
+        let key = format!("dua_view:{}:{}", dua_id, client);
+        match self.cache.set_if_absent(&key, debounce).await {
+            Ok(true) => {
+                if let Err(e) = self.repository.record_view(dua_id).await {
+                    warn!("Failed to record view of dua {}: {}", dua_id, e);
+                }
+            }
+            Ok(false) => debug!("Skipping repeat view of dua {} by {}", dua_id, client),
+            Err(e) => warn!("Failed to debounce view of dua {}: {}", dua_id, e),
+        }
+    }
+
     // ============= STATISTICS =============
 
     pub async fn get_stats(&self) -> ApiResult<StatsResponse> {
-        let cache_key = "stats:global";
-        
-        // Try cache first
-        if let Ok(Some(cached_stats)) = self.cache.get::<StatsResponse>(&cache_key).await {
-            debug!("Returning cached stats");
-            return Ok(cached_stats);
-        }
-        
-        // Fetch from database
-        let stats = self.repository.get_stats().await?;
-        
-        // Cache for 15 minutes
-        if let Err(e) = self.cache.set(&cache_key, &stats, Some(Duration::from_secs(900))).await {
-            warn!("Failed to cache stats: {}", e);
-        }
-        
-        Ok(stats)
+        // Cache for 15 minutes; concurrent misses share one computation
+        self.cache
+            .get_or_compute("stats:global", Some(Duration::from_secs(900)), || self.repository.get_stats())
+            .await
     }
 
     // ============= CACHE MANAGEMENT =============
@@ -363,10 +405,22 @@ impl DuaService {
         query.event_trigger.hash(&mut hasher);
         query.source_type.hash(&mut hasher);
         query.authenticity.hash(&mut hasher);
+        query.dua_type.hash(&mut hasher);
+        query.repetitions.hash(&mut hasher);
+        query.calendar.hash(&mut hasher);
+        query.hijri_month.hash(&mut hasher);
+        query.created_after.hash(&mut hasher);
+        query.created_before.hash(&mut hasher);
+        query.updated_after.hash(&mut hasher);
+        query.updated_before.hash(&mut hasher);
+        query.status.hash(&mut hasher);
+        query.include_drafts.hash(&mut hasher);
         query.page.hash(&mut hasher);
         query.per_page.hash(&mut hasher);
         query.sort.hash(&mut hasher);
         query.order.hash(&mut hasher);
+        query.count_only.hash(&mut hasher);
+        query.lang.hash(&mut hasher);
         
         format!("dua_search:{:x}", hasher.finish())
     }
@@ -0,0 +1,148 @@
+// Keyword-based tag and category suggestions for content editors.
+// Suggestions are never applied automatically; editors review them first.
+
+use std::collections::HashSet;
+
+use crate::models::LabelSuggestion;
+
+/// Confidence when every word of a label's name appears in the dua's title
+const TITLE_MATCH_CONFIDENCE: f64 = 0.9;
+/// Confidence when every word of a label's name appears in the dua's text
+const TEXT_MATCH_CONFIDENCE: f64 = 0.7;
+/// Confidence when only a related keyword from `KEYWORD_RULES` appears
+const RULE_MATCH_CONFIDENCE: f64 = 0.5;
+
+/// Related words for common themes. A rule applies to any tag or category
+/// whose slug contains the theme, so `morning` covers `morning-adhkar` too.
+const KEYWORD_RULES: &[(&str, &[&str])] = &[
+    ("morning", &["morning", "dawn", "fajr", "sabah", "awake", "woke"]),
+    ("evening", &["evening", "dusk", "maghrib", "masa", "night"]),
+    ("sleep", &["sleep", "bed", "asleep", "lie"]),
+    ("travel", &["travel", "journey", "ride", "mount", "vehicle"]),
+    ("food", &["eat", "eating", "food", "drink", "meal", "provided"]),
+    ("forgiveness", &["forgive", "forgiveness", "repent", "repentance", "sins"]),
+    ("protection", &["protect", "protection", "refuge", "evil", "harm"]),
+    ("rain", &["rain", "rainfall", "clouds"]),
+    ("illness", &["sick", "sickness", "illness", "heal", "cure", "pain"]),
+    ("anxiety", &["anxiety", "distress", "worry", "grief", "sorrow"]),
+    ("gratitude", &["praise", "thank", "thanks", "grateful", "blessing"]),
+];
+
+/// A tag or category that can be suggested
+pub struct Label<'a> {
+    pub slug: &'a str,
+    pub name: &'a str,
+}
+
+/// The text of a dua that suggestions are drawn from
+pub struct DuaText<'a> {
+    pub title: &'a str,
+    /// Translation, transliteration and any translated texts
+    pub body: Vec<&'a str>,
+}
+
+fn words(text: &str) -> HashSet<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(|w| w.to_lowercase())
+        .collect()
+}
+
+/// Scores `labels` against the dua's text, skipping those already assigned.
+/// Results are ordered by confidence, highest first.
+pub fn suggest_labels(
+    text: &DuaText,
+    labels: &[Label],
+    assigned: &HashSet<&str>,
+) -> Vec<LabelSuggestion> {
+    let title_words = words(text.title);
+    let mut text_words = title_words.clone();
+    for body in &text.body {
+        text_words.extend(words(body));
+    }
+
+    let mut suggestions: Vec<LabelSuggestion> = labels
+        .iter()
+        .filter(|label| !assigned.contains(label.slug))
+        .filter_map(|label| {
+            let name_words = words(label.name);
+            if !name_words.is_empty() {
+                let mut matched: Vec<String> = name_words.iter().cloned().collect();
+                matched.sort();
+                if name_words.is_subset(&title_words) {
+                    return Some(LabelSuggestion::new(label, TITLE_MATCH_CONFIDENCE, matched));
+                }
+                if name_words.is_subset(&text_words) {
+                    return Some(LabelSuggestion::new(label, TEXT_MATCH_CONFIDENCE, matched));
+                }
+            }
+
+            let mut matched: Vec<String> = KEYWORD_RULES
+                .iter()
+                .filter(|(theme, _)| label.slug.contains(theme))
+                .flat_map(|(_, keywords)| keywords.iter())
+                .filter(|keyword| text_words.contains(**keyword))
+                .map(|keyword| keyword.to_string())
+                .collect();
+            if matched.is_empty() {
+                return None;
+            }
+            matched.sort();
+            matched.dedup();
+            Some(LabelSuggestion::new(label, RULE_MATCH_CONFIDENCE, matched))
+        })
+        .collect();
+
+    suggestions.sort_by(|a, b| {
+        b.confidence
+            .total_cmp(&a.confidence)
+            .then_with(|| a.slug.cmp(&b.slug))
+    });
+    suggestions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn labels() -> Vec<Label<'static>> {
+        vec![
+            Label { slug: "morning-adhkar", name: "Morning Adhkar" },
+            Label { slug: "evening", name: "Evening" },
+            Label { slug: "travel", name: "Travel" },
+            Label { slug: "protection", name: "Protection" },
+        ]
+    }
+
+    #[test]
+    fn test_morning_text_suggests_morning_tag() {
+        let text = DuaText {
+            title: "Upon waking",
+            body: vec!["We have reached the morning and at this very time the whole kingdom belongs to Allah"],
+        };
+
+        let suggestions = suggest_labels(&text, &labels(), &HashSet::new());
+
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(suggestions[0].slug, "morning-adhkar");
+        assert_eq!(suggestions[0].confidence, RULE_MATCH_CONFIDENCE);
+        assert_eq!(suggestions[0].matched, vec!["morning".to_string()]);
+    }
+
+    #[test]
+    fn test_name_matches_rank_above_rules_and_assigned_are_skipped() {
+        let text = DuaText {
+            title: "Dua for travel",
+            body: vec!["I seek refuge in You from the evil of the journey"],
+        };
+
+        let suggestions = suggest_labels(&text, &labels(), &HashSet::new());
+        let slugs: Vec<&str> = suggestions.iter().map(|s| s.slug.as_str()).collect();
+        assert_eq!(slugs, vec!["travel", "protection"]);
+        assert_eq!(suggestions[0].confidence, TITLE_MATCH_CONFIDENCE);
+
+        let assigned = HashSet::from(["travel"]);
+        let suggestions = suggest_labels(&text, &labels(), &assigned);
+        assert!(suggestions.iter().all(|s| s.slug != "travel"));
+    }
+}
@@ -1,11 +1,17 @@
 use chrono::{DateTime, Offset, Datelike, FixedOffset, NaiveDate};
 use hijri_date::HijriDate;
 use libm::{acos, asin, atan, atan2, cos, floor, sin, sqrt, tan};
-use shared::error::{ApiError, ApiResult};
+use shared::{
+    error::{ApiError, ApiResult},
+    geo::longitude_delta,
+};
+use std::sync::Arc;
 use tracing::{debug, info, warn};
 
+use crate::hijri_cache::HijriCache;
 use crate::models::{
-    Adjustments, Coordinates, HighLatitudeRule, MethodSettings, Midnight, MinuteOrAngle, School,
+    Adjustments, CalculationWarning, CalculationWarningCode, Coordinates, HighLatitudeRule,
+    MethodSettings, Midnight, MinuteOrAngle, Rounding, RoundingPolicy, School, Shafaq,
     StandardMethod,
 };
 
@@ -42,10 +48,46 @@ fn fix(a: f64, b: f64) -> f64 {
     }
 }
 
+/// Minutes from sunset to the end of shafaq, per the Moonsighting Committee's
+/// seasonal model: four anchor values scaled by latitude, interpolated over
+/// the days since the winter solstice.
+fn seasonal_isha_minutes(shafaq: Shafaq, latitude: f64, date: NaiveDate) -> f64 {
+    let lat = latitude.abs() / 55.0;
+    let (a, b, c, d) = match shafaq {
+        Shafaq::General => (75.0 + 25.60 * lat, 75.0 + 2.05 * lat, 75.0 - 9.21 * lat, 75.0 + 6.14 * lat),
+        Shafaq::Ahmer => (62.0 + 17.40 * lat, 62.0 - 7.16 * lat, 62.0 + 5.12 * lat, 62.0 + 19.44 * lat),
+        Shafaq::Abyad => (75.0 + 25.60 * lat, 75.0 + 7.16 * lat, 75.0 + 36.84 * lat, 75.0 + 81.84 * lat),
+    };
+
+    let days_in_year = if date.leap_year() { 366 } else { 365 };
+    let day_of_year = date.ordinal0() as i32;
+    let days = if latitude >= 0.0 {
+        (day_of_year + 10) % days_in_year
+    } else {
+        (day_of_year - if date.leap_year() { 173 } else { 172 }).rem_euclid(days_in_year)
+    } as f64;
+
+    if days < 91.0 {
+        a + (b - a) / 91.0 * days
+    } else if days < 137.0 {
+        b + (c - b) / 46.0 * (days - 91.0)
+    } else if days < 183.0 {
+        c + (d - c) / 46.0 * (days - 137.0)
+    } else if days < 229.0 {
+        d + (c - d) / 46.0 * (days - 183.0)
+    } else if days < 275.0 {
+        c + (b - c) / 46.0 * (days - 229.0)
+    } else {
+        b + (a - b) / 91.0 * (days - 275.0)
+    }
+}
+
 pub struct PrayerCalculator {
     coordinates: Coordinates,
     method_settings: MethodSettings,
     adjustments: Adjustments,
+    rounding: RoundingPolicy,
+    horizon_angle: Option<f64>,
 }
 
 impl PrayerCalculator {
@@ -58,13 +100,37 @@ impl PrayerCalculator {
             coordinates,
             method_settings,
             adjustments,
+            rounding: RoundingPolicy::default(),
+            horizon_angle: None,
         }
     }
 
+    /// Sets how each time is rounded to the minute; nearest when `None`.
+    pub fn with_rounding(mut self, rounding: Option<RoundingPolicy>) -> Self {
+        self.rounding = rounding.unwrap_or_default();
+        self
+    }
+
+    /// Sets the observer's visible horizon in degrees above (positive) or
+    /// below (negative) the astronomical horizon, e.g. from mountains or buildings.
+    pub fn with_horizon_angle(mut self, horizon_angle: Option<f64>) -> Self {
+        self.horizon_angle = horizon_angle;
+        self
+    }
+
     pub fn calculate_prayer_times(
         &self,
         date: DateTime<FixedOffset>,
     ) -> ApiResult<super::models::PrayerTimes> {
+        self.calculate_prayer_times_with_warnings(date).map(|(times, _)| times)
+    }
+
+    /// The day's times along with anything unusual about them, e.g. a high
+    /// latitude rule that replaced Fajr
+    pub fn calculate_prayer_times_with_warnings(
+        &self,
+        date: DateTime<FixedOffset>,
+    ) -> ApiResult<(super::models::PrayerTimes, Vec<CalculationWarning>)> {
         info!("Starting prayer time calculation for date: {}", date);
         
         // Compute the raw floating-point prayer times
@@ -85,26 +151,29 @@ impl PrayerCalculator {
         
         let hijri = self.calculate_hijri_date(date.date_naive())?;
 
-        Ok(super::models::PrayerTimes {
-            imsak: self.format_time(times.imsak, date, self.adjustments.imsak),
-            fajr: self.format_time(times.fajr, date, self.adjustments.fajr),
-            sunrise: self.format_time(times.sunrise, date, self.adjustments.sunrise),
-            dhuhr: self.format_time(times.dhuhr, date, self.adjustments.dhuhr),
-            asr: self.format_time(times.asr, date, self.adjustments.asr),
-            sunset: self.format_time(times.sunset, date, self.adjustments.sunset),
-            maghrib: self.format_time(times.maghrib, date, self.adjustments.maghrib),
-            isha: self.format_time(times.isha, date, self.adjustments.isha),
-            midnight: self.format_time(times.midnight, date, self.adjustments.midnight),
-            first_third: self.format_time(times.first_third, date, self.adjustments.first_third),
-            last_third: self.format_time(times.last_third, date, self.adjustments.last_third),
+        let prayer_times = super::models::PrayerTimes {
+            imsak: self.format_time(times.imsak, date, self.adjustments.imsak, self.rounding.imsak),
+            fajr: self.format_time(times.fajr, date, self.adjustments.fajr, self.rounding.fajr),
+            sunrise: self.format_time(times.sunrise, date, self.adjustments.sunrise, self.rounding.sunrise),
+            dhuhr: self.format_time(times.dhuhr, date, self.adjustments.dhuhr, self.rounding.dhuhr),
+            asr: self.format_time(times.asr, date, self.adjustments.asr, self.rounding.asr),
+            sunset: self.format_time(times.sunset, date, self.adjustments.sunset, self.rounding.sunset),
+            maghrib: self.format_time(times.maghrib, date, self.adjustments.maghrib, self.rounding.maghrib),
+            isha: self.format_time(times.isha, date, self.adjustments.isha, self.rounding.isha),
+            midnight: self.format_time(times.midnight, date, self.adjustments.midnight, self.rounding.midnight),
+            first_third: self.format_time(times.first_third, date, self.adjustments.first_third, self.rounding.first_third),
+            last_third: self.format_time(times.last_third, date, self.adjustments.last_third, self.rounding.last_third),
             date: date.format("%d/%m/%Y").to_string(),
             hijri: hijri.format("%d/%m/%Y").to_string(),
-        })
+            fasting: None,
+        };
+
+        Ok((prayer_times, times.warnings))
     }
 
     pub fn calculate_qibla_direction(&self) -> f64 {
-        let kaaba_lat = 21.4224779_f64;
-        let kaaba_lng = 39.8251832_f64;
+        let kaaba_lat = shared::geo::KAABA.latitude;
+        let kaaba_lng = shared::geo::KAABA.longitude;
 
         let lat1 = dtr(self.coordinates.latitude);
         let lng1 = dtr(self.coordinates.longitude);
@@ -137,6 +206,27 @@ impl PrayerCalculator {
         times.sunrise = self.sun_angle_time(rise_set_angle, eqt, decl, -1.0)?;
         times.sunset = self.sun_angle_time(rise_set_angle, eqt, decl, 1.0)?;
 
+        // Prayers whose angle the sun never reaches today; their times are
+        // the sun's closest approach unless something below replaces them
+        let mut unreached = Vec::new();
+        if !self.reaches_angle(rise_set_angle, decl)? {
+            unreached.extend(["sunrise", "sunset"]);
+        }
+        if !self.reaches_angle(self.method_settings.fajr, decl)? {
+            unreached.push("fajr");
+        }
+        for (prayer, setting) in [
+            ("maghrib", &self.method_settings.maghrib),
+            ("isha", &self.method_settings.isha),
+            ("imsak", &self.method_settings.imsak),
+        ] {
+            if let MinuteOrAngle::Angle { angle } = setting {
+                if !self.reaches_angle(*angle, decl)? {
+                    unreached.push(prayer);
+                }
+            }
+        }
+
         // Fajr calculation
         let fajr_angle = match self.method_settings.imsak {
             MinuteOrAngle::Angle { angle } => angle,
@@ -173,6 +263,20 @@ impl PrayerCalculator {
             }
         }
 
+        // Moonsighting Committee: below 55° Isha is no later than the
+        // seasonal twilight for the chosen shafaq
+        if let Some(shafaq) = self.method_settings.shafaq {
+            if self.coordinates.latitude.abs() < 55.0 {
+                let twilight = seasonal_isha_minutes(shafaq, self.coordinates.latitude, date.date_naive());
+                let seasonal = times.sunset + twilight / 60.0;
+                debug!("Seasonal Isha ({:?}): {} minutes after sunset", shafaq, twilight);
+                if times.isha.is_nan() || seasonal < times.isha {
+                    times.isha = seasonal;
+                    unreached.retain(|prayer| *prayer != "isha");
+                }
+            }
+        }
+
         // Imsak calculation
         match &self.method_settings.imsak {
             MinuteOrAngle::Angle { angle } => {
@@ -184,9 +288,36 @@ impl PrayerCalculator {
         }
 
         // Apply high latitude adjustments
+        let day = date.format("%d/%m/%Y").to_string();
         if let Some(rule) = self.method_settings.high_lat {
             debug!("Applying high latitude rule: {:?}", rule);
+            let (fajr, isha) = (times.fajr, times.isha);
             times = self.adjust_high_latitudes(times, rule)?;
+            let changes = [("fajr", fajr, times.fajr), ("isha", isha, times.isha)];
+            for (prayer, before, after) in changes {
+                if before.to_bits() != after.to_bits() {
+                    unreached.retain(|p| *p != prayer);
+                    times.warnings.push(CalculationWarning {
+                        code: CalculationWarningCode::HighLatitudeAdjustment,
+                        prayer: prayer.to_string(),
+                        date: day.clone(),
+                        days: 1,
+                        message: format!("Set by the {:?} high latitude rule", rule),
+                    });
+                }
+            }
+        }
+        for prayer in unreached {
+            debug!("Sun doesn't reach the {} angle on {}", prayer, day);
+            times.warnings.push(CalculationWarning {
+                code: CalculationWarningCode::PrayerUndefined,
+                prayer: prayer.to_string(),
+                date: day.clone(),
+                days: 1,
+                message: "The sun doesn't reach this prayer's angle; the time shown is its \
+                          closest approach"
+                    .to_string(),
+            });
         }
 
         // Calculate night portions for Midnight, First Third, Last Third
@@ -204,9 +335,13 @@ impl PrayerCalculator {
         times.first_third = times.sunset + night_length / 3.0;
         times.last_third = times.sunset + (night_length * 2.0) / 3.0;
 
-        // Apply longitude adjustment
-        let lng_diff = self.coordinates.longitude / 15.0;
-        debug!("Longitude difference adjustment: {} hours", lng_diff);
+        // Apply longitude adjustment and shift from UTC into the requested timezone
+        // The offset between the zone's meridian and the location's, taken the
+        // short way round so zones across the date line (Samoa is UTC+13 at
+        // 172°W) stay within half a day
+        let tz_meridian = date.offset().local_minus_utc() as f64 / 240.0;
+        let lng_diff = -longitude_delta(self.coordinates.longitude, tz_meridian) / 15.0;
+        debug!("Longitude/timezone adjustment: {} hours", lng_diff);
         times.imsak = fix_hour(times.imsak - lng_diff);
         times.fajr = fix_hour(times.fajr - lng_diff);
         times.sunrise = fix_hour(times.sunrise - lng_diff);
@@ -263,8 +398,23 @@ impl PrayerCalculator {
     }
 
     fn sun_angle_time(&self, angle: f64, eqt: f64, decl: f64, direction: f64) -> ApiResult<f64> {
-        let lat = dtr(self.coordinates.latitude);
         let noon = self.mid_day(eqt);
+        let cos_range = self.hour_angle_cos(angle, decl)?.clamp(-1.0, 1.0);
+        let t = rtd(acos(cos_range)) / 15.0;
+
+        Ok(noon + direction * t)
+    }
+
+    /// Whether the sun gets `angle` degrees below the horizon (above it when
+    /// negative) on a day with declination `decl`
+    fn reaches_angle(&self, angle: f64, decl: f64) -> ApiResult<bool> {
+        Ok(self.hour_angle_cos(angle, decl)?.abs() <= 1.0)
+    }
+
+    /// Cosine of the hour angle at which the sun is at `angle`; outside
+    /// [-1, 1] when it never gets there
+    fn hour_angle_cos(&self, angle: f64, decl: f64) -> ApiResult<f64> {
+        let lat = dtr(self.coordinates.latitude);
 
         let p1 = -sin(dtr(angle)) - sin(dtr(decl)) * sin(lat);
         let p2 = cos(dtr(decl)) * cos(lat);
@@ -278,10 +428,7 @@ impl PrayerCalculator {
             ));
         }
 
-        let cos_range = (p1 / p2).clamp(-1.0, 1.0);
-        let t = rtd(acos(cos_range)) / 15.0;
-
-        Ok(noon + direction * t)
+        Ok(p1 / p2)
     }
 
     fn asr_time(&self, factor: f64, eqt: f64, decl: f64) -> ApiResult<f64> {
@@ -290,11 +437,18 @@ impl PrayerCalculator {
         let angle = rtd(atan(1.0 / (factor + tan((lat - decl_rad).abs()))));
         debug!("Asr shadow factor: {}, Asr Angle: {}", factor, angle);
 
-        self.sun_angle_time(angle, eqt, decl, 1.0)
+        // Asr is a sun altitude above the horizon, not a depression below it
+        self.sun_angle_time(-angle, eqt, decl, 1.0)
     }
 
+    /// Sun depression at sunrise/sunset: refraction plus the solar semi-diameter
+    /// (0.833°), then either the dip of the horizon seen from `elevation`, or the
+    /// observer's own horizon angle when given.
     fn rise_set_angle(&self) -> f64 {
-        0.833 + 0.0347 * sqrt(self.coordinates.elevation.abs())
+        match self.horizon_angle {
+            Some(horizon) => 0.833 - horizon,
+            None => 0.833 + 0.0347 * sqrt(self.coordinates.elevation.abs()),
+        }
     }
 
     fn adjust_high_latitudes(
@@ -355,24 +509,30 @@ impl PrayerCalculator {
         Ok(times)
     }
 
-    fn calculate_hijri_date(&self, date: NaiveDate) -> ApiResult<HijriDate> {
-        let year: i16 = date.year().try_into().map_err(|e| {
-            ApiError::Calculation(format!("Failed to convert year to i16: {}", e))
-        })?;
-        let month: u8 = date.month().try_into().map_err(|e| {
-            ApiError::Calculation(format!("Failed to convert month to u8: {}", e))
-        })?;
-        let day: u8 = date.day().try_into().map_err(|e| {
-            ApiError::Calculation(format!("Failed to convert day to u8: {}", e))
-        })?;
-
-        HijriDate::from_gr(year.try_into().unwrap(), month.into(), day.into())
+    /// Converted through the shared cache, as multi-day requests convert the
+    /// same dates over and over
+    pub fn calculate_hijri_date(&self, date: NaiveDate) -> ApiResult<Arc<HijriDate>> {
+        if date.year() < 1 {
+            return Err(ApiError::Calculation(format!(
+                "Cannot convert year {} to Hijri",
+                date.year()
+            )));
+        }
+
+        HijriCache::global()
+            .to_hijri(date)
             .map_err(|e| ApiError::Calculation(format!("Failed to calculate Hijri date: {}", e)))
     }
 
-    fn format_time(&self, time: f64, date: DateTime<FixedOffset>, adjustment: i8) -> String {
+    fn format_time(
+        &self,
+        time: f64,
+        date: DateTime<FixedOffset>,
+        adjustment: i8,
+        rounding: Rounding,
+    ) -> String {
         debug!("Entering format_time for raw time: {}", time);
-        debug!("Adjustment: {}", adjustment);
+        debug!("Adjustment: {}, rounding: {:?}", adjustment, rounding);
 
         if time.is_nan() {
             warn!("Time is NaN, returning 'Invalid Time'");
@@ -382,7 +542,7 @@ impl PrayerCalculator {
         let offset = date.offset();
         debug!("Timezone offset: {} seconds", offset.local_minus_utc());
 
-        let total_minutes = (time * 60.0).round() as i32 + adjustment as i32;
+        let total_minutes = rounding.apply(time * 60.0) + adjustment as i32;
         debug!("Total minutes (raw time * 60 + adjustment): {}", total_minutes);
 
         // Convert total minutes to a duration and add to a naive date
@@ -418,6 +578,7 @@ struct RawPrayerTimes {
     midnight: f64,
     first_third: f64,
     last_third: f64,
+    warnings: Vec<CalculationWarning>,
 }
 
 impl StandardMethod {
@@ -612,4 +773,228 @@ impl StandardMethod {
             },
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{NaiveDateTime, Timelike};
+
+    fn london_calculator() -> PrayerCalculator {
+        PrayerCalculator::new(
+            Coordinates {
+                latitude: 51.5074,
+                longitude: -0.1278,
+                elevation: 0.0,
+            },
+            StandardMethod::Mwl.to_method_settings(),
+            Adjustments::default(),
+        )
+    }
+
+    #[test]
+    fn test_repeated_hijri_conversions_are_cached() {
+        let calculator = london_calculator();
+        let year: Vec<NaiveDate> = NaiveDate::from_ymd_opt(2031, 1, 1)
+            .unwrap()
+            .iter_days()
+            .take(365)
+            .collect();
+
+        let first: Vec<_> = year
+            .iter()
+            .map(|d| calculator.calculate_hijri_date(*d).unwrap())
+            .collect();
+        let hits_before = HijriCache::global().hits();
+        let second: Vec<_> = year
+            .iter()
+            .map(|d| calculator.calculate_hijri_date(*d).unwrap())
+            .collect();
+
+        assert_eq!(first, second);
+        assert!(HijriCache::global().hits() - hits_before >= 365);
+    }
+
+    #[test]
+    fn test_fajr_rounded_up_is_never_early() {
+        let timezone = FixedOffset::east_opt(0).unwrap();
+        let calculator = london_calculator().with_rounding(Some(RoundingPolicy {
+            fajr: Rounding::Up,
+            ..Default::default()
+        }));
+
+        for date in NaiveDate::from_ymd_opt(2024, 1, 1).unwrap().iter_days().take(60) {
+            let date = date
+                .and_hms_opt(12, 0, 0)
+                .unwrap()
+                .and_local_timezone(timezone)
+                .unwrap();
+            let raw = calculator.compute_times(date).unwrap();
+            let shown = calculator.calculate_prayer_times(date).unwrap().fajr;
+
+            let shown = NaiveDateTime::parse_from_str(&shown, "%d/%m/%Y %H:%M").unwrap();
+            let minutes = shown.hour() as f64 * 60.0 + shown.minute() as f64;
+            assert!(minutes >= raw.fajr * 60.0, "{} before {}", shown, raw.fajr);
+            assert!(minutes - raw.fajr * 60.0 < 1.0, "{} after {}", shown, raw.fajr);
+        }
+    }
+
+    #[test]
+    fn test_horizon_angle_delays_sunrise() {
+        let date = NaiveDate::from_ymd_opt(2024, 3, 20)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap()
+            .and_local_timezone(FixedOffset::east_opt(0).unwrap())
+            .unwrap();
+
+        let default_times = london_calculator().compute_times(date).unwrap();
+        let raised_times = london_calculator()
+            .with_horizon_angle(Some(2.0))
+            .compute_times(date)
+            .unwrap();
+
+        // A 2° obstruction hides the sun for roughly 10-20 minutes at this latitude
+        let delay_minutes = (raised_times.sunrise - default_times.sunrise) * 60.0;
+        assert!(delay_minutes > 8.0 && delay_minutes < 25.0, "{}", delay_minutes);
+        assert!(raised_times.sunset < default_times.sunset);
+    }
+
+    #[test]
+    fn test_abyad_shafaq_delays_summer_isha() {
+        let date = NaiveDate::from_ymd_opt(2024, 6, 21)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap()
+            .and_local_timezone(FixedOffset::east_opt(3600).unwrap())
+            .unwrap();
+        let times_with = |shafaq| {
+            let mut settings = StandardMethod::Moonsighting.to_method_settings();
+            settings.shafaq = Some(shafaq);
+            let coordinates = Coordinates {
+                latitude: 51.5074,
+                longitude: -0.1278,
+                elevation: 0.0,
+            };
+            PrayerCalculator::new(coordinates, settings, Adjustments::default())
+                .compute_times(date)
+                .unwrap()
+        };
+
+        let general = times_with(Shafaq::General);
+        let abyad = times_with(Shafaq::Abyad);
+
+        // Near midsummer the white glow lingers over an hour longer than the general shafaq
+        let general_minutes = (general.isha - general.sunset) * 60.0;
+        let abyad_minutes = (abyad.isha - abyad.sunset) * 60.0;
+        assert!((75.0..90.0).contains(&general_minutes), "{}", general_minutes);
+        assert!((140.0..160.0).contains(&abyad_minutes), "{}", abyad_minutes);
+    }
+
+    #[test]
+    fn test_prayer_times_either_side_of_date_line() {
+        let local_noon = |offset_hours: i32| {
+            NaiveDate::from_ymd_opt(2024, 3, 20)
+                .unwrap()
+                .and_hms_opt(12, 0, 0)
+                .unwrap()
+                .and_local_timezone(FixedOffset::east_opt(offset_hours * 3600).unwrap())
+                .unwrap()
+        };
+        let times_at = |latitude, longitude, offset_hours| {
+            let coordinates = Coordinates {
+                latitude,
+                longitude,
+                elevation: 0.0,
+            };
+            PrayerCalculator::new(coordinates, StandardMethod::Mwl.to_method_settings(), Adjustments::default())
+                .calculate_prayer_times(local_noon(offset_hours))
+                .unwrap()
+        };
+
+        // Suva, Fiji at UTC+12 and Apia, Samoa at UTC+13 across the line
+        let fiji = times_at(-18.1416, 178.4419, 12);
+        let samoa = times_at(-13.8333, -171.7667, 13);
+
+        let parse = |t: &str| chrono::NaiveDateTime::parse_from_str(t, "%d/%m/%Y %H:%M").unwrap();
+        for times in [&fiji, &samoa] {
+            assert_eq!(times.date, "20/03/2024");
+            let dhuhr = parse(&times.dhuhr);
+            assert_eq!(dhuhr.date(), NaiveDate::from_ymd_opt(2024, 3, 20).unwrap());
+            assert!((12..=13).contains(&dhuhr.hour()), "{}", times.dhuhr);
+            assert!(parse(&times.fajr) < parse(&times.sunrise));
+            assert!(parse(&times.sunrise) < dhuhr);
+            assert!(dhuhr < parse(&times.maghrib));
+        }
+        // Samoa's solar noon is 6.8° of longitude (about 27 minutes) behind its zone meridian
+        let samoa_noon = parse(&samoa.dhuhr).time();
+        assert!(samoa_noon > chrono::NaiveTime::from_hms_opt(12, 20, 0).unwrap(), "{}", samoa.dhuhr);
+    }
+
+    #[test]
+    fn test_fasting_duration_spans_suhoor_to_iftar() {
+        let date = NaiveDate::from_ymd_opt(2025, 3, 10)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap()
+            .and_local_timezone(FixedOffset::east_opt(0).unwrap())
+            .unwrap();
+
+        let times = london_calculator().calculate_prayer_times(date).unwrap();
+        let fasting = crate::models::FastingTimes::from_prayer_times(&times).unwrap();
+
+        let parse = |t: &str| chrono::NaiveDateTime::parse_from_str(t, "%d/%m/%Y %H:%M").unwrap();
+        assert_eq!(fasting.suhoor_ends, times.imsak);
+        assert_eq!(fasting.iftar, times.maghrib);
+        assert_eq!(
+            fasting.fasting_duration_minutes,
+            (parse(&times.maghrib) - parse(&fasting.suhoor_ends)).num_minutes()
+        );
+        // Early March in London the fast lasts between 12 and 14 hours
+        let duration = fasting.fasting_duration_minutes;
+        assert!((720..840).contains(&duration), "{}", duration);
+    }
+
+    #[test]
+    fn test_high_latitude_rule_is_reported_as_warning() {
+        let date = NaiveDate::from_ymd_opt(2024, 6, 21)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap()
+            .and_local_timezone(FixedOffset::east_opt(2 * 3600).unwrap())
+            .unwrap();
+        // Oslo at midsummer: the sun stays well above MWL's 18° Fajr angle
+        let oslo = |high_lat| {
+            let mut settings = StandardMethod::Mwl.to_method_settings();
+            settings.high_lat = high_lat;
+            let coordinates = Coordinates {
+                latitude: 59.9139,
+                longitude: 10.7522,
+                elevation: 0.0,
+            };
+            PrayerCalculator::new(coordinates, settings, Adjustments::default())
+                .calculate_prayer_times_with_warnings(date)
+                .unwrap()
+                .1
+        };
+        let codes = |warnings: &[CalculationWarning], prayer: &str| -> Vec<CalculationWarningCode> {
+            warnings.iter().filter(|w| w.prayer == prayer).map(|w| w.code).collect()
+        };
+
+        let adjusted = oslo(Some(HighLatitudeRule::AngleBased));
+        assert_eq!(codes(&adjusted, "fajr"), [CalculationWarningCode::HighLatitudeAdjustment]);
+        assert_eq!(adjusted[0].date, "21/06/2024");
+
+        let unadjusted = oslo(None);
+        assert_eq!(codes(&unadjusted, "fajr"), [CalculationWarningCode::PrayerUndefined]);
+        assert!(codes(&unadjusted, "sunrise").is_empty());
+
+        // A week of the same rule is one warning covering seven days
+        let mut week = Vec::new();
+        for _ in 0..7 {
+            crate::models::merge_warnings(&mut week, adjusted.clone());
+        }
+        assert_eq!(week.len(), adjusted.len());
+        assert!(week.iter().all(|w| w.days == 7));
+    }
+}
@@ -1,17 +1,31 @@
-use axum::{Extension, Json};
+use axum::{
+    extract::{Path, Query},
+    http::{HeaderMap, StatusCode},
+    Extension, Json,
+};
 use chrono::{DateTime, Duration, FixedOffset, Utc};
-use shared::{cache::Cache, error::ApiResult};
+use shared::{
+    cache::{Cache, CachedJson},
+    error::ApiResult,
+    extract::ApiJson,
+};
 use std::{sync::Arc, time::Duration as StdDuration};
 use tracing::{debug, info};
 use validator::Validate;
 
 use crate::{
     calculations::PrayerCalculator,
+    hijri_cache::HijriCache,
+    locations::{LocationOwner, LocationStore},
     models::{
-        Coordinates, MetaData, NextPrayer, PrayerTimesRequest, PrayerTimesResponse, StandardMethod,
-        Timespan,
+        merge_warnings, project_prayer_times, Adjustments, Coordinates, FastingTimes, HijriSummary,
+        MetaData, MethodName, MethodSource, NextPrayer, PrayerDefaults, PrayerTimesRequest,
+        PrayerTimesResponse, QiblaSummary, ResolveMethodQueryParams, ResolvedMethodResponse,
+        SaveLocationRequest, SavedLocation, SummaryResponse, Timespan, TodayPrayerTimesResponse,
+        TodayQueryParams, WarmupFailure, WarmupRequest, WarmupResponse,
     },
     preferred::PreferredMethodMap,
+    registry::MethodRegistry,
     services::TimezoneParsing,
 };
 
@@ -20,30 +34,94 @@ use crate::{
 pub async fn prayer_times_handler(
     Extension(cache): Extension<Cache>,
     Extension(preferred): Extension<Arc<PreferredMethodMap>>,
-    Json(request): Json<PrayerTimesRequest>,
-) -> ApiResult<Json<PrayerTimesResponse>> {
+    Extension(registry): Extension<Arc<MethodRegistry>>,
+    Extension(defaults): Extension<Arc<PrayerDefaults>>,
+    ApiJson(request): ApiJson<PrayerTimesRequest>,
+) -> ApiResult<CachedJson> {
+    cached_prayer_times(&cache, &preferred, &registry, &defaults, request).await
+}
+
+/// Precomputes and caches responses for a list of `/prayer-times` bodies,
+/// e.g. popular cities before the morning rush, so the first real request
+/// for each is a cache hit. Failures are reported per entry.
+pub async fn warmup_handler(
+    Extension(cache): Extension<Cache>,
+    Extension(preferred): Extension<Arc<PreferredMethodMap>>,
+    Extension(registry): Extension<Arc<MethodRegistry>>,
+    Extension(defaults): Extension<Arc<PrayerDefaults>>,
+    ApiJson(mut request): ApiJson<WarmupRequest>,
+) -> ApiResult<Json<WarmupResponse>> {
+    request.check().map_err(shared::error::ApiError::Validation)?;
+
+    // Bound the whole batch up front rather than part-way through it
+    let mut total_days = 0u32;
+    for location in &mut request.locations {
+        location.apply_defaults(&defaults);
+        total_days += u32::from(requested_days(location));
+    }
+    defaults.check_batch_days(total_days)?;
+    info!("Warming the cache for {} locations ({} days)", request.locations.len(), total_days);
+
+    let mut response = WarmupResponse::default();
+    for (index, location) in request.locations.into_iter().enumerate() {
+        match cached_prayer_times(&cache, &preferred, &registry, &defaults, location).await {
+            Ok(_) => response.warmed += 1,
+            Err(e) => response.failed.push(WarmupFailure {
+                index,
+                error: e.to_string(),
+            }),
+        }
+    }
+
+    Ok(Json(response))
+}
+
+/// Days `request` would compute, or 0 when its timezone or timespan is
+/// invalid and it will fail without computing anything
+fn requested_days(request: &PrayerTimesRequest) -> u16 {
+    let Ok(timezone) = TimezoneParsing::parse_timezone(&request.timezone) else {
+        return 0;
+    };
+    parse_timespan(request.timespan.clone().unwrap_or_default(), timezone)
+        .map(|(_, day_count)| day_count)
+        .unwrap_or(0)
+}
+
+/// The `/prayer-times` response for `request`, from the cache or calculated
+/// and then cached
+async fn cached_prayer_times(
+    cache: &Cache,
+    preferred: &PreferredMethodMap,
+    registry: &MethodRegistry,
+    defaults: &PrayerDefaults,
+    mut request: PrayerTimesRequest,
+) -> ApiResult<CachedJson> {
     info!(
         "Processing prayer times request for coordinates: {:.4}, {:.4}",
         request.latitude, request.longitude
     );
     debug!("Incoming request details: {:?}", request);
+    request.apply_defaults(defaults);
 
     // Validate the request
     debug!("Starting request validation.");
     request
         .validate()
         .map_err(|e| shared::error::ApiError::Validation(format!("Validation failed: {}", e)))?;
+    let prayer_fields = request
+        .prayer_fields()
+        .map_err(shared::error::ApiError::Validation)?;
     debug!("Request validation successful.");
 
     // Create cache key for this request
-    let cache_key = create_cache_key(&request);
+    let cache_key = create_cache_key(&request, defaults.cache_coordinate_decimals);
     debug!("Generated cache key: {}", cache_key);
 
     // Try to get from cache first
     debug!("Checking cache for key: {}", cache_key);
-    if let Ok(Some(cached_response)) = cache.get::<PrayerTimesResponse>(&cache_key).await {
+    if let Ok(Some(cached_response)) = cache.get_response(&cache_key).await {
         debug!("Cache hit. Returning cached prayer times for key: {}", cache_key);
-        return Ok(Json(cached_response));
+        return Ok(cached_response);
     }
     debug!("Cache miss. Calculating new prayer times.");
 
@@ -62,7 +140,8 @@ pub async fn prayer_times_handler(
 
     // Determine calculation method
     debug!("Determining calculation method.");
-    let (method_settings, standard_method) = determine_method(&request, &preferred)?;
+    let (method_settings, standard_method, method_source) =
+        determine_method(&request, preferred, registry, defaults)?;
     debug!("Determined method: {:?}, with settings: {:?}", standard_method, method_settings);
 
     // Get timespan - clone to avoid move
@@ -72,12 +151,7 @@ pub async fn prayer_times_handler(
     debug!("Timespan parsed. Start date: {:?}, Day count: {}", start_date, day_count);
 
     // Validate day count
-    if day_count > 366 {
-        debug!("Day count exceeds limit: {}", day_count);
-        return Err(shared::error::ApiError::InvalidInput(
-            "Day count cannot exceed 366 days".to_string(),
-        ));
-    }
+    defaults.check_days(day_count)?;
 
     // Get adjustments - clone to avoid move
     let adjustments = request.adjustments.clone().unwrap_or_default();
@@ -85,22 +159,36 @@ pub async fn prayer_times_handler(
 
     // Create calculator
     let calculator =
-        PrayerCalculator::new(coordinates, method_settings.clone(), adjustments.clone());
+        PrayerCalculator::new(coordinates, method_settings.clone(), adjustments.clone())
+            .with_horizon_angle(request.horizon_angle)
+            .with_rounding(request.rounding.clone());
 
     // Calculate prayer times for all requested days
     debug!("Starting calculation loop for {} days.", day_count);
     let mut prayers = Vec::new();
+    let mut day_warnings = Vec::new();
     for i in 0..day_count {
         let current_date = start_date + Duration::days(i as i64);
         debug!("Calculating prayer times for date: {:?}", current_date);
-        let prayer_times = calculator.calculate_prayer_times(current_date)?;
+        let (mut prayer_times, warnings) =
+            calculator.calculate_prayer_times_with_warnings(current_date)?;
+        if request.ramadan_mode {
+            prayer_times.fasting = FastingTimes::from_prayer_times(&prayer_times);
+        }
         prayers.push(prayer_times);
+        day_warnings.push(warnings);
     }
     debug!("Prayer times calculation loop finished. Calculated {} days.", prayers.len());
+    debug!(
+        "Hijri date cache: {} hits, {} misses",
+        HijriCache::global().hits(),
+        HijriCache::global().misses()
+    );
 
     // Calculate next prayer if applicable
     debug!("Calculating next prayer based on timespan: {:?}", timespan);
-    let next_prayer = calculate_next_prayer(&timespan, start_date, &prayers);
+    let now = Utc::now().with_timezone(&timezone);
+    let next_prayer = calculate_next_prayer(&timespan, now, &prayers);
     debug!("Next prayer result: {:?}", next_prayer);
 
     // Calculate qibla direction
@@ -111,11 +199,12 @@ pub async fn prayer_times_handler(
     // Create metadata
     let meta = MetaData {
         method: standard_method,
+        method_source,
         settings: method_settings,
         timezone: request.timezone.clone(),
         adjustments: Some(adjustments),
         coordinates,
-        calculation_time: Utc::now().to_rfc3339(),
+        calculation_time: shared::time::now_rfc3339(),
     };
 
     // Remove extra day for DaysFromToday(1) case
@@ -123,18 +212,31 @@ pub async fn prayer_times_handler(
         debug!("Timespan is DaysFromToday(1), removing the extra calculated day.");
         prayers.pop();
     }
+
+    let mut warnings = Vec::new();
+    for day in day_warnings.into_iter().take(prayers.len()) {
+        merge_warnings(&mut warnings, day);
+    }
     
     let response = PrayerTimesResponse {
         qibla_direction,
         next: next_prayer,
+        today_index: timespan.today_index(),
         prayers,
+        warnings,
         meta,
     };
 
+    // Serialize once; the same bytes are cached and returned
+    let body = match prayer_fields {
+        Some(ref fields) => CachedJson::from_value(&project_response(&response, fields)?)?,
+        None => CachedJson::from_value(&response)?,
+    };
+
     // Cache the response for 1 hour
     debug!("Caching response for key: {} for 1 hour.", cache_key);
     if let Err(e) = cache
-        .set(&cache_key, &response, Some(StdDuration::from_secs(3600)))
+        .set_response(&cache_key, &body, Some(StdDuration::from_secs(3600)))
         .await
     {
         tracing::warn!("Failed to cache prayer times response: {}", e);
@@ -145,25 +247,225 @@ pub async fn prayer_times_handler(
         "Successfully calculated prayer times for {} days",
         day_count
     );
-    Ok(Json(response))
+    Ok(body)
+}
+
+/// The response with each day trimmed to the requested time fields
+fn project_response(
+    response: &PrayerTimesResponse,
+    fields: &[&str],
+) -> ApiResult<serde_json::Value> {
+    let mut value = serde_json::to_value(response)?;
+    if let Some(days) = value["prayers"].as_array_mut() {
+        for day in days {
+            project_prayer_times(day, fields);
+        }
+    }
+    Ok(value)
+}
+
+/// Compact handler for widgets: today's five prayers plus the next one.
+pub async fn prayer_times_today_handler(
+    Extension(preferred): Extension<Arc<PreferredMethodMap>>,
+    Extension(registry): Extension<Arc<MethodRegistry>>,
+    Extension(defaults): Extension<Arc<PrayerDefaults>>,
+    Query(params): Query<TodayQueryParams>,
+) -> ApiResult<Json<TodayPrayerTimesResponse>> {
+    let mut request = params.into_request();
+    request.apply_defaults(&defaults);
+    info!(
+        "Processing today's prayer times for coordinates: {:.4}, {:.4}",
+        request.latitude, request.longitude
+    );
+
+    request
+        .validate()
+        .map_err(|e| shared::error::ApiError::Validation(format!("Validation failed: {}", e)))?;
+
+    let timezone = TimezoneParsing::parse_timezone(&request.timezone)?;
+    let coordinates = Coordinates {
+        latitude: request.latitude,
+        longitude: request.longitude,
+        elevation: request.elevation.unwrap_or(0.0),
+    };
+    let (method_settings, _, _) = determine_method(&request, &preferred, &registry, &defaults)?;
+    let calculator = PrayerCalculator::new(coordinates, method_settings, Adjustments::default())
+        .with_horizon_angle(request.horizon_angle);
+
+    // Tomorrow is only needed to find the next prayer after today's Isha
+    let now = Utc::now().with_timezone(&timezone);
+    let days = [
+        calculator.calculate_prayer_times(now)?,
+        calculator.calculate_prayer_times(now + Duration::days(1))?,
+    ];
+    let next = calculate_next_prayer(&Timespan::DaysFromToday(1), now, &days);
+    let [today, _] = days;
+
+    Ok(Json(TodayPrayerTimesResponse {
+        date: today.date.clone(),
+        hijri: today.hijri.clone(),
+        prayers_today: today.into(),
+        next,
+    }))
+}
+
+/// Saves a named location for a user and returns it with its id
+pub async fn save_location_handler(
+    Extension(cache): Extension<Cache>,
+    headers: HeaderMap,
+    Json(request): Json<SaveLocationRequest>,
+) -> ApiResult<(StatusCode, Json<SavedLocation>)> {
+    let owner = location_owner(&headers)?;
+    request
+        .validate()
+        .map_err(|e| shared::error::ApiError::Validation(format!("Validation failed: {}", e)))?;
+
+    let location = LocationStore::new(cache).save(&owner, request).await?;
+    Ok((StatusCode::CREATED, Json(location)))
+}
+
+/// Deletes a saved location; only the API key that saved it can
+pub async fn delete_location_handler(
+    Extension(cache): Extension<Cache>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> ApiResult<StatusCode> {
+    let owner = location_owner(&headers)?;
+    LocationStore::new(cache).delete(&owner, &id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Saved locations belong to the `X-API-Key` they were saved with
+fn location_owner(headers: &HeaderMap) -> ApiResult<LocationOwner> {
+    let key = headers
+        .get("x-api-key")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default();
+    LocationOwner::from_api_key(key)
+}
+
+/// Today's prayer times for a saved location, as `/today` would return them
+pub async fn saved_location_times_handler(
+    Extension(cache): Extension<Cache>,
+    preferred: Extension<Arc<PreferredMethodMap>>,
+    registry: Extension<Arc<MethodRegistry>>,
+    defaults: Extension<Arc<PrayerDefaults>>,
+    Path(id): Path<String>,
+) -> ApiResult<Json<TodayPrayerTimesResponse>> {
+    let location = LocationStore::new(cache).get(&id).await?;
+    debug!("Computing today's times for saved location: {:?}", location);
+
+    prayer_times_today_handler(preferred, registry, defaults, Query(location.to_today_params()))
+        .await
+}
+
+/// Today's prayer times, the qibla and the Hijri date in one response, so a
+/// client's first launch needs a single round trip. Takes the same body as
+/// `/prayer-times`; `timespan` and `prayers` don't apply and are ignored.
+pub async fn prayer_times_summary_handler(
+    Extension(preferred): Extension<Arc<PreferredMethodMap>>,
+    Extension(registry): Extension<Arc<MethodRegistry>>,
+    Extension(defaults): Extension<Arc<PrayerDefaults>>,
+    ApiJson(mut request): ApiJson<PrayerTimesRequest>,
+) -> ApiResult<Json<SummaryResponse>> {
+    request.apply_defaults(&defaults);
+    info!(
+        "Processing prayer times summary for coordinates: {:.4}, {:.4}",
+        request.latitude, request.longitude
+    );
+
+    request
+        .validate()
+        .map_err(|e| shared::error::ApiError::Validation(format!("Validation failed: {}", e)))?;
+
+    let timezone = TimezoneParsing::parse_timezone(&request.timezone)?;
+    let coordinates = Coordinates {
+        latitude: request.latitude,
+        longitude: request.longitude,
+        elevation: request.elevation.unwrap_or(0.0),
+    };
+    let (method_settings, standard_method, method_source) =
+        determine_method(&request, &preferred, &registry, &defaults)?;
+    let adjustments = request.adjustments.clone().unwrap_or_default();
+    let calculator =
+        PrayerCalculator::new(coordinates, method_settings.clone(), adjustments.clone())
+            .with_horizon_angle(request.horizon_angle)
+            .with_rounding(request.rounding.clone());
+
+    // Tomorrow is only needed to find the next prayer after today's Isha
+    let now = Utc::now().with_timezone(&timezone);
+    let mut days = [
+        calculator.calculate_prayer_times(now)?,
+        calculator.calculate_prayer_times(now + Duration::days(1))?,
+    ];
+    if request.ramadan_mode {
+        for day in &mut days {
+            day.fasting = FastingTimes::from_prayer_times(day);
+        }
+    }
+    let next = calculate_next_prayer(&Timespan::DaysFromToday(1), now, &days);
+    let [today, _] = days;
+    let hijri = calculator.calculate_hijri_date(now.date_naive())?;
+
+    Ok(Json(SummaryResponse {
+        today,
+        next,
+        qibla: QiblaSummary {
+            direction: calculator.calculate_qibla_direction(),
+        },
+        hijri: HijriSummary::from(hijri.as_ref()),
+        meta: MetaData {
+            method: standard_method,
+            method_source,
+            settings: method_settings,
+            timezone: request.timezone.clone(),
+            adjustments: Some(adjustments),
+            coordinates,
+            calculation_time: shared::time::now_rfc3339(),
+        },
+    }))
+}
+
+/// Returns the method settings a method/country pair resolves to, for
+/// checking which angles a calculation would use.
+pub async fn resolve_method_handler(
+    Extension(preferred): Extension<Arc<PreferredMethodMap>>,
+    Extension(registry): Extension<Arc<MethodRegistry>>,
+    Extension(defaults): Extension<Arc<PrayerDefaults>>,
+    Query(params): Query<ResolveMethodQueryParams>,
+) -> ApiResult<Json<ResolvedMethodResponse>> {
+    debug!("Resolving method settings for: {:?}", params);
+    let request = params.into_request();
+    let (settings, method, method_source) = determine_method(&request, &preferred, &registry, &defaults)?;
+
+    Ok(Json(ResolvedMethodResponse {
+        method,
+        method_source,
+        settings,
+    }))
 }
 
 /// Creates a unique cache key based on the request parameters.
-fn create_cache_key(request: &PrayerTimesRequest) -> String {
+fn create_cache_key(request: &PrayerTimesRequest, coordinate_decimals: u32) -> String {
     use std::collections::hash_map::DefaultHasher;
     use std::hash::{Hash, Hasher};
 
     let mut hasher = DefaultHasher::new();
 
-    // Hash the main parameters that affect calculation
-    request.latitude.to_bits().hash(&mut hasher);
-    request.longitude.to_bits().hash(&mut hasher);
+    // Hash the main parameters that affect calculation. Coordinates are
+    // rounded first so nearly identical locations share an entry
+    round_coordinate(request.latitude, coordinate_decimals).to_bits().hash(&mut hasher);
+    round_coordinate(request.longitude, coordinate_decimals).to_bits().hash(&mut hasher);
     request.elevation.unwrap_or(0.0).to_bits().hash(&mut hasher);
+    request.horizon_angle.map(f64::to_bits).hash(&mut hasher);
     request.method.hash(&mut hasher);
     request.country.hash(&mut hasher);
     request.timezone.hash(&mut hasher);
     request.high_lat.hash(&mut hasher);
     request.school.hash(&mut hasher);
+    request.shafaq.hash(&mut hasher);
+    request.ramadan_mode.hash(&mut hasher);
+    request.prayers.hash(&mut hasher);
 
     // For custom method, we need to hash the settings
     if let Some(ref custom) = request.custom {
@@ -179,6 +481,12 @@ fn create_cache_key(request: &PrayerTimesRequest) -> String {
         }
     }
 
+    if let Some(ref rounding) = request.rounding {
+        if let Ok(json_str) = serde_json::to_string(rounding) {
+            json_str.hash(&mut hasher);
+        }
+    }
+
     // Hash timespan
     if let Some(ref timespan) = request.timespan {
         if let Ok(json_str) = serde_json::to_string(timespan) {
@@ -191,26 +499,27 @@ fn create_cache_key(request: &PrayerTimesRequest) -> String {
     key
 }
 
-/// Determines the prayer calculation method from the request.
+fn round_coordinate(value: f64, decimals: u32) -> f64 {
+    let factor = 10f64.powi(decimals as i32);
+    // Adding zero turns -0.0 into 0.0, which hashes differently
+    (value * factor).round() / factor + 0.0
+}
+
+/// Determines the prayer calculation method from the request, falling back to
+/// the country's preferred method and then the configured default, along with
+/// which of those it came from.
 fn determine_method(
     request: &PrayerTimesRequest,
     preferred: &PreferredMethodMap,
-) -> ApiResult<(crate::models::MethodSettings, Option<StandardMethod>)> {
+    registry: &MethodRegistry,
+    defaults: &PrayerDefaults,
+) -> ApiResult<(crate::models::MethodSettings, Option<MethodName>, MethodSource)> {
     if let Some(ref custom) = request.custom {
         debug!("Using custom method from request.");
-        let mut settings = crate::models::MethodSettings {
-            fajr: custom.fajr.unwrap_or(18.0),
-            isha: parse_minute_or_angle(&custom.isha.as_ref().unwrap_or(&"18.0".to_string()))?,
-            midnight: custom.midnight.unwrap_or_default(),
-            maghrib: parse_minute_or_angle(
-                &custom.maghrib.as_ref().unwrap_or(&"0 min".to_string()),
-            )?,
-            imsak: parse_minute_or_angle(&custom.imsak.as_ref().unwrap_or(&"10 min".to_string()))?,
-            dhuhr: custom.dhuhr.unwrap_or(0.0),
-            shafaq: custom.shafaq,
-            school: custom.school.unwrap_or_default(),
-            high_lat: custom.high_lat,
-        };
+        custom.validate().map_err(|e| {
+            shared::error::ApiError::Validation(format!("Invalid custom method: {}", e))
+        })?;
+        let mut settings = custom.to_method_settings()?;
 
         // Override with request-level settings
         if let Some(high_lat) = request.high_lat {
@@ -221,20 +530,32 @@ fn determine_method(
             debug!("Overriding school with request value: {:?}", school);
             settings.school = school;
         }
+        if let Some(shafaq) = request.shafaq {
+            debug!("Overriding shafaq with request value: {:?}", shafaq);
+            settings.shafaq = Some(shafaq);
+        }
 
         debug!("Custom method settings after overrides: {:?}", settings);
-        return Ok((settings, None));
+        return Ok((settings, None, MethodSource::Custom));
     }
 
     // Use standard method
-    let standard_method = if let Some(method) = request.method {
+    let (standard_method, source) = if let Some(MethodName::Registered(ref name)) = request.method {
+        debug!("Using registered method from request: {}", name);
+        let mut settings = registry.get(name)?;
+        apply_request_overrides(request, &mut settings);
+        return Ok((settings, request.method.clone(), MethodSource::Registry));
+    } else if let Some(MethodName::Standard(method)) = request.method {
         debug!("Using standard method from request: {:?}", method);
-        method
+        (method, MethodSource::Explicit)
     } else if let Some(ref country) = request.country {
         debug!("No method provided, looking up preferred method for country: {}", country);
         let preferred_method = preferred.get(country)?;
         debug!("Found preferred method for country: {:?}", preferred_method);
-        preferred_method
+        (preferred_method, MethodSource::Country)
+    } else if let Some(method) = defaults.method {
+        debug!("No method or country provided, using configured default: {:?}", method);
+        (method, MethodSource::Default)
     } else {
         debug!("No method, custom method, or country provided.");
         return Err(shared::error::ApiError::InvalidInput(
@@ -244,8 +565,17 @@ fn determine_method(
 
     let mut settings = standard_method.to_method_settings();
     debug!("Base settings for standard method: {:?}", settings);
+    apply_request_overrides(request, &mut settings);
+    
+    debug!("Final settings for standard method: {:?}", settings);
+    Ok((settings, Some(standard_method.into()), source))
+}
 
-    // Apply request-level overrides
+/// Request-level `high_lat`, `school` and `shafaq` override the method's own
+fn apply_request_overrides(
+    request: &PrayerTimesRequest,
+    settings: &mut crate::models::MethodSettings,
+) {
     if let Some(high_lat) = request.high_lat {
         debug!("Overriding high_lat with request value: {:?}", high_lat);
         settings.high_lat = Some(high_lat);
@@ -254,27 +584,9 @@ fn determine_method(
         debug!("Overriding school with request value: {:?}", school);
         settings.school = school;
     }
-    
-    debug!("Final settings for standard method: {:?}", settings);
-    Ok((settings, Some(standard_method)))
-}
-
-/// Parses a string into either a minute value or an angle value.
-fn parse_minute_or_angle(value: &str) -> ApiResult<crate::models::MinuteOrAngle> {
-    debug!("Parsing minute or angle from string: '{}'", value);
-    if value.ends_with("min") {
-        let minute_str = value.split_whitespace().next().unwrap_or("0");
-        let minute: f64 = minute_str.parse().map_err(|_| {
-            shared::error::ApiError::InvalidInput(format!("Invalid minute value: {}", value))
-        })?;
-        debug!("Parsed as Minute: {}", minute);
-        Ok(crate::models::MinuteOrAngle::Minute { minute })
-    } else {
-        let angle: f64 = value.parse().map_err(|_| {
-            shared::error::ApiError::InvalidInput(format!("Invalid angle value: {}", value))
-        })?;
-        debug!("Parsed as Angle: {}", angle);
-        Ok(crate::models::MinuteOrAngle::Angle { angle })
+    if let Some(shafaq) = request.shafaq {
+        debug!("Overriding shafaq with request value: {:?}", shafaq);
+        settings.shafaq = Some(shafaq);
     }
 }
 
@@ -360,38 +672,118 @@ fn parse_timespan(
         }
         Timespan::HijriYear(hijri_year) => {
             debug!("Timespan is HijriYear({})", hijri_year);
-            let hijri_start = hijri_date::HijriDate::from_hijri(hijri_year as usize, 1, 1)
-                .map_err(|e| {
-                    shared::error::ApiError::DateParsing(format!("Invalid Hijri year: {}", e))
+            let (first_day, _) = hijri_month_start(hijri_year, 1)?;
+            let start = first_day
+                .and_hms_opt(12, 0, 0)
+                .unwrap()
+                .and_local_timezone(timezone)
+                .single()
+                .ok_or_else(|| {
+                    shared::error::ApiError::DateParsing(
+                        "Failed to create datetime with timezone".to_string(),
+                    )
                 })?;
 
-            let start = chrono::NaiveDate::from_ymd_opt(
-                hijri_start.year_gr() as i32,
-                hijri_start.month_gr() as u32,
-                hijri_start.day_gr() as u32,
-            )
-            .ok_or_else(|| {
-                shared::error::ApiError::DateParsing(
-                    "Failed to convert Hijri date to Gregorian".to_string(),
-                )
-            })?
-            .and_hms_opt(12, 0, 0)
-            .unwrap()
-            .and_local_timezone(timezone)
-            .single()
-            .ok_or_else(|| {
-                shared::error::ApiError::DateParsing(
-                    "Failed to create datetime with timezone".to_string(),
-                )
-            })?;
+            // Hijri years are 354 or 355 days; count through the end of Dhu
+            // al-Hijjah so the last supported year needs no following one
+            let (last_month, last_month_len) = hijri_month_start(hijri_year, 12)?;
+            let days = ((last_month - first_day).num_days() + last_month_len as i64) as u16;
+            debug!("Calculated start date: {:?}, days in year: {}", start, days);
+            Ok((start, days))
+        }
+        Timespan::HijriMonth(month_name, hijri_year) => {
+            debug!("Timespan is HijriMonth({}, {})", month_name, hijri_year);
+            let month = parse_hijri_month_name(&month_name)?;
+            let (first_day, days) = hijri_month_start(hijri_year, month)?;
+            let start = first_day
+                .and_hms_opt(12, 0, 0)
+                .unwrap()
+                .and_local_timezone(timezone)
+                .single()
+                .ok_or_else(|| {
+                    shared::error::ApiError::DateParsing(
+                        "Failed to create datetime with timezone".to_string(),
+                    )
+                })?;
 
-            // Hijri years are typically 354 or 355 days
-            debug!("Calculated start date: {:?}, days in year: 355", start);
-            Ok((start, 355))
+            debug!("Calculated start date: {:?}, days in month: {}", start, days);
+            Ok((start, days))
+        }
+        Timespan::Around { days_before, days_after } => {
+            debug!("Timespan is Around({}, {})", days_before, days_after);
+            let start = Utc::now().with_timezone(&timezone) - Duration::days(days_before as i64);
+            // Oversized windows saturate and are rejected by the day count limit
+            let day_count = u16::try_from(days_before as u32 + days_after as u32 + 1)
+                .unwrap_or(u16::MAX);
+            debug!("Calculated start date: {:?}, day count: {}", start, day_count);
+            Ok((start, day_count))
         }
     }
 }
 
+/// The Gregorian date of the first day of a Hijri month, and the month's
+/// length in days (29 or 30)
+fn hijri_month_start(hijri_year: i32, hijri_month: usize) -> ApiResult<(chrono::NaiveDate, u16)> {
+    let year = usize::try_from(hijri_year)
+        .ok()
+        .filter(|&year| year > 0)
+        .ok_or_else(|| {
+            shared::error::ApiError::DateParsing(format!("Invalid Hijri year: {}", hijri_year))
+        })?;
+    let hijri = hijri_date::HijriDate::from_hijri(year, hijri_month, 1).map_err(|e| {
+        shared::error::ApiError::DateParsing(format!("Invalid Hijri year: {}", e))
+    })?;
+
+    let first_day = chrono::NaiveDate::from_ymd_opt(
+        hijri.year_gr() as i32,
+        hijri.month_gr() as u32,
+        hijri.day_gr() as u32,
+    )
+    .ok_or_else(|| {
+        shared::error::ApiError::DateParsing("Failed to convert Hijri date to Gregorian".to_string())
+    })?;
+    Ok((first_day, hijri.month_len() as u16))
+}
+
+/// Parses a Hijri month name, in English transliteration or Arabic, into its
+/// number. Transliterations vary, so case, apostrophes, hyphens and spaces
+/// are ignored and the common spellings are accepted.
+fn parse_hijri_month_name(month_name: &str) -> ApiResult<usize> {
+    debug!("Parsing Hijri month name: '{}'", month_name);
+    let normalized: String = month_name
+        .to_lowercase()
+        .chars()
+        .filter(|c| !matches!(c, ' ' | '-' | '_' | '\'' | '’' | 'ʿ' | 'ʾ' | '`'))
+        .collect();
+    let month_num = match normalized.as_str() {
+        "muharram" | "محرم" => 1,
+        "safar" | "صفر" => 2,
+        "rabialawwal" | "rabiulawwal" | "rabialawal" | "rabiulawal" | "rabi1" | "ربيعالأول"
+        | "ربيعالاول" => 3,
+        "rabialthani" | "rabiulthani" | "rabialakhir" | "rabiulakhir" | "rabiussani" | "rabi2"
+        | "ربيعالثاني" | "ربيعالآخر" => 4,
+        "jumadaalula" | "jumadaalawwal" | "jumadalula" | "jumadalawwal" | "jumada1"
+        | "جمادىالأولى" | "جمادىالاولى" => 5,
+        "jumadaalthani" | "jumadaalakhirah" | "jumadaalakhira" | "jumadalthani"
+        | "jumadalakhirah" | "jumadalakhira" | "jumada2" | "جمادىالآخرة" | "جمادىالثانية" => 6,
+        "rajab" | "رجب" => 7,
+        "shaban" | "shabaan" | "شعبان" => 8,
+        "ramadan" | "ramadhan" | "ramazan" | "رمضان" => 9,
+        "shawwal" | "shawal" | "شوال" => 10,
+        "dhualqadah" | "dhualqidah" | "dhulqadah" | "dhulqidah" | "zulqadah" | "zulqidah"
+        | "ذوالقعدة" => 11,
+        "dhualhijjah" | "dhulhijjah" | "dhulhijja" | "zulhijjah" | "ذوالحجة" => 12,
+        _ => {
+            return Err(shared::error::ApiError::InvalidInput(format!(
+                "Invalid Hijri month name: {}",
+                month_name
+            )))
+        }
+    };
+    debug!("Parsed Hijri month name to number: {}", month_num);
+    Ok(month_num)
+}
+
 /// Helper function to parse a month name string into its corresponding number.
 fn parse_month_name(month_name: &str) -> ApiResult<u32> {
     debug!("Parsing month name: '{}'", month_name);
@@ -449,13 +841,12 @@ fn is_leap_year(year: i32) -> bool {
 /// Calculates the next upcoming prayer time based on the current time.
 fn calculate_next_prayer(
     timespan: &Timespan,
-    start_date: DateTime<FixedOffset>,
+    now: DateTime<FixedOffset>,
     prayers: &[crate::models::PrayerTimes],
 ) -> Option<NextPrayer> {
     debug!("Attempting to calculate next prayer time.");
     match timespan {
-        Timespan::DaysFromToday(_) => {
-            let now = start_date;
+        Timespan::DaysFromToday(_) | Timespan::Around { .. } => {
             debug!("Current time for comparison: {:?}", now);
 
             for prayer in prayers {
@@ -469,10 +860,11 @@ fn calculate_next_prayer(
                 ];
 
                 for (name, time_str) in &prayer_times {
+                    // Times are formatted in the request's local time without an offset
                     if let Ok(prayer_time) =
-                        chrono::DateTime::parse_from_str(time_str, "%d/%m/%Y %H:%M")
+                        chrono::NaiveDateTime::parse_from_str(time_str, "%d/%m/%Y %H:%M")
                     {
-                        if prayer_time > now {
+                        if prayer_time > now.naive_local() {
                             debug!("Found next prayer: {} at {}", name, time_str);
                             return Some(NextPrayer {
                                 name: name.to_string(),
@@ -488,6 +880,744 @@ fn calculate_next_prayer(
         _ => {
             debug!("Not calculating next prayer for timespan type: {:?}", timespan);
             None
-        }, // Only calculate next prayer for "days from today"
+        }, // Only calculate next prayer for spans that include today
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{MinuteOrAngle, Shafaq, StandardMethod};
+    use axum::extract::FromRequest;
+    use chrono::NaiveDateTime;
+
+    /// The cache, or `None` when Redis isn't reachable
+    async fn connect() -> Option<Cache> {
+        Cache::new(&shared::config::RedisConfig {
+            url: "redis://localhost:6379".to_string(),
+            pool_max_open: 10,
+            pool_max_idle: 5,
+            pool_timeout: 30,
+            pool_expire: 300,
+        })
+        .await
+        .ok()
+    }
+
+    fn makkah() -> TodayQueryParams {
+        TodayQueryParams {
+            lat: 21.4225,
+            lng: 39.8262,
+            elevation: None,
+            horizon_angle: None,
+            method: Some(StandardMethod::Makkah.into()),
+            country: None,
+            timezone: Some("+03:00".to_string()),
+            high_lat: None,
+            school: None,
+            shafaq: None,
+        }
+    }
+
+    fn preferred() -> Extension<Arc<PreferredMethodMap>> {
+        Extension(Arc::new(PreferredMethodMap::load("nonexistent.csv").unwrap()))
+    }
+
+    fn registry() -> Extension<Arc<MethodRegistry>> {
+        Extension(Arc::new(MethodRegistry::default()))
+    }
+
+    fn no_defaults() -> Extension<Arc<PrayerDefaults>> {
+        Extension(Arc::new(PrayerDefaults::default()))
+    }
+
+    #[tokio::test]
+    async fn test_warmup_caches_each_location() {
+        let Some(cache) = connect().await else {
+            return;
+        };
+
+        let body = serde_json::json!({
+            "latitude": 24.4672,
+            "longitude": 39.6111,
+            "method": "makkah",
+            "timezone": "+03:00"
+        });
+        let mut expected: PrayerTimesRequest = serde_json::from_value(body.clone()).unwrap();
+        expected.apply_defaults(&PrayerDefaults::default());
+        let key = create_cache_key(&expected, PrayerDefaults::default().cache_coordinate_decimals);
+        cache.delete(&key).await.unwrap();
+
+        let request: WarmupRequest = serde_json::from_value(serde_json::json!({
+            "locations": [body, { "latitude": 95.0, "longitude": 0.0, "method": "makkah", "timezone": "UTC" }]
+        }))
+        .unwrap();
+        let Json(response) =
+            warmup_handler(Extension(cache.clone()), preferred(), registry(), no_defaults(), ApiJson(request))
+                .await
+                .unwrap();
+
+        assert_eq!(response.warmed, 1);
+        assert_eq!(response.failed.len(), 1);
+        assert_eq!(response.failed[0].index, 1);
+        assert!(cache.exists(&key).await.unwrap());
+        cache.delete(&key).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_over_limit_batch_is_rejected_before_computing() {
+        let Some(cache) = connect().await else {
+            return;
+        };
+
+        let body = serde_json::json!({
+            "latitude": 33.5138,
+            "longitude": 36.2765,
+            "method": "makkah",
+            "timezone": "+03:00",
+            "timespan": { "daysfromdate": ["01/01/2024", 300] }
+        });
+        let expected: PrayerTimesRequest = serde_json::from_value(body.clone()).unwrap();
+        let key = create_cache_key(&expected, PrayerDefaults::default().cache_coordinate_decimals);
+        cache.delete(&key).await.unwrap();
+
+        // Each entry is within max_days, together they are not
+        let defaults = PrayerDefaults {
+            max_batch_days: 500,
+            ..PrayerDefaults::default()
+        };
+        let request: WarmupRequest =
+            serde_json::from_value(serde_json::json!({ "locations": [body.clone(), body] })).unwrap();
+        let result = warmup_handler(
+            Extension(cache.clone()),
+            preferred(),
+            registry(),
+            Extension(Arc::new(defaults)),
+            ApiJson(request),
+        )
+        .await;
+
+        match result {
+            Err(shared::error::ApiError::InvalidInput(message)) => {
+                assert!(message.contains("600") && message.contains("500"), "{}", message)
+            }
+            other => panic!("expected invalid input, got {:?}", other.map(|_| ())),
+        }
+        assert!(!cache.exists(&key).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_saved_location_times_match_today() {
+        let Some(cache) = connect().await else {
+            return;
+        };
+
+        let (status, Json(saved)) =
+            save_location_handler(Extension(cache.clone()), api_key("user-1"), Json(makkah_location()))
+                .await
+                .unwrap();
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(saved.name, "Makkah");
+
+        let Json(times) = saved_location_times_handler(
+            Extension(cache.clone()),
+            preferred(),
+            registry(),
+            no_defaults(),
+            Path(saved.id.clone()),
+        )
+        .await
+        .unwrap();
+        let Json(expected) =
+            prayer_times_today_handler(preferred(), registry(), no_defaults(), Query(makkah()))
+                .await
+                .unwrap();
+        cache.delete(&format!("prayer_location:{}", saved.id)).await.unwrap();
+
+        assert_eq!(times.date, expected.date);
+        assert_eq!(times.prayers_today.fajr, expected.prayers_today.fajr);
+        assert!(saved_location_times_handler(
+            Extension(cache),
+            preferred(),
+            registry(),
+            no_defaults(),
+            Path("missing".to_string()),
+        )
+        .await
+        .is_err());
+    }
+
+    fn makkah_location() -> SaveLocationRequest {
+        serde_json::from_value(serde_json::json!({
+            "name": " Makkah ",
+            "lat": 21.4225,
+            "lng": 39.8262,
+            "timezone": "+03:00",
+            "method": "makkah"
+        }))
+        .unwrap()
+    }
+
+    fn api_key(key: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-api-key", key.parse().unwrap());
+        headers
+    }
+
+    #[tokio::test]
+    async fn test_saved_locations_belong_to_their_api_key() {
+        let Some(cache) = connect().await else {
+            return;
+        };
+        let owner = api_key(&uuid::Uuid::new_v4().to_string());
+
+        let anonymous =
+            save_location_handler(Extension(cache.clone()), HeaderMap::new(), Json(makkah_location())).await;
+        assert!(matches!(anonymous, Err(shared::error::ApiError::Authentication(_))));
+
+        let mut saved = Vec::new();
+        for _ in 0..crate::locations::MAX_LOCATIONS_PER_USER {
+            let (_, Json(location)) =
+                save_location_handler(Extension(cache.clone()), owner.clone(), Json(makkah_location()))
+                    .await
+                    .unwrap();
+            saved.push(location.id);
+        }
+        let over_cap =
+            save_location_handler(Extension(cache.clone()), owner.clone(), Json(makkah_location())).await;
+        assert!(matches!(over_cap, Err(shared::error::ApiError::Validation(_))));
+
+        // Another key can't delete it, and it reads as missing to them
+        let stranger = delete_location_handler(
+            Extension(cache.clone()),
+            api_key("someone-else"),
+            Path(saved[0].clone()),
+        )
+        .await;
+        assert!(matches!(stranger, Err(shared::error::ApiError::NotFound(_))));
+
+        for id in &saved {
+            let status = delete_location_handler(Extension(cache.clone()), owner.clone(), Path(id.clone()))
+                .await
+                .unwrap();
+            assert_eq!(status, StatusCode::NO_CONTENT);
+        }
+        assert!(saved_location_times_handler(
+            Extension(cache.clone()),
+            preferred(),
+            registry(),
+            no_defaults(),
+            Path(saved[0].clone()),
+        )
+        .await
+        .is_err());
+
+        // Deleting frees room under the cap
+        let (_, Json(again)) =
+            save_location_handler(Extension(cache.clone()), owner.clone(), Json(makkah_location()))
+                .await
+                .unwrap();
+        delete_location_handler(Extension(cache), owner, Path(again.id)).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_saves_stop_at_the_cap() {
+        let Some(cache) = connect().await else {
+            return;
+        };
+        let owner = api_key(&uuid::Uuid::new_v4().to_string());
+
+        let saves: Vec<_> = (0..2 * crate::locations::MAX_LOCATIONS_PER_USER)
+            .map(|_| {
+                tokio::spawn(save_location_handler(
+                    Extension(cache.clone()),
+                    owner.clone(),
+                    Json(makkah_location()),
+                ))
+            })
+            .collect();
+        let mut saved = Vec::new();
+        for save in saves {
+            if let Ok((_, Json(location))) = save.await.unwrap() {
+                saved.push(location.id);
+            }
+        }
+
+        for id in &saved {
+            delete_location_handler(Extension(cache.clone()), owner.clone(), Path(id.clone()))
+                .await
+                .unwrap();
+        }
+        assert_eq!(saved.len(), crate::locations::MAX_LOCATIONS_PER_USER);
+    }
+
+    #[tokio::test]
+    async fn test_today_handler_for_makkah() {
+        let before = Utc::now().with_timezone(&FixedOffset::east_opt(3 * 3600).unwrap());
+        let Json(response) = prayer_times_today_handler(preferred(), registry(), no_defaults(), Query(makkah()))
+            .await
+            .unwrap();
+        let after = Utc::now().with_timezone(&FixedOffset::east_opt(3 * 3600).unwrap());
+
+        let dates = [before, after].map(|d| d.format("%d/%m/%Y").to_string());
+        assert!(dates.contains(&response.date));
+        assert!(!response.hijri.is_empty());
+
+        let prayers = &response.prayers_today;
+        let times: Vec<NaiveDateTime> = [
+            &prayers.fajr,
+            &prayers.dhuhr,
+            &prayers.asr,
+            &prayers.maghrib,
+            &prayers.isha,
+        ]
+        .iter()
+        .map(|t| NaiveDateTime::parse_from_str(t, "%d/%m/%Y %H:%M").unwrap())
+        .collect();
+        assert!(times.windows(2).all(|w| w[0] < w[1]));
+
+        // Dhuhr in Makkah falls between roughly 12:05 and 12:40 local time all year
+        let dhuhr = times[1].format("%H:%M").to_string();
+        assert!(dhuhr.as_str() > "12:00" && dhuhr.as_str() < "12:45", "{}", dhuhr);
+
+        // With tomorrow computed there is always an upcoming prayer
+        let next = response.next.expect("next prayer");
+        let next_time = NaiveDateTime::parse_from_str(&next.time, "%d/%m/%Y %H:%M").unwrap();
+        assert!(next_time > before.naive_local() - Duration::minutes(1));
+    }
+
+    #[tokio::test]
+    async fn test_summary_has_prayers_qibla_and_hijri() {
+        let request: PrayerTimesRequest = serde_json::from_value(serde_json::json!({
+            "latitude": 51.5074,
+            "longitude": -0.1278,
+            "method": "mwl",
+            "timezone": "+00:00"
+        }))
+        .unwrap();
+
+        let Json(summary) = prayer_times_summary_handler(preferred(), registry(), no_defaults(), ApiJson(request))
+            .await
+            .unwrap();
+
+        assert!(!summary.today.fajr.is_empty() && !summary.today.isha.is_empty());
+        assert_eq!(summary.today.date, Utc::now().format("%d/%m/%Y").to_string());
+        assert!(summary.next.is_some());
+        // London faces roughly east-south-east towards Makkah
+        assert!((summary.qibla.direction - 119.0).abs() < 1.0, "{}", summary.qibla.direction);
+        assert!(summary.hijri.year >= 1447 && (1..=12).contains(&summary.hijri.month));
+        assert_eq!(summary.today.hijri, summary.hijri.date);
+        assert_eq!(summary.meta.method_source, MethodSource::Explicit);
+        assert!(shared::time::is_rfc3339_utc(&summary.meta.calculation_time));
+    }
+
+    #[tokio::test]
+    async fn test_today_handler_requires_method_or_country() {
+        let mut params = makkah();
+        params.method = None;
+        assert!(prayer_times_today_handler(preferred(), registry(), no_defaults(), Query(params))
+            .await
+            .is_err());
+
+        let mut params = makkah();
+        params.method = None;
+        params.country = Some("pakistan".to_string());
+        assert!(prayer_times_today_handler(preferred(), registry(), no_defaults(), Query(params))
+            .await
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_lat_lng_only_request_uses_configured_defaults() {
+        let params = TodayQueryParams {
+            method: None,
+            timezone: None,
+            ..makkah()
+        };
+        assert!(prayer_times_today_handler(preferred(), registry(), no_defaults(), Query(params))
+            .await
+            .is_err());
+
+        let config = shared::config::PrayerTimesConfig {
+            default_method: Some("Makkah".to_string()),
+            default_timezone: Some("Asia/Riyadh".to_string()),
+            ..Default::default()
+        };
+        let defaults = Extension(Arc::new(PrayerDefaults::from_config(&config).unwrap()));
+        let params = TodayQueryParams {
+            method: None,
+            timezone: None,
+            ..makkah()
+        };
+        let Json(defaulted) = prayer_times_today_handler(preferred(), registry(), defaults, Query(params))
+            .await
+            .unwrap();
+        let Json(explicit) = prayer_times_today_handler(preferred(), registry(), no_defaults(), Query(makkah()))
+            .await
+            .unwrap();
+
+        assert_eq!(defaulted.prayers_today.fajr, explicit.prayers_today.fajr);
+        assert_eq!(defaulted.prayers_today.isha, explicit.prayers_today.isha);
+    }
+
+    #[tokio::test]
+    async fn test_longitude_wraps_and_poles_are_rejected() {
+        let request: PrayerTimesRequest = serde_json::from_value(serde_json::json!({
+            "latitude": 21.4225,
+            "longitude": 190.0,
+            "method": "makkah",
+            "timezone": "+03:00"
+        }))
+        .unwrap();
+        assert!((request.longitude - -170.0).abs() < 1e-9, "{}", request.longitude);
+        assert!(request.validate().is_ok());
+
+        let uri: axum::http::Uri = "/today?lat=21.4225&lng=-320.0&method=makkah&timezone=%2B03:00"
+            .parse()
+            .unwrap();
+        let Query(params) = Query::<TodayQueryParams>::try_from_uri(&uri).unwrap();
+        assert!((params.lng - 40.0).abs() < 1e-9, "{}", params.lng);
+
+        let params = TodayQueryParams { lat: 90.0, ..makkah() };
+        match prayer_times_today_handler(preferred(), registry(), no_defaults(), Query(params)).await {
+            Err(shared::error::ApiError::Validation(message)) => {
+                assert!(message.contains("latitude_at_pole"), "{}", message)
+            }
+            other => panic!("expected a validation error, got {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn test_invalid_default_method_is_rejected() {
+        let config = shared::config::PrayerTimesConfig {
+            default_method: Some("nonsense".to_string()),
+            ..Default::default()
+        };
+        assert!(PrayerDefaults::from_config(&config).is_err());
+    }
+
+    fn custom_request(custom: serde_json::Value) -> PrayerTimesRequest {
+        let mut request = makkah().into_request();
+        request.method = None;
+        request.custom = Some(serde_json::from_value(custom).unwrap());
+        request
+    }
+
+    #[test]
+    fn test_out_of_range_fajr_angle_is_rejected() {
+        let request = custom_request(serde_json::json!({ "fajr": 90.0 }));
+        let errors = request.validate().unwrap_err();
+        assert!(errors.to_string().contains("fajr"), "{}", errors);
+        assert!(determine_method(
+            &request,
+            &preferred(),
+            &MethodRegistry::default(),
+            &PrayerDefaults::default()
+        )
+        .is_err());
+
+        let request = custom_request(serde_json::json!({ "fajr": 18.5, "isha": "90 min" }));
+        assert!(request.validate().is_ok());
+        assert!(determine_method(
+            &request,
+            &preferred(),
+            &MethodRegistry::default(),
+            &PrayerDefaults::default()
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn test_negative_maghrib_minute_is_rejected() {
+        let request = custom_request(serde_json::json!({ "maghrib": "-5 min" }));
+        let errors = request.validate().unwrap_err();
+        assert!(errors.to_string().contains("maghrib"), "{}", errors);
+        assert!(determine_method(
+            &request,
+            &preferred(),
+            &MethodRegistry::default(),
+            &PrayerDefaults::default()
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_nearby_coordinates_share_cache_key() {
+        let mut request = makkah().into_request();
+        request.latitude = 40.7128;
+        request.longitude = -74.0060;
+        let key = create_cache_key(&request, 4);
+
+        request.latitude = 40.71280001;
+        request.longitude = -74.00600002;
+        assert_eq!(create_cache_key(&request, 4), key);
+
+        // About 22m away is a different entry at 4 decimals but not at 2
+        request.latitude = 40.7130;
+        assert_ne!(create_cache_key(&request, 4), key);
+        let coarse = create_cache_key(&request, 2);
+        request.latitude = 40.7128;
+        assert_eq!(create_cache_key(&request, 2), coarse);
+    }
+
+    #[test]
+    fn test_registered_method_resolves_by_name() {
+        let registry =
+            MethodRegistry::from_json(r#"{ "ukmc": { "fajr": 18.0, "isha": "17" } }"#).unwrap();
+        let mut request: PrayerTimesRequest = serde_json::from_value(serde_json::json!({
+            "latitude": 51.5074,
+            "longitude": -0.1278,
+            "method": "UKMC"
+        }))
+        .unwrap();
+
+        let (settings, method, source) =
+            determine_method(&request, &preferred(), &registry, &PrayerDefaults::default())
+                .unwrap();
+        assert!(matches!(settings.isha, MinuteOrAngle::Angle { angle } if angle == 17.0));
+        assert_eq!(method, Some(MethodName::Registered("UKMC".to_string())));
+        assert_eq!(source, MethodSource::Registry);
+
+        request.method = Some(MethodName::Registered("unknown".to_string()));
+        assert!(
+            determine_method(&request, &preferred(), &registry, &PrayerDefaults::default())
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_around_timespan_spans_yesterday_to_tomorrow() {
+        let timezone = FixedOffset::east_opt(3 * 3600).unwrap();
+        let timespan = Timespan::Around { days_before: 1, days_after: 1 };
+
+        let (start, day_count) = parse_timespan(timespan.clone(), timezone).unwrap();
+        let now = Utc::now().with_timezone(&timezone);
+        assert_eq!(day_count, 3);
+        assert_eq!(start.date_naive(), (now - Duration::days(1)).date_naive());
+        assert_eq!(timespan.today_index(), Some(1));
+
+        let coordinates = Coordinates {
+            latitude: 21.4225,
+            longitude: 39.8262,
+            elevation: 0.0,
+        };
+        let calculator = PrayerCalculator::new(
+            coordinates,
+            StandardMethod::Makkah.to_method_settings(),
+            Adjustments::default(),
+        );
+        let prayers: Vec<_> = (0..day_count)
+            .map(|i| calculator.calculate_prayer_times(start + Duration::days(i as i64)).unwrap())
+            .collect();
+        assert_eq!(prayers[1].date, now.format("%d/%m/%Y").to_string());
+
+        // The next prayer is after now, not after the start of the window
+        let next = calculate_next_prayer(&timespan, now, &prayers).expect("next prayer");
+        let next_time = NaiveDateTime::parse_from_str(&next.time, "%d/%m/%Y %H:%M").unwrap();
+        assert!(next_time > now.naive_local());
+    }
+
+    #[test]
+    fn test_hijri_year_timespan_counts_actual_days() {
+        let timezone = FixedOffset::east_opt(3 * 3600).unwrap();
+
+        // 1444 AH ran from 30 July 2022 to 18 July 2023: 354 days
+        let (start, day_count) = parse_timespan(Timespan::HijriYear(1444), timezone).unwrap();
+        assert_eq!(start.format("%Y").to_string(), "2022");
+        assert_eq!(day_count, 354);
+
+        let (_, leap_count) = parse_timespan(Timespan::HijriYear(1445), timezone).unwrap();
+        assert!(leap_count == 354 || leap_count == 355, "{}", leap_count);
+        assert!(parse_timespan(Timespan::HijriYear(-1), timezone).is_err());
+
+        // 1499 is the last year hijri_date handles, so there is no 1500 to count up to
+        let (_, last_count) = parse_timespan(Timespan::HijriYear(1499), timezone).unwrap();
+        assert!(last_count == 354 || last_count == 355, "{}", last_count);
+        assert!(parse_timespan(Timespan::HijriYear(1500), timezone).is_err());
+    }
+
+    #[test]
+    fn test_timespan_accepts_documented_shapes() {
+        let timespan: Timespan =
+            serde_json::from_value(serde_json::json!({ "DaysFromToday": 3 })).unwrap();
+        assert!(matches!(timespan, Timespan::DaysFromToday(3)));
+
+        let request: PrayerTimesRequest = serde_json::from_value(serde_json::json!({
+            "latitude": 21.4225,
+            "longitude": 39.8262,
+            "timespan": { "daysfromdate": ["01/03/2024", 7] }
+        }))
+        .unwrap();
+        assert!(matches!(
+            request.timespan,
+            Some(Timespan::DaysFromDate(ref date, 7)) if date == "01/03/2024"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_malformed_timespan_is_invalid_input_with_guidance() {
+        for malformed in [
+            serde_json::json!({ "daysfromdate": "01/03/2024" }),
+            serde_json::json!({ "weeks": 2 }),
+            serde_json::json!("daysfromtoday"),
+        ] {
+            let error = serde_json::from_value::<Timespan>(malformed).unwrap_err();
+            assert!(error.to_string().contains("Expected one of"), "{}", error);
+        }
+
+        let body = r#"{"latitude": 21.4225, "longitude": 39.8262, "timespan": {"month": 2024}}"#;
+        let req = axum::http::Request::builder()
+            .header(axum::http::header::CONTENT_TYPE, "application/json")
+            .body(axum::body::Body::from(body))
+            .unwrap();
+        let extracted = <ApiJson<PrayerTimesRequest> as FromRequest<(), _>>::from_request(req, &()).await;
+        match extracted {
+            Err(shared::error::ApiError::InvalidInput(message)) => {
+                assert!(message.contains("'month'"), "{}", message);
+                assert!(message.contains("gregorianyear"), "{}", message);
+            }
+            other => panic!("expected invalid input, got {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn test_hijri_month_timespan_covers_ramadan() {
+        let timezone = FixedOffset::east_opt(3 * 3600).unwrap();
+        let timespan: Timespan =
+            serde_json::from_value(serde_json::json!({ "hijrimonth": ["Ramadan", 1445] })).unwrap();
+
+        let (start, day_count) = parse_timespan(timespan, timezone).unwrap();
+        let (next_start, _) =
+            parse_timespan(Timespan::HijriMonth("shawwal".to_string(), 1445), timezone).unwrap();
+        assert!(day_count == 29 || day_count == 30, "{}", day_count);
+        assert_eq!(start + Duration::days(day_count as i64), next_start);
+
+        let calculator = PrayerCalculator::new(
+            Coordinates {
+                latitude: 21.4225,
+                longitude: 39.8262,
+                elevation: 0.0,
+            },
+            StandardMethod::Makkah.to_method_settings(),
+            Adjustments::default(),
+        );
+        let timetable: Vec<_> = (0..day_count)
+            .map(|i| calculator.calculate_prayer_times(start + Duration::days(i as i64)).unwrap())
+            .collect();
+        // hijri_date does not zero-pad the day and month
+        assert_eq!(timetable.first().unwrap().hijri, "1/9/1445");
+        assert_eq!(timetable.last().unwrap().hijri, format!("{}/9/1445", day_count));
+
+        // Dhu al-Hijjah ends the year, and ends the supported range in 1499
+        let (_, last_month) =
+            parse_timespan(Timespan::HijriMonth("dhul hijjah".to_string(), 1499), timezone).unwrap();
+        assert!(last_month == 29 || last_month == 30, "{}", last_month);
+
+        let arabic = parse_timespan(Timespan::HijriMonth("رمضان".to_string(), 1445), timezone);
+        assert_eq!(arabic.unwrap(), (start, day_count));
+        assert_eq!(parse_hijri_month_name("Dhul-Hijjah").unwrap(), 12);
+        assert_eq!(parse_hijri_month_name("Rabi' al-Awwal").unwrap(), 3);
+        assert!(parse_hijri_month_name("ramadhaan month").is_err());
+    }
+
+    #[test]
+    fn test_prayers_filter_omits_other_times() {
+        let mut request = makkah().into_request();
+        request.prayers = Some("fajr, dhuhr,asr,maghrib,ISHA".to_string());
+        let fields = request.prayer_fields().unwrap().unwrap();
+        assert_eq!(fields, vec!["fajr", "dhuhr", "asr", "maghrib", "isha"]);
+
+        let coordinates = Coordinates {
+            latitude: 21.4225,
+            longitude: 39.8262,
+            elevation: 0.0,
+        };
+        let calculator = PrayerCalculator::new(
+            coordinates,
+            StandardMethod::Makkah.to_method_settings(),
+            Adjustments::default(),
+        );
+        let timezone = FixedOffset::east_opt(3 * 3600).unwrap();
+        let times = calculator.calculate_prayer_times(Utc::now().with_timezone(&timezone)).unwrap();
+        let mut day = serde_json::to_value(&times).unwrap();
+        project_prayer_times(&mut day, &fields);
+
+        for kept in ["date", "hijri", "fajr", "dhuhr", "asr", "maghrib", "isha"] {
+            assert!(day.get(kept).is_some(), "{} missing", kept);
+        }
+        for dropped in ["imsak", "sunrise", "sunset", "midnight", "first_third", "last_third"] {
+            assert!(day.get(dropped).is_none(), "{} kept", dropped);
+        }
+
+        request.prayers = Some("fajr,tahajjud".to_string());
+        assert!(request.prayer_fields().unwrap_err().contains("tahajjud"));
+        request.prayers = None;
+        assert_eq!(request.prayer_fields().unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_requested_shafaq_overrides_method_default() {
+        let params = ResolveMethodQueryParams {
+            method: Some(StandardMethod::Moonsighting.into()),
+            country: None,
+            high_lat: None,
+            school: None,
+            shafaq: Some(Shafaq::Abyad),
+        };
+        let Json(resolved) = resolve_method_handler(preferred(), registry(), no_defaults(), Query(params))
+            .await
+            .unwrap();
+
+        assert_eq!(resolved.settings.shafaq, Some(Shafaq::Abyad));
+    }
+
+    #[test]
+    fn test_method_source_reflects_how_method_was_chosen() {
+        let source = |request: &PrayerTimesRequest, defaults: &PrayerDefaults| {
+            determine_method(request, &preferred(), &MethodRegistry::default(), defaults)
+                .unwrap()
+                .2
+        };
+        let configured = PrayerDefaults {
+            method: Some(StandardMethod::Mwl),
+            ..Default::default()
+        };
+
+        let mut request = makkah().into_request();
+        assert_eq!(source(&request, &configured), MethodSource::Explicit);
+
+        request.method = None;
+        request.country = Some("turkey".to_string());
+        assert_eq!(source(&request, &configured), MethodSource::Country);
+
+        request.country = None;
+        assert_eq!(source(&request, &configured), MethodSource::Default);
+
+        let custom = custom_request(serde_json::json!({ "fajr": 18.5 }));
+        assert_eq!(source(&custom, &configured), MethodSource::Custom);
+    }
+
+    #[tokio::test]
+    async fn test_resolve_country_returns_diyanet_settings() {
+        let params = ResolveMethodQueryParams {
+            method: None,
+            country: Some("turkey".to_string()),
+            high_lat: None,
+            school: None,
+            shafaq: None,
+        };
+        let Json(resolved) = resolve_method_handler(preferred(), registry(), no_defaults(), Query(params))
+            .await
+            .unwrap();
+
+        assert_eq!(resolved.method, Some(StandardMethod::Diyanet.into()));
+        assert_eq!(
+            serde_json::to_value(&resolved).unwrap()["method_source"],
+            "country"
+        );
+        assert_eq!(
+            serde_json::to_value(&resolved.settings).unwrap(),
+            serde_json::to_value(StandardMethod::Diyanet.to_method_settings()).unwrap()
+        );
     }
 }
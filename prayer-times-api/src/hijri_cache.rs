@@ -0,0 +1,127 @@
+// Memoized Gregorian to Hijri conversion. A year-long timetable converts
+// 365 consecutive dates, and neighbouring requests mostly ask for the same
+// ones, so recent conversions are kept in a small process-wide LRU.
+// HijriDate is not Clone, so entries are shared behind an Arc.
+
+use chrono::{Datelike, NaiveDate};
+use hijri_date::HijriDate;
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex, OnceLock,
+    },
+};
+
+/// Dates kept; enough for a couple of years of timetables
+pub const HIJRI_CACHE_CAPACITY: usize = 1024;
+
+/// Least-recently-used cache of conversions keyed by Gregorian date
+#[derive(Debug)]
+pub struct HijriCache {
+    capacity: usize,
+    entries: Mutex<Entries>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+#[derive(Debug, Default)]
+struct Entries {
+    /// Conversion and the tick it was last used at
+    dates: HashMap<NaiveDate, (Arc<HijriDate>, u64)>,
+    tick: u64,
+}
+
+impl HijriCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            entries: Mutex::new(Entries::default()),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// The cache shared by every calculator
+    pub fn global() -> &'static HijriCache {
+        static CACHE: OnceLock<HijriCache> = OnceLock::new();
+        CACHE.get_or_init(|| HijriCache::new(HIJRI_CACHE_CAPACITY))
+    }
+
+    pub fn to_hijri(&self, date: NaiveDate) -> Result<Arc<HijriDate>, String> {
+        {
+            let mut entries = self.entries.lock().unwrap();
+            entries.tick += 1;
+            let tick = entries.tick;
+            if let Some((hijri, last_used)) = entries.dates.get_mut(&date) {
+                *last_used = tick;
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                return Ok(Arc::clone(hijri));
+            }
+        }
+
+        // Converted outside the lock; a concurrent miss on the same date
+        // just converts it twice
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        let hijri = Arc::new(
+            HijriDate::from_gr(
+                date.year() as usize,
+                date.month() as usize,
+                date.day() as usize,
+            )
+            .map_err(|e| e.to_string())?,
+        );
+
+        let mut entries = self.entries.lock().unwrap();
+        if entries.dates.len() >= self.capacity && !entries.dates.contains_key(&date) {
+            let oldest = entries
+                .dates
+                .iter()
+                .min_by_key(|(_, (_, last_used))| *last_used)
+                .map(|(date, _)| *date);
+            if let Some(oldest) = oldest {
+                entries.dates.remove(&oldest);
+            }
+        }
+        let tick = entries.tick;
+        entries.dates.insert(date, (Arc::clone(&hijri), tick));
+
+        Ok(hijri)
+    }
+
+    pub fn hits(&self) -> u64 {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    pub fn misses(&self) -> u64 {
+        self.misses.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 3, day).unwrap()
+    }
+
+    #[test]
+    fn test_repeated_conversions_hit_the_cache() {
+        let cache = HijriCache::new(2);
+
+        let first = cache.to_hijri(date(1)).unwrap();
+        assert_eq!(cache.to_hijri(date(1)).unwrap(), first);
+        assert_eq!((cache.hits(), cache.misses()), (1, 1));
+
+        // Day 1 was used more recently than day 2, so day 2 is evicted
+        cache.to_hijri(date(2)).unwrap();
+        cache.to_hijri(date(1)).unwrap();
+        cache.to_hijri(date(3)).unwrap();
+        assert_eq!((cache.hits(), cache.misses()), (2, 3));
+        cache.to_hijri(date(1)).unwrap();
+        assert_eq!(cache.hits(), 3);
+        cache.to_hijri(date(2)).unwrap();
+        assert_eq!(cache.misses(), 4);
+    }
+}
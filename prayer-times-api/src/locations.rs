@@ -0,0 +1,159 @@
+// Named locations saved for a user. They live in Redis keyed by a generated
+// id, so a client can keep a list of ids and fetch today's times for each
+// without resending coordinates.
+//
+// The user is whoever holds the `X-API-Key` sent when saving; only a digest
+// of it is stored. Locations expire a while after they're saved, and each
+// user keeps a bounded list of them.
+
+use redis::Script;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use shared::{
+    cache::Cache,
+    error::{ApiError, ApiResult},
+};
+use std::time::Duration;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::models::{SaveLocationRequest, SavedLocation};
+
+/// How long a saved location lives
+const LOCATION_TTL: Duration = Duration::from_secs(90 * 24 * 3600);
+/// Locations a single user may keep at once
+pub const MAX_LOCATIONS_PER_USER: usize = 20;
+
+/// Stores a location and adds it to its owner's set unless they're at the
+/// cap, in one step so concurrent saves can't both take the last slot.
+/// Returns the owner's count after saving, or -1 at the cap.
+const SAVE_SCRIPT: &str = r#"
+if redis.call('SCARD', KEYS[1]) >= tonumber(ARGV[1]) then
+    return -1
+end
+redis.call('SET', KEYS[2], ARGV[2], 'EX', ARGV[3])
+redis.call('SADD', KEYS[1], ARGV[4])
+redis.call('EXPIRE', KEYS[1], ARGV[3])
+return redis.call('SCARD', KEYS[1])
+"#;
+
+/// The owner a location was saved for, as a digest of their API key
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocationOwner(String);
+
+impl LocationOwner {
+    pub fn from_api_key(key: &str) -> ApiResult<Self> {
+        let key = key.trim();
+        if key.is_empty() {
+            return Err(ApiError::authentication("X-API-Key header is required"));
+        }
+        Ok(Self(format!("{:x}", Sha256::digest(key.as_bytes()))))
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct StoredLocation {
+    owner: String,
+    location: SavedLocation,
+}
+
+#[derive(Clone)]
+pub struct LocationStore {
+    cache: Cache,
+}
+
+impl LocationStore {
+    pub fn new(cache: Cache) -> Self {
+        Self { cache }
+    }
+
+    fn key(id: &str) -> String {
+        format!("prayer_location:{}", id)
+    }
+
+    /// A Redis set of the owner's location ids
+    fn owner_key(owner: &LocationOwner) -> String {
+        format!("prayer_location_ids:{}", owner.0)
+    }
+
+    /// Drops ids whose location has expired from the owner's set
+    async fn prune_expired(&self, owner: &LocationOwner) -> ApiResult<()> {
+        let mut expired = Vec::new();
+        for id in self.cache.set_members(&Self::owner_key(owner)).await? {
+            if !self.cache.exists(&Self::key(&id)).await? {
+                expired.push(id);
+            }
+        }
+        self.cache.remove_members(&Self::owner_key(owner), &expired).await
+    }
+
+    pub async fn save(
+        &self,
+        owner: &LocationOwner,
+        request: SaveLocationRequest,
+    ) -> ApiResult<SavedLocation> {
+        self.prune_expired(owner).await?;
+
+        let location = SavedLocation {
+            id: Uuid::new_v4().to_string(),
+            name: request.name.trim().to_string(),
+            lat: request.lat,
+            lng: request.lng,
+            timezone: request.timezone,
+            method: request.method,
+            created_at: shared::time::now_rfc3339(),
+        };
+        let stored = StoredLocation {
+            owner: owner.0.clone(),
+            location,
+        };
+
+        let script = Script::new(SAVE_SCRIPT);
+        let mut invocation = script.prepare_invoke();
+        invocation
+            .key(Self::owner_key(owner))
+            .key(Self::key(&stored.location.id))
+            .arg(MAX_LOCATIONS_PER_USER)
+            .arg(serde_json::to_string(&stored)?)
+            .arg(LOCATION_TTL.as_secs())
+            .arg(&stored.location.id);
+        let saved: i64 = self.cache.invoke_script(&invocation).await?;
+        if saved < 0 {
+            return Err(ApiError::validation(format!(
+                "At most {} locations can be saved; delete one first",
+                MAX_LOCATIONS_PER_USER
+            )));
+        }
+
+        info!("Saved location {} ({} for its owner)", stored.location.id, saved);
+        Ok(stored.location)
+    }
+
+    pub async fn get(&self, id: &str) -> ApiResult<SavedLocation> {
+        self.cache
+            .get::<StoredLocation>(&Self::key(id))
+            .await?
+            .map(|stored| stored.location)
+            .ok_or_else(|| ApiError::not_found(format!("Location {}", id)))
+    }
+
+    /// Deletes one of the owner's locations. Someone else's location is
+    /// reported as missing rather than forbidden, so ids can't be probed.
+    pub async fn delete(&self, owner: &LocationOwner, id: &str) -> ApiResult<()> {
+        let owned = self
+            .cache
+            .get::<StoredLocation>(&Self::key(id))
+            .await?
+            .is_some_and(|stored| stored.owner == owner.0);
+        if !owned {
+            return Err(ApiError::not_found(format!("Location {}", id)));
+        }
+
+        self.cache.delete(&Self::key(id)).await?;
+        self.cache
+            .remove_members(&Self::owner_key(owner), &[id.to_string()])
+            .await?;
+        info!("Deleted location {}", id);
+        Ok(())
+    }
+}
@@ -1,28 +1,42 @@
 use axum::{
     middleware,
-    routing::{get, post},
+    routing::{delete, get, post},
     Extension, Router, Server,
 };
 use dotenv::dotenv;
 use shared::{
+    admin::{admin_auth_middleware, admin_router, AdminToken},
     cache::Cache,
     config::AppConfig,
-    middleware::{cors_layer, rate_limit_middleware, timeout_layer, trace_layer},
+    latency::{latency_middleware, LatencyTracker},
+    middleware::{
+        cors_layer, rate_limit_middleware, read_only_middleware, security_headers_middleware,
+        timeout_middleware, trace_layer, ReadOnlyMode, RouteTimeouts, SecurityHeaders,
+    },
     rate_limit::RateLimiter,
     ApiResult,
 };
-use std::{net::SocketAddr, sync::Arc};
+use std::{net::SocketAddr, sync::Arc, time::Duration};
 use tracing::{info, level_filters::LevelFilter};
 use tracing_subscriber::{EnvFilter, FmtSubscriber};
 
 mod calculations;
 mod handlers;
+mod hijri_cache;
+mod locations;
 mod models;
 mod preferred;
+mod registry;
 mod services;
 
-use handlers::prayer_times_handler;
+use handlers::{
+    delete_location_handler, prayer_times_handler, prayer_times_summary_handler,
+    prayer_times_today_handler, resolve_method_handler, save_location_handler,
+    saved_location_times_handler, warmup_handler,
+};
+use models::PrayerDefaults;
 use preferred::PreferredMethodMap;
+use registry::MethodRegistry;
 
 #[tokio::main]
 async fn main() -> ApiResult<()> {
@@ -58,22 +72,84 @@ async fn main() -> ApiResult<()> {
     let rate_limiter = RateLimiter::new(cache.clone(), config.rate_limit.clone());
 
     // Load preferred methods
-    let preferred_methods = Arc::new(PreferredMethodMap::load("preferred.csv")?);
-    info!("Preferred methods loaded successfully");
+    let preferred_methods = Arc::new(PreferredMethodMap::load(
+        &config.prayer_times.preferred_methods_path,
+    )?);
+
+    let method_registry = Arc::new(MethodRegistry::load(
+        &config.prayer_times.methods_path,
+    )?);
+
+    let prayer_defaults = Arc::new(PrayerDefaults::from_config(&config.prayer_times)?);
+
+    let admin_token = AdminToken::new(config.server.admin_token.clone());
+
+    // Maintenance switch; the calculation POSTs and cache warmup never write
+    // saved data, so only saving and deleting locations is refused
+    let read_only = ReadOnlyMode::new(config.server.read_only)
+        .exempt("/api/v1/prayer-times")
+        .exempt("/api/v1/prayer-times/summary")
+        .exempt("/api/v1/prayer-times/warmup");
+    if read_only.is_enabled() {
+        info!("Read-only mode enabled: write requests will be rejected");
+    }
+
+    // Warmup computes many locations in one request
+    let timeouts = RouteTimeouts::from_config(&config.server)
+        .with_override(
+            "/api/v1/prayer-times/warmup",
+            Duration::from_secs(config.server.long_timeout_secs),
+        )
+        .with_override(
+            "/health",
+            Duration::from_secs(config.server.short_timeout_secs),
+        );
+    let latency = LatencyTracker::default();
 
     // Build the application
     let app = Router::new()
         .route("/api/v1/prayer-times", post(prayer_times_handler))
+        .route("/api/v1/prayer-times/today", get(prayer_times_today_handler))
+        .route("/api/v1/prayer-times/summary", post(prayer_times_summary_handler))
+        .route("/api/v1/prayer-times/resolve", get(resolve_method_handler))
+        .route("/api/v1/prayer-times/locations", post(save_location_handler))
+        .route(
+            "/api/v1/prayer-times/locations/:id",
+            delete(delete_location_handler),
+        )
+        .route(
+            "/api/v1/prayer-times/locations/:id/times",
+            get(saved_location_times_handler),
+        )
         .route("/health", get(health_check))
+        .merge(admin_router(
+            rate_limiter.clone(),
+            latency.clone(),
+            read_only.clone(),
+            admin_token.clone(),
+        ))
+        .merge(
+            Router::new()
+                .route("/api/v1/prayer-times/warmup", post(warmup_handler))
+                .layer(middleware::from_fn_with_state(admin_token, admin_auth_middleware)),
+        )
+        .layer(middleware::from_fn_with_state(read_only, read_only_middleware))
         .layer(middleware::from_fn_with_state(
             rate_limiter.clone(),
             rate_limit_middleware,
         ))
-        .layer(timeout_layer())
-        .layer(cors_layer())
+        .layer(middleware::from_fn_with_state(timeouts, timeout_middleware))
+        .layer(middleware::from_fn_with_state(latency, latency_middleware))
+        .layer(middleware::from_fn_with_state(
+            SecurityHeaders::from_config(&config.server),
+            security_headers_middleware,
+        ))
+        .layer(cors_layer(&config.cors))
         .layer(trace_layer())
         .layer(Extension(cache))
-        .layer(Extension(preferred_methods));
+        .layer(Extension(preferred_methods))
+        .layer(Extension(method_registry))
+        .layer(Extension(prayer_defaults));
 
     // Start the server - using axum 0.6 syntax like the working zakat example
     let addr: SocketAddr = config.bind_address().parse()?;
@@ -1,8 +1,13 @@
-use chrono::{DateTime, FixedOffset, NaiveDate};
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime};
 use hijri_date::HijriDate;
 use serde::{Deserialize, Serialize};
-use shared::validation::{
-    validate_elevation, validate_latitude, validate_longitude, validate_timezone,
+use shared::{
+    config::PrayerTimesConfig,
+    error::{ApiError, ApiResult},
+    validation::{
+        validate_elevation, validate_latitude, validate_longitude, validate_not_pole,
+        validate_timezone,
+    },
 };
 use validator::{Validate, ValidationError};
 
@@ -30,6 +35,21 @@ pub enum StandardMethod {
     Jakim,   // Malaysia
 }
 
+/// A method chosen by name: a built-in one, or one an operator defined in
+/// the method registry file. Serializes as the bare name either way.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, Hash)]
+#[serde(untagged)]
+pub enum MethodName {
+    Standard(StandardMethod),
+    Registered(String),
+}
+
+impl From<StandardMethod> for MethodName {
+    fn from(method: StandardMethod) -> Self {
+        MethodName::Standard(method)
+    }
+}
+
 #[derive(Debug, Clone, Copy, Deserialize, Serialize, Hash)]
 #[serde(rename_all = "lowercase")]
 pub enum HighLatitudeRule {
@@ -52,7 +72,9 @@ pub enum Midnight {
     Jafari,
 }
 
-#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+/// Twilight the Moonsighting Committee's seasonal Isha waits for: the red
+/// glow (ahmer), the white glow (abyad) or their blend (general).
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, Hash, PartialEq)]
 #[serde(rename_all = "lowercase")]
 pub enum Shafaq {
     General,
@@ -60,19 +82,47 @@ pub enum Shafaq {
     Abyad,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, Validate)]
 pub struct CustomMethod {
+    #[validate(custom = "validate_angle_field")]
     pub fajr: Option<f64>,
+    #[validate(custom = "validate_minute_or_angle_field")]
     pub isha: Option<String>,
     pub midnight: Option<Midnight>,
+    #[validate(custom = "validate_minute_or_angle_field")]
     pub maghrib: Option<String>,
+    #[validate(custom = "validate_minute_or_angle_field")]
     pub imsak: Option<String>,
+    #[validate(custom = "validate_minute_field")]
     pub dhuhr: Option<f64>,
     pub shafaq: Option<Shafaq>,
     pub school: Option<School>,
     pub high_lat: Option<HighLatitudeRule>,
 }
 
+impl CustomMethod {
+    /// Settings with the usual defaults for anything left out: 18° Fajr and
+    /// Isha, Maghrib at sunset and Imsak 10 minutes before Fajr
+    pub fn to_method_settings(&self) -> ApiResult<MethodSettings> {
+        let parse = |value: Option<&String>, default: &str| {
+            MinuteOrAngle::parse(value.map_or(default, String::as_str))
+                .map_err(ApiError::InvalidInput)
+        };
+
+        Ok(MethodSettings {
+            fajr: self.fajr.unwrap_or(18.0),
+            isha: parse(self.isha.as_ref(), "18.0")?,
+            midnight: self.midnight.unwrap_or_default(),
+            maghrib: parse(self.maghrib.as_ref(), "0 min")?,
+            imsak: parse(self.imsak.as_ref(), "10 min")?,
+            dhuhr: self.dhuhr.unwrap_or(0.0),
+            shafaq: self.shafaq,
+            school: self.school.unwrap_or_default(),
+            high_lat: self.high_lat,
+        })
+    }
+}
+
 #[derive(Debug, Clone, Default, Deserialize, Serialize)]
 #[serde(default)]
 pub struct Adjustments {
@@ -89,7 +139,51 @@ pub struct Adjustments {
     pub last_third: i8,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+/// How a computed time is rounded to the minute it's shown as
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, Hash, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Rounding {
+    #[default]
+    Nearest,
+    /// Never earlier than the computed time
+    Up,
+    /// Never later than the computed time
+    Down,
+}
+
+impl Rounding {
+    /// Whole minutes for a time given in fractional minutes
+    pub fn apply(self, minutes: f64) -> i32 {
+        let rounded = match self {
+            Rounding::Nearest => minutes.round(),
+            Rounding::Up => minutes.ceil(),
+            Rounding::Down => minutes.floor(),
+        };
+        rounded as i32
+    }
+}
+
+/// Rounding per time, e.g. Fajr up and Maghrib down as printed mosque
+/// timetables do to err on the side of caution. Unset times round to nearest.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct RoundingPolicy {
+    pub imsak: Rounding,
+    pub fajr: Rounding,
+    pub sunrise: Rounding,
+    pub dhuhr: Rounding,
+    pub asr: Rounding,
+    pub sunset: Rounding,
+    pub maghrib: Rounding,
+    pub isha: Rounding,
+    pub midnight: Rounding,
+    pub first_third: Rounding,
+    pub last_third: Rounding,
+}
+
+/// Serialized as a single-key object, e.g. `{"daysfromtoday": 7}`. See
+/// `Timespan::parse` for the accepted shapes.
+#[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "lowercase")]
 pub enum Timespan {
     DaysFromToday(u16),
@@ -97,6 +191,10 @@ pub enum Timespan {
     Month(String, i32),        // Month name and year
     GregorianYear(i32),
     HijriYear(i32),
+    /// Hijri month name (English or Arabic, e.g. `ramadan` or `رمضان`) and year
+    HijriMonth(String, i32),
+    /// Today plus the given number of days either side of it
+    Around { days_before: u16, days_after: u16 },
 }
 
 #[derive(Debug, Deserialize, Validate)]
@@ -104,13 +202,17 @@ pub struct PrayerTimesRequest {
     #[validate(custom = "validate_latitude_field")]
     pub latitude: f64,
 
+    #[serde(deserialize_with = "shared::geo::deserialize_longitude")]
     #[validate(custom = "validate_longitude_field")]
     pub longitude: f64,
 
-    pub method: Option<StandardMethod>,
+    pub method: Option<MethodName>,
+    #[validate]
     pub custom: Option<CustomMethod>,
     pub country: Option<String>,
 
+    /// Falls back to the configured default timezone when omitted
+    #[serde(default)]
     #[validate(custom = "validate_timezone_field")]
     pub timezone: String,
 
@@ -119,19 +221,471 @@ pub struct PrayerTimesRequest {
     #[validate(custom = "validate_elevation_field")]
     pub elevation: Option<f64>,
 
+    /// Height of the visible horizon in degrees (positive when raised by
+    /// mountains or buildings). Unlike `elevation`, which lowers the horizon
+    /// seen from a height, this replaces the sunrise/sunset depression outright.
+    #[validate(range(min = -5.0, max = 10.0))]
+    pub horizon_angle: Option<f64>,
+
     pub adjustments: Option<Adjustments>,
+    /// How each time is rounded to the minute; nearest when omitted
+    pub rounding: Option<RoundingPolicy>,
+    pub high_lat: Option<HighLatitudeRule>,
+    pub school: Option<School>,
+    /// Overrides the method's shafaq; setting it switches Isha to the
+    /// seasonal twilight below 55° latitude
+    pub shafaq: Option<Shafaq>,
+
+    /// Adds a suhoor/iftar block to each day
+    #[serde(default)]
+    pub ramadan_mode: bool,
+
+    /// Comma-separated time fields to return for each day, e.g.
+    /// `fajr,dhuhr,asr,maghrib,isha`; all of them when omitted
+    pub prayers: Option<String>,
+}
+
+/// Time fields of `PrayerTimes` that `prayers` can select. The date, Hijri
+/// date and fasting block are always returned.
+pub const PRAYER_TIME_FIELDS: &[&str] = &[
+    "imsak",
+    "fajr",
+    "sunrise",
+    "dhuhr",
+    "asr",
+    "sunset",
+    "maghrib",
+    "isha",
+    "midnight",
+    "first_third",
+    "last_third",
+];
+
+impl PrayerTimesRequest {
+    /// The requested time fields, or None for all of them
+    pub fn prayer_fields(&self) -> Result<Option<Vec<&'static str>>, String> {
+        let Some(ref prayers) = self.prayers else {
+            return Ok(None);
+        };
+
+        let mut fields = Vec::new();
+        for name in prayers.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+            let field = PRAYER_TIME_FIELDS
+                .iter()
+                .find(|f| f.eq_ignore_ascii_case(name))
+                .ok_or_else(|| {
+                    format!(
+                        "Unknown prayer '{}'. Valid values: {}",
+                        name,
+                        PRAYER_TIME_FIELDS.join(", ")
+                    )
+                })?;
+            fields.push(*field);
+        }
+        if fields.is_empty() {
+            return Err("prayers must name at least one time".to_string());
+        }
+        Ok(Some(fields))
+    }
+
+    /// Fills in the configured timezone when the request left it blank
+    pub fn apply_defaults(&mut self, defaults: &PrayerDefaults) {
+        if self.timezone.trim().is_empty() {
+            if let Some(ref timezone) = defaults.timezone {
+                self.timezone = timezone.clone();
+            }
+        }
+    }
+}
+
+/// Finest cache key precision accepted; beyond it nothing is shared anyway
+const MAX_CACHE_COORDINATE_DECIMALS: u32 = 8;
+
+/// Deployment defaults for requests that omit a method or timezone, plus how
+/// precisely coordinates are keyed in the cache
+#[derive(Debug, Clone)]
+pub struct PrayerDefaults {
+    pub method: Option<StandardMethod>,
+    pub timezone: Option<String>,
+    pub cache_coordinate_decimals: u32,
+    pub max_days: u16,
+    pub max_batch_days: u32,
+}
+
+impl Default for PrayerDefaults {
+    fn default() -> Self {
+        let config = PrayerTimesConfig::default();
+        Self {
+            method: None,
+            timezone: None,
+            cache_coordinate_decimals: config.cache_coordinate_decimals,
+            max_days: config.max_days,
+            max_batch_days: config.max_batch_days,
+        }
+    }
+}
+
+impl PrayerDefaults {
+    /// Parses and checks the configured values so a typo fails at startup
+    /// rather than on the first request that relies on it.
+    pub fn from_config(config: &PrayerTimesConfig) -> ApiResult<Self> {
+        let method = config
+            .default_method
+            .as_deref()
+            .map(|name| {
+                serde_json::from_value(serde_json::Value::String(name.trim().to_lowercase()))
+                    .map_err(|_| {
+                        ApiError::InvalidInput(format!("Unknown default prayer method: {}", name))
+                    })
+            })
+            .transpose()?;
+
+        if let Some(ref timezone) = config.default_timezone {
+            validate_timezone(timezone)?;
+        }
+
+        if config.cache_coordinate_decimals > MAX_CACHE_COORDINATE_DECIMALS {
+            return Err(ApiError::InvalidInput(format!(
+                "Cache coordinate decimals must be at most {}",
+                MAX_CACHE_COORDINATE_DECIMALS
+            )));
+        }
+
+        if config.max_days == 0 || config.max_batch_days < u32::from(config.max_days) {
+            return Err(ApiError::InvalidInput(
+                "Max days must be at least 1 and no more than max batch days".to_string(),
+            ));
+        }
+
+        Ok(Self {
+            method,
+            timezone: config.default_timezone.clone(),
+            cache_coordinate_decimals: config.cache_coordinate_decimals,
+            max_days: config.max_days,
+            max_batch_days: config.max_batch_days,
+        })
+    }
+
+    /// Rejects a request for more than `max_days` days
+    pub fn check_days(&self, day_count: u16) -> ApiResult<()> {
+        if day_count > self.max_days {
+            return Err(ApiError::InvalidInput(format!(
+                "Day count cannot exceed {} days",
+                self.max_days
+            )));
+        }
+        Ok(())
+    }
+
+    /// Rejects a multi-location request whose entries add up to more than
+    /// `max_batch_days` days
+    pub fn check_batch_days(&self, total_days: u32) -> ApiResult<()> {
+        if total_days > self.max_batch_days {
+            return Err(ApiError::InvalidInput(format!(
+                "Requested {} days in total; a batch may compute at most {}",
+                total_days, self.max_batch_days
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Query parameters for `GET /api/v1/prayer-times/today`
+#[derive(Debug, Deserialize)]
+pub struct TodayQueryParams {
+    pub lat: f64,
+    #[serde(deserialize_with = "shared::geo::deserialize_longitude")]
+    pub lng: f64,
+    pub elevation: Option<f64>,
+    pub horizon_angle: Option<f64>,
+    pub method: Option<MethodName>,
+    pub country: Option<String>,
+    pub timezone: Option<String>,
     pub high_lat: Option<HighLatitudeRule>,
     pub school: Option<School>,
+    pub shafaq: Option<Shafaq>,
+}
+
+impl TodayQueryParams {
+    pub fn into_request(self) -> PrayerTimesRequest {
+        PrayerTimesRequest {
+            latitude: self.lat,
+            longitude: self.lng,
+            method: self.method,
+            custom: None,
+            country: self.country,
+            timezone: self.timezone.unwrap_or_default(),
+            timespan: Some(Timespan::DaysFromToday(1)),
+            elevation: self.elevation,
+            horizon_angle: self.horizon_angle,
+            adjustments: None,
+            rounding: None,
+            high_lat: self.high_lat,
+            school: self.school,
+            shafaq: self.shafaq,
+            ramadan_mode: false,
+            prayers: None,
+        }
+    }
+}
+
+/// Query parameters for `GET /api/v1/prayer-times/resolve`
+#[derive(Debug, Deserialize)]
+pub struct ResolveMethodQueryParams {
+    pub method: Option<MethodName>,
+    pub country: Option<String>,
+    pub high_lat: Option<HighLatitudeRule>,
+    pub school: Option<School>,
+    pub shafaq: Option<Shafaq>,
+}
+
+impl ResolveMethodQueryParams {
+    /// Location and timezone are irrelevant to method resolution and left unset
+    pub fn into_request(self) -> PrayerTimesRequest {
+        PrayerTimesRequest {
+            latitude: 0.0,
+            longitude: 0.0,
+            method: self.method,
+            custom: None,
+            country: self.country,
+            timezone: String::new(),
+            timespan: None,
+            elevation: None,
+            horizon_angle: None,
+            adjustments: None,
+            rounding: None,
+            high_lat: self.high_lat,
+            school: self.school,
+            shafaq: self.shafaq,
+            ramadan_mode: false,
+            prayers: None,
+        }
+    }
+}
+
+/// The method a request would be calculated with, without computing any times
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ResolvedMethodResponse {
+    pub method: Option<MethodName>,
+    pub method_source: MethodSource,
+    pub settings: MethodSettings,
+}
+
+/// Where the calculation method came from, so clients can explain it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MethodSource {
+    /// The request named a `method`
+    Explicit,
+    /// Looked up from the request's `country`
+    Country,
+    /// The request gave its own angles in `custom`
+    Custom,
+    /// The configured default method
+    Default,
+    /// The request named a method from the method registry file
+    Registry,
+}
+
+/// The five daily prayers, formatted as DD/MM/YYYY HH:MM
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DailyPrayers {
+    pub fajr: String,
+    pub dhuhr: String,
+    pub asr: String,
+    pub maghrib: String,
+    pub isha: String,
+}
+
+impl From<PrayerTimes> for DailyPrayers {
+    fn from(times: PrayerTimes) -> Self {
+        Self {
+            fajr: times.fajr,
+            dhuhr: times.dhuhr,
+            asr: times.asr,
+            maghrib: times.maghrib,
+            isha: times.isha,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TodayPrayerTimesResponse {
+    pub date: String,  // DD/MM/YYYY
+    pub hijri: String, // DD/MM/YYYY (Hijri)
+    pub prayers_today: DailyPrayers,
+    pub next: Option<NextPrayer>,
+}
+
+/// Most locations a single warmup request may precompute
+pub const MAX_WARMUP_LOCATIONS: usize = 100;
+
+/// Body for `POST /api/v1/prayer-times/warmup`
+#[derive(Debug, Deserialize)]
+pub struct WarmupRequest {
+    /// `/prayer-times` bodies to precompute, exactly as clients will send them
+    pub locations: Vec<PrayerTimesRequest>,
+}
+
+impl WarmupRequest {
+    pub fn check(&self) -> Result<(), String> {
+        if self.locations.is_empty() || self.locations.len() > MAX_WARMUP_LOCATIONS {
+            return Err(format!(
+                "locations must have between 1 and {} entries",
+                MAX_WARMUP_LOCATIONS
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct WarmupResponse {
+    /// Entries now in the cache, including ones that already were
+    pub warmed: usize,
+    pub failed: Vec<WarmupFailure>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WarmupFailure {
+    /// Position of the entry in `locations`
+    pub index: usize,
+    pub error: String,
+}
+
+/// Body for `POST /api/v1/prayer-times/locations`
+#[derive(Debug, Deserialize, Validate)]
+pub struct SaveLocationRequest {
+    #[validate(length(min = 1, max = 100))]
+    pub name: String,
+    #[validate(custom = "validate_latitude_field")]
+    pub lat: f64,
+    #[serde(deserialize_with = "shared::geo::deserialize_longitude")]
+    #[validate(custom = "validate_longitude_field")]
+    pub lng: f64,
+    #[validate(custom = "validate_timezone_field")]
+    pub timezone: Option<String>,
+    pub method: Option<MethodName>,
+}
+
+/// A named location a user saved, so "my cities" lists can fetch times by id
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedLocation {
+    pub id: String,
+    pub name: String,
+    pub lat: f64,
+    pub lng: f64,
+    pub timezone: Option<String>,
+    pub method: Option<MethodName>,
+    pub created_at: String,
+}
+
+impl SavedLocation {
+    /// The `/today` query this location stands for
+    pub fn to_today_params(&self) -> TodayQueryParams {
+        TodayQueryParams {
+            lat: self.lat,
+            lng: self.lng,
+            elevation: None,
+            horizon_angle: None,
+            method: self.method.clone(),
+            country: None,
+            timezone: self.timezone.clone(),
+            high_lat: None,
+            school: None,
+            shafaq: None,
+        }
+    }
+}
+
+/// Everything a client needs on first launch for one location: today's
+/// prayers, the qibla and the Hijri date
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SummaryResponse {
+    pub today: PrayerTimes,
+    pub next: Option<NextPrayer>,
+    pub qibla: QiblaSummary,
+    pub hijri: HijriSummary,
+    pub meta: MetaData,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct QiblaSummary {
+    /// Degrees clockwise from true north towards the Kaaba
+    pub direction: f64,
+}
+
+/// Today's date in the tabular Hijri calendar
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HijriSummary {
+    pub date: String, // DD/MM/YYYY
+    pub day: u32,
+    pub month: u32,
+    pub year: u32,
+}
+
+impl From<&HijriDate> for HijriSummary {
+    fn from(hijri: &HijriDate) -> Self {
+        Self {
+            date: hijri.format("%d/%m/%Y").to_string(),
+            day: hijri.day() as u32,
+            month: hijri.month() as u32,
+            year: hijri.year() as u32,
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct PrayerTimesResponse {
     pub qibla_direction: f64,
     pub next: Option<NextPrayer>,
+    /// Index of today in `prayers` when the timespan is anchored on today
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub today_index: Option<usize>,
     pub prayers: Vec<PrayerTimes>,
+    /// Why some of the times may look unusual, e.g. a high latitude rule
+    /// moved them; empty for an ordinary timetable
+    #[serde(default)]
+    pub warnings: Vec<CalculationWarning>,
     pub meta: MetaData,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CalculationWarningCode {
+    /// The method's high latitude rule replaced the astronomical time
+    HighLatitudeAdjustment,
+    /// The sun never reaches the prayer's angle that day, so the time shown
+    /// is its closest approach
+    PrayerUndefined,
+}
+
+/// Something a client may want to explain alongside the times. Repeats
+/// across a timespan are reported once, from the first day they apply.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CalculationWarning {
+    pub code: CalculationWarningCode,
+    /// Lowercase prayer name, as in `PrayerTimes`
+    pub prayer: String,
+    pub date: String, // DD/MM/YYYY
+    /// Days of the response it applies to
+    pub days: u32,
+    pub message: String,
+}
+
+/// Folds one day's warnings into a timespan's, counting repeats
+pub fn merge_warnings(warnings: &mut Vec<CalculationWarning>, day: Vec<CalculationWarning>) {
+    for warning in day {
+        match warnings
+            .iter_mut()
+            .find(|w| w.code == warning.code && w.prayer == warning.prayer)
+        {
+            Some(existing) => existing.days += 1,
+            None => warnings.push(warning),
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct NextPrayer {
     pub name: String, // Changed from &'static str to String to avoid lifetime issues
@@ -153,11 +707,46 @@ pub struct PrayerTimes {
     pub last_third: String,
     pub date: String,  // DD/MM/YYYY
     pub hijri: String, // DD/MM/YYYY (Hijri)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fasting: Option<FastingTimes>,
+}
+
+/// Removes the time fields not in `keep` from a serialized `PrayerTimes`
+pub fn project_prayer_times(day: &mut serde_json::Value, keep: &[&str]) {
+    if let Some(day) = day.as_object_mut() {
+        day.retain(|key, _| !PRAYER_TIME_FIELDS.contains(&key.as_str()) || keep.contains(&key.as_str()));
+    }
+}
+
+/// Suhoor and iftar for a fasting day, included in Ramadan mode
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FastingTimes {
+    pub suhoor_ends: String, // Imsak, DD/MM/YYYY HH:MM
+    pub iftar: String,       // Maghrib, DD/MM/YYYY HH:MM
+    pub fasting_duration_minutes: i64,
+}
+
+impl FastingTimes {
+    /// Suhoor ends at imsak, so any imsak offset configured in the method or
+    /// adjustments carries through. None when either time could not be
+    /// calculated (e.g. at extreme latitudes).
+    pub fn from_prayer_times(times: &PrayerTimes) -> Option<Self> {
+        let parse = |time: &str| NaiveDateTime::parse_from_str(time, "%d/%m/%Y %H:%M").ok();
+        let suhoor_ends = parse(&times.imsak)?;
+        let iftar = parse(&times.maghrib)?;
+
+        Some(Self {
+            suhoor_ends: times.imsak.clone(),
+            iftar: times.maghrib.clone(),
+            fasting_duration_minutes: (iftar - suhoor_ends).num_minutes(),
+        })
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct MetaData {
-    pub method: Option<StandardMethod>,
+    pub method: Option<MethodName>,
+    pub method_source: MethodSource,
     pub settings: MethodSettings,
     pub timezone: String,
     pub adjustments: Option<Adjustments>,
@@ -185,6 +774,36 @@ pub enum MinuteOrAngle {
     Angle { angle: f64 },
 }
 
+/// Twilight angles used by real methods fall well inside this range
+const MAX_ANGLE_DEGREES: f64 = 25.0;
+const MINUTES_PER_DAY: f64 = 1440.0;
+
+impl MinuteOrAngle {
+    /// Parses `"90 min"` as minutes and anything else as an angle in degrees,
+    /// rejecting angles outside 0-25° and minutes outside a day.
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let value = value.trim();
+        if value.ends_with("min") {
+            let minute_str = value.split_whitespace().next().unwrap_or("0");
+            let minute: f64 = minute_str
+                .parse()
+                .map_err(|_| format!("Invalid minute value: {}", value))?;
+            if !(0.0..=MINUTES_PER_DAY).contains(&minute) {
+                return Err(format!("Minutes must be between 0 and 1440: {}", value));
+            }
+            Ok(MinuteOrAngle::Minute { minute })
+        } else {
+            let angle: f64 = value
+                .parse()
+                .map_err(|_| format!("Invalid angle value: {}", value))?;
+            if !(0.0..=MAX_ANGLE_DEGREES).contains(&angle) {
+                return Err(format!("Angle must be between 0 and 25 degrees: {}", value));
+            }
+            Ok(MinuteOrAngle::Angle { angle })
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct Coordinates {
     pub latitude: f64,
@@ -194,7 +813,8 @@ pub struct Coordinates {
 
 // Validation functions - corrected signatures to match validator expectations
 fn validate_latitude_field(lat: f64) -> Result<(), ValidationError> {
-    validate_latitude(lat).map_err(|_| ValidationError::new("invalid_latitude"))
+    validate_latitude(lat).map_err(|_| ValidationError::new("invalid_latitude"))?;
+    validate_not_pole(lat).map_err(|_| ValidationError::new("latitude_at_pole"))
 }
 
 fn validate_longitude_field(lng: f64) -> Result<(), ValidationError> {
@@ -209,12 +829,98 @@ fn validate_timezone_field(timezone: &str) -> Result<(), ValidationError> {
     validate_timezone(timezone).map_err(|_| ValidationError::new("invalid_timezone"))
 }
 
+fn validate_angle_field(angle: f64) -> Result<(), ValidationError> {
+    if !(0.0..=MAX_ANGLE_DEGREES).contains(&angle) {
+        return Err(ValidationError::new("invalid_angle"));
+    }
+    Ok(())
+}
+
+fn validate_minute_field(minute: f64) -> Result<(), ValidationError> {
+    if !(0.0..=MINUTES_PER_DAY).contains(&minute) {
+        return Err(ValidationError::new("invalid_minute"));
+    }
+    Ok(())
+}
+
+fn validate_minute_or_angle_field(value: &str) -> Result<(), ValidationError> {
+    MinuteOrAngle::parse(value)
+        .map(|_| ())
+        .map_err(|_| ValidationError::new("invalid_minute_or_angle"))
+}
+
 impl Default for Timespan {
     fn default() -> Self {
         Timespan::DaysFromToday(1)
     }
 }
 
+/// Accepted timespan shapes, quoted in errors
+const TIMESPAN_SHAPES: &str = concat!(
+    r#"{"daysfromtoday": 7}, {"daysfromdate": ["DD/MM/YYYY", 7]}, {"month": ["january", 2024]}, "#,
+    r#"{"gregorianyear": 2024}, {"hijriyear": 1445}, {"hijrimonth": ["ramadan", 1445]} "#,
+    r#"or {"around": {"days_before": 3, "days_after": 3}}"#,
+);
+
+impl<'de> Deserialize<'de> for Timespan {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = serde_json::Value::deserialize(deserializer)?;
+        Timespan::parse(&value).map_err(serde::de::Error::custom)
+    }
+}
+
+impl Timespan {
+    /// Reads a timespan object. The key is matched ignoring case and
+    /// underscores, so `DaysFromToday` and `days_from_today` work too.
+    /// Errors list the accepted shapes.
+    fn parse(value: &serde_json::Value) -> Result<Self, String> {
+        #[derive(Deserialize)]
+        struct Around {
+            days_before: u16,
+            days_after: u16,
+        }
+
+        let invalid = |detail: String| {
+            format!("Invalid timespan: {}. Expected one of {}", detail, TIMESPAN_SHAPES)
+        };
+        let Some((key, inner)) = value
+            .as_object()
+            .filter(|object| object.len() == 1)
+            .and_then(|object| object.iter().next())
+        else {
+            return Err(invalid(format!("expected an object with a single key, got {}", value)));
+        };
+        let kind: String = key.chars().filter(|c| *c != '_').collect::<String>().to_lowercase();
+        let shape = |e: serde_json::Error| invalid(format!("'{}' {}", key, e));
+        let inner = inner.clone();
+
+        match kind.as_str() {
+            "daysfromtoday" => serde_json::from_value(inner).map(Timespan::DaysFromToday),
+            "daysfromdate" => serde_json::from_value(inner)
+                .map(|(date, days)| Timespan::DaysFromDate(date, days)),
+            "month" => serde_json::from_value(inner)
+                .map(|(month, year)| Timespan::Month(month, year)),
+            "gregorianyear" => serde_json::from_value(inner).map(Timespan::GregorianYear),
+            "hijriyear" => serde_json::from_value(inner).map(Timespan::HijriYear),
+            "hijrimonth" => serde_json::from_value(inner)
+                .map(|(month, year)| Timespan::HijriMonth(month, year)),
+            "around" => serde_json::from_value(inner)
+                .map(|Around { days_before, days_after }| Timespan::Around { days_before, days_after }),
+            _ => return Err(invalid(format!("unknown kind '{}'", key))),
+        }
+        .map_err(shape)
+    }
+
+    /// Position of today within the returned days, for spans anchored on today
+    pub fn today_index(&self) -> Option<usize> {
+        match self {
+            Timespan::DaysFromToday(_) => Some(0),
+            Timespan::Around { days_before, .. } => Some(*days_before as usize),
+            _ => None,
+        }
+    }
+}
+
 impl Default for School {
     fn default() -> Self {
         School::Standard
@@ -39,12 +39,17 @@ impl PreferredMethodMap {
                         }
                     }
                 }
-                info!("Loaded {} preferred method mappings from {}", count, path);
+                info!("Using {} preferred method mappings from {}", count, path);
             }
             Err(e) => {
-                warn!("Failed to load preferred methods file {}: {}", path, e);
                 // Use default mappings if file is not found
                 Self::load_default_mappings(&mut map);
+                warn!(
+                    "Preferred methods file {} not loaded ({}); using {} built-in mappings",
+                    path,
+                    e,
+                    map.len()
+                );
             }
         }
 
@@ -160,6 +165,29 @@ mod tests {
         assert!(map.get("unknown_country").is_err());
     }
 
+    #[test]
+    fn test_loads_overrides_from_configured_path() {
+        let path = std::env::temp_dir().join(format!("preferred-{}.csv", std::process::id()));
+        std::fs::write(&path, "country,alternative,method\nAtlantis,atl,karachi\n").unwrap();
+        let config = shared::config::PrayerTimesConfig {
+            preferred_methods_path: path.to_string_lossy().into_owned(),
+            ..Default::default()
+        };
+
+        let map = PreferredMethodMap::load(&config.preferred_methods_path);
+        std::fs::remove_file(&path).unwrap();
+
+        let map = map.unwrap();
+        assert_eq!(map.get("atlantis").unwrap(), StandardMethod::Karachi);
+        assert_eq!(map.get("ATL").unwrap(), StandardMethod::Karachi);
+        // The file replaces the built-in mappings rather than extending them
+        assert!(map.get("usa").is_err());
+        assert_eq!(
+            shared::config::PrayerTimesConfig::default().preferred_methods_path,
+            "preferred.csv"
+        );
+    }
+
     #[test]
     fn test_list_supported_countries() {
         let map = PreferredMethodMap::load("nonexistent.csv").unwrap();
@@ -0,0 +1,98 @@
+// Calculation methods defined in data rather than code. Operators list them
+// in a JSON file keyed by name, each entry shaped like a request's `custom`
+// block, and clients select them with `method` like the built-in ones:
+//
+//     { "ukmc": { "fajr": 18.0, "isha": "17", "school": "standard" } }
+
+use shared::error::{ApiError, ApiResult};
+use std::collections::HashMap;
+use tracing::{info, warn};
+use validator::Validate;
+
+use crate::models::{CustomMethod, MethodSettings, StandardMethod};
+
+#[derive(Debug, Default)]
+pub struct MethodRegistry {
+    methods: HashMap<String, MethodSettings>,
+}
+
+impl MethodRegistry {
+    /// Loads the file at `path`. Without one only the built-in methods are
+    /// available; invalid entries are skipped with a warning.
+    pub fn load(path: &str) -> ApiResult<Self> {
+        match std::fs::read_to_string(path) {
+            Ok(json) => {
+                let registry = Self::from_json(&json)?;
+                info!("Using {} registered methods from {}", registry.methods.len(), path);
+                Ok(registry)
+            }
+            Err(e) => {
+                info!("Method registry {} not loaded ({}); using built-in methods only", path, e);
+                Ok(Self::default())
+            }
+        }
+    }
+
+    pub fn from_json(json: &str) -> ApiResult<Self> {
+        let definitions: HashMap<String, CustomMethod> = serde_json::from_str(json)
+            .map_err(|e| ApiError::InvalidInput(format!("Invalid method registry: {}", e)))?;
+
+        let mut methods = HashMap::new();
+        for (name, definition) in definitions {
+            let name = name.trim().to_lowercase();
+            // Built-in names always resolve to the enum, so an entry could
+            // never be selected
+            if serde_json::from_value::<StandardMethod>(serde_json::Value::String(name.clone()))
+                .is_ok()
+            {
+                warn!("Ignoring registered method '{}': it's a built-in method", name);
+                continue;
+            }
+
+            let settings = definition
+                .validate()
+                .map_err(|e| e.to_string())
+                .and_then(|_| definition.to_method_settings().map_err(|e| e.to_string()));
+            match settings {
+                Ok(settings) => {
+                    methods.insert(name, settings);
+                }
+                Err(e) => warn!("Ignoring registered method '{}': {}", name, e),
+            }
+        }
+
+        Ok(Self { methods })
+    }
+
+    pub fn get(&self, name: &str) -> ApiResult<MethodSettings> {
+        self.methods
+            .get(&name.trim().to_lowercase())
+            .cloned()
+            .ok_or_else(|| ApiError::InvalidInput(format!("Unknown calculation method: {}", name)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::MinuteOrAngle;
+
+    #[test]
+    fn test_invalid_and_builtin_entries_are_skipped() {
+        let registry = MethodRegistry::from_json(
+            r#"{
+                "Local": { "fajr": 16.5, "isha": "75 min" },
+                "steep": { "fajr": 40.0 },
+                "mwl": { "fajr": 10.0 }
+            }"#,
+        )
+        .unwrap();
+
+        let local = registry.get("local").unwrap();
+        assert_eq!(local.fajr, 16.5);
+        assert!(matches!(local.isha, MinuteOrAngle::Minute { minute } if minute == 75.0));
+        assert!(registry.get("steep").is_err());
+        assert!(registry.get("mwl").is_err());
+        assert!(MethodRegistry::from_json("not json").is_err());
+    }
+}
@@ -1,20 +1,50 @@
-use libm::{asin, atan2, cos, sin, sqrt};
-use shared::error::ApiResult;
+use libm::{acos, asin, atan2, cos, sin, sqrt};
+use shared::{
+    error::{ApiError, ApiResult},
+    geo::{longitude_delta, ReferencePoint, SacredReference, KAABA},
+};
 
-use crate::models::{CoordinatesValidation, LocationInfo, QiblaDetailed, QiblaResponse};
+use crate::models::{
+    BearingMethod, CoordinatesValidation, DistanceUnit, LocationInfo, PathPoint, QiblaDetailed,
+    QiblaResponse,
+};
 
 const PI: f64 = std::f64::consts::PI;
+/// Distances are always great-circle distances
+const DISTANCE_FORMULA: &str = "Haversine great-circle distance";
 const EARTH_RADIUS_KM: f64 = 6371.0;
-
-// Kaaba coordinates (most precise available)
-const KAABA_LATITUDE: f64 = 21.4224779;
-const KAABA_LONGITUDE: f64 = 39.8251832;
-const KAABA_ELEVATION: f64 = 333.0; // meters above sea level
+/// The same radius in statute miles (1.609344 km) and nautical miles (1.852 km)
+const EARTH_RADIUS_MI: f64 = EARTH_RADIUS_KM / 1.609344;
+const EARTH_RADIUS_NMI: f64 = EARTH_RADIUS_KM / 1.852;
+/// Assumed horizontal error of user-supplied coordinates (typical phone GPS)
+const ASSUMED_POSITION_ERROR_KM: f64 = 0.05;
+/// Decimal places for bearings and distances unless a request asks otherwise
+const DEFAULT_BEARING_DECIMALS: u32 = 6;
+const DEFAULT_DISTANCE_DECIMALS: u32 = 2;
+pub const MAX_PRECISION: u32 = 6;
+
+/// Rotation from a building's heading to the qibla, normalized to (-180, 180]
+/// so the shorter turn is reported.
+pub fn wall_rotation(qibla_direction: f64, building_heading: f64) -> f64 {
+    let rotation = (qibla_direction - building_heading).rem_euclid(360.0);
+    if rotation > 180.0 {
+        rotation - 360.0
+    } else {
+        rotation
+    }
+}
 
 pub struct QiblaCalculator {
     latitude: f64,
     longitude: f64,
     elevation: f64,
+    /// Point that bearings and distances are computed to; the Kaaba unless overridden
+    target: &'static ReferencePoint,
+    bearing_decimals: u32,
+    distance_decimals: u32,
+    /// Unit of the primary `distance` field
+    units: DistanceUnit,
+    bearing_method: BearingMethod,
 }
 
 impl QiblaCalculator {
@@ -23,12 +53,53 @@ impl QiblaCalculator {
             latitude,
             longitude,
             elevation,
+            target: SacredReference::Kaaba.point(),
+            bearing_decimals: DEFAULT_BEARING_DECIMALS,
+            distance_decimals: DEFAULT_DISTANCE_DECIMALS,
+            units: DistanceUnit::default(),
+            bearing_method: BearingMethod::default(),
+        }
+    }
+
+    pub fn with_reference(mut self, reference: SacredReference) -> Self {
+        self.target = reference.point();
+        self
+    }
+
+    pub fn with_units(mut self, units: Option<DistanceUnit>) -> Self {
+        self.units = units.unwrap_or_default();
+        self
+    }
+
+    /// Rounds bearings and distances to `precision` decimal places (at most 6)
+    /// instead of the defaults of 6 for bearings and 2 for distances.
+    pub fn with_precision(mut self, precision: Option<u32>) -> Self {
+        if let Some(precision) = precision {
+            let precision = precision.min(MAX_PRECISION);
+            self.bearing_decimals = precision;
+            self.distance_decimals = precision;
+        }
+        self
+    }
+
+    /// Attribution for the response: the bearing formula, then the distance one
+    fn calculation_method(&self) -> String {
+        format!("{}; {}", self.bearing_method.formula(), DISTANCE_FORMULA)
+    }
+
+    fn target_location(&self) -> LocationInfo {
+        LocationInfo {
+            latitude: self.target.latitude,
+            longitude: self.target.longitude,
+            elevation: self.target.elevation,
+            description: Some(self.target.description.to_string()),
         }
     }
 
     pub fn calculate_qibla_direction(&self) -> ApiResult<QiblaResponse> {
         let qibla_direction = self.calculate_bearing_to_kaaba()?;
         let distance_km = self.calculate_distance_to_kaaba()?;
+        let distance = self.distance_to_target(self.units)?;
         
         let location = LocationInfo {
             latitude: self.latitude,
@@ -37,21 +108,19 @@ impl QiblaCalculator {
             description: self.get_location_description(),
         };
 
-        let kaaba_location = LocationInfo {
-            latitude: KAABA_LATITUDE,
-            longitude: KAABA_LONGITUDE,
-            elevation: KAABA_ELEVATION,
-            description: Some("Holy Kaaba, Masjid al-Haram, Mecca, Saudi Arabia".to_string()),
-        };
+        let kaaba_location = self.target_location();
 
         Ok(QiblaResponse {
             qibla_direction,
             qibla_direction_compass: self.degrees_to_compass(qibla_direction),
+            distance,
+            units: self.units,
             distance_km,
             location,
             kaaba_location,
-            calculation_method: "Great Circle Method (Haversine Formula)".to_string(),
-            calculation_time: chrono::Utc::now().to_rfc3339(),
+            bearing_method: self.bearing_method,
+            calculation_method: self.calculation_method(),
+            calculation_time: shared::time::now_rfc3339(),
         })
     }
 
@@ -59,7 +128,9 @@ impl QiblaCalculator {
         let qibla_direction = self.calculate_bearing_to_kaaba()?;
         let bearing_from_kaaba = self.calculate_bearing_from_kaaba()?;
         let distance_km = self.calculate_distance_to_kaaba()?;
-        let distance_miles = distance_km * 0.621371;
+        let distance_miles = self.distance_to_target(DistanceUnit::Mi)?;
+        let distance_nautical_miles = self.distance_to_target(DistanceUnit::Nmi)?;
+        let distance = self.distance_to_target(self.units)?;
         
         let location = LocationInfo {
             latitude: self.latitude,
@@ -68,37 +139,79 @@ impl QiblaCalculator {
             description: self.get_location_description(),
         };
 
-        let kaaba_location = LocationInfo {
-            latitude: KAABA_LATITUDE,
-            longitude: KAABA_LONGITUDE,
-            elevation: KAABA_ELEVATION,
-            description: Some("Holy Kaaba, Masjid al-Haram, Mecca, Saudi Arabia".to_string()),
-        };
+        let kaaba_location = self.target_location();
 
         let validation = self.validate_coordinates();
+        let angular_uncertainty = self.angular_uncertainty_degrees(distance_km);
 
         Ok(QiblaDetailed {
             qibla_direction,
             qibla_direction_compass: self.degrees_to_compass(qibla_direction),
+            distance,
+            units: self.units,
             distance_km,
             distance_miles,
+            distance_nautical_miles,
             bearing_from_kaaba,
             bearing_from_kaaba_compass: self.degrees_to_compass(bearing_from_kaaba),
             location,
             kaaba_location,
-            calculation_method: "Great Circle Method (Haversine Formula)".to_string(),
-            calculation_time: chrono::Utc::now().to_rfc3339(),
+            bearing_method: self.bearing_method,
+            calculation_method: self.calculation_method(),
+            calculation_time: shared::time::now_rfc3339(),
             coordinates_validation: validation,
+            angular_uncertainty_degrees: self.round_to_precision(angular_uncertainty, 4),
+            uncertainty_advisory: self.uncertainty_advisory(angular_uncertainty),
+            path: None,
+            wall_rotation: None,
         })
     }
 
+    /// Points along the great circle from the origin to the Kaaba, spherically
+    /// interpolated (slerp) so that they render as the true shortest path.
+    pub fn great_circle_path(&self, points: u32) -> ApiResult<Vec<PathPoint>> {
+        let to_vector = |lat: f64, lng: f64| {
+            let (lat, lng) = (self.degrees_to_radians(lat), self.degrees_to_radians(lng));
+            [cos(lat) * cos(lng), cos(lat) * sin(lng), sin(lat)]
+        };
+        let a = to_vector(self.latitude, self.longitude);
+        let b = to_vector(self.target.latitude, self.target.longitude);
+
+        let dot = (a[0] * b[0] + a[1] * b[1] + a[2] * b[2]).clamp(-1.0, 1.0);
+        let angle = acos(dot);
+        let sin_angle = sin(angle);
+        if sin_angle.abs() < 1e-12 && angle > PI / 2.0 {
+            return Err(ApiError::calculation(
+                "Every great circle through the reference point's antipode reaches it; the path is undefined",
+            ));
+        }
+
+        let steps = points.max(2) - 1;
+        Ok((0..=steps)
+            .map(|i| {
+                let f = i as f64 / steps as f64;
+                // At the Kaaba itself the path degenerates to a single point
+                let (wa, wb) = if sin_angle.abs() < 1e-12 {
+                    (1.0 - f, f)
+                } else {
+                    (
+                        sin((1.0 - f) * angle) / sin_angle,
+                        sin(f * angle) / sin_angle,
+                    )
+                };
+                let [x, y, z] = [0, 1, 2].map(|k| wa * a[k] + wb * b[k]);
+                PathPoint {
+                    latitude: self.radians_to_degrees(atan2(z, sqrt(x * x + y * y))),
+                    longitude: self.radians_to_degrees(atan2(y, x)),
+                }
+            })
+            .collect())
+    }
+
     fn calculate_bearing_to_kaaba(&self) -> ApiResult<f64> {
         let lat1 = self.degrees_to_radians(self.latitude);
-        let lon1 = self.degrees_to_radians(self.longitude);
-        let lat2 = self.degrees_to_radians(KAABA_LATITUDE);
-        let lon2 = self.degrees_to_radians(KAABA_LONGITUDE);
-
-        let dlon = lon2 - lon1;
+        let lat2 = self.degrees_to_radians(self.target.latitude);
+        let dlon = self.degrees_to_radians(longitude_delta(self.longitude, self.target.longitude));
 
         let y = sin(dlon) * cos(lat2);
         let x = cos(lat1) * sin(lat2) - sin(lat1) * cos(lat2) * cos(dlon);
@@ -113,16 +226,13 @@ impl QiblaCalculator {
             bearing_degrees
         };
 
-        Ok(self.round_to_precision(normalized, 6))
+        Ok(self.round_to_precision(normalized, self.bearing_decimals))
     }
 
     fn calculate_bearing_from_kaaba(&self) -> ApiResult<f64> {
-        let lat1 = self.degrees_to_radians(KAABA_LATITUDE);
-        let lon1 = self.degrees_to_radians(KAABA_LONGITUDE);
+        let lat1 = self.degrees_to_radians(self.target.latitude);
         let lat2 = self.degrees_to_radians(self.latitude);
-        let lon2 = self.degrees_to_radians(self.longitude);
-
-        let dlon = lon2 - lon1;
+        let dlon = self.degrees_to_radians(longitude_delta(self.target.longitude, self.longitude));
 
         let y = sin(dlon) * cos(lat2);
         let x = cos(lat1) * sin(lat2) - sin(lat1) * cos(lat2) * cos(dlon);
@@ -137,24 +247,58 @@ impl QiblaCalculator {
             bearing_degrees
         };
 
-        Ok(self.round_to_precision(normalized, 6))
+        Ok(self.round_to_precision(normalized, self.bearing_decimals))
     }
 
     fn calculate_distance_to_kaaba(&self) -> ApiResult<f64> {
+        self.distance_to_target(DistanceUnit::Km)
+    }
+
+    fn distance_to_target(&self, units: DistanceUnit) -> ApiResult<f64> {
         let lat1 = self.degrees_to_radians(self.latitude);
-        let lon1 = self.degrees_to_radians(self.longitude);
-        let lat2 = self.degrees_to_radians(KAABA_LATITUDE);
-        let lon2 = self.degrees_to_radians(KAABA_LONGITUDE);
+        let lat2 = self.degrees_to_radians(self.target.latitude);
 
         let dlat = lat2 - lat1;
-        let dlon = lon2 - lon1;
+        let dlon = self.degrees_to_radians(longitude_delta(self.longitude, self.target.longitude));
 
         let a = sin(dlat / 2.0) * sin(dlat / 2.0)
             + cos(lat1) * cos(lat2) * sin(dlon / 2.0) * sin(dlon / 2.0);
         let c = 2.0 * asin(sqrt(a));
 
-        let distance = EARTH_RADIUS_KM * c;
-        Ok(self.round_to_precision(distance, 2))
+        let radius = match units {
+            DistanceUnit::Km => EARTH_RADIUS_KM,
+            DistanceUnit::Mi => EARTH_RADIUS_MI,
+            DistanceUnit::Nmi => EARTH_RADIUS_NMI,
+        };
+        Ok(self.round_to_precision(radius * c, self.distance_decimals))
+    }
+
+    /// Worst-case bearing error if the origin is off by
+    /// `ASSUMED_POSITION_ERROR_KM` perpendicular to the line of sight.
+    fn angular_uncertainty_degrees(&self, distance_km: f64) -> f64 {
+        if distance_km <= ASSUMED_POSITION_ERROR_KM {
+            return 180.0;
+        }
+        self.radians_to_degrees(atan2(ASSUMED_POSITION_ERROR_KM, distance_km))
+    }
+
+    fn uncertainty_advisory(&self, uncertainty_degrees: f64) -> String {
+        if uncertainty_degrees < 0.1 {
+            "Direction is insensitive to normal GPS error at this distance.".to_string()
+        } else if uncertainty_degrees < 1.0 {
+            "Small GPS errors shift the direction slightly; precise coordinates are recommended."
+                .to_string()
+        } else if uncertainty_degrees < 10.0 {
+            format!(
+                "Direction may be off by about {:.0}° from GPS error alone; use precise coordinates.",
+                uncertainty_degrees.ceil()
+            )
+        } else {
+            format!(
+                "Too close to {} for a computed bearing to be reliable; face it directly if visible.",
+                self.target.description
+            )
+        }
     }
 
     fn degrees_to_radians(&self, degrees: f64) -> f64 {
@@ -195,7 +339,7 @@ impl QiblaCalculator {
         ))
     }
 
-    fn validate_coordinates(&self) -> CoordinatesValidation {
+    pub fn validate_coordinates(&self) -> CoordinatesValidation {
         let mut warnings = Vec::new();
         let mut suggestions = Vec::new();
         let is_valid = true;
@@ -221,9 +365,11 @@ impl QiblaCalculator {
 
         // Check if very close to Kaaba
         let distance = self.calculate_distance_to_kaaba().unwrap_or(0.0);
-        if distance < 1.0 {
+        if distance < 1.0 && *self.target == KAABA {
             warnings.push("You are very close to the Kaaba. Qibla direction may not be meaningful at this distance.".to_string());
             suggestions.push("If you are in Masjid al-Haram, face towards the center of the Kaaba.".to_string());
+        } else if distance < 1.0 {
+            warnings.push(format!("You are very close to {}. Direction may not be meaningful at this distance.", self.target.description));
         }
 
         CoordinatesValidation {
@@ -273,6 +419,21 @@ mod tests {
         assert!(result.qibla_direction > 50.0 && result.qibla_direction < 70.0);
         // Distance should be around 10,305-10,324 km based on real-world data
         assert!(result.distance_km > 10200.0 && result.distance_km < 10400.0);
+        assert!(shared::time::is_rfc3339_utc(&result.calculation_time));
+    }
+
+    #[test]
+    fn test_calculation_method_names_bearing_and_distance_formulas() {
+        let calculator = QiblaCalculator::new(51.5074, -0.1278, 0.0);
+        let basic = calculator.calculate_qibla_direction().unwrap();
+        let detailed = calculator.calculate_detailed_qibla().unwrap();
+
+        assert_eq!(basic.bearing_method, BearingMethod::GreatCircle);
+        assert_eq!(detailed.bearing_method, basic.bearing_method);
+        assert!(basic.calculation_method.starts_with(basic.bearing_method.formula()));
+        assert!(!basic.bearing_method.formula().contains("Haversine"));
+        assert!(basic.calculation_method.ends_with("Haversine great-circle distance"));
+        assert_eq!(detailed.calculation_method, basic.calculation_method);
     }
 
     #[test]
@@ -297,6 +458,25 @@ mod tests {
         assert!(result.distance_km > 2700.0 && result.distance_km < 2900.0);
     }
 
+    #[test]
+    fn test_date_line_neighbours_get_similar_qibla() {
+        // Suva, Fiji (east of the date line) and Apia, Samoa (west of it)
+        let fiji = QiblaCalculator::new(-18.1416, 178.4419, 0.0)
+            .calculate_qibla_direction()
+            .unwrap();
+        let samoa = QiblaCalculator::new(-13.8333, -171.7667, 0.0)
+            .calculate_qibla_direction()
+            .unwrap();
+
+        // Both face roughly west-northwest, a few degrees apart
+        for result in [&fiji, &samoa] {
+            assert!(result.qibla_direction > 270.0 && result.qibla_direction < 300.0, "{}", result.qibla_direction);
+        }
+        assert!((fiji.qibla_direction - samoa.qibla_direction).abs() < 10.0);
+        assert!(fiji.distance_km > 14500.0 && fiji.distance_km < 16000.0, "{}", fiji.distance_km);
+        assert!(samoa.distance_km > fiji.distance_km && samoa.distance_km < 17000.0, "{}", samoa.distance_km);
+    }
+
     #[test]
     fn test_distance_calculation() {
         let calculator = QiblaCalculator::new(0.0, 0.0, 0.0);
@@ -308,6 +488,103 @@ mod tests {
         assert!(distance > 4900.0 && distance < 5000.0);
     }
 
+    #[test]
+    fn test_great_circle_path_endpoints() {
+        let calculator = QiblaCalculator::new(40.7128, -74.0060, 10.0);
+        let path = calculator.great_circle_path(50).unwrap();
+
+        assert_eq!(path.len(), 50);
+        let first = path.first().unwrap();
+        let last = path.last().unwrap();
+        assert!((first.latitude - 40.7128).abs() < 1e-9);
+        assert!((first.longitude - -74.0060).abs() < 1e-9);
+        assert!((last.latitude - calculator.target.latitude).abs() < 1e-9);
+        assert!((last.longitude - calculator.target.longitude).abs() < 1e-9);
+
+        // The great circle from New York bows north over the Atlantic
+        assert!(path.iter().any(|p| p.latitude > 45.0));
+    }
+
+    #[test]
+    fn test_great_circle_path_antipode_is_rejected() {
+        let kaaba = SacredReference::Kaaba.point();
+        let calculator = QiblaCalculator::new(-kaaba.latitude, kaaba.longitude - 180.0, 0.0);
+        assert!(calculator.great_circle_path(10).is_err());
+    }
+
+    #[test]
+    fn test_reference_changes_bearing() {
+        let kaaba = QiblaCalculator::new(51.5074, -0.1278, 25.0)
+            .calculate_qibla_direction()
+            .unwrap();
+        let medina = QiblaCalculator::new(51.5074, -0.1278, 25.0)
+            .with_reference(SacredReference::MasjidNabawi)
+            .calculate_qibla_direction()
+            .unwrap();
+
+        assert!((kaaba.qibla_direction - medina.qibla_direction).abs() > 0.5);
+        assert!(medina.distance_km < kaaba.distance_km);
+        assert_eq!(medina.kaaba_location.latitude, 24.4672132);
+    }
+
+    #[test]
+    fn test_angular_uncertainty_grows_near_mecca() {
+        // A hotel near Masjid al-Haram (~2 km away) vs New York
+        let near = QiblaCalculator::new(21.4400, 39.8200, 300.0)
+            .calculate_detailed_qibla()
+            .unwrap();
+        let far = QiblaCalculator::new(40.7128, -74.0060, 10.0)
+            .calculate_detailed_qibla()
+            .unwrap();
+
+        assert!(near.angular_uncertainty_degrees > far.angular_uncertainty_degrees * 100.0);
+        assert!(far.angular_uncertainty_degrees < 0.01);
+        assert_ne!(near.uncertainty_advisory, far.uncertainty_advisory);
+    }
+
+    #[test]
+    fn test_precision_controls_bearing_decimals() {
+        let calculator = QiblaCalculator::new(40.7128, -74.0060, 0.0).with_precision(Some(1));
+        let result = calculator.calculate_detailed_qibla().unwrap();
+
+        for value in [result.qibla_direction, result.bearing_from_kaaba, result.distance_km] {
+            assert_eq!(value, (value * 10.0).round() / 10.0);
+        }
+        assert!((result.qibla_direction - 58.5).abs() < 0.1);
+
+        let whole = QiblaCalculator::new(40.7128, -74.0060, 0.0).with_precision(Some(0));
+        assert_eq!(whole.calculate_qibla_direction().unwrap().qibla_direction, 58.0);
+    }
+
+    #[test]
+    fn test_distance_units() {
+        let calculator = QiblaCalculator::new(40.7128, -74.0060, 0.0);
+        let detailed = calculator.calculate_detailed_qibla().unwrap();
+
+        assert_eq!(detailed.units, DistanceUnit::Km);
+        assert_eq!(detailed.distance, detailed.distance_km);
+        let expected_nmi = detailed.distance_km * 0.539957;
+        assert!((detailed.distance_nautical_miles - expected_nmi).abs() < 0.01);
+        let expected_mi = detailed.distance_km * 0.621371;
+        assert!((detailed.distance_miles - expected_mi).abs() < 0.01);
+
+        let basic = calculator
+            .with_units(Some(DistanceUnit::Nmi))
+            .calculate_qibla_direction()
+            .unwrap();
+        assert_eq!(basic.units, DistanceUnit::Nmi);
+        assert_eq!(basic.distance, detailed.distance_nautical_miles);
+    }
+
+    #[test]
+    fn test_wall_rotation() {
+        assert_eq!(wall_rotation(100.0, 30.0), 70.0);
+        assert_eq!(wall_rotation(10.0, 350.0), 20.0);
+        assert_eq!(wall_rotation(30.0, 300.0), 90.0);
+        assert_eq!(wall_rotation(300.0, 30.0), -90.0);
+        assert_eq!(wall_rotation(210.0, 30.0), 180.0);
+    }
+
     #[test]
     fn test_compass_directions() {
         let calculator = QiblaCalculator::new(0.0, 0.0, 0.0);
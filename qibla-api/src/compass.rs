@@ -0,0 +1,83 @@
+// A compass rose with the qibla marked, for clients that want to show a
+// direction without drawing one themselves. The arrow is drawn pointing north
+// and rotated by the bearing, so the markup stays the same apart from the
+// rotation and the label.
+
+const SIZE: f64 = 200.0;
+const CENTER: f64 = SIZE / 2.0;
+const RADIUS: f64 = 90.0;
+
+/// SVG document for a bearing in degrees clockwise from true north. `label` is
+/// printed under the rose, e.g. `NE (58.5°)`.
+pub fn render_compass_svg(bearing: f64, label: &str) -> String {
+    let mut svg = format!(
+        r##"<svg xmlns="http://www.w3.org/2000/svg" width="{size}" height="{height}" viewBox="0 0 {size} {height}">
+<circle cx="{c}" cy="{c}" r="{r}" fill="#ffffff" stroke="#333333" stroke-width="2"/>
+"##,
+        size = SIZE,
+        height = SIZE + 30.0,
+        c = CENTER,
+        r = RADIUS,
+    );
+
+    // Ticks every 30°, longer at the cardinal points
+    for degrees in (0..360).step_by(30) {
+        let length = if degrees % 90 == 0 { 12.0 } else { 6.0 };
+        svg.push_str(&format!(
+            r##"<line x1="{c}" y1="{y1}" x2="{c}" y2="{y2}" stroke="#333333" stroke-width="2" transform="rotate({degrees} {c} {c})"/>
+"##,
+            c = CENTER,
+            y1 = CENTER - RADIUS,
+            y2 = CENTER - RADIUS + length,
+        ));
+    }
+
+    for (name, x, y) in [
+        ("N", CENTER, CENTER - RADIUS + 28.0),
+        ("E", CENTER + RADIUS - 24.0, CENTER + 5.0),
+        ("S", CENTER, CENTER + RADIUS - 18.0),
+        ("W", CENTER - RADIUS + 24.0, CENTER + 5.0),
+    ] {
+        svg.push_str(&format!(
+            r##"<text x="{x}" y="{y}" font-family="sans-serif" font-size="14" text-anchor="middle" fill="#333333">{name}</text>
+"##
+        ));
+    }
+
+    svg.push_str(&format!(
+        r##"<g transform="rotate({bearing:.1} {c} {c})">
+<line x1="{c}" y1="{c}" x2="{c}" y2="{tip_base}" stroke="#0a7d3b" stroke-width="4"/>
+<polygon points="{c},{tip} {left},{tip_base} {right},{tip_base}" fill="#0a7d3b"/>
+</g>
+<circle cx="{c}" cy="{c}" r="4" fill="#333333"/>
+<text x="{c}" y="{label_y}" font-family="sans-serif" font-size="14" text-anchor="middle" fill="#333333">Qibla {label}</text>
+</svg>
+"##,
+        c = CENTER,
+        tip = CENTER - RADIUS + 16.0,
+        tip_base = CENTER - RADIUS + 34.0,
+        left = CENTER - 8.0,
+        right = CENTER + 8.0,
+        label_y = SIZE + 20.0,
+        label = escape_text(label),
+    ));
+
+    svg
+}
+
+fn escape_text(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_arrow_is_rotated_by_bearing() {
+        let svg = render_compass_svg(58.4818, "ENE (58.5°)");
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.contains(r#"<g transform="rotate(58.5 100 100)">"#));
+        assert!(svg.contains("Qibla ENE (58.5°)"));
+    }
+}
@@ -1,45 +1,92 @@
-use axum::{extract::Query, Extension, Json};
+use axum::{
+    extract::Query,
+    http::{header, HeaderValue},
+    Extension, Json,
+};
 use serde::Deserialize;
-use shared::{cache::Cache, error::ApiResult};
+use shared::{
+    cache::{Cache, CachedJson},
+    config::QiblaConfig,
+    error::ApiResult,
+    geo::{normalize_longitude, SacredReference},
+};
 use std::time::Duration;
 use tracing::{debug, info};
 use validator::Validate;
 
 use crate::{
-    calculations::QiblaCalculator,
-    models::QiblaRequest,
+    calculations::{wall_rotation, QiblaCalculator},
+    compass::render_compass_svg,
+    models::{
+        parse_coordinate, parse_coordinate_pair, Axis, CoordinateValidationResult,
+        CoordinatesValidation, DistanceUnit, QiblaDetailed, QiblaRequest, QiblaResponse,
+        ValidateCoordinatesRequest, ValidateCoordinatesResponse,
+    },
 };
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Default, Deserialize)]
 pub struct QiblaQueryParams {
-    pub lat: Option<f64>,
-    pub lng: Option<f64>,
+    /// Decimal degrees or DMS, e.g. `21.4225` or `21°25'20.9"N`
+    pub lat: Option<String>,
+    pub lng: Option<String>,
+    /// Alternative to `lat`/`lng`: a single `lat,lng` pair
+    pub coords: Option<String>,
     pub elevation: Option<f64>,
     pub detailed: Option<bool>,
+    pub path_points: Option<u32>,
+    pub reference: Option<SacredReference>,
+    pub building_heading: Option<f64>,
+    pub precision: Option<u32>,
+    pub units: Option<DistanceUnit>,
+}
+
+impl QiblaQueryParams {
+    /// Resolves the query's coordinates to decimal degrees
+    fn coordinates(&self) -> ApiResult<(f64, f64)> {
+        let parsed = match (&self.coords, &self.lat, &self.lng) {
+            (Some(coords), _, _) => parse_coordinate_pair(coords),
+            (None, Some(lat), Some(lng)) => parse_coordinate(lat, Axis::Latitude)
+                .and_then(|lat| Ok((lat, parse_coordinate(lng, Axis::Longitude)?))),
+            _ => {
+                return Err(shared::error::ApiError::InvalidInput(
+                    "Latitude and longitude are required as 'lat' and 'lng' parameters \
+                     or as 'coords=lat,lng'"
+                        .to_string(),
+                ))
+            }
+        };
+
+        parsed
+            .map(|(lat, lng)| (lat, normalize_longitude(lng)))
+            .map_err(shared::error::ApiError::InvalidInput)
+    }
 }
 
 pub async fn qibla_handler(
     Extension(cache): Extension<Cache>,
+    Extension(config): Extension<QiblaConfig>,
     query: Option<Query<QiblaQueryParams>>,
     body: Option<Json<QiblaRequest>>,
-) -> ApiResult<Json<serde_json::Value>> {
+) -> ApiResult<CachedJson> {
     // Handle both GET (query params) and POST (JSON body) requests
-    let (request, detailed) = if let Some(Json(req)) = body {
-        (req, false) // Default detailed to false for JSON body
+    let (mut request, detailed) = if let Some(Json(req)) = body {
+        // JSON bodies are basic unless a path or wall rotation is requested
+        let detailed = req.path_points.is_some() || req.building_heading.is_some();
+        (req, detailed)
     } else if let Some(Query(ref params)) = query {
-        if let (Some(lat), Some(lng)) = (params.lat, params.lng) {
-            let request = QiblaRequest {
-                latitude: lat,
-                longitude: lng,
-                elevation: params.elevation,
-            };
-            let detailed = params.detailed.unwrap_or(false);
-            (request, detailed)
-        } else {
-            return Err(shared::error::ApiError::InvalidInput(
-                "Latitude and longitude are required as 'lat' and 'lng' parameters".to_string(),
-            ));
-        }
+        let (lat, lng) = params.coordinates()?;
+        let request = QiblaRequest {
+            latitude: lat,
+            longitude: lng,
+            elevation: params.elevation,
+            path_points: params.path_points,
+            reference: params.reference,
+            building_heading: params.building_heading,
+            precision: params.precision,
+            units: params.units,
+        };
+        let detailed = params.detailed.unwrap_or(false) || params.building_heading.is_some();
+        (request, detailed)
     } else {
         return Err(shared::error::ApiError::InvalidInput(
             "Request body or query parameters required".to_string(),
@@ -56,38 +103,147 @@ pub async fn qibla_handler(
         .validate()
         .map_err(|e| shared::error::ApiError::Validation(format!("Validation failed: {}", e)))?;
 
-    // Create cache key
-    let cache_key = create_cache_key(&request, detailed);
+    // Only the detailed result is cached; basic responses are projected from
+    // it, so either kind of request fills the entry for the other. A path is
+    // only worth computing when it will be returned.
+    if !detailed {
+        request.path_points = None;
+    }
+    let cache_key = create_cache_key(&request, config.cache_coordinate_decimals);
+
+    let response = match cache.get_response(&cache_key).await {
+        Ok(Some(cached_response)) => {
+            debug!("Returning cached qibla calculation for key: {}", cache_key);
+            cached_response
+        }
+        _ => {
+            let response = calculate_detailed(&request)?;
+
+            // Cache the response for 24 hours (qibla direction doesn't change frequently)
+            if let Err(e) = cache
+                .set_response(&cache_key, &response, Some(Duration::from_secs(86400)))
+                .await
+            {
+                tracing::warn!("Failed to cache qibla response: {}", e);
+            }
+            response
+        }
+    };
 
-    // Try to get from cache first
-    if let Ok(Some(cached_response)) = cache.get::<serde_json::Value>(&cache_key).await {
-        debug!("Returning cached qibla calculation for key: {}", cache_key);
-        return Ok(Json(cached_response));
+    info!("Successfully calculated qibla direction");
+    if detailed {
+        Ok(response)
+    } else {
+        basic_response(&response)
     }
+}
 
-    // Create calculator
+/// The detailed qibla for a validated request, with its path and wall
+/// rotation when asked for
+fn calculate_detailed(request: &QiblaRequest) -> ApiResult<CachedJson> {
     let (lat, lng, elevation) = request.to_coordinates();
-    let calculator = QiblaCalculator::new(lat, lng, elevation);
+    let calculator = QiblaCalculator::new(lat, lng, elevation)
+        .with_reference(request.reference.unwrap_or_default())
+        .with_precision(request.precision)
+        .with_units(request.units);
 
-    // Calculate qibla direction
-    let response = if detailed {
-        let detailed_result = calculator.calculate_detailed_qibla()?;
-        serde_json::to_value(detailed_result)?
-    } else {
-        let basic_result = calculator.calculate_qibla_direction()?;
-        serde_json::to_value(basic_result)?
+    let mut detailed_result = calculator.calculate_detailed_qibla()?;
+    if let Some(points) = request.path_points {
+        detailed_result.path = Some(calculator.great_circle_path(points)?);
+    }
+    detailed_result.wall_rotation = request
+        .building_heading
+        .map(|heading| wall_rotation(detailed_result.qibla_direction, heading));
+    CachedJson::from_value(&detailed_result)
+}
+
+/// Projects a detailed response down to the basic fields
+fn basic_response(detailed: &CachedJson) -> ApiResult<CachedJson> {
+    let detailed: QiblaDetailed = serde_json::from_slice(detailed.as_bytes())?;
+    CachedJson::from_value(&QiblaResponse::from(detailed))
+}
+
+/// The qibla from the query's coordinates drawn on a compass rose, as an SVG
+/// image clients can show or save
+pub async fn qibla_compass_handler(
+    Query(params): Query<QiblaQueryParams>,
+) -> ApiResult<([(header::HeaderName, HeaderValue); 2], String)> {
+    let (lat, lng) = params.coordinates()?;
+    let request = QiblaRequest {
+        latitude: lat,
+        longitude: lng,
+        elevation: params.elevation,
+        path_points: None,
+        reference: params.reference,
+        building_heading: None,
+        precision: params.precision,
+        units: None,
     };
+    request
+        .validate()
+        .map_err(|e| shared::error::ApiError::Validation(format!("Validation failed: {}", e)))?;
 
-    // Cache the response for 24 hours (qibla direction doesn't change frequently)
-    if let Err(e) = cache
-        .set(&cache_key, &response, Some(Duration::from_secs(86400)))
-        .await
-    {
-        tracing::warn!("Failed to cache qibla response: {}", e);
-    }
+    let (lat, lng, elevation) = request.to_coordinates();
+    let qibla = QiblaCalculator::new(lat, lng, elevation)
+        .with_reference(request.reference.unwrap_or_default())
+        .with_precision(request.precision)
+        .calculate_qibla_direction()?;
+    debug!("Rendering compass for bearing {}", qibla.qibla_direction);
 
-    info!("Successfully calculated qibla direction");
-    Ok(Json(response))
+    let svg = render_compass_svg(qibla.qibla_direction, &qibla.qibla_direction_compass);
+    Ok((
+        [
+            (header::CONTENT_TYPE, HeaderValue::from_static("image/svg+xml")),
+            (
+                header::CONTENT_DISPOSITION,
+                HeaderValue::from_static("inline; filename=\"qibla-compass.svg\""),
+            ),
+        ],
+        svg,
+    ))
+}
+
+/// Runs the coordinate sanity checks over a batch of points, e.g. to clean a
+/// dataset of user locations. Out-of-range entries are reported, not rejected.
+pub async fn validate_coordinates_handler(
+    Json(request): Json<ValidateCoordinatesRequest>,
+) -> ApiResult<Json<ValidateCoordinatesResponse>> {
+    request
+        .validate()
+        .map_err(|e| shared::error::ApiError::Validation(format!("Validation failed: {}", e)))?;
+    info!("Validating {} coordinates", request.coordinates.len());
+
+    let results: Vec<CoordinateValidationResult> = request
+        .coordinates
+        .into_iter()
+        .map(|entry| {
+            let validation = match entry.validate() {
+                Ok(()) => QiblaCalculator::new(
+                    entry.latitude,
+                    entry.longitude,
+                    entry.elevation.unwrap_or(0.0),
+                )
+                .validate_coordinates(),
+                Err(e) => CoordinatesValidation {
+                    is_valid: false,
+                    warnings: vec![format!("Coordinates out of range: {}", e)],
+                    suggestions: vec![
+                        "Latitude must be within ±90° and longitude within ±180°.".to_string(),
+                    ],
+                },
+            };
+            CoordinateValidationResult {
+                coordinates: entry,
+                validation,
+            }
+        })
+        .collect();
+
+    let flagged = results
+        .iter()
+        .filter(|result| !result.validation.warnings.is_empty())
+        .count();
+    Ok(Json(ValidateCoordinatesResponse { results, flagged }))
 }
 
 pub async fn health_check(Extension(cache): Extension<Cache>) -> ApiResult<&'static str> {
@@ -95,63 +251,240 @@ pub async fn health_check(Extension(cache): Extension<Cache>) -> ApiResult<&'sta
     Ok("OK")
 }
 
-fn create_cache_key(request: &QiblaRequest, detailed: bool) -> String {
+/// Finest cache key precision accepted; beyond it nothing is shared anyway
+pub const MAX_CACHE_COORDINATE_DECIMALS: u32 = 8;
+
+/// Keys the detailed result a request resolves to. Basic and detailed
+/// requests for the same point share a key; `precision` and `units` change
+/// the cached numbers, so they are part of it.
+fn create_cache_key(request: &QiblaRequest, coordinate_decimals: u32) -> String {
     use std::collections::hash_map::DefaultHasher;
     use std::hash::{Hash, Hasher};
 
     let mut hasher = DefaultHasher::new();
 
     // Hash coordinates with limited precision for better cache hits
-    let lat_rounded = (request.latitude * 10000.0).round() / 10000.0;
-    let lng_rounded = (request.longitude * 10000.0).round() / 10000.0;
+    let lat_rounded = round_coordinate(request.latitude, coordinate_decimals);
+    let lng_rounded = round_coordinate(request.longitude, coordinate_decimals);
     let elevation_rounded = request.elevation.unwrap_or(0.0).round();
 
     lat_rounded.to_bits().hash(&mut hasher);
     lng_rounded.to_bits().hash(&mut hasher);
     elevation_rounded.to_bits().hash(&mut hasher);
-    detailed.hash(&mut hasher);
+    request.path_points.hash(&mut hasher);
+    request.reference.unwrap_or_default().hash(&mut hasher);
+    request.building_heading.map(f64::to_bits).hash(&mut hasher);
+    request.precision.hash(&mut hasher);
+    request.units.unwrap_or_default().hash(&mut hasher);
 
     format!("qibla:{:x}", hasher.finish())
 }
 
+fn round_coordinate(value: f64, decimals: u32) -> f64 {
+    let factor = 10f64.powi(decimals as i32);
+    // Adding zero turns -0.0 into 0.0, which hashes differently
+    (value * factor).round() / factor + 0.0
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    /// The cache, or `None` when Redis isn't reachable
+    async fn connect() -> Option<Cache> {
+        Cache::new(&shared::config::RedisConfig {
+            url: "redis://localhost:6379".to_string(),
+            pool_max_open: 10,
+            pool_max_idle: 5,
+            pool_timeout: 30,
+            pool_expire: 300,
+        })
+        .await
+        .ok()
+    }
+
     #[test]
     fn test_cache_key_generation() {
         let request = QiblaRequest {
             latitude: 40.7128,
             longitude: -74.0060,
             elevation: Some(10.0),
+            ..Default::default()
         };
 
-        let key1 = create_cache_key(&request, false);
-        let key2 = create_cache_key(&request, false);
-        let key3 = create_cache_key(&request, true);
+        let key1 = create_cache_key(&request, 4);
+        let key2 = create_cache_key(&request, 4);
+        let key3 = create_cache_key(
+            &QiblaRequest {
+                precision: Some(2),
+                ..request
+            },
+            4,
+        );
 
         assert_eq!(key1, key2);
         assert_ne!(key1, key3);
     }
 
+    #[tokio::test]
+    async fn test_longitude_wraps_and_poles_are_rejected() {
+        let uri: axum::http::Uri = "/qibla?lat=21.0&lng=190".parse().unwrap();
+        let Query(params) = Query::<QiblaQueryParams>::try_from_uri(&uri).unwrap();
+        assert_eq!(params.coordinates().unwrap(), (21.0, -170.0));
+
+        let request: QiblaRequest =
+            serde_json::from_value(serde_json::json!({ "latitude": 21.0, "longitude": 190.0 })).unwrap();
+        assert_eq!(request.longitude, -170.0);
+        assert!(request.validate().is_ok());
+
+        let uri: axum::http::Uri = "/qibla/compass?lat=90&lng=0".parse().unwrap();
+        let query = Query::<QiblaQueryParams>::try_from_uri(&uri).unwrap();
+        match qibla_compass_handler(query).await {
+            Err(shared::error::ApiError::Validation(message)) => {
+                assert!(message.contains("latitude_at_pole"), "{}", message)
+            }
+            other => panic!("expected a validation error, got {:?}", other.map(|_| ())),
+        }
+    }
+
     #[test]
     fn test_similar_coordinates_same_cache() {
         let request1 = QiblaRequest {
             latitude: 40.7128,
             longitude: -74.0060,
             elevation: Some(10.0),
+            ..Default::default()
         };
 
         let request2 = QiblaRequest {
             latitude: 40.71279, // Very slightly different
             longitude: -74.00599,
             elevation: Some(10.0),
+            ..Default::default()
         };
 
-        let key1 = create_cache_key(&request1, false);
-        let key2 = create_cache_key(&request2, false);
+        let key1 = create_cache_key(&request1, 4);
+        let key2 = create_cache_key(&request2, 4);
 
         // Should be the same due to rounding
         assert_eq!(key1, key2);
+        assert_ne!(create_cache_key(&request1, 6), create_cache_key(&request2, 6));
+    }
+
+    #[tokio::test]
+    async fn test_compass_svg_points_at_qibla() {
+        let params = QiblaQueryParams {
+            lat: Some("40.7128".to_string()),
+            lng: Some("-74.0060".to_string()),
+            ..Default::default()
+        };
+        let (headers, svg) = qibla_compass_handler(Query(params)).await.unwrap();
+
+        let bearing = QiblaCalculator::new(40.7128, -74.0060, 0.0)
+            .calculate_qibla_direction()
+            .unwrap()
+            .qibla_direction;
+        assert!(svg.contains(&format!("rotate({:.1} 100 100)", bearing)), "{}", svg);
+        assert!(svg.contains("NE (58.5°)"));
+        assert_eq!(headers[0].1, "image/svg+xml");
+    }
+
+    #[tokio::test]
+    async fn test_validate_flags_null_island_and_ocean_points() {
+        let request: ValidateCoordinatesRequest = serde_json::from_value(serde_json::json!({
+            "coordinates": [
+                { "latitude": 0.0, "longitude": 0.0 },
+                { "latitude": 30.0, "longitude": -40.0 },
+                { "latitude": 51.5074, "longitude": -0.1278 },
+                { "latitude": 95.0, "longitude": 10.0 }
+            ]
+        }))
+        .unwrap();
+
+        let Json(response) = validate_coordinates_handler(Json(request)).await.unwrap();
+        let warnings: Vec<&Vec<String>> = response
+            .results
+            .iter()
+            .map(|result| &result.validation.warnings)
+            .collect();
+
+        assert!(warnings[0].iter().any(|w| w.contains("0°N, 0°E")));
+        assert!(warnings[1].iter().any(|w| w.contains("ocean")));
+        assert!(warnings[2].is_empty());
+        assert!(!response.results[3].validation.is_valid);
+        assert_eq!(response.flagged, 3);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_query_coordinates_from_dms_and_coords() {
+        let dms = QiblaQueryParams {
+            lat: Some("21°25'20.9\"N".to_string()),
+            lng: Some("39°49'34.0\"E".to_string()),
+            ..Default::default()
+        };
+        let (lat, lng) = dms.coordinates().unwrap();
+        assert!((lat - 21.422472).abs() < 1e-6 && (lng - 39.826111).abs() < 1e-6);
+
+        let pair = QiblaQueryParams {
+            coords: Some("40.7128,-74.0060".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(pair.coordinates().unwrap(), (40.7128, -74.0060));
+
+        let malformed = QiblaQueryParams {
+            lat: Some("north-ish".to_string()),
+            lng: Some("0".to_string()),
+            ..Default::default()
+        };
+        assert!(matches!(
+            malformed.coordinates(),
+            Err(shared::error::ApiError::InvalidInput(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_basic_request_after_detailed_is_cache_hit() {
+        let Some(cache) = connect().await else {
+            return;
+        };
+        let query = |detailed: bool| {
+            let uri: axum::http::Uri = format!(
+                "/qibla?lat=-33.8688&lng=151.2093&elevation=58&precision=3&detailed={}",
+                detailed
+            )
+            .parse()
+            .unwrap();
+            Some(Query::<QiblaQueryParams>::try_from_uri(&uri).unwrap())
+        };
+        let request = QiblaRequest {
+            latitude: -33.8688,
+            longitude: 151.2093,
+            elevation: Some(58.0),
+            precision: Some(3),
+            ..Default::default()
+        };
+        let _ = cache.delete(&create_cache_key(&request, 4)).await;
+
+        let config = QiblaConfig::default();
+        let detailed = qibla_handler(
+            Extension(cache.clone()),
+            Extension(config.clone()),
+            query(true),
+            None,
+        )
+        .await
+        .unwrap();
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        let basic = qibla_handler(Extension(cache), Extension(config), query(false), None)
+            .await
+            .unwrap();
+
+        let detailed: serde_json::Value = serde_json::from_slice(detailed.as_bytes()).unwrap();
+        let basic: serde_json::Value = serde_json::from_slice(basic.as_bytes()).unwrap();
+        // A fresh calculation would carry a later timestamp
+        assert_eq!(basic["calculation_time"], detailed["calculation_time"]);
+        assert_eq!(basic["qibla_direction"], detailed["qibla_direction"]);
+        assert!(detailed.get("bearing_from_kaaba").is_some());
+        assert!(basic.get("bearing_from_kaaba").is_none());
+    }
+}
@@ -5,23 +5,31 @@ use axum::{
 };
 use dotenv::dotenv;
 use shared::{
+    admin::{admin_router, AdminToken},
     cache::Cache,
     config::AppConfig,
+    latency::{latency_middleware, LatencyTracker},
     middleware::{
-        cors_layer, rate_limit_middleware, timeout_layer, trace_layer,
+        cors_layer, rate_limit_middleware, read_only_middleware, security_headers_middleware,
+        timeout_middleware, trace_layer, ReadOnlyMode, RouteTimeouts, SecurityHeaders,
     },
     SimpleRateLimiter,
+    ApiError,
     ApiResult,
 };
-use std::net::SocketAddr;
+use std::{net::SocketAddr, time::Duration};
 use tracing::{info, level_filters::LevelFilter};
 use tracing_subscriber::{EnvFilter, FmtSubscriber};
 
 mod calculations;
+mod compass;
 mod handlers;
 mod models;
 
-use handlers::{health_check, qibla_handler};
+use handlers::{
+    health_check, qibla_compass_handler, qibla_handler, validate_coordinates_handler,
+    MAX_CACHE_COORDINATE_DECIMALS,
+};
 
 #[tokio::main]
 async fn main() -> ApiResult<()> {
@@ -47,6 +55,13 @@ async fn main() -> ApiResult<()> {
     let config = AppConfig::from_env()?;
     info!("Configuration loaded successfully");
 
+    if config.qibla.cache_coordinate_decimals > MAX_CACHE_COORDINATE_DECIMALS {
+        return Err(ApiError::InvalidInput(format!(
+            "Cache coordinate decimals must be at most {}",
+            MAX_CACHE_COORDINATE_DECIMALS
+        )));
+    }
+
     // Initialize cache (Redis)
     let cache = Cache::new(&config.redis).await?;
     info!("Cache connected successfully");
@@ -54,19 +69,48 @@ async fn main() -> ApiResult<()> {
     // Initialize rate limiter
     let rate_limiter = SimpleRateLimiter::new(cache.clone(), config.rate_limit.clone());
 
+    // Maintenance switch; every qibla POST only computes a result
+    let read_only = ReadOnlyMode::new(config.server.read_only)
+        .exempt("/api/v1/qibla")
+        .exempt("/api/v1/qibla/validate");
+    if read_only.is_enabled() {
+        info!("Read-only mode enabled: write requests will be rejected");
+    }
+
+    let timeouts = RouteTimeouts::from_config(&config.server).with_override(
+        "/health",
+        Duration::from_secs(config.server.short_timeout_secs),
+    );
+    let latency = LatencyTracker::default();
+
     // Build the application
     let app = Router::new()
         .route("/api/v1/qibla", post(qibla_handler))
         .route("/api/v1/qibla", get(qibla_handler)) // Support GET for simple queries
+        .route("/api/v1/qibla/validate", post(validate_coordinates_handler))
+        .route("/api/v1/qibla/compass.svg", get(qibla_compass_handler))
         .route("/health", get(health_check))
+        .merge(admin_router(
+            rate_limiter.clone(),
+            latency.clone(),
+            read_only.clone(),
+            AdminToken::new(config.server.admin_token.clone()),
+        ))
+        .layer(middleware::from_fn_with_state(read_only, read_only_middleware))
         .layer(middleware::from_fn_with_state(
             rate_limiter.clone(),
             rate_limit_middleware,
         ))
-        .layer(timeout_layer())
-        .layer(cors_layer())
+        .layer(middleware::from_fn_with_state(timeouts, timeout_middleware))
+        .layer(middleware::from_fn_with_state(latency, latency_middleware))
+        .layer(middleware::from_fn_with_state(
+            SecurityHeaders::from_config(&config.server),
+            security_headers_middleware,
+        ))
+        .layer(cors_layer(&config.cors))
         .layer(trace_layer())
-        .layer(Extension(cache));
+        .layer(Extension(cache))
+        .layer(Extension(config.qibla.clone()));
 
     // Start the server
     let addr: SocketAddr = config.bind_address().parse()?;
@@ -1,29 +1,83 @@
 use serde::{Deserialize, Serialize};
-use validator::Validate;
+use shared::geo::SacredReference;
+use validator::{Validate, ValidationError};
 
-#[derive(Debug, Deserialize, Validate)]
+#[derive(Debug, Default, Deserialize, Validate)]
 pub struct QiblaRequest {
-    #[validate(range(min = -90.0, max = 90.0))]
+    #[validate(range(min = -90.0, max = 90.0), custom = "validate_not_pole_field")]
     pub latitude: f64,
 
+    #[serde(deserialize_with = "shared::geo::deserialize_longitude")]
     #[validate(range(min = -180.0, max = 180.0))]
     pub longitude: f64,
 
     pub elevation: Option<f64>,
+
+    /// Number of points (endpoints included) along the great circle to the
+    /// Kaaba, capped at 500; only returned with the detailed response
+    #[validate(range(min = 2, max = 500))]
+    pub path_points: Option<u32>,
+
+    /// Point to compute the bearing to (`kaaba` by default)
+    pub reference: Option<SacredReference>,
+
+    /// Direction the building's "north" wall faces, in degrees clockwise from
+    /// true north; adds `wall_rotation` to the detailed response
+    #[validate(range(min = 0.0, max = 360.0))]
+    pub building_heading: Option<f64>,
+    /// Decimal places for bearings and distances (0-6); 6 and 2 by default
+    #[validate(range(max = 6))]
+    pub precision: Option<u32>,
+    /// Unit of the `distance` field (`km` by default)
+    pub units: Option<DistanceUnit>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DistanceUnit {
+    #[default]
+    Km,
+    Mi,
+    Nmi,
+}
+
+/// How the bearing to the reference point is computed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BearingMethod {
+    /// Initial bearing of the great circle, i.e. the shortest path
+    #[default]
+    GreatCircle,
+}
+
+impl BearingMethod {
+    /// The formula behind the bearing, for `calculation_method`
+    pub fn formula(self) -> &'static str {
+        match self {
+            BearingMethod::GreatCircle => "Great circle initial bearing (spherical trigonometry, atan2)",
+        }
+    }
 }
 
 #[derive(Debug, Serialize)]
 pub struct QiblaResponse {
     pub qibla_direction: f64,
     pub qibla_direction_compass: String,
+    /// Distance in the requested `units`
+    pub distance: f64,
+    pub units: DistanceUnit,
     pub distance_km: f64,
     pub location: LocationInfo,
+    /// The requested reference point (the Kaaba by default)
     pub kaaba_location: LocationInfo,
+    /// How `qibla_direction` was computed
+    pub bearing_method: BearingMethod,
+    /// The bearing and distance formulas used, e.g. for attribution
     pub calculation_method: String,
     pub calculation_time: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct LocationInfo {
     pub latitude: f64,
     pub longitude: f64,
@@ -31,30 +85,264 @@ pub struct LocationInfo {
     pub description: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct QiblaDetailed {
     pub qibla_direction: f64,
     pub qibla_direction_compass: String,
+    /// Distance in the requested `units`
+    pub distance: f64,
+    pub units: DistanceUnit,
     pub distance_km: f64,
     pub distance_miles: f64,
+    pub distance_nautical_miles: f64,
     pub bearing_from_kaaba: f64,
     pub bearing_from_kaaba_compass: String,
     pub location: LocationInfo,
+    /// The requested reference point (the Kaaba by default)
     pub kaaba_location: LocationInfo,
+    /// How `qibla_direction` was computed
+    pub bearing_method: BearingMethod,
+    /// The bearing and distance formulas used, e.g. for attribution
     pub calculation_method: String,
     pub calculation_time: String,
     pub coordinates_validation: CoordinatesValidation,
+    /// How far the bearing may be off given typical GPS error at this distance
+    pub angular_uncertainty_degrees: f64,
+    pub uncertainty_advisory: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<Vec<PathPoint>>,
+    /// Degrees to rotate a rectangular layout aligned with `building_heading`
+    /// so that one wall faces the qibla, in (-180, 180]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wall_rotation: Option<f64>,
 }
 
-#[derive(Debug, Serialize)]
+/// A detailed response already carries every basic field, so cached detailed
+/// results serve basic requests too
+impl From<QiblaDetailed> for QiblaResponse {
+    fn from(detailed: QiblaDetailed) -> Self {
+        QiblaResponse {
+            qibla_direction: detailed.qibla_direction,
+            qibla_direction_compass: detailed.qibla_direction_compass,
+            distance: detailed.distance,
+            units: detailed.units,
+            distance_km: detailed.distance_km,
+            location: detailed.location,
+            kaaba_location: detailed.kaaba_location,
+            bearing_method: detailed.bearing_method,
+            calculation_method: detailed.calculation_method,
+            calculation_time: detailed.calculation_time,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct PathPoint {
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
 pub struct CoordinatesValidation {
     pub is_valid: bool,
     pub warnings: Vec<String>,
     pub suggestions: Vec<String>,
 }
 
+/// Body of `POST /api/v1/qibla/validate`
+#[derive(Debug, Deserialize, Validate)]
+pub struct ValidateCoordinatesRequest {
+    #[validate(length(min = 1, max = 1000))]
+    pub coordinates: Vec<CoordinateEntry>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, Validate)]
+pub struct CoordinateEntry {
+    #[validate(range(min = -90.0, max = 90.0))]
+    pub latitude: f64,
+    #[validate(range(min = -180.0, max = 180.0))]
+    pub longitude: f64,
+    pub elevation: Option<f64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CoordinateValidationResult {
+    #[serde(flatten)]
+    pub coordinates: CoordinateEntry,
+    pub validation: CoordinatesValidation,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ValidateCoordinatesResponse {
+    pub results: Vec<CoordinateValidationResult>,
+    /// Entries with at least one warning
+    pub flagged: usize,
+}
+
+fn validate_not_pole_field(lat: f64) -> Result<(), ValidationError> {
+    shared::validation::validate_not_pole(lat).map_err(|_| ValidationError::new("latitude_at_pole"))
+}
+
 impl QiblaRequest {
     pub fn to_coordinates(&self) -> (f64, f64, f64) {
         (self.latitude, self.longitude, self.elevation.unwrap_or(0.0))
     }
-}
\ No newline at end of file
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Axis {
+    Latitude,
+    Longitude,
+}
+
+impl Axis {
+    fn name(self) -> &'static str {
+        match self {
+            Axis::Latitude => "latitude",
+            Axis::Longitude => "longitude",
+        }
+    }
+
+    fn hemisphere_sign(self, letter: char) -> Option<f64> {
+        match (self, letter) {
+            (Axis::Latitude, 'N') | (Axis::Longitude, 'E') => Some(1.0),
+            (Axis::Latitude, 'S') | (Axis::Longitude, 'W') => Some(-1.0),
+            _ => None,
+        }
+    }
+}
+
+/// Parses a coordinate given either in decimal degrees (`21.4225`, `-39.8`)
+/// or degrees/minutes/seconds as copied from map apps (`21°25'20.9"N`,
+/// `39 49 34 E`). Range checks are left to request validation.
+pub fn parse_coordinate(value: &str, axis: Axis) -> Result<f64, String> {
+    let invalid = || format!("Invalid {} '{}'", axis.name(), value);
+    let mut text = value.trim().to_uppercase();
+
+    // Optional hemisphere letter at either end
+    let mut hemisphere = None;
+    if let Some(letter) = text.chars().last().filter(|c| c.is_ascii_alphabetic()) {
+        hemisphere = Some(letter);
+        text.pop();
+    } else if let Some(letter) = text.chars().next().filter(|c| c.is_ascii_alphabetic()) {
+        hemisphere = Some(letter);
+        text.remove(0);
+    }
+    let hemisphere_sign = match hemisphere {
+        Some(letter) => Some(axis.hemisphere_sign(letter).ok_or_else(|| {
+            format!(
+                "Invalid {} '{}': hemisphere '{}' does not apply to {}",
+                axis.name(),
+                value,
+                letter,
+                axis.name()
+            )
+        })?),
+        None => None,
+    };
+
+    let normalized: String = text
+        .chars()
+        .map(|c| match c {
+            '°' | 'º' | '\'' | '"' | '′' | '″' | '’' | '”' => ' ',
+            other => other,
+        })
+        .collect();
+    let parts: Vec<&str> = normalized.split_whitespace().collect();
+    if parts.is_empty() || parts.len() > 3 {
+        return Err(invalid());
+    }
+
+    let degrees: f64 = parts[0].parse().map_err(|_| invalid())?;
+    let mut fractional = 0.0;
+    for (part, divisor) in parts[1..].iter().zip([60.0, 3600.0]) {
+        let component: f64 = part.parse().map_err(|_| invalid())?;
+        if !(0.0..60.0).contains(&component) {
+            return Err(format!(
+                "Invalid {} '{}': minutes and seconds must be between 0 and 60",
+                axis.name(),
+                value
+            ));
+        }
+        fractional += component / divisor;
+    }
+    if !degrees.is_finite() || (parts.len() > 1 && degrees.fract() != 0.0) {
+        return Err(invalid());
+    }
+
+    let negative = parts[0].starts_with('-');
+    let magnitude = degrees.abs() + fractional;
+    match hemisphere_sign {
+        Some(_) if negative => Err(format!(
+            "Invalid {} '{}': use either a minus sign or a hemisphere letter, not both",
+            axis.name(),
+            value
+        )),
+        Some(sign) => Ok(sign * magnitude),
+        None if negative => Ok(-magnitude),
+        None => Ok(magnitude),
+    }
+}
+
+/// Parses a `lat,lng` pair. DMS pairs without a comma
+/// (`21°25'20.9"N 39°49'34.0"E`) are split after the latitude's hemisphere.
+pub fn parse_coordinate_pair(value: &str) -> Result<(f64, f64), String> {
+    let trimmed = value.trim();
+    let (lat, lng) = match trimmed.split_once(',') {
+        Some(pair) => pair,
+        None => {
+            let split = trimmed
+                .find(|c: char| matches!(c.to_ascii_uppercase(), 'N' | 'S'))
+                .map(|i| i + 1)
+                .ok_or_else(|| format!("Invalid coords '{}': expected 'lat,lng'", value))?;
+            trimmed.split_at(split)
+        }
+    };
+
+    Ok((
+        parse_coordinate(lat, Axis::Latitude)?,
+        parse_coordinate(lng, Axis::Longitude)?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-6, "{} != {}", actual, expected);
+    }
+
+    #[test]
+    fn test_parse_dms_coordinate() {
+        let lat = parse_coordinate("21°25'20.9\"N", Axis::Latitude).unwrap();
+        assert_close(lat, 21.0 + 25.0 / 60.0 + 20.9 / 3600.0);
+
+        let lng = parse_coordinate("73°58'30\"W", Axis::Longitude).unwrap();
+        assert_close(lng, -(73.0 + 58.0 / 60.0 + 30.0 / 3600.0));
+
+        assert_close(parse_coordinate("-33.8688", Axis::Latitude).unwrap(), -33.8688);
+        assert_close(parse_coordinate("S 33 52 7.7", Axis::Latitude).unwrap(), -33.868806);
+    }
+
+    #[test]
+    fn test_parse_coords_pair() {
+        let (lat, lng) = parse_coordinate_pair("21.4225, 39.8262").unwrap();
+        assert_close(lat, 21.4225);
+        assert_close(lng, 39.8262);
+
+        let (lat, lng) = parse_coordinate_pair("21°25'20.9\"N 39°49'34.0\"E").unwrap();
+        assert_close(lat, 21.422472);
+        assert_close(lng, 39.826111);
+    }
+
+    #[test]
+    fn test_parse_coordinate_rejects_malformed() {
+        assert!(parse_coordinate("abc", Axis::Latitude).is_err());
+        assert!(parse_coordinate("21°75'N", Axis::Latitude).is_err());
+        assert!(parse_coordinate("21°25'E", Axis::Latitude).is_err());
+        assert!(parse_coordinate("-21°25'N", Axis::Latitude).is_err());
+        assert!(parse_coordinate("21.5°25'N", Axis::Latitude).is_err());
+        assert!(parse_coordinate_pair("21.4225").is_err());
+    }
+}
@@ -0,0 +1,325 @@
+// Operator endpoints shared by every service. They're guarded by a bearer
+// token from `SERVER__ADMIN_TOKEN`; without one configured they're disabled.
+
+use axum::{
+    extract::State,
+    http::{header, HeaderMap, Request},
+    middleware::{self, Next},
+    response::Response,
+    routing::{get, post},
+    Json, Router,
+};
+use serde::Deserialize;
+use std::sync::Arc;
+use tracing::info;
+
+use crate::{
+    error::{ApiError, ApiResult},
+    latency::{latency_stats_handler, LatencyTracker},
+    middleware::ReadOnlyMode,
+    SimpleRateLimiter,
+};
+
+/// Bearer token required by admin endpoints
+#[derive(Clone, Default)]
+pub struct AdminToken(Option<Arc<str>>);
+
+impl AdminToken {
+    pub fn new(token: Option<String>) -> Self {
+        Self(token.filter(|t| !t.trim().is_empty()).map(Arc::from))
+    }
+
+    fn matches(&self, presented: &str) -> bool {
+        let Some(ref expected) = self.0 else {
+            return false;
+        };
+        // Compare without short-circuiting so timing doesn't leak a prefix
+        expected.len() == presented.len()
+            && expected
+                .bytes()
+                .zip(presented.bytes())
+                .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+                == 0
+    }
+
+    /// Whether the request carries this token as a bearer credential. Lets
+    /// public handlers honour editor-only options for admins.
+    pub fn authorizes(&self, headers: &HeaderMap) -> bool {
+        let presented = headers
+            .get(header::AUTHORIZATION)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "))
+            .unwrap_or_default();
+        self.matches(presented.trim())
+    }
+}
+
+/// Rejects requests without the admin bearer token: 403 when admin endpoints
+/// are disabled, 401 for a missing or wrong token.
+pub async fn admin_auth_middleware<B>(
+    State(token): State<AdminToken>,
+    headers: HeaderMap,
+    req: Request<B>,
+    next: Next<B>,
+) -> Result<Response, ApiError> {
+    if token.0.is_none() {
+        return Err(ApiError::authorization("Admin endpoints are disabled"));
+    }
+
+    if !token.authorizes(&headers) {
+        return Err(ApiError::authentication("Invalid admin token"));
+    }
+
+    Ok(next.run(req).await)
+}
+
+/// Where operators toggle read-only mode; always exempt from it
+pub const READ_ONLY_ADMIN_PATH: &str = "/admin/read-only";
+
+/// Resets only touch Redis, so they stay allowed in read-only mode
+pub const RATE_LIMIT_RESET_PATH: &str = "/admin/rate-limit/reset";
+
+#[derive(Debug, Deserialize)]
+pub struct ResetRateLimitRequest {
+    /// The client identifier the rate limiter keys on, i.e. its resolved IP
+    pub identifier: String,
+}
+
+/// Clears a client's rate limit counters, in Redis and locally
+pub async fn reset_rate_limit_handler(
+    State(rate_limiter): State<SimpleRateLimiter>,
+    Json(request): Json<ResetRateLimitRequest>,
+) -> ApiResult<Json<serde_json::Value>> {
+    let identifier = request.identifier.trim();
+    if identifier.is_empty() {
+        return Err(ApiError::validation("identifier is required"));
+    }
+
+    info!("Resetting rate limit for {}", identifier);
+    rate_limiter.reset_rate_limit(identifier).await?;
+
+    Ok(Json(serde_json::json!({
+        "identifier": identifier,
+        "reset": true
+    })))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetReadOnlyRequest {
+    pub enabled: bool,
+}
+
+/// Reports whether read-only maintenance mode is on
+pub async fn read_only_status_handler(
+    State(read_only): State<ReadOnlyMode>,
+) -> Json<serde_json::Value> {
+    Json(serde_json::json!({ "read_only": read_only.is_enabled() }))
+}
+
+/// Turns read-only maintenance mode on or off without a restart
+pub async fn set_read_only_handler(
+    State(read_only): State<ReadOnlyMode>,
+    Json(request): Json<SetReadOnlyRequest>,
+) -> Json<serde_json::Value> {
+    info!("Setting read-only mode to {}", request.enabled);
+    read_only.set(request.enabled);
+
+    Json(serde_json::json!({ "read_only": read_only.is_enabled() }))
+}
+
+/// Admin routes, ready to merge into a service's router
+pub fn admin_router(
+    rate_limiter: SimpleRateLimiter,
+    latency: LatencyTracker,
+    read_only: ReadOnlyMode,
+    token: AdminToken,
+) -> Router {
+    Router::new()
+        .route(RATE_LIMIT_RESET_PATH, post(reset_rate_limit_handler))
+        .with_state(rate_limiter)
+        .merge(
+            Router::new()
+                .route("/admin/stats/latency", get(latency_stats_handler))
+                .with_state(latency),
+        )
+        .merge(
+            Router::new()
+                .route(
+                    READ_ONLY_ADMIN_PATH,
+                    get(read_only_status_handler).put(set_read_only_handler),
+                )
+                .with_state(read_only),
+        )
+        .layer(middleware::from_fn_with_state(token, admin_auth_middleware))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        cache::Cache,
+        config::{RateLimitConfig, RedisConfig},
+    };
+    use axum::{body::Body, http::StatusCode};
+    use tower::ServiceExt;
+
+    fn guarded(token: AdminToken) -> Router {
+        Router::new()
+            .route("/admin/ping", post(|| async { "pong" }))
+            .layer(middleware::from_fn_with_state(token, admin_auth_middleware))
+    }
+
+    async fn post_status(app: Router, authorization: Option<&str>) -> StatusCode {
+        let mut req = Request::builder().method("POST").uri("/admin/ping");
+        if let Some(value) = authorization {
+            req = req.header(header::AUTHORIZATION, value);
+        }
+        app.oneshot(req.body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+            .status()
+    }
+
+    #[tokio::test]
+    async fn test_admin_routes_require_configured_token() {
+        let token = AdminToken::new(Some("s3cret".to_string()));
+        assert_eq!(
+            post_status(guarded(token.clone()), None).await,
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            post_status(guarded(token.clone()), Some("Bearer wrong")).await,
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            post_status(guarded(token), Some("Bearer s3cret")).await,
+            StatusCode::OK
+        );
+
+        let disabled = AdminToken::new(None);
+        assert_eq!(
+            post_status(guarded(disabled), Some("Bearer s3cret")).await,
+            StatusCode::FORBIDDEN
+        );
+    }
+
+    #[tokio::test]
+    async fn test_reset_allows_throttled_client_again() {
+        let redis_config = RedisConfig {
+            url: "redis://localhost:6379".to_string(),
+            pool_max_open: 10,
+            pool_max_idle: 5,
+            pool_timeout: 30,
+            pool_expire: 300,
+        };
+        let Ok(cache) = Cache::new(&redis_config).await else {
+            return;
+        };
+        let limiter = SimpleRateLimiter::new(
+            cache,
+            RateLimitConfig {
+                requests_per_minute: 2,
+                burst_size: 2,
+                requests_per_day: None,
+                cleanup_interval: 60,
+                warning_threshold_percent: 10,
+                trusted_proxies: String::new(),
+            },
+        );
+        let client = "203.0.113.251";
+        limiter.reset_rate_limit(client).await.unwrap();
+
+        for _ in 0..2 {
+            assert!(limiter.check_rate_limit(client).await.unwrap());
+        }
+        assert!(!limiter.check_rate_limit(client).await.unwrap());
+
+        let app = admin_router(
+            limiter.clone(),
+            LatencyTracker::default(),
+            ReadOnlyMode::new(false),
+            AdminToken::new(Some("s3cret".to_string())),
+        );
+        let req = Request::builder()
+            .method("POST")
+            .uri("/admin/rate-limit/reset")
+            .header(header::AUTHORIZATION, "Bearer s3cret")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(format!(r#"{{"identifier": "{}"}}"#, client)))
+            .unwrap();
+        let response = app.oneshot(req).await.unwrap();
+
+        let allowed = limiter.check_rate_limit(client).await.unwrap();
+        limiter.reset_rate_limit(client).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(allowed);
+    }
+
+    #[tokio::test]
+    async fn test_read_only_toggle_through_admin_endpoint() {
+        let redis_config = RedisConfig {
+            url: "redis://localhost:6379".to_string(),
+            pool_max_open: 10,
+            pool_max_idle: 5,
+            pool_timeout: 30,
+            pool_expire: 300,
+        };
+        let Ok(cache) = Cache::new(&redis_config).await else {
+            return;
+        };
+        let limiter = SimpleRateLimiter::new(
+            cache,
+            RateLimitConfig {
+                requests_per_minute: 100,
+                burst_size: 10,
+                requests_per_day: None,
+                cleanup_interval: 60,
+                warning_threshold_percent: 10,
+                trusted_proxies: String::new(),
+            },
+        );
+
+        let read_only = ReadOnlyMode::new(false);
+        let app = Router::new()
+            .route("/items", post(|| async { "created" }))
+            .merge(admin_router(
+                limiter,
+                LatencyTracker::default(),
+                read_only.clone(),
+                AdminToken::new(Some("s3cret".to_string())),
+            ))
+            .layer(middleware::from_fn_with_state(
+                read_only.clone(),
+                crate::middleware::read_only_middleware,
+            ));
+        let set = |enabled: bool| {
+            Request::builder()
+                .method("PUT")
+                .uri(READ_ONLY_ADMIN_PATH)
+                .header(header::AUTHORIZATION, "Bearer s3cret")
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(format!(r#"{{"enabled": {}}}"#, enabled)))
+                .unwrap()
+        };
+        let create = || {
+            Request::builder()
+                .method("POST")
+                .uri("/items")
+                .body(Body::empty())
+                .unwrap()
+        };
+
+        let response = app.clone().oneshot(set(true)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(read_only.is_enabled());
+        let response = app.clone().oneshot(create()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+        // The toggle itself stays reachable so the mode can be switched off
+        let response = app.clone().oneshot(set(false)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = app.oneshot(create()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
@@ -1,11 +1,125 @@
 use anyhow::anyhow;
-use redis::{aio::ConnectionManager, cmd, AsyncCommands, Client};
-use serde::{Deserialize, Serialize};
-use std::time::Duration;
-use tracing::info;
+use axum::{
+    http::header,
+    response::{IntoResponse, Response},
+};
+use redis::{
+    aio::ConnectionManager, cmd, AsyncCommands, Client, FromRedisValue, Script, ScriptInvocation,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{
+    future::Future,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+use tracing::{info, warn};
+use uuid::Uuid;
 
 use crate::{config::RedisConfig, error::ApiResult};
 
+/// How long a caller computing a missing value holds its lock. Waiters stop
+/// waiting and compute themselves once this passes.
+const COMPUTE_LOCK_TTL: Duration = Duration::from_secs(10);
+/// How often waiters check whether the lock holder has cached the value
+const COMPUTE_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// INCRBY that sets the expiry only while the key has none: on creation, or
+/// to repair a counter left without one. A TTL of 0 sets no expiry.
+const INCREMENT_SCRIPT: &str = r#"
+local count = redis.call('INCRBY', KEYS[1], ARGV[1])
+local ttl = tonumber(ARGV[2])
+if ttl > 0 and redis.call('TTL', KEYS[1]) == -1 then
+    redis.call('EXPIRE', KEYS[1], ttl)
+end
+return count
+"#;
+
+/// DEL that only removes a lock still holding the caller's token
+const UNLOCK_SCRIPT: &str = r#"
+if redis.call('GET', KEYS[1]) == ARGV[1] then
+    return redis.call('DEL', KEYS[1])
+end
+return 0
+"#;
+
+/// Expiry for stale-while-revalidate caching. Entries are fresh until the
+/// soft TTL, then returned as stale until Redis drops them at the hard TTL.
+#[derive(Debug, Clone, Copy)]
+pub struct StalePolicy {
+    pub soft_ttl: Duration,
+    pub hard_ttl: Duration,
+}
+
+/// How [`Cache::compute_locked`] stores what it computes
+#[derive(Debug, Clone, Copy)]
+enum Expiry {
+    Ttl(Option<Duration>),
+    Stale(StalePolicy),
+}
+
+/// A compute lock taken by [`Cache::try_lock`]
+struct ComputeLock {
+    key: String,
+    token: String,
+}
+
+/// A value read with [`Cache::get_with_staleness`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cached<T> {
+    pub value: T,
+    /// Past the soft TTL; callers should serve it and revalidate
+    pub is_stale: bool,
+}
+
+/// How entries written by [`Cache::set_with_policy`] are stored
+#[derive(Serialize, Deserialize)]
+struct StaleEntry<T> {
+    value: T,
+    fresh_until_ms: u64,
+}
+
+impl<T> StaleEntry<T> {
+    fn into_cached(self, now_ms: u64) -> Cached<T> {
+        Cached {
+            is_stale: now_ms >= self.fresh_until_ms,
+            value: self.value,
+        }
+    }
+}
+
+fn unix_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+/// An already-serialized JSON response body.
+///
+/// Caching these bytes lets a cache hit skip both deserializing the cached
+/// value and re-serializing it for the response.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CachedJson(Vec<u8>);
+
+impl CachedJson {
+    pub fn from_value<T: Serialize>(value: &T) -> ApiResult<Self> {
+        Ok(CachedJson(serde_json::to_vec(value)?))
+    }
+
+    pub fn from_bytes(bytes: Vec<u8>) -> Self {
+        CachedJson(bytes)
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl IntoResponse for CachedJson {
+    fn into_response(self) -> Response {
+        ([(header::CONTENT_TYPE, "application/json")], self.0).into_response()
+    }
+}
+
 #[derive(Clone)]
 pub struct Cache {
     connection: ConnectionManager,
@@ -61,6 +175,46 @@ impl Cache {
         Ok(())
     }
 
+    pub async fn get_bytes(&self, key: &str) -> ApiResult<Option<Vec<u8>>> {
+        let mut conn = self.connection.clone();
+        let value: Option<Vec<u8>> = conn.get(key).await?;
+        Ok(value)
+    }
+
+    pub async fn set_bytes(&self, key: &str, value: &[u8], ttl: Option<Duration>) -> ApiResult<()> {
+        let mut conn = self.connection.clone();
+
+        match ttl {
+            Some(duration) => {
+                let seconds = duration
+                    .as_secs()
+                    .try_into()
+                    .map_err(|_| crate::error::ApiError::Internal(anyhow!("Duration too large")))?;
+                let _: () = conn.set_ex(key, value, seconds).await?;
+            }
+            None => {
+                let _: () = conn.set(key, value).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Fetches a cached response body stored with [`Cache::set_response`].
+    /// Entries written by [`Cache::set`] are JSON too, so they read back the same way.
+    pub async fn get_response(&self, key: &str) -> ApiResult<Option<CachedJson>> {
+        Ok(self.get_bytes(key).await?.map(CachedJson::from_bytes))
+    }
+
+    pub async fn set_response(
+        &self,
+        key: &str,
+        response: &CachedJson,
+        ttl: Option<Duration>,
+    ) -> ApiResult<()> {
+        self.set_bytes(key, response.as_bytes(), ttl).await
+    }
+
     pub async fn delete(&self, key: &str) -> ApiResult<()> {
         let mut conn = self.connection.clone();
         let _: () = conn.del(key).await?;
@@ -73,19 +227,266 @@ impl Cache {
         Ok(exists)
     }
 
-    pub async fn increment(&self, key: &str, by: i64, ttl: Option<Duration>) -> ApiResult<i64> {
+    /// Members of the Redis set at `key`; empty if it doesn't exist
+    pub async fn set_members(&self, key: &str) -> ApiResult<Vec<String>> {
+        let mut conn = self.connection.clone();
+        Ok(conn.smembers(key).await?)
+    }
+
+    /// Removes `members` from the Redis set at `key`
+    pub async fn remove_members(&self, key: &str, members: &[String]) -> ApiResult<()> {
+        if members.is_empty() {
+            return Ok(());
+        }
+        let mut conn = self.connection.clone();
+        let _: () = conn.srem(key, members).await?;
+        Ok(())
+    }
+
+    /// Sets `key` only if it does not exist yet, expiring after `ttl`.
+    /// Returns whether the key was set, so callers can act once per window.
+    pub async fn set_if_absent(&self, key: &str, ttl: Duration) -> ApiResult<bool> {
+        let mut conn = self.connection.clone();
+        let result: Option<String> = cmd("SET")
+            .arg(key)
+            .arg(1)
+            .arg("NX")
+            .arg("EX")
+            .arg(ttl.as_secs().max(1))
+            .query_async(&mut conn)
+            .await?;
+        Ok(result.is_some())
+    }
+
+    /// Stores `value` for stale-while-revalidate reads: fresh until
+    /// `policy.soft_ttl`, kept until `policy.hard_ttl`.
+    pub async fn set_with_policy<T>(
+        &self,
+        key: &str,
+        value: &T,
+        policy: StalePolicy,
+    ) -> ApiResult<()>
+    where
+        T: Serialize,
+    {
+        let entry = StaleEntry {
+            value,
+            fresh_until_ms: unix_millis() + policy.soft_ttl.as_millis() as u64,
+        };
+        self.set(key, &entry, Some(policy.hard_ttl.max(policy.soft_ttl)))
+            .await
+    }
+
+    /// Reads an entry written by [`Cache::set_with_policy`], flagging it as
+    /// stale once past its soft TTL.
+    pub async fn get_with_staleness<T>(&self, key: &str) -> ApiResult<Option<Cached<T>>>
+    where
+        T: DeserializeOwned,
+    {
+        Ok(self
+            .get::<StaleEntry<T>>(key)
+            .await?
+            .map(|entry| entry.into_cached(unix_millis())))
+    }
+
+    /// Returns the cached value for `key`, computing and caching it on a miss.
+    ///
+    /// Concurrent misses on the same key don't all compute: the first caller
+    /// takes a short-lived lock (`SET NX`) and the rest poll the cache until
+    /// its value appears. If Redis is unavailable, every caller computes.
+    pub async fn get_or_compute<T, F, Fut>(
+        &self,
+        key: &str,
+        ttl: Option<Duration>,
+        compute: F,
+    ) -> ApiResult<T>
+    where
+        T: Serialize + DeserializeOwned,
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = ApiResult<T>>,
+    {
+        if let Ok(Some(value)) = self.get(key).await {
+            return Ok(value);
+        }
+
+        self.compute_locked(key, Expiry::Ttl(ttl), compute).await
+    }
+
+    /// Like [`Cache::get_or_compute`], but stores the value with `policy`.
+    /// A stale value is returned at once while one caller recomputes it in
+    /// the background under the same lock; a miss waits as above.
+    pub async fn get_or_compute_stale<T, F, Fut>(
+        &self,
+        key: &str,
+        policy: StalePolicy,
+        compute: F,
+    ) -> ApiResult<T>
+    where
+        T: Serialize + DeserializeOwned + Send + Sync + 'static,
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = ApiResult<T>> + Send,
+    {
+        let stale = match self.get_with_staleness::<T>(key).await {
+            Ok(Some(cached)) if !cached.is_stale => return Ok(cached.value),
+            Ok(Some(cached)) => cached.value,
+            Ok(None) | Err(_) => {
+                return self
+                    .compute_locked(key, Expiry::Stale(policy), compute)
+                    .await
+            }
+        };
+
+        if let Ok(Some(lock)) = self.try_lock(key).await {
+            let cache = self.clone();
+            let key = key.to_string();
+            tokio::spawn(async move {
+                match compute().await {
+                    Ok(value) => {
+                        if let Err(e) = cache.set_with_policy(&key, &value, policy).await {
+                            warn!("Failed to cache {}: {}", key, e);
+                        }
+                    }
+                    Err(e) => warn!("Failed to revalidate {}: {}", key, e),
+                }
+                cache.unlock(&lock).await;
+            });
+        }
+
+        Ok(stale)
+    }
+
+    /// Computes `key` under its lock, or waits for whoever holds it
+    async fn compute_locked<T, F, Fut>(&self, key: &str, expiry: Expiry, compute: F) -> ApiResult<T>
+    where
+        T: Serialize + DeserializeOwned,
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = ApiResult<T>>,
+    {
+        let deadline = Instant::now() + COMPUTE_LOCK_TTL;
+        let lock = loop {
+            match self.try_lock(key).await {
+                Ok(Some(lock)) => break Some(lock),
+                Ok(None) => {}
+                Err(e) => {
+                    warn!("Failed to take compute lock for {}: {}", key, e);
+                    break None;
+                }
+            }
+
+            tokio::time::sleep(COMPUTE_POLL_INTERVAL).await;
+            if let Ok(Some(cached)) = self.read_entry(key, expiry).await {
+                return Ok(cached.value);
+            }
+            if Instant::now() >= deadline {
+                warn!("Timed out waiting for compute lock on {}", key);
+                break None;
+            }
+        };
+
+        // The previous lock holder may have cached the value just before we
+        // took the lock
+        if let Some(lock) = lock {
+            if let Ok(Some(cached)) = self.read_entry(key, expiry).await {
+                self.unlock(&lock).await;
+                return Ok(cached.value);
+            }
+            let result = self.compute_and_store(key, expiry, compute).await;
+            self.unlock(&lock).await;
+            return result;
+        }
+
+        self.compute_and_store(key, expiry, compute).await
+    }
+
+    async fn compute_and_store<T, F, Fut>(
+        &self,
+        key: &str,
+        expiry: Expiry,
+        compute: F,
+    ) -> ApiResult<T>
+    where
+        T: Serialize,
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = ApiResult<T>>,
+    {
+        let value = compute().await?;
+        let stored = match expiry {
+            Expiry::Ttl(ttl) => self.set(key, &value, ttl).await,
+            Expiry::Stale(policy) => self.set_with_policy(key, &value, policy).await,
+        };
+        if let Err(e) = stored {
+            warn!("Failed to cache {}: {}", key, e);
+        }
+        Ok(value)
+    }
+
+    async fn read_entry<T>(&self, key: &str, expiry: Expiry) -> ApiResult<Option<Cached<T>>>
+    where
+        T: DeserializeOwned,
+    {
+        match expiry {
+            Expiry::Ttl(_) => Ok(self.get(key).await?.map(|value| Cached {
+                value,
+                is_stale: false,
+            })),
+            Expiry::Stale(_) => self.get_with_staleness(key).await,
+        }
+    }
+
+    /// Takes the compute lock for `key`, holding a token unique to this
+    /// caller so it can only ever release its own lock
+    async fn try_lock(&self, key: &str) -> ApiResult<Option<ComputeLock>> {
+        let lock = ComputeLock {
+            key: format!("lock:{}", key),
+            token: Uuid::new_v4().to_string(),
+        };
         let mut conn = self.connection.clone();
-        let result: i64 = conn.incr(key, by).await?;
+        let result: Option<String> = cmd("SET")
+            .arg(&lock.key)
+            .arg(&lock.token)
+            .arg("NX")
+            .arg("EX")
+            .arg(COMPUTE_LOCK_TTL.as_secs())
+            .query_async(&mut conn)
+            .await?;
+        Ok(result.map(|_| lock))
+    }
 
-        if let Some(duration) = ttl {
-            let seconds = duration
+    /// Releases `lock` if it's still ours. A holder that outlived the TTL
+    /// must not delete the lock another caller has since taken.
+    async fn unlock(&self, lock: &ComputeLock) {
+        let script = Script::new(UNLOCK_SCRIPT);
+        let mut invocation = script.prepare_invoke();
+        invocation.key(&lock.key).arg(&lock.token);
+        if let Err(e) = self.invoke_script::<i64>(&invocation).await {
+            warn!("Failed to release {}: {}", lock.key, e);
+        }
+    }
+
+    pub async fn invoke_script<T>(&self, invocation: &ScriptInvocation<'_>) -> ApiResult<T>
+    where
+        T: FromRedisValue,
+    {
+        let mut conn = self.connection.clone();
+        Ok(invocation.invoke_async(&mut conn).await?)
+    }
+
+    /// Increments a counter, starting its TTL when the key is created. Later
+    /// increments leave the TTL alone so a fixed window isn't extended, and
+    /// the script is atomic so a counter can't be left without an expiry.
+    pub async fn increment(&self, key: &str, by: i64, ttl: Option<Duration>) -> ApiResult<i64> {
+        let seconds: i64 = match ttl {
+            Some(duration) => duration
                 .as_secs()
                 .try_into()
-                .map_err(|_| crate::error::ApiError::Internal(anyhow!("Duration too large")))?;
-            let _: () = conn.expire(key, seconds).await?;
-        }
+                .map_err(|_| crate::error::ApiError::Internal(anyhow!("Duration too large")))?,
+            None => 0,
+        };
 
-        Ok(result)
+        let script = Script::new(INCREMENT_SCRIPT);
+        let mut invocation = script.prepare_invoke();
+        invocation.key(key).arg(by).arg(seconds);
+        self.invoke_script(&invocation).await
     }
 
     pub async fn health_check(&self) -> ApiResult<()> {
@@ -101,6 +502,263 @@ mod tests {
     use super::*;
     use serde_json::json;
 
+    async fn body_bytes(response: Response) -> Vec<u8> {
+        hyper::body::to_bytes(response.into_body())
+            .await
+            .unwrap()
+            .to_vec()
+    }
+
+    #[tokio::test]
+    async fn test_cached_json_hit_matches_miss() {
+        let value = json!({"prayers": [{"fajr": "05:12"}], "qibla_direction": 118.99});
+
+        // Miss path: serialize once, store the bytes, respond with them
+        let miss = CachedJson::from_value(&value).unwrap();
+        let stored = miss.as_bytes().to_vec();
+        let miss_response = miss.into_response();
+        assert_eq!(
+            miss_response.headers()[header::CONTENT_TYPE],
+            "application/json"
+        );
+
+        // Hit path: respond with the stored bytes as-is
+        let hit_response = CachedJson::from_bytes(stored).into_response();
+        assert_eq!(
+            hit_response.headers()[header::CONTENT_TYPE],
+            "application/json"
+        );
+
+        assert_eq!(
+            body_bytes(miss_response).await,
+            body_bytes(hit_response).await
+        );
+    }
+
+    #[test]
+    fn test_entry_past_soft_ttl_is_stale() {
+        let entry = StaleEntry {
+            value: json!({"gold": 85}),
+            fresh_until_ms: 1_000,
+        };
+        let stored = serde_json::to_string(&entry).unwrap();
+        let entry: StaleEntry<serde_json::Value> = serde_json::from_str(&stored).unwrap();
+
+        let cached = entry.into_cached(1_500);
+        assert!(cached.is_stale);
+        assert_eq!(cached.value, json!({"gold": 85}));
+
+        let fresh = StaleEntry {
+            value: 1,
+            fresh_until_ms: 1_000,
+        }
+        .into_cached(999);
+        assert!(!fresh.is_stale);
+    }
+
+    #[tokio::test]
+    async fn test_stale_value_is_served_until_hard_ttl() {
+        // Skip if REDIS_URL is not set
+        let Ok(url) = std::env::var("REDIS_URL") else {
+            return;
+        };
+
+        let config = RedisConfig {
+            url,
+            pool_max_open: 10,
+            pool_max_idle: 5,
+            pool_timeout: 30,
+            pool_expire: 300,
+        };
+        let cache = Cache::new(&config).await.unwrap();
+        let key = format!("test_stale:{}", uuid::Uuid::new_v4());
+        let policy = StalePolicy {
+            soft_ttl: Duration::ZERO,
+            hard_ttl: Duration::from_secs(60),
+        };
+
+        cache
+            .set_with_policy(&key, &json!({"gold": 85}), policy)
+            .await
+            .unwrap();
+        let cached = cache.get_with_staleness::<serde_json::Value>(&key).await;
+        cache.delete(&key).await.unwrap();
+
+        let cached = cached.unwrap().expect("entry within hard TTL");
+        assert!(cached.is_stale);
+        assert_eq!(cached.value, json!({"gold": 85}));
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_misses_compute_once() {
+        // Skip if REDIS_URL is not set
+        let Ok(url) = std::env::var("REDIS_URL") else {
+            return;
+        };
+
+        let config = RedisConfig {
+            url,
+            pool_max_open: 10,
+            pool_max_idle: 5,
+            pool_timeout: 30,
+            pool_expire: 300,
+        };
+        let cache = Cache::new(&config).await.unwrap();
+        let key = format!("test_stampede:{}", uuid::Uuid::new_v4());
+        let computes = std::sync::Arc::new(std::sync::atomic::AtomicU32::new(0));
+
+        let callers = (0..10).map(|_| {
+            let cache = cache.clone();
+            let key = key.clone();
+            let computes = computes.clone();
+            tokio::spawn(async move {
+                cache
+                    .get_or_compute(&key, Some(Duration::from_secs(60)), || async {
+                        computes.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                        tokio::time::sleep(Duration::from_millis(200)).await;
+                        Ok(json!({"total": 42}))
+                    })
+                    .await
+            })
+        });
+        let results = futures::future::join_all(callers).await;
+
+        cache.delete(&key).await.unwrap();
+
+        for result in results {
+            assert_eq!(result.unwrap().unwrap(), json!({"total": 42}));
+        }
+        assert_eq!(computes.load(std::sync::atomic::Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_stale_value_is_returned_while_recomputing() {
+        // Skip if REDIS_URL is not set
+        let Ok(url) = std::env::var("REDIS_URL") else {
+            return;
+        };
+
+        let config = RedisConfig {
+            url,
+            pool_max_open: 10,
+            pool_max_idle: 5,
+            pool_timeout: 30,
+            pool_expire: 300,
+        };
+        let cache = Cache::new(&config).await.unwrap();
+        let key = format!("test_stale_compute:{}", uuid::Uuid::new_v4());
+        let policy = StalePolicy {
+            soft_ttl: Duration::from_secs(60),
+            hard_ttl: Duration::from_secs(120),
+        };
+
+        let missed = cache
+            .get_or_compute_stale(&key, policy, || async { Ok(json!({"gold": 85})) })
+            .await;
+        cache
+            .set_with_policy(
+                &key,
+                &json!({"gold": 85}),
+                StalePolicy {
+                    soft_ttl: Duration::ZERO,
+                    ..policy
+                },
+            )
+            .await
+            .unwrap();
+        let stale = cache
+            .get_or_compute_stale(&key, policy, || async { Ok(json!({"gold": 90})) })
+            .await;
+        let mut refreshed = None;
+        for _ in 0..20 {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            refreshed = cache
+                .get_with_staleness::<serde_json::Value>(&key)
+                .await
+                .unwrap();
+            if refreshed.as_ref().is_some_and(|cached| !cached.is_stale) {
+                break;
+            }
+        }
+        cache.delete(&key).await.unwrap();
+
+        assert_eq!(missed.unwrap(), json!({"gold": 85}));
+        assert_eq!(stale.unwrap(), json!({"gold": 85}));
+        assert_eq!(refreshed.unwrap().value, json!({"gold": 90}));
+    }
+
+    #[tokio::test]
+    async fn test_expired_lock_holder_cannot_release_new_lock() {
+        // Skip if REDIS_URL is not set
+        let Ok(url) = std::env::var("REDIS_URL") else {
+            return;
+        };
+
+        let config = RedisConfig {
+            url,
+            pool_max_open: 10,
+            pool_max_idle: 5,
+            pool_timeout: 30,
+            pool_expire: 300,
+        };
+        let cache = Cache::new(&config).await.unwrap();
+        let key = format!("test_lock:{}", uuid::Uuid::new_v4());
+
+        let expired = cache.try_lock(&key).await.unwrap().unwrap();
+        // The first holder's TTL runs out and another caller takes the lock
+        cache.delete(&expired.key).await.unwrap();
+        let current = cache.try_lock(&key).await.unwrap().unwrap();
+
+        cache.unlock(&expired).await;
+        let still_held = cache.exists(&current.key).await;
+        cache.unlock(&current).await;
+        let released = cache.exists(&current.key).await;
+
+        assert!(still_held.unwrap());
+        assert!(!released.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_increment_sets_ttl_only_on_first_hit() {
+        let config = RedisConfig {
+            url: "redis://localhost:6379".to_string(),
+            pool_max_open: 10,
+            pool_max_idle: 5,
+            pool_timeout: 30,
+            pool_expire: 300,
+        };
+        let Ok(cache) = Cache::new(&config).await else {
+            return;
+        };
+        let key = "test_increment_ttl";
+        cache.delete(key).await.unwrap();
+        let mut conn = cache.connection.clone();
+
+        assert_eq!(
+            cache
+                .increment(key, 1, Some(Duration::from_secs(60)))
+                .await
+                .unwrap(),
+            1
+        );
+        let ttl: i64 = conn.ttl(key).await.unwrap();
+        assert!((1..=60).contains(&ttl), "ttl {}", ttl);
+
+        // A later hit must not push the window back out to 60s
+        let _: () = conn.expire(key, 5).await.unwrap();
+        assert_eq!(
+            cache
+                .increment(key, 1, Some(Duration::from_secs(60)))
+                .await
+                .unwrap(),
+            2
+        );
+        let ttl: i64 = conn.ttl(key).await.unwrap();
+        cache.delete(key).await.unwrap();
+
+        assert!((1..=5).contains(&ttl), "ttl {}", ttl);
+    }
+
     #[tokio::test]
     async fn test_cache_operations() {
         // Skip if REDIS_URL is not set
@@ -128,6 +786,11 @@ mod tests {
             let retrieved: Option<serde_json::Value> = cache.get(test_key).await.unwrap();
             assert_eq!(retrieved, Some(test_value));
 
+            // Test raw response bytes
+            let body = CachedJson::from_value(&json!({"test": "bytes"})).unwrap();
+            assert!(cache.set_response(test_key, &body, None).await.is_ok());
+            assert_eq!(cache.get_response(test_key).await.unwrap(), Some(body));
+
             // Test delete
             assert!(cache.delete(test_key).await.is_ok());
             let retrieved: Option<serde_json::Value> = cache.get(test_key).await.unwrap();
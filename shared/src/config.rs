@@ -1,13 +1,24 @@
 use config::{Config, ConfigError, Environment};
 use serde::Deserialize;
+use std::{collections::HashMap, net::IpAddr, time::Duration};
+use tracing::warn;
+
+use crate::cache::StalePolicy;
 
 #[derive(Debug, Deserialize, Clone)]
 pub struct DatabaseConfig {
     pub url: String,
     pub max_connections: u32,
     pub min_connections: u32,
+    /// Seconds to wait for the database at startup
     pub connect_timeout: u64,
+    /// How long a request waits for a pooled connection before giving up
+    /// with a 503
+    pub acquire_timeout_ms: u64,
     pub idle_timeout: u64,
+    /// Queries run through `Database::timed` that take longer than this are
+    /// logged at WARN; 0 turns the logging off
+    pub slow_query_threshold_ms: u64,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -21,9 +32,40 @@ pub struct RedisConfig {
 
 #[derive(Debug, Deserialize, Clone)]
 pub struct RateLimitConfig {
+    /// Sustained rate; the token bucket refills at this pace.
     pub requests_per_minute: u32,
+    /// Bucket size: how many requests can arrive back to back.
     pub burst_size: u32,
+    /// Cap on requests per client per UTC day, on top of the per-minute
+    /// rate. Unset (or 0) leaves days uncapped.
+    #[serde(default)]
+    pub requests_per_day: Option<u32>,
     pub cleanup_interval: u64,
+    /// Flag responses with `X-RateLimit-Warning` once the remaining requests
+    /// drop below this percentage of the limit.
+    pub warning_threshold_percent: u32,
+    /// Comma-separated IPs of load balancers whose `X-Forwarded-For` and
+    /// `X-Real-IP` headers are believed. Empty trusts no forwarding headers.
+    #[serde(default)]
+    pub trusted_proxies: String,
+}
+
+impl RateLimitConfig {
+    /// `trusted_proxies` parsed, skipping entries that aren't IP addresses
+    pub fn trusted_proxy_ips(&self) -> Vec<IpAddr> {
+        self.trusted_proxies
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .filter_map(|entry| match entry.parse() {
+                Ok(ip) => Some(ip),
+                Err(_) => {
+                    warn!("Ignoring invalid trusted proxy address: {}", entry);
+                    None
+                }
+            })
+            .collect()
+    }
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -31,6 +73,260 @@ pub struct ServerConfig {
     pub host: String,
     pub port: u16,
     pub workers: Option<usize>,
+    /// Reject writes with a 503 while still serving reads (maintenance windows).
+    pub read_only: bool,
+    /// Default per-request timeout.
+    pub request_timeout_secs: u64,
+    /// Timeout for cheap endpoints such as health checks.
+    pub short_timeout_secs: u64,
+    /// Timeout for batch and semantic endpoints that legitimately run longer.
+    pub long_timeout_secs: u64,
+    /// Bearer token for `/admin` endpoints; they are disabled when unset.
+    pub admin_token: Option<String>,
+    /// `max-age` of the `Strict-Transport-Security` header; 0 leaves it out.
+    /// Only enable it when every client reaches the service over HTTPS.
+    pub hsts_max_age_secs: u64,
+}
+
+/// Page size limits for paginated list endpoints.
+#[derive(Debug, Deserialize, Clone)]
+pub struct PaginationConfig {
+    pub default_per_page: u32,
+    /// Larger `per_page` requests are clamped to this.
+    pub max_per_page: u32,
+}
+
+impl Default for PaginationConfig {
+    fn default() -> Self {
+        Self {
+            default_per_page: 20,
+            max_per_page: 100,
+        }
+    }
+}
+
+impl PaginationConfig {
+    /// The page size to use for a request: the default when absent, otherwise
+    /// clamped to `1..=max_per_page`.
+    pub fn per_page(&self, requested: Option<u32>) -> u32 {
+        requested
+            .unwrap_or(self.default_per_page)
+            .clamp(1, self.max_per_page.max(1))
+    }
+}
+
+/// View tracking for dua popularity.
+#[derive(Debug, Deserialize, Clone)]
+pub struct PopularityConfig {
+    /// Count views of individual duas. Off by default.
+    pub track_views: bool,
+    /// Repeat views of a dua by the same client within this window count once.
+    pub view_debounce_secs: u64,
+    /// How often `popularity_score` is recomputed from view counts.
+    pub recompute_interval_secs: u64,
+}
+
+/// CORS response headers. Origins, methods and request headers are open to
+/// everyone; these control what browsers may read and how long they keep a
+/// preflight.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct CorsConfig {
+    /// Comma-separated response headers scripts on other origins may read.
+    /// Browsers only expose the CORS-safelisted ones otherwise.
+    pub expose_headers: String,
+    /// How long browsers may cache a preflight response.
+    pub max_age_secs: u64,
+}
+
+impl Default for CorsConfig {
+    fn default() -> Self {
+        Self {
+            expose_headers: "x-ratelimit-limit,x-ratelimit-remaining,x-ratelimit-reset,\
+                             x-ratelimit-warning,x-request-id,etag,retry-after,x-debounce-ms"
+                .to_string(),
+            max_age_secs: 86400,
+        }
+    }
+}
+
+/// Dua statistics. Totals are incremented by database triggers as duas,
+/// categories, tags and bundles are written; a periodic refresh recounts
+/// them to correct drift and updates the figures the triggers can't keep
+/// (most popular category, recent additions).
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct StatsConfig {
+    /// How often `dua_stats` is recounted from the tables.
+    pub refresh_interval_secs: u64,
+}
+
+impl Default for StatsConfig {
+    fn default() -> Self {
+        Self {
+            refresh_interval_secs: 3600,
+        }
+    }
+}
+
+/// Dua media settings.
+#[derive(Debug, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct MediaConfig {
+    /// Directory media `file_path`s are relative to. WAV files inside it are
+    /// read for their duration; unset, local files are never opened.
+    pub local_root: Option<String>,
+}
+
+/// Keyword search and autocomplete settings.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct SearchConfig {
+    /// What a search with an empty or whitespace-only `q` gets back.
+    pub empty_query: EmptyQueryPolicy,
+    /// Shorter autocomplete prefixes (in characters) get no suggestions.
+    pub autocomplete_min_length: usize,
+    /// How long suggestions for a prefix are cached, in Redis and by clients.
+    pub autocomplete_cache_ttl_secs: u64,
+    /// Suggested client-side delay between keystrokes and requests.
+    pub autocomplete_debounce_ms: u64,
+    /// Larger search and autocomplete `limit`s are clamped to this.
+    pub max_limit: u32,
+}
+
+impl Default for SearchConfig {
+    fn default() -> Self {
+        Self {
+            empty_query: EmptyQueryPolicy::default(),
+            autocomplete_min_length: 2,
+            autocomplete_cache_ttl_secs: 60,
+            autocomplete_debounce_ms: 250,
+            max_limit: 100,
+        }
+    }
+}
+
+impl SearchConfig {
+    /// The number of results to return: `default` when absent, otherwise
+    /// clamped to `1..=max_limit`.
+    pub fn limit(&self, requested: Option<u32>, default: u32) -> u32 {
+        requested.unwrap_or(default).clamp(1, self.max_limit.max(1))
+    }
+}
+
+/// Response to a search query that is blank once trimmed.
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum EmptyQueryPolicy {
+    /// 200 with no results
+    #[default]
+    Empty,
+    /// 400 asking for a query
+    Reject,
+}
+
+/// Zakat API settings.
+///
+/// For Zakat al-Fitr, one sa' is a measure of volume, so its weight depends
+/// on the staple. Contemporary estimates range from about 2.03 kg (wheat, per
+/// Ibn Uthaymin's measurement) to 2.5-3 kg for lighter or bulkier staples such
+/// as dates and rice, with the Hanafi school using roughly 3.25 kg.
+#[derive(Debug, Deserialize, Clone)]
+pub struct ZakatConfig {
+    pub fitr_sa_weights_kg: HashMap<String, f64>,
+    pub fitr_default_sa_weight_kg: f64,
+    /// Cached nisab rates are served as-is for this long...
+    pub nisab_cache_soft_ttl_secs: u64,
+    /// ...then served stale while refreshing in the background until this.
+    pub nisab_cache_hard_ttl_secs: u64,
+    /// Where calculations get gold and silver prices from.
+    pub nisab_source: NisabSource,
+    /// JSON endpoint live metal prices and exchange rates are read from;
+    /// the built-in sample values are used when unset.
+    #[serde(default)]
+    pub rate_provider_url: Option<String>,
+    /// Estimated value in USD of one animal given as livestock zakat, by
+    /// animal (`sheep`, `cattle`, `camel`), for the cash alternative.
+    pub livestock_values_usd: HashMap<String, f64>,
+}
+
+/// Source of the metal prices nisab is computed from.
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum NisabSource {
+    /// The `nisab_rates` table, the same prices `/nisab` reports. Built-in
+    /// prices are used only when the table can't be read.
+    #[default]
+    Database,
+    /// The rate provider's prices (the built-in ones unless
+    /// `rate_provider_url` is set), without touching the database.
+    Builtin,
+}
+
+impl ZakatConfig {
+    pub fn nisab_cache_policy(&self) -> StalePolicy {
+        StalePolicy {
+            soft_ttl: Duration::from_secs(self.nisab_cache_soft_ttl_secs),
+            hard_ttl: Duration::from_secs(self.nisab_cache_hard_ttl_secs),
+        }
+    }
+}
+
+/// Deployment-wide fallbacks for prayer time requests that omit them.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct PrayerTimesConfig {
+    /// Standard method name (e.g. `mwl`) used when a request gives no method,
+    /// custom settings or country.
+    pub default_method: Option<String>,
+    /// Timezone (IANA name or `+HH:MM`) used when a request omits one.
+    pub default_timezone: Option<String>,
+    /// CSV of country to preferred method overrides. Relative paths resolve
+    /// against the working directory; built-in mappings are used when the
+    /// file can't be read.
+    pub preferred_methods_path: String,
+    /// JSON file of extra calculation methods, keyed by name. Only the
+    /// built-in methods are available when the file can't be read.
+    pub methods_path: String,
+    /// Decimal places coordinates are rounded to in cache keys, so nearby
+    /// requests share an entry. 4 is about 11m.
+    pub cache_coordinate_decimals: u32,
+    /// Most days a single request may compute.
+    pub max_days: u16,
+    /// Most days a multi-location request (e.g. warmup) may compute across
+    /// all of its entries.
+    pub max_batch_days: u32,
+}
+
+impl Default for PrayerTimesConfig {
+    fn default() -> Self {
+        Self {
+            default_method: None,
+            default_timezone: None,
+            preferred_methods_path: "preferred.csv".to_string(),
+            methods_path: "methods.json".to_string(),
+            cache_coordinate_decimals: 4,
+            max_days: 366,
+            max_batch_days: 3660,
+        }
+    }
+}
+
+/// Qibla response caching.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct QiblaConfig {
+    /// Decimal places coordinates are rounded to in cache keys, so nearby
+    /// requests share an entry. 4 is about 11m.
+    pub cache_coordinate_decimals: u32,
+}
+
+impl Default for QiblaConfig {
+    fn default() -> Self {
+        Self {
+            cache_coordinate_decimals: 4,
+        }
+    }
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -39,6 +335,21 @@ pub struct AppConfig {
     pub database: DatabaseConfig,
     pub redis: RedisConfig,
     pub rate_limit: RateLimitConfig,
+    pub pagination: PaginationConfig,
+    pub popularity: PopularityConfig,
+    pub zakat: ZakatConfig,
+    #[serde(default)]
+    pub prayer_times: PrayerTimesConfig,
+    #[serde(default)]
+    pub qibla: QiblaConfig,
+    #[serde(default)]
+    pub search: SearchConfig,
+    #[serde(default)]
+    pub stats: StatsConfig,
+    #[serde(default)]
+    pub media: MediaConfig,
+    #[serde(default)]
+    pub cors: CorsConfig,
     pub rust_log: Option<String>,
 }
 
@@ -47,10 +358,17 @@ impl AppConfig {
         let config = Config::builder()
             .set_default("server.host", "0.0.0.0")?
             .set_default("server.port", 3000)?
+            .set_default("server.read_only", false)?
+            .set_default("server.request_timeout_secs", 30)?
+            .set_default("server.short_timeout_secs", 5)?
+            .set_default("server.long_timeout_secs", 120)?
+            .set_default("server.hsts_max_age_secs", 0)?
             .set_default("database.max_connections", 100)?
             .set_default("database.min_connections", 5)?
             .set_default("database.connect_timeout", 30)?
+            .set_default("database.acquire_timeout_ms", 5000)?
             .set_default("database.idle_timeout", 600)?
+            .set_default("database.slow_query_threshold_ms", 500)?
             .set_default("redis.pool_max_open", 100)?
             .set_default("redis.pool_max_idle", 20)?
             .set_default("redis.pool_timeout", 30)?
@@ -58,6 +376,25 @@ impl AppConfig {
             .set_default("rate_limit.requests_per_minute", 100)?
             .set_default("rate_limit.burst_size", 10)?
             .set_default("rate_limit.cleanup_interval", 60)?
+            .set_default("rate_limit.warning_threshold_percent", 10)?
+            .set_default("rate_limit.trusted_proxies", "")?
+            .set_default("pagination.default_per_page", 20)?
+            .set_default("pagination.max_per_page", 100)?
+            .set_default("popularity.track_views", false)?
+            .set_default("popularity.view_debounce_secs", 3600)?
+            .set_default("popularity.recompute_interval_secs", 3600)?
+            .set_default("zakat.fitr_sa_weights_kg.wheat", 2.04)?
+            .set_default("zakat.fitr_sa_weights_kg.barley", 2.04)?
+            .set_default("zakat.fitr_sa_weights_kg.dates", 2.5)?
+            .set_default("zakat.fitr_sa_weights_kg.raisins", 2.5)?
+            .set_default("zakat.fitr_sa_weights_kg.rice", 3.0)?
+            .set_default("zakat.fitr_default_sa_weight_kg", 2.5)?
+            .set_default("zakat.nisab_cache_soft_ttl_secs", 3600)?
+            .set_default("zakat.nisab_cache_hard_ttl_secs", 86400)?
+            .set_default("zakat.nisab_source", "database")?
+            .set_default("zakat.livestock_values_usd.sheep", 250.0)?
+            .set_default("zakat.livestock_values_usd.cattle", 800.0)?
+            .set_default("zakat.livestock_values_usd.camel", 1500.0)?
             .set_default("rust_log", "info")?
             .add_source(Environment::default().separator("__"))
             .build()?;
@@ -1,28 +1,71 @@
 use sqlx::{postgres::PgPoolOptions, PgPool};
-use std::time::Duration;
-use tracing::info;
+use std::{
+    future::Future,
+    time::{Duration, Instant},
+};
+use tracing::{info, warn};
 
 use crate::{config::DatabaseConfig, error::ApiResult};
 
 #[derive(Clone)]
 pub struct Database {
     pub pool: PgPool,
+    /// Queries run through [`Database::timed`] slower than this are logged
+    slow_query_threshold: Option<Duration>,
 }
 
 impl Database {
     pub async fn new(config: &DatabaseConfig) -> ApiResult<Self> {
         info!("Connecting to database...");
 
+        // Requests wait at most `acquire_timeout_ms` for a connection; when the
+        // pool is exhausted they fail fast with a 503 rather than queueing
         let pool = PgPoolOptions::new()
             .max_connections(config.max_connections)
             .min_connections(config.min_connections)
-            .acquire_timeout(Duration::from_secs(config.connect_timeout))
+            .acquire_timeout(Duration::from_millis(config.acquire_timeout_ms.max(1)))
             .idle_timeout(Duration::from_secs(config.idle_timeout))
-            .connect(&config.url)
-            .await?;
+            .connect_lazy(&config.url)?;
+
+        // Startup gets the longer `connect_timeout` for the database to come up
+        let deadline = Instant::now() + Duration::from_secs(config.connect_timeout);
+        loop {
+            match pool.acquire().await {
+                Ok(_) => break,
+                Err(e) if Instant::now() >= deadline => return Err(e.into()),
+                Err(e) => {
+                    warn!("Database not reachable yet, retrying: {}", e);
+                    tokio::time::sleep(Duration::from_millis(500)).await;
+                }
+            }
+        }
 
         info!("Database connected successfully");
-        Ok(Database { pool })
+        Ok(Database {
+            pool,
+            slow_query_threshold: (config.slow_query_threshold_ms > 0)
+                .then(|| Duration::from_millis(config.slow_query_threshold_ms)),
+        })
+    }
+
+    /// Runs a query, logging it at WARN with the elapsed time when it takes
+    /// longer than the configured threshold
+    pub async fn timed<F: Future>(&self, sql: &str, query: F) -> F::Output {
+        let started = Instant::now();
+        let result = query.await;
+        let elapsed = started.elapsed();
+
+        if self
+            .slow_query_threshold
+            .is_some_and(|threshold| elapsed > threshold)
+        {
+            warn!(
+                elapsed_ms = elapsed.as_millis() as u64,
+                "Slow query: {}",
+                sql.split_whitespace().collect::<Vec<_>>().join(" ")
+            );
+        }
+        result
     }
 
     pub async fn health_check(&self) -> ApiResult<()> {
@@ -43,6 +86,13 @@ impl Database {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::error::ApiError;
+    use axum::{
+        http::{header, StatusCode},
+        response::IntoResponse,
+    };
+    use std::sync::{Arc, Mutex};
+    use tracing_subscriber::fmt::MakeWriter;
 
     #[tokio::test]
     async fn test_database_connection() {
@@ -57,7 +107,9 @@ mod tests {
             max_connections: 5,
             min_connections: 1,
             connect_timeout: 30,
+            acquire_timeout_ms: 5000,
             idle_timeout: 600,
+            slow_query_threshold_ms: 500,
         };
 
         let db = Database::new(&config).await;
@@ -67,4 +119,100 @@ mod tests {
             assert!(db.health_check().await.is_ok());
         }
     }
+
+    /// Collects formatted log output so a test can inspect it
+    #[derive(Clone, Default)]
+    struct CapturedLogs(Arc<Mutex<Vec<u8>>>);
+
+    impl std::io::Write for CapturedLogs {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl<'a> MakeWriter<'a> for CapturedLogs {
+        type Writer = Self;
+
+        fn make_writer(&'a self) -> Self::Writer {
+            self.clone()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_slow_query_is_logged() {
+        let logs = CapturedLogs::default();
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer(logs.clone())
+            .with_ansi(false)
+            .finish();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        // Never connects: the timed "queries" below don't touch the pool
+        let db = Database {
+            pool: PgPoolOptions::new()
+                .connect_lazy("postgres://localhost/unused")
+                .unwrap(),
+            slow_query_threshold: Some(Duration::from_millis(20)),
+        };
+
+        let fast = db.timed("SELECT 'fast'", async { 1 }).await;
+        let slow = db
+            .timed("SELECT\n    'slow'", async {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                2
+            })
+            .await;
+        let output = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+
+        assert_eq!((fast, slow), (1, 2));
+        assert!(output.contains("WARN"), "{}", output);
+        assert!(output.contains("Slow query: SELECT 'slow'"), "{}", output);
+        assert!(output.contains("elapsed_ms="), "{}", output);
+        assert!(!output.contains("fast"), "{}", output);
+    }
+
+    #[tokio::test]
+    async fn test_exhausted_pool_is_service_unavailable() {
+        let Ok(url) = std::env::var("DATABASE_URL") else {
+            return;
+        };
+
+        let db = Database::new(&DatabaseConfig {
+            url,
+            max_connections: 1,
+            min_connections: 0,
+            connect_timeout: 30,
+            acquire_timeout_ms: 100,
+            idle_timeout: 600,
+            slow_query_threshold_ms: 500,
+        })
+        .await
+        .unwrap();
+
+        // The only connection is busy for longer than the acquire timeout
+        let busy = db.clone();
+        let slow = tokio::spawn(async move {
+            sqlx::query("SELECT pg_sleep(0.5)")
+                .execute(&busy.pool)
+                .await
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        let error = db.health_check().await.unwrap_err();
+        slow.await.unwrap().unwrap();
+
+        assert!(matches!(
+            error,
+            ApiError::Database(sqlx::Error::PoolTimedOut)
+        ));
+        let response = error.into_response();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert!(response.headers().contains_key(header::RETRY_AFTER));
+        assert!(db.health_check().await.is_ok());
+    }
 }
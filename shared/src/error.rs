@@ -1,5 +1,5 @@
 use axum::{
-    http::StatusCode,
+    http::{header, HeaderValue, StatusCode},
     response::{IntoResponse, Response},
     Json,
 };
@@ -70,11 +70,26 @@ pub enum ApiError {
 
     #[error("Network error: {0}")]
     Network(String),
+
+    #[error("Service unavailable: {0}")]
+    ServiceUnavailable(String),
 }
 
+/// Seconds clients are asked to wait when the connection pool is exhausted
+const POOL_RETRY_AFTER_SECS: u64 = 1;
+
 impl IntoResponse for ApiError {
     fn into_response(self) -> Response {
+        let retry_after = self.retry_after();
+        let code = self.code();
         let (status, message) = match self {
+            ApiError::Database(sqlx::Error::PoolTimedOut | sqlx::Error::PoolClosed) => {
+                tracing::warn!("Database connection pool exhausted");
+                (
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    "Service busy, please retry",
+                )
+            }
             ApiError::Database(ref e) => {
                 tracing::error!("Database error: {}", e);
                 (StatusCode::INTERNAL_SERVER_ERROR, "Internal server error")
@@ -126,14 +141,24 @@ impl IntoResponse for ApiError {
             ApiError::Authentication(ref msg) => (StatusCode::UNAUTHORIZED, msg.as_str()),
             ApiError::Authorization(ref msg) => (StatusCode::FORBIDDEN, msg.as_str()),
             ApiError::Network(ref msg) => (StatusCode::BAD_GATEWAY, msg.as_str()),
+            ApiError::ServiceUnavailable(ref msg) => {
+                (StatusCode::SERVICE_UNAVAILABLE, msg.as_str())
+            }
         };
 
         let body = Json(json!({
             "error": message,
+            "code": code,
             "status": status.as_u16()
         }));
 
-        (status, body).into_response()
+        let mut response = (status, body).into_response();
+        if let Some(secs) = retry_after {
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, HeaderValue::from(secs));
+        }
+        response
     }
 }
 
@@ -194,9 +219,74 @@ impl ApiError {
         ApiError::Network(message.to_string())
     }
 
+    pub fn service_unavailable<T: std::fmt::Display>(message: T) -> Self {
+        ApiError::ServiceUnavailable(message.to_string())
+    }
+
     pub fn internal<T: std::fmt::Display>(message: T) -> Self {
         ApiError::Internal(anyhow::anyhow!(message.to_string()))
     }
+
+    /// Stable machine-readable code returned as `code` next to the human
+    /// `error` message, for clients to branch on:
+    ///
+    /// | code               | status | meaning                                  |
+    /// |--------------------|--------|------------------------------------------|
+    /// | `validation`       | 400    | a field failed validation                |
+    /// | `invalid_input`    | 400    | the request couldn't be interpreted      |
+    /// | `invalid_timezone` | 400    | unknown timezone name or offset          |
+    /// | `invalid_date`     | 400    | unparseable date                         |
+    /// | `invalid_address`  | 400    | unparseable network address              |
+    /// | `invalid_uuid`     | 400    | malformed UUID                           |
+    /// | `invalid_decimal`  | 400    | malformed decimal amount                 |
+    /// | `calculation`      | 400    | no result for these inputs               |
+    /// | `unauthenticated`  | 401    | missing or wrong credentials             |
+    /// | `forbidden`        | 403    | credentials can't access this            |
+    /// | `not_found`        | 404    | no such resource                         |
+    /// | `timeout`          | 408    | the request took too long                |
+    /// | `rate_limited`     | 429    | too many requests, see `Retry-After`     |
+    /// | `internal`         | 500    | server fault; details are only logged    |
+    /// | `upstream`         | 502    | a service we depend on failed            |
+    /// | `unavailable`      | 503    | temporarily overloaded or down           |
+    ///
+    /// Codes are never renamed; new ones may be added.
+    pub fn code(&self) -> &'static str {
+        match self {
+            ApiError::Validation(_) => "validation",
+            ApiError::InvalidInput(_) => "invalid_input",
+            ApiError::TimezoneParsing(_) => "invalid_timezone",
+            ApiError::DateParsing(_) => "invalid_date",
+            ApiError::AddressParsing(_) => "invalid_address",
+            ApiError::Uuid(_) => "invalid_uuid",
+            ApiError::DecimalParsing(_) => "invalid_decimal",
+            ApiError::Calculation(_) => "calculation",
+            ApiError::Authentication(_) => "unauthenticated",
+            ApiError::Authorization(_) => "forbidden",
+            ApiError::NotFound(_) => "not_found",
+            ApiError::Timeout(_) => "timeout",
+            ApiError::RateLimitExceeded => "rate_limited",
+            ApiError::Http(_) | ApiError::Network(_) => "upstream",
+            ApiError::Database(sqlx::Error::PoolTimedOut | sqlx::Error::PoolClosed)
+            | ApiError::ServiceUnavailable(_) => "unavailable",
+            ApiError::Database(_)
+            | ApiError::Redis(_)
+            | ApiError::Config(_)
+            | ApiError::Serialization(_)
+            | ApiError::Internal(_)
+            | ApiError::Io(_) => "internal",
+        }
+    }
+
+    /// Seconds a client should wait before retrying, for errors caused by
+    /// load rather than by the request
+    pub fn retry_after(&self) -> Option<u64> {
+        match self {
+            ApiError::Database(sqlx::Error::PoolTimedOut | sqlx::Error::PoolClosed) => {
+                Some(POOL_RETRY_AFTER_SECS)
+            }
+            _ => None,
+        }
+    }
 }
 
 pub type ApiResult<T> = Result<T, ApiError>;
@@ -222,6 +312,28 @@ mod tests {
         assert!(response.status() == StatusCode::NOT_FOUND);
     }
 
+    #[tokio::test]
+    async fn test_not_found_has_stable_code() {
+        let response = ApiError::not_found("Dua").into_response();
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+        assert_eq!(body["code"], "not_found");
+        assert_eq!(body["error"], "Dua not found");
+        assert_eq!(body["status"], 404);
+    }
+
+    #[test]
+    fn test_pool_timeout_is_unavailable_with_retry_after() {
+        let response = ApiError::Database(sqlx::Error::PoolTimedOut).into_response();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(response.headers()[header::RETRY_AFTER], "1");
+
+        let response = ApiError::Database(sqlx::Error::RowNotFound).into_response();
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert!(!response.headers().contains_key(header::RETRY_AFTER));
+    }
+
     #[test]
     fn test_rate_limit_error() {
         let error = ApiError::RateLimitExceeded;
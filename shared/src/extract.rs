@@ -0,0 +1,61 @@
+// Request extractors that fail with `ApiError`, so malformed input gets the
+// same JSON error body as every other client error.
+
+use axum::{async_trait, body::HttpBody, extract::FromRequest, http::Request, BoxError, Json};
+use serde::de::DeserializeOwned;
+
+use crate::error::ApiError;
+
+/// `Json<T>` whose rejections are `ApiError::InvalidInput`. Deserialization
+/// errors keep serde's message, including any a custom `Deserialize` wrote.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ApiJson<T>(pub T);
+
+#[async_trait]
+impl<T, S, B> FromRequest<S, B> for ApiJson<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync,
+    B: HttpBody + Send + 'static,
+    B::Data: Send,
+    B::Error: Into<BoxError>,
+{
+    type Rejection = ApiError;
+
+    async fn from_request(req: Request<B>, state: &S) -> Result<Self, Self::Rejection> {
+        let Json(value) = Json::<T>::from_request(req, state)
+            .await
+            .map_err(|rejection| ApiError::InvalidInput(rejection.body_text()))?;
+        Ok(Self(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, http::header};
+    use serde::Deserialize;
+
+    #[derive(Debug, Deserialize)]
+    struct Counted {
+        #[allow(dead_code)]
+        count: u32,
+    }
+
+    #[tokio::test]
+    async fn test_malformed_body_is_invalid_input() {
+        let req = Request::builder()
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(r#"{"count": "many"}"#))
+            .unwrap();
+        match ApiJson::<Counted>::from_request(req, &()).await {
+            Err(ApiError::InvalidInput(message)) => {
+                assert!(message.contains("count"), "{}", message)
+            }
+            other => panic!(
+                "expected invalid input, got {:?}",
+                other.map(|ApiJson(body)| body)
+            ),
+        }
+    }
+}
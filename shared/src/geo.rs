@@ -0,0 +1,117 @@
+use serde::{Deserialize, Serialize};
+
+/// A fixed point on the earth that bearings can be computed towards.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReferencePoint {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub latitude: f64,
+    pub longitude: f64,
+    /// Meters above sea level
+    pub elevation: f64,
+}
+
+/// Centre of the Kaaba, from the OpenStreetMap outline of the structure
+/// (cross-checked against satellite imagery). Ground level of the mataf is
+/// roughly 333 m above sea level.
+pub const KAABA: ReferencePoint = ReferencePoint {
+    name: "kaaba",
+    description: "Holy Kaaba, Masjid al-Haram, Mecca, Saudi Arabia",
+    latitude: 21.4224779,
+    longitude: 39.8251832,
+    elevation: 333.0,
+};
+
+/// The Prophet's chamber (green dome) in Masjid an-Nabawi, from
+/// OpenStreetMap. Medina lies at about 608 m above sea level.
+pub const MASJID_NABAWI: ReferencePoint = ReferencePoint {
+    name: "masjid_nabawi",
+    description: "Masjid an-Nabawi, Medina, Saudi Arabia",
+    latitude: 24.4672132,
+    longitude: 39.6112009,
+    elevation: 608.0,
+};
+
+/// The Qibli prayer hall of Masjid al-Aqsa (the first qibla), from
+/// OpenStreetMap. The Haram al-Sharif plateau is about 740 m above sea level.
+pub const MASJID_AL_AQSA: ReferencePoint = ReferencePoint {
+    name: "masjid_al_aqsa",
+    description: "Masjid al-Aqsa, Jerusalem",
+    latitude: 31.7761,
+    longitude: 35.2358,
+    elevation: 740.0,
+};
+
+/// Eastward difference from one longitude to another, in (-180, 180].
+/// Taking the short way round keeps bearings and time offsets sane for
+/// places either side of the date line.
+pub fn longitude_delta(from: f64, to: f64) -> f64 {
+    let delta = (to - from).rem_euclid(360.0);
+    if delta > 180.0 {
+        delta - 360.0
+    } else {
+        delta
+    }
+}
+
+/// Wraps a longitude into [-180, 180], so 190 becomes -170. Values already
+/// in range are returned unchanged.
+pub fn normalize_longitude(longitude: f64) -> f64 {
+    if (-180.0..=180.0).contains(&longitude) || !longitude.is_finite() {
+        longitude
+    } else {
+        longitude_delta(0.0, longitude)
+    }
+}
+
+/// `#[serde(deserialize_with = "shared::geo::deserialize_longitude")]`:
+/// reads a longitude and wraps it with [`normalize_longitude`].
+pub fn deserialize_longitude<'de, D>(deserializer: D) -> Result<f64, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    f64::deserialize(deserializer).map(normalize_longitude)
+}
+
+/// Selectable reference for direction calculations; the Kaaba unless asked otherwise.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SacredReference {
+    #[default]
+    Kaaba,
+    MasjidNabawi,
+    MasjidAlAqsa,
+}
+
+impl SacredReference {
+    pub fn point(self) -> &'static ReferencePoint {
+        match self {
+            SacredReference::Kaaba => &KAABA,
+            SacredReference::MasjidNabawi => &MASJID_NABAWI,
+            SacredReference::MasjidAlAqsa => &MASJID_AL_AQSA,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_longitude_delta_crosses_date_line() {
+        assert_eq!(longitude_delta(179.0, -179.0), 2.0);
+        assert_eq!(longitude_delta(-179.0, 179.0), -2.0);
+        assert_eq!(longitude_delta(0.0, 180.0), 180.0);
+        assert_eq!(longitude_delta(0.0, -180.0), 180.0);
+        assert!((longitude_delta(-171.76, 39.83) - -148.41).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_reference_points() {
+        assert_eq!(SacredReference::default().point(), &KAABA);
+        assert_eq!(SacredReference::MasjidNabawi.point().name, "masjid_nabawi");
+
+        let parsed: SacredReference = serde_json::from_str("\"masjid_al_aqsa\"").unwrap();
+        assert_eq!(parsed, SacredReference::MasjidAlAqsa);
+    }
+}
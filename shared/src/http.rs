@@ -0,0 +1,139 @@
+use std::{
+    collections::hash_map::RandomState,
+    fmt::Display,
+    future::Future,
+    hash::{BuildHasher, Hasher},
+    time::Duration,
+};
+use tracing::warn;
+
+use crate::error::{ApiError, ApiResult};
+
+/// Bounded exponential backoff for calls to external services.
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    /// Total attempts, including the first
+    pub max_attempts: u32,
+    /// Delay before the first retry; doubled for each one after
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(200),
+            max_delay: Duration::from_secs(5),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Backoff before retry number `retry` (starting at 1), with jitter so
+    /// instances retrying the same outage spread out. The delay is drawn
+    /// from the upper half of the exponential step.
+    fn delay(&self, retry: u32) -> Duration {
+        let exponential = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(retry.saturating_sub(1)))
+            .min(self.max_delay);
+        let half = exponential / 2;
+        let jitter_range = (exponential - half).as_millis() as u64;
+        if jitter_range == 0 {
+            return exponential;
+        }
+        half + Duration::from_millis(random_u64() % (jitter_range + 1))
+    }
+}
+
+/// Cheap randomness for jitter from std's randomly seeded hasher
+fn random_u64() -> u64 {
+    RandomState::new().build_hasher().finish()
+}
+
+/// Runs `operation` until it succeeds or `policy.max_attempts` is reached,
+/// sleeping with exponential backoff between attempts. The last error is
+/// returned as `ApiError::Network`.
+pub async fn retry<T, E, F, Fut>(policy: &RetryPolicy, name: &str, mut operation: F) -> ApiResult<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+    E: Display,
+{
+    let max_attempts = policy.max_attempts.max(1);
+    let mut attempt = 1;
+    loop {
+        match operation().await {
+            Ok(value) => return Ok(value),
+            Err(e) if attempt >= max_attempts => {
+                return Err(ApiError::network(format!(
+                    "{} failed after {} attempts: {}",
+                    name, attempt, e
+                )));
+            }
+            Err(e) => {
+                let delay = policy.delay(attempt);
+                warn!(
+                    "{} failed (attempt {}/{}), retrying in {:?}: {}",
+                    name, attempt, max_attempts, delay, e
+                );
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn fast_policy() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(5),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_retry_succeeds_after_two_failures() {
+        let calls = AtomicU32::new(0);
+        let result = retry(&fast_policy(), "flaky provider", || async {
+            match calls.fetch_add(1, Ordering::SeqCst) {
+                0 | 1 => Err("connection reset"),
+                _ => Ok(42),
+            }
+        })
+        .await;
+
+        assert_eq!(result.unwrap(), 42);
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_retry_gives_up_with_network_error() {
+        let calls = AtomicU32::new(0);
+        let result: ApiResult<()> = retry(&fast_policy(), "down provider", || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Err("timed out")
+        })
+        .await;
+
+        assert!(matches!(result, Err(ApiError::Network(ref msg)) if msg.contains("timed out")));
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn test_delay_is_bounded() {
+        let policy = RetryPolicy::default();
+        for retry in 1..20 {
+            let delay = policy.delay(retry);
+            assert!(delay <= policy.max_delay, "{:?}", delay);
+        }
+        let first = policy.delay(1);
+        assert!(first >= Duration::from_millis(100) && first <= Duration::from_millis(200));
+    }
+}
@@ -0,0 +1,171 @@
+// In-process request latency per route, for quick checks without going
+// through Prometheus. Each route keeps its most recent durations in a bounded
+// ring buffer, so percentiles reflect recent traffic and memory stays fixed.
+
+use axum::{
+    extract::{MatchedPath, State},
+    http::Request,
+    middleware::Next,
+    response::Response,
+    Json,
+};
+use serde::Serialize;
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+/// Durations kept per route; older ones are dropped first
+pub const LATENCY_SAMPLES_PER_ROUTE: usize = 1000;
+
+/// Route recorded for requests that matched no route, so probes of random
+/// paths can't grow the map
+const UNMATCHED_ROUTE: &str = "<unmatched>";
+
+/// Recent request durations by route pattern (e.g. `/v1/duas/:id`). Clones
+/// share the same buffers.
+#[derive(Clone, Debug)]
+pub struct LatencyTracker {
+    capacity: usize,
+    routes: Arc<Mutex<HashMap<String, VecDeque<Duration>>>>,
+}
+
+impl Default for LatencyTracker {
+    fn default() -> Self {
+        Self::new(LATENCY_SAMPLES_PER_ROUTE)
+    }
+}
+
+/// Percentiles of a route's recent durations, in milliseconds
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RouteLatency {
+    pub route: String,
+    /// Samples the percentiles are computed from
+    pub samples: usize,
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+    pub p99_ms: f64,
+}
+
+impl LatencyTracker {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            routes: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    pub fn record(&self, route: &str, duration: Duration) {
+        let mut routes = self.routes.lock().unwrap();
+        let samples = routes
+            .entry(route.to_string())
+            .or_insert_with(|| VecDeque::with_capacity(self.capacity));
+        if samples.len() == self.capacity {
+            samples.pop_front();
+        }
+        samples.push_back(duration);
+    }
+
+    /// Percentiles for every route seen so far, sorted by route
+    pub fn snapshot(&self) -> Vec<RouteLatency> {
+        let routes = self.routes.lock().unwrap();
+        let mut snapshot: Vec<RouteLatency> = routes
+            .iter()
+            .filter(|(_, samples)| !samples.is_empty())
+            .map(|(route, samples)| {
+                let mut sorted: Vec<Duration> = samples.iter().copied().collect();
+                sorted.sort_unstable();
+                RouteLatency {
+                    route: route.clone(),
+                    samples: sorted.len(),
+                    p50_ms: percentile_ms(&sorted, 50.0),
+                    p95_ms: percentile_ms(&sorted, 95.0),
+                    p99_ms: percentile_ms(&sorted, 99.0),
+                }
+            })
+            .collect();
+        snapshot.sort_by(|a, b| a.route.cmp(&b.route));
+        snapshot
+    }
+}
+
+/// Nearest-rank percentile of sorted, non-empty samples
+fn percentile_ms(sorted: &[Duration], percentile: f64) -> f64 {
+    let rank = ((percentile / 100.0) * sorted.len() as f64).ceil() as usize;
+    let duration = sorted[rank.clamp(1, sorted.len()) - 1];
+    (duration.as_secs_f64() * 1_000_000.0).round() / 1000.0
+}
+
+/// Times each request and records it under its matched route
+pub async fn latency_middleware<B>(
+    State(tracker): State<LatencyTracker>,
+    matched_path: Option<MatchedPath>,
+    req: Request<B>,
+    next: Next<B>,
+) -> Response {
+    let route = matched_path
+        .as_ref()
+        .map_or(UNMATCHED_ROUTE, |p| p.as_str())
+        .to_string();
+
+    let started = Instant::now();
+    let response = next.run(req).await;
+    tracker.record(&route, started.elapsed());
+
+    response
+}
+
+/// p50/p95/p99 request durations per route; mounted behind the admin token
+pub async fn latency_stats_handler(
+    State(tracker): State<LatencyTracker>,
+) -> Json<serde_json::Value> {
+    let routes = tracker.snapshot();
+    Json(serde_json::json!({
+        "samples_per_route": tracker.capacity,
+        "routes": routes,
+        "total": routes.len()
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, extract::Path, middleware, routing::get, Router};
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn test_percentiles_are_populated_and_ordered() {
+        let tracker = LatencyTracker::new(100);
+        let app = Router::new()
+            .route(
+                "/sleep/:ms",
+                get(|Path(ms): Path<u64>| async move {
+                    tokio::time::sleep(Duration::from_millis(ms)).await;
+                    "done"
+                }),
+            )
+            .layer(middleware::from_fn_with_state(
+                tracker.clone(),
+                latency_middleware,
+            ));
+
+        for ms in 0..20 {
+            let req = Request::builder()
+                .uri(format!("/sleep/{}", ms))
+                .body(Body::empty())
+                .unwrap();
+            app.clone().oneshot(req).await.unwrap();
+        }
+
+        let Json(stats) = latency_stats_handler(State(tracker)).await;
+        let route = &stats["routes"][0];
+        let [p50, p95, p99] =
+            ["p50_ms", "p95_ms", "p99_ms"].map(|key| route[key].as_f64().unwrap());
+
+        assert_eq!(route["route"], "/sleep/:ms");
+        assert_eq!(route["samples"], 20);
+        assert!(p50 >= 9.0, "p50 {}", p50);
+        assert!(p50 < p95 && p95 <= p99, "{} {} {}", p50, p95, p99);
+    }
+}
@@ -1,17 +1,27 @@
+pub mod admin;
 pub mod cache;
 pub mod config;
 pub mod database;
 pub mod error;
+pub mod extract;
+pub mod geo;
+pub mod http;
+pub mod latency;
 pub mod middleware;
+pub mod pagination;
 pub mod simple_rate_limit;
+pub mod time;
 pub mod validation;
 
 pub use cache::*;
 pub use config::*;
 pub use database::*;
 pub use error::*;
+pub use geo::*;
+pub use latency::{LatencyTracker, RouteLatency};
 pub use middleware::*;
-pub use simple_rate_limit::{RateLimiter, SimpleRateLimiter};
+pub use pagination::{PageItem, Paginated};
+pub use simple_rate_limit::{RateDecision, RateLimiter, SimpleRateLimiter};
 pub use validation::*;
 
 // Re-export for backwards compatibility
@@ -1,8 +1,14 @@
 use axum::{
-    extract::State,
-    http::{HeaderMap, Request},
+    extract::{ConnectInfo, MatchedPath, State},
+    http::{header, HeaderMap, HeaderName, HeaderValue, Method, Request},
     middleware::Next,
-    response::Response,
+    response::{IntoResponse, Response},
+};
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
 };
 use std::time::Duration;
 use tower_http::{
@@ -10,17 +16,38 @@ use tower_http::{
     timeout::TimeoutLayer,
     trace::TraceLayer,
 };
-use tracing::info;
+use tracing::{info, warn};
+
+use crate::{
+    admin::{RATE_LIMIT_RESET_PATH, READ_ONLY_ADMIN_PATH},
+    config::{CorsConfig, ServerConfig},
+    error::ApiError,
+    RateDecision, SimpleRateLimiter,
+};
 
-use crate::{error::ApiError, SimpleRateLimiter};
+/// Creates CORS layer with permissive settings for public APIs; exposed
+/// headers and preflight caching come from `config`
+pub fn cors_layer(config: &CorsConfig) -> CorsLayer {
+    let exposed: Vec<HeaderName> = config
+        .expose_headers
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .filter_map(|name| match HeaderName::try_from(name) {
+            Ok(header) => Some(header),
+            Err(_) => {
+                warn!("Ignoring invalid CORS exposed header: {}", name);
+                None
+            }
+        })
+        .collect();
 
-/// Creates CORS layer with permissive settings for public APIs
-pub fn cors_layer() -> CorsLayer {
     CorsLayer::new()
         .allow_origin(Any)
         .allow_methods(Any)
         .allow_headers(Any)
-        .max_age(Duration::from_secs(86400)) // 24 hours
+        .expose_headers(exposed)
+        .max_age(Duration::from_secs(config.max_age_secs))
 }
 
 /// Creates timeout layer with 30 second timeout
@@ -28,6 +55,112 @@ pub fn timeout_layer() -> TimeoutLayer {
     TimeoutLayer::new(Duration::from_secs(30))
 }
 
+/// Request timeouts with per-route overrides, keyed by route pattern
+/// (e.g. `/v1/duas/:id`).
+#[derive(Clone, Debug)]
+pub struct RouteTimeouts {
+    default: Duration,
+    overrides: Arc<HashMap<String, Duration>>,
+}
+
+impl RouteTimeouts {
+    pub fn new(default: Duration) -> Self {
+        Self {
+            default,
+            overrides: Arc::new(HashMap::new()),
+        }
+    }
+
+    /// Uses `server.request_timeout_secs` as the default
+    pub fn from_config(config: &ServerConfig) -> Self {
+        Self::new(Duration::from_secs(config.request_timeout_secs))
+    }
+
+    pub fn with_override(mut self, route: &str, timeout: Duration) -> Self {
+        Arc::make_mut(&mut self.overrides).insert(route.to_string(), timeout);
+        self
+    }
+
+    pub fn timeout_for(&self, route: &str) -> Duration {
+        self.overrides.get(route).copied().unwrap_or(self.default)
+    }
+}
+
+/// Applies the route's timeout, answering with `ApiError::Timeout` (408) on expiry
+pub async fn timeout_middleware<B>(
+    State(timeouts): State<RouteTimeouts>,
+    matched_path: Option<MatchedPath>,
+    req: Request<B>,
+    next: Next<B>,
+) -> Result<Response, ApiError>
+where
+    B: Send + 'static,
+{
+    let route = matched_path
+        .as_ref()
+        .map(|p| p.as_str().to_string())
+        .unwrap_or_else(|| req.uri().path().to_string());
+    let timeout = timeouts.timeout_for(&route);
+
+    tokio::time::timeout(timeout, next.run(req))
+        .await
+        .map_err(|e| {
+            info!("Request to {} timed out after {:?}", route, timeout);
+            ApiError::from(e)
+        })
+}
+
+/// Security headers added to every response. HSTS is only sent when
+/// configured, since it locks browsers into HTTPS for `max-age`.
+#[derive(Clone, Debug, Default)]
+pub struct SecurityHeaders {
+    hsts: Option<HeaderValue>,
+}
+
+impl SecurityHeaders {
+    pub fn new(hsts_max_age_secs: u64) -> Self {
+        let hsts = (hsts_max_age_secs > 0).then(|| {
+            HeaderValue::from_str(&format!("max-age={}; includeSubDomains", hsts_max_age_secs))
+                .expect("HSTS value is a valid header")
+        });
+        Self { hsts }
+    }
+
+    /// Uses `server.hsts_max_age_secs`
+    pub fn from_config(config: &ServerConfig) -> Self {
+        Self::new(config.hsts_max_age_secs)
+    }
+}
+
+/// Sets `X-Content-Type-Options`, `X-Frame-Options`, `Referrer-Policy` and,
+/// when configured, `Strict-Transport-Security`. Headers a handler already
+/// set are left alone.
+pub async fn security_headers_middleware<B>(
+    State(security): State<SecurityHeaders>,
+    req: Request<B>,
+    next: Next<B>,
+) -> Response {
+    let mut response = next.run(req).await;
+    let headers = response.headers_mut();
+
+    headers
+        .entry(header::X_CONTENT_TYPE_OPTIONS)
+        .or_insert(HeaderValue::from_static("nosniff"));
+    headers
+        .entry(header::X_FRAME_OPTIONS)
+        .or_insert(HeaderValue::from_static("DENY"));
+    headers
+        .entry(header::REFERRER_POLICY)
+        .or_insert(HeaderValue::from_static("no-referrer"));
+    if let Some(ref hsts) = security.hsts {
+        headers
+            .entry(header::STRICT_TRANSPORT_SECURITY)
+            .or_insert_with(|| hsts.clone());
+    }
+
+    response
+}
+
 /// Creates tracing layer for request logging
 pub fn trace_layer(
 ) -> TraceLayer<tower_http::classify::SharedClassifier<tower_http::classify::ServerErrorsAsFailures>>
@@ -35,29 +168,80 @@ pub fn trace_layer(
     TraceLayer::new_for_http()
 }
 
-/// Rate limiting middleware
+/// The client address resolved by [`rate_limit_middleware`], available to
+/// handlers as a request extension.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientIp(pub String);
+
+/// Resolves the client address for a connection from `peer`.
+///
+/// Forwarding headers are only believed when the peer is a trusted proxy;
+/// anyone else could set them to dodge rate limits. In `X-Forwarded-For` the
+/// right-most hop that isn't a trusted proxy is the client, as entries left
+/// of it are client-supplied. Without connect info the client is `unknown`.
+pub fn resolve_client_ip(headers: &HeaderMap, peer: Option<IpAddr>, trusted: &[IpAddr]) -> String {
+    let Some(peer) = peer else {
+        return "unknown".to_string();
+    };
+    if !trusted.contains(&peer) {
+        return peer.to_string();
+    }
+
+    let header_ip = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+    let forwarded = header_ip("x-forwarded-for").and_then(|hops| {
+        hops.rsplit(',')
+            .filter_map(|hop| hop.trim().parse::<IpAddr>().ok())
+            .find(|ip| !trusted.contains(ip))
+    });
+    let real_ip = || header_ip("x-real-ip").and_then(|ip| ip.trim().parse::<IpAddr>().ok());
+
+    forwarded.or_else(real_ip).unwrap_or(peer).to_string()
+}
+
+/// Rate limiting middleware, keyed by the resolved client IP.
+///
+/// Requests past the limit get a 429 with `Retry-After`; allowed requests that
+/// leave the client close to the limit are marked with `X-RateLimit-Warning: true`.
 pub async fn rate_limit_middleware<B>(
     State(rate_limiter): State<SimpleRateLimiter>,
     headers: HeaderMap,
-    req: Request<B>,
+    mut req: Request<B>,
     next: Next<B>,
 ) -> Result<Response, ApiError>
 where
     B: Send + 'static,
 {
-    let client_ip = headers
-        .get("x-forwarded-for")
-        .and_then(|v| v.to_str().ok())
-        .or_else(|| headers.get("x-real-ip").and_then(|v| v.to_str().ok()))
-        .unwrap_or("unknown")
-        .to_string();
-
-    if !rate_limiter.check_rate_limit(&client_ip).await? {
+    let peer = req
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip());
+    let client_ip = resolve_client_ip(&headers, peer, rate_limiter.trusted_proxies());
+    req.extensions_mut().insert(ClientIp(client_ip.clone()));
+
+    if let RateDecision::Limited { retry_after_secs } = rate_limiter.check(&client_ip).await? {
         info!("Rate limit exceeded for IP: {}", client_ip);
-        return Err(ApiError::RateLimitExceeded);
+        let mut response = ApiError::RateLimitExceeded.into_response();
+        response
+            .headers_mut()
+            .insert(header::RETRY_AFTER, HeaderValue::from(retry_after_secs));
+        return Ok(response);
+    }
+
+    let remaining = rate_limiter.get_remaining_requests(&client_ip).await?;
+    let near_limit = rate_limiter.is_near_limit(remaining);
+    if near_limit {
+        info!(
+            "Client {} is approaching the rate limit ({} requests remaining)",
+            client_ip, remaining
+        );
     }
 
-    let response = next.run(req).await;
+    let mut response = next.run(req).await;
+    if near_limit {
+        response
+            .headers_mut()
+            .insert("x-ratelimit-warning", HeaderValue::from_static("true"));
+    }
     Ok(response)
 }
 
@@ -72,3 +256,350 @@ where
 
     next.run(req).await
 }
+
+/// Shared read-only (maintenance) switch.
+///
+/// Clones share the same flag, so toggling it through any handle, such as
+/// the `/admin/read-only` endpoint, takes effect on the next request without
+/// restarting the service.
+#[derive(Clone, Debug)]
+pub struct ReadOnlyMode {
+    enabled: Arc<AtomicBool>,
+    exempt_paths: Arc<Vec<String>>,
+}
+
+impl ReadOnlyMode {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled: Arc::new(AtomicBool::new(enabled)),
+            exempt_paths: Arc::new(
+                ["/health", READ_ONLY_ADMIN_PATH, RATE_LIMIT_RESET_PATH]
+                    .map(String::from)
+                    .to_vec(),
+            ),
+        }
+    }
+
+    /// Allows writes-by-method to a route that does not change data, e.g. a
+    /// POST endpoint that only computes a result. `path` is matched against
+    /// the route as registered, so it may contain parameters (`/items/:id`).
+    pub fn exempt(mut self, path: &str) -> Self {
+        Arc::make_mut(&mut self.exempt_paths).push(path.to_string());
+        self
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    pub fn set(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+    }
+
+    fn allows(&self, method: &Method, path: &str) -> bool {
+        !self.is_enabled()
+            || matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS)
+            || self.exempt_paths.iter().any(|p| p == path)
+    }
+}
+
+/// Rejects non-read requests with a 503 while read-only mode is enabled
+pub async fn read_only_middleware<B>(
+    State(mode): State<ReadOnlyMode>,
+    matched_path: Option<MatchedPath>,
+    req: Request<B>,
+    next: Next<B>,
+) -> Result<Response, ApiError>
+where
+    B: Send + 'static,
+{
+    let route = matched_path
+        .as_ref()
+        .map_or_else(|| req.uri().path(), |p| p.as_str());
+    if !mode.allows(req.method(), route) {
+        info!(
+            "Rejected {} {} while in read-only mode",
+            req.method(),
+            req.uri().path()
+        );
+        return Err(ApiError::service_unavailable(
+            "Service is in read-only maintenance mode; write requests are temporarily disabled",
+        ));
+    }
+
+    Ok(next.run(req).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        cache::Cache,
+        config::{RateLimitConfig, RedisConfig},
+    };
+    use axum::{
+        body::Body,
+        http::StatusCode,
+        middleware,
+        routing::{get, post},
+        Router,
+    };
+    use tower::ServiceExt;
+
+    fn app(mode: ReadOnlyMode) -> Router {
+        Router::new()
+            .route(
+                "/items",
+                get(|| async { "list" }).post(|| async { "created" }),
+            )
+            .route("/items/:id/preview", post(|| async { "preview" }))
+            .route("/health", get(|| async { "ok" }).post(|| async { "ok" }))
+            .layer(middleware::from_fn_with_state(mode, read_only_middleware))
+    }
+
+    async fn status(app: Router, method: Method, uri: &str) -> StatusCode {
+        let req = Request::builder()
+            .method(method)
+            .uri(uri)
+            .body(Body::empty())
+            .unwrap();
+        app.oneshot(req).await.unwrap().status()
+    }
+
+    #[tokio::test]
+    async fn test_cors_exposes_headers_and_caches_preflight() {
+        let config = CorsConfig {
+            max_age_secs: 600,
+            ..CorsConfig::default()
+        };
+        let app = Router::new()
+            .route("/items", get(|| async { "list" }))
+            .layer(cors_layer(&config));
+
+        let req = Request::builder()
+            .uri("/items")
+            .header(header::ORIGIN, "https://example.org")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(req).await.unwrap();
+        let exposed = response.headers()[header::ACCESS_CONTROL_EXPOSE_HEADERS]
+            .to_str()
+            .unwrap()
+            .to_string();
+        for name in [
+            "x-ratelimit-remaining",
+            "x-request-id",
+            "etag",
+            "x-debounce-ms",
+        ] {
+            assert!(exposed.split(',').any(|h| h.trim() == name), "{}", exposed);
+        }
+
+        let preflight = Request::builder()
+            .method(Method::OPTIONS)
+            .uri("/items")
+            .header(header::ORIGIN, "https://example.org")
+            .header(header::ACCESS_CONTROL_REQUEST_METHOD, "GET")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(preflight).await.unwrap();
+        assert_eq!(response.headers()[header::ACCESS_CONTROL_MAX_AGE], "600");
+    }
+
+    #[tokio::test]
+    async fn test_security_headers_are_set() {
+        let app = |security: SecurityHeaders| {
+            Router::new()
+                .route("/items", get(|| async { "list" }))
+                .layer(middleware::from_fn_with_state(
+                    security,
+                    security_headers_middleware,
+                ))
+        };
+        let get_items = || {
+            Request::builder()
+                .uri("/items")
+                .body(Body::empty())
+                .unwrap()
+        };
+
+        let response = app(SecurityHeaders::new(0))
+            .oneshot(get_items())
+            .await
+            .unwrap();
+        let headers = response.headers();
+        assert_eq!(headers[header::X_CONTENT_TYPE_OPTIONS], "nosniff");
+        assert_eq!(headers[header::X_FRAME_OPTIONS], "DENY");
+        assert_eq!(headers[header::REFERRER_POLICY], "no-referrer");
+        assert!(!headers.contains_key(header::STRICT_TRANSPORT_SECURITY));
+
+        let response = app(SecurityHeaders::new(31_536_000))
+            .oneshot(get_items())
+            .await
+            .unwrap();
+        assert_eq!(
+            response.headers()[header::STRICT_TRANSPORT_SECURITY],
+            "max-age=31536000; includeSubDomains"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_route_timeout_override_returns_408() {
+        let timeouts = RouteTimeouts::new(Duration::from_secs(5))
+            .with_override("/slow", Duration::from_millis(20));
+        let app = Router::new()
+            .route(
+                "/slow",
+                get(|| async {
+                    tokio::time::sleep(Duration::from_millis(200)).await;
+                    "late"
+                }),
+            )
+            .route("/fast", get(|| async { "quick" }))
+            .layer(middleware::from_fn_with_state(timeouts, timeout_middleware));
+
+        assert_eq!(
+            status(app.clone(), Method::GET, "/slow").await,
+            StatusCode::REQUEST_TIMEOUT
+        );
+        assert_eq!(status(app, Method::GET, "/fast").await, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_read_only_rejects_writes_but_serves_reads() {
+        let mode = ReadOnlyMode::new(true);
+
+        assert_eq!(
+            status(app(mode.clone()), Method::POST, "/items").await,
+            StatusCode::SERVICE_UNAVAILABLE
+        );
+        assert_eq!(
+            status(app(mode.clone()), Method::GET, "/items").await,
+            StatusCode::OK
+        );
+        assert_eq!(
+            status(app(mode), Method::POST, "/health").await,
+            StatusCode::OK
+        );
+    }
+
+    #[tokio::test]
+    async fn test_read_only_toggle_takes_effect_immediately() {
+        let mode = ReadOnlyMode::new(false);
+        assert_eq!(
+            status(app(mode.clone()), Method::POST, "/items").await,
+            StatusCode::OK
+        );
+
+        mode.set(true);
+        assert_eq!(
+            status(app(mode.clone()), Method::POST, "/items").await,
+            StatusCode::SERVICE_UNAVAILABLE
+        );
+
+        let exempted = ReadOnlyMode::new(true).exempt("/items");
+        assert_eq!(
+            status(app(exempted), Method::POST, "/items").await,
+            StatusCode::OK
+        );
+
+        let exempted = ReadOnlyMode::new(true).exempt("/items/:id/preview");
+        assert_eq!(
+            status(app(exempted), Method::POST, "/items/42/preview").await,
+            StatusCode::OK
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_warning_on_last_allowed_request() {
+        let redis_config = RedisConfig {
+            url: "redis://localhost:6379".to_string(),
+            pool_max_open: 10,
+            pool_max_idle: 5,
+            pool_timeout: 30,
+            pool_expire: 300,
+        };
+        let Ok(cache) = Cache::new(&redis_config).await else {
+            return;
+        };
+        let limiter = SimpleRateLimiter::new(
+            cache,
+            RateLimitConfig {
+                requests_per_minute: 5,
+                burst_size: 5,
+                requests_per_day: None,
+                cleanup_interval: 60,
+                warning_threshold_percent: 20,
+                trusted_proxies: "127.0.0.1".to_string(),
+            },
+        );
+        let client = "203.0.113.250";
+        let proxy = ConnectInfo(SocketAddr::from(([127, 0, 0, 1], 40000)));
+        limiter.reset_rate_limit(client).await.unwrap();
+
+        let app = Router::new()
+            .route("/items", get(|| async { "list" }))
+            .layer(middleware::from_fn_with_state(
+                limiter.clone(),
+                rate_limit_middleware,
+            ));
+
+        let mut warnings = Vec::new();
+        for _ in 0..5 {
+            let req = Request::builder()
+                .uri("/items")
+                .header("x-real-ip", client)
+                .extension(proxy)
+                .body(Body::empty())
+                .unwrap();
+            let response = app.clone().oneshot(req).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            warnings.push(response.headers().contains_key("x-ratelimit-warning"));
+        }
+
+        let req = Request::builder()
+            .uri("/items")
+            .header("x-real-ip", client)
+            .extension(proxy)
+            .body(Body::empty())
+            .unwrap();
+        let limited = app.oneshot(req).await.unwrap();
+        limiter.reset_rate_limit(client).await.unwrap();
+
+        assert_eq!(warnings, vec![false, false, false, false, true]);
+        assert_eq!(limited.status(), StatusCode::TOO_MANY_REQUESTS);
+        // One token refills every 60s / 5 requests
+        assert_eq!(limited.headers()[header::RETRY_AFTER], "12");
+    }
+
+    #[test]
+    fn test_forwarded_for_is_only_trusted_from_trusted_proxies() {
+        let proxy: IpAddr = "10.0.0.2".parse().unwrap();
+        let stranger: IpAddr = "198.51.100.9".parse().unwrap();
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "x-forwarded-for",
+            HeaderValue::from_static("1.2.3.4, 203.0.113.7, 10.0.0.2"),
+        );
+
+        // Right-most hop that isn't one of our proxies
+        assert_eq!(
+            resolve_client_ip(&headers, Some(proxy), &[proxy]),
+            "203.0.113.7"
+        );
+        // A client can't pick its own bucket by setting the header
+        assert_eq!(
+            resolve_client_ip(&headers, Some(stranger), &[proxy]),
+            "198.51.100.9"
+        );
+        assert_eq!(resolve_client_ip(&headers, None, &[proxy]), "unknown");
+
+        let mut headers = HeaderMap::new();
+        headers.insert("x-real-ip", HeaderValue::from_static("203.0.113.8"));
+        assert_eq!(
+            resolve_client_ip(&headers, Some(proxy), &[proxy]),
+            "203.0.113.8"
+        );
+        assert_eq!(resolve_client_ip(&headers, Some(proxy), &[]), "10.0.0.2");
+    }
+}
@@ -0,0 +1,86 @@
+use serde::{ser::SerializeStruct, Serialize, Serializer};
+
+/// Names the field a page of these items is serialized under, so paginated
+/// responses keep their established shape (`{"duas": [...], "total": ...}`).
+pub trait PageItem {
+    const FIELD: &'static str;
+}
+
+/// One page of a list endpoint's results.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Paginated<T> {
+    pub items: Vec<T>,
+    /// Matching items across all pages
+    pub total: i64,
+    pub page: u32,
+    pub per_page: u32,
+    pub total_pages: u32,
+}
+
+impl<T> Paginated<T> {
+    pub fn new(items: Vec<T>, total: i64, page: u32, per_page: u32) -> Self {
+        Self {
+            items,
+            total,
+            page,
+            per_page,
+            total_pages: total_pages(total, per_page),
+        }
+    }
+}
+
+/// Pages needed for `total` items. An empty result is still one (empty)
+/// page, so `page=1` is always in range.
+pub fn total_pages(total: i64, per_page: u32) -> u32 {
+    let per_page = i64::from(per_page.max(1));
+    let pages = (total.max(0) + per_page - 1) / per_page;
+    pages.clamp(1, i64::from(u32::MAX)) as u32
+}
+
+impl<T: PageItem + Serialize> Serialize for Paginated<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut page = serializer.serialize_struct("Paginated", 5)?;
+        page.serialize_field(T::FIELD, &self.items)?;
+        page.serialize_field("total", &self.total)?;
+        page.serialize_field("page", &self.page)?;
+        page.serialize_field("per_page", &self.per_page)?;
+        page.serialize_field("total_pages", &self.total_pages)?;
+        page.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    struct Item(u32);
+
+    impl PageItem for Item {
+        const FIELD: &'static str = "things";
+    }
+
+    impl Serialize for Item {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            self.0.serialize(serializer)
+        }
+    }
+
+    #[test]
+    fn test_total_pages_rounding() {
+        assert_eq!(total_pages(0, 20), 1);
+        assert_eq!(total_pages(20, 20), 1);
+        assert_eq!(total_pages(21, 20), 2);
+        assert_eq!(total_pages(1, 20), 1);
+        assert_eq!(total_pages(9_007_199_254_740_993, 1), u32::MAX);
+    }
+
+    #[test]
+    fn test_items_serialize_under_their_field_name() {
+        let page = Paginated::new(vec![Item(1), Item(2)], 5, 1, 2);
+        assert_eq!(
+            serde_json::to_value(&page).unwrap(),
+            json!({"things": [1, 2], "total": 5, "page": 1, "per_page": 2, "total_pages": 3})
+        );
+    }
+}
@@ -74,7 +74,9 @@ mod tests {
         let rate_limit_config = RateLimitConfig {
             requests_per_minute: 5,
             burst_size: 2,
+            requests_per_day: None,
             cleanup_interval: 60,
+            warning_threshold_percent: 10,
         };
 
         let rate_limiter = RateLimiter::new(cache, rate_limit_config);
@@ -1,98 +1,268 @@
 // Simple rate limiting implementation without external dependencies
 // This avoids potential compatibility issues with governor crate
 
+use chrono::{NaiveDate, Utc};
+use redis::Script;
 use std::collections::HashMap;
+use std::net::IpAddr;
 use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
 use tracing::{debug, warn};
 
 use crate::{cache::Cache, config::RateLimitConfig, error::ApiResult};
 
+/// Redis side of [`TokenBucket`], run atomically so instances sharing a
+/// bucket can't race. Uses the Redis clock so instance clock skew doesn't
+/// matter. A cost of 0 only reports the tokens left.
+const TOKEN_BUCKET_SCRIPT: &str = r#"
+local capacity = tonumber(ARGV[1])
+local per_ms = tonumber(ARGV[2])
+local cost = tonumber(ARGV[3])
+local time = redis.call('TIME')
+local now = tonumber(time[1]) * 1000 + math.floor(tonumber(time[2]) / 1000)
+local bucket = redis.call('HMGET', KEYS[1], 'tokens', 'updated')
+local tokens = tonumber(bucket[1]) or capacity
+local updated = tonumber(bucket[2]) or now
+tokens = math.min(capacity, tokens + math.max(0, now - updated) * per_ms)
+local allowed = 0
+if tokens >= cost then
+    tokens = tokens - cost
+    allowed = 1
+end
+redis.call('HSET', KEYS[1], 'tokens', tostring(tokens), 'updated', now)
+redis.call('PEXPIRE', KEYS[1], math.ceil((capacity - tokens) / per_ms) + 1000)
+return {allowed, math.floor(tokens)}
+"#;
+
+/// Daily counters are keyed by UTC date, so they only need to outlast their
+/// day; the slack covers clock skew between instances
+const DAY_COUNTER_TTL: Duration = Duration::from_secs(2 * 86400);
+
+/// Token bucket: holds up to `burst_size` tokens and refills continuously at
+/// `requests_per_minute`. Each request takes a token, so short bursts up to
+/// the burst size pass while the sustained rate stays at the per-minute limit.
+#[derive(Debug, Clone, Copy)]
+struct TokenBucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl TokenBucket {
+    fn full(capacity: f64, now: Instant) -> Self {
+        Self {
+            tokens: capacity,
+            updated: now,
+        }
+    }
+
+    fn refill(&mut self, now: Instant, capacity: f64, per_sec: f64) {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * per_sec).min(capacity);
+        self.updated = now;
+    }
+
+    fn try_take(&mut self, now: Instant, capacity: f64, per_sec: f64) -> bool {
+        self.refill(now, capacity, per_sec);
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// Outcome of [`SimpleRateLimiter::check`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RateDecision {
+    Allowed,
+    /// Refused; a retry after this many seconds would be allowed
+    Limited {
+        retry_after_secs: u64,
+    },
+}
+
 #[derive(Clone)]
 pub struct SimpleRateLimiter {
     cache: Cache,
     config: RateLimitConfig,
-    local_cache: std::sync::Arc<RwLock<HashMap<String, (u32, Instant)>>>,
+    trusted_proxies: std::sync::Arc<Vec<IpAddr>>,
+    script: std::sync::Arc<Script>,
+    local_cache: std::sync::Arc<RwLock<HashMap<String, TokenBucket>>>,
+    /// Requests counted today per identifier, when Redis is unavailable
+    local_days: std::sync::Arc<RwLock<HashMap<String, (NaiveDate, i64)>>>,
 }
 
 impl SimpleRateLimiter {
     pub fn new(cache: Cache, config: RateLimitConfig) -> Self {
         Self {
             cache,
+            trusted_proxies: std::sync::Arc::new(config.trusted_proxy_ips()),
             config,
+            script: std::sync::Arc::new(Script::new(TOKEN_BUCKET_SCRIPT)),
             local_cache: std::sync::Arc::new(RwLock::new(HashMap::new())),
+            local_days: std::sync::Arc::new(RwLock::new(HashMap::new())),
         }
     }
 
+    /// Proxies whose forwarding headers identify the client
+    pub fn trusted_proxies(&self) -> &[IpAddr] {
+        &self.trusted_proxies
+    }
+
+    fn capacity(&self) -> f64 {
+        self.config.burst_size.max(1) as f64
+    }
+
+    fn refill_per_sec(&self) -> f64 {
+        self.config.requests_per_minute.max(1) as f64 / 60.0
+    }
+
+    fn redis_key(identifier: &str) -> String {
+        format!("rate_limit_bucket:{}", identifier)
+    }
+
+    fn day_key(identifier: &str, day: NaiveDate) -> String {
+        format!("rate_limit_day:{}:{}", identifier, day)
+    }
+
+    /// Whether a request fits both the per-minute rate and the daily quota.
+    /// Only requests within the rate count towards the quota.
     pub async fn check_rate_limit(&self, identifier: &str) -> ApiResult<bool> {
-        // Try Redis first (for distributed rate limiting)
-        if let Ok(allowed) = self.check_redis_rate_limit(identifier).await {
-            return Ok(allowed);
+        Ok(self.check(identifier).await? == RateDecision::Allowed)
+    }
+
+    /// Like [`SimpleRateLimiter::check_rate_limit`], but a refused request
+    /// also learns when to retry: once the bucket refills a token for the
+    /// per-minute rate, or at the next UTC day for the daily quota.
+    pub async fn check(&self, identifier: &str) -> ApiResult<RateDecision> {
+        // Try Redis first (for distributed rate limiting), falling back to
+        // in-memory rate limiting
+        let allowed = match self.check_redis_rate_limit(identifier).await {
+            Ok(allowed) => allowed,
+            Err(_) => self.check_local_rate_limit(identifier).await?,
+        };
+        if !allowed {
+            return Ok(RateDecision::Limited {
+                retry_after_secs: (1.0 / self.refill_per_sec()).ceil() as u64,
+            });
         }
 
-        // Fallback to in-memory rate limiting
-        self.check_local_rate_limit(identifier).await
+        if !self.check_daily_quota(identifier).await? {
+            let now = Utc::now();
+            let tomorrow = (now.date_naive() + chrono::Duration::days(1))
+                .and_hms_opt(0, 0, 0)
+                .unwrap_or_default()
+                .and_utc();
+            return Ok(RateDecision::Limited {
+                retry_after_secs: (tomorrow - now).num_seconds().max(1) as u64,
+            });
+        }
+
+        Ok(RateDecision::Allowed)
     }
 
-    async fn check_redis_rate_limit(&self, identifier: &str) -> ApiResult<bool> {
-        let key = format!("rate_limit:{}", identifier);
-        let window = Duration::from_secs(60); // 1 minute window
+    /// Counts a request against `requests_per_day` for the current UTC day
+    async fn check_daily_quota(&self, identifier: &str) -> ApiResult<bool> {
+        let Some(limit) = self.config.requests_per_day.filter(|limit| *limit > 0) else {
+            return Ok(true);
+        };
 
-        // Get current count
-        let current_count: i64 = self.cache.increment(&key, 1, Some(window)).await?;
+        let today = Utc::now().date_naive();
+        let used = match self
+            .cache
+            .increment(&Self::day_key(identifier, today), 1, Some(DAY_COUNTER_TTL))
+            .await
+        {
+            Ok(used) => used,
+            Err(_) => self.count_local_day(identifier, today).await,
+        };
 
         debug!(
-            "Redis rate limit check for {}: {}/{}",
-            identifier, current_count, self.config.requests_per_minute
+            "Daily quota check for {}: {} of {} requests used",
+            identifier, used, limit
         );
 
-        if current_count > self.config.requests_per_minute as i64 {
+        let allowed = used <= i64::from(limit);
+        if !allowed {
             warn!(
-                "Redis rate limit exceeded for {}: {}",
-                identifier, current_count
+                "Daily quota of {} requests exceeded for {}",
+                limit, identifier
             );
-            return Ok(false);
         }
 
-        Ok(true)
+        Ok(allowed)
     }
 
-    async fn check_local_rate_limit(&self, identifier: &str) -> ApiResult<bool> {
-        let now = Instant::now();
-        let window = Duration::from_secs(60);
+    async fn count_local_day(&self, identifier: &str, today: NaiveDate) -> i64 {
+        let mut days = self.local_days.write().await;
 
-        let mut cache = self.local_cache.write().await;
+        // Yesterday's counts no longer matter
+        days.retain(|_, (day, _)| *day == today);
 
-        // Clean up old entries
-        cache.retain(|_, (_, timestamp)| now.duration_since(*timestamp) < window);
+        let (_, used) = days.entry(identifier.to_string()).or_insert((today, 0));
+        *used += 1;
+        *used
+    }
+
+    /// Runs the token bucket script, returning (allowed, tokens left)
+    async fn redis_bucket(&self, identifier: &str, cost: u32) -> ApiResult<(bool, i64)> {
+        let mut invocation = self.script.prepare_invoke();
+        invocation
+            .key(Self::redis_key(identifier))
+            .arg(self.capacity())
+            .arg(self.refill_per_sec() / 1000.0)
+            .arg(cost);
+        let (allowed, remaining): (i64, i64) = self.cache.invoke_script(&invocation).await?;
+        Ok((allowed == 1, remaining))
+    }
 
-        // Check current count for this identifier
-        let entry = cache.entry(identifier.to_string()).or_insert((0, now));
+    async fn check_redis_rate_limit(&self, identifier: &str) -> ApiResult<bool> {
+        let (allowed, remaining) = self.redis_bucket(identifier, 1).await?;
 
-        // Reset count if window has passed
-        if now.duration_since(entry.1) >= window {
-            entry.0 = 0;
-            entry.1 = now;
+        debug!(
+            "Redis rate limit check for {}: {} tokens left",
+            identifier, remaining
+        );
+
+        if !allowed {
+            warn!("Redis rate limit exceeded for {}", identifier);
         }
 
-        entry.0 += 1;
+        Ok(allowed)
+    }
+
+    async fn check_local_rate_limit(&self, identifier: &str) -> ApiResult<bool> {
+        let now = Instant::now();
+        let capacity = self.capacity();
+        let per_sec = self.refill_per_sec();
+
+        let mut cache = self.local_cache.write().await;
+
+        // Drop buckets that have refilled completely; they behave like new ones
+        let refill_time = Duration::from_secs_f64(capacity / per_sec);
+        cache.retain(|_, bucket| now.saturating_duration_since(bucket.updated) < refill_time);
+
+        let bucket = cache
+            .entry(identifier.to_string())
+            .or_insert_with(|| TokenBucket::full(capacity, now));
+        let allowed = bucket.try_take(now, capacity, per_sec);
 
         debug!(
-            "Local rate limit check for {}: {}/{}",
-            identifier, entry.0, self.config.requests_per_minute
+            "Local rate limit check for {}: {:.1} tokens left",
+            identifier, bucket.tokens
         );
 
-        if entry.0 > self.config.requests_per_minute {
-            warn!("Local rate limit exceeded for {}: {}", identifier, entry.0);
-            return Ok(false);
+        if !allowed {
+            warn!("Local rate limit exceeded for {}", identifier);
         }
 
-        Ok(true)
+        Ok(allowed)
     }
 
     pub async fn get_remaining_requests(&self, identifier: &str) -> ApiResult<i64> {
         // Try Redis first
-        if let Ok(remaining) = self.get_redis_remaining(identifier).await {
+        if let Ok((_, remaining)) = self.redis_bucket(identifier, 0).await {
             return Ok(remaining);
         }
 
@@ -100,32 +270,38 @@ impl SimpleRateLimiter {
         self.get_local_remaining(identifier).await
     }
 
-    async fn get_redis_remaining(&self, identifier: &str) -> ApiResult<i64> {
-        let key = format!("rate_limit:{}", identifier);
-
-        match self.cache.get::<i64>(&key).await? {
-            Some(count) => Ok((self.config.requests_per_minute as i64 - count).max(0)),
-            None => Ok(self.config.requests_per_minute as i64),
-        }
-    }
-
     async fn get_local_remaining(&self, identifier: &str) -> ApiResult<i64> {
         let cache = self.local_cache.read().await;
+        let capacity = self.capacity();
 
         match cache.get(identifier) {
-            Some((count, _)) => Ok((self.config.requests_per_minute as i64 - *count as i64).max(0)),
-            None => Ok(self.config.requests_per_minute as i64),
+            Some(bucket) => {
+                let mut bucket = *bucket;
+                bucket.refill(Instant::now(), capacity, self.refill_per_sec());
+                Ok(bucket.tokens.floor() as i64)
+            }
+            None => Ok(capacity as i64),
         }
     }
 
+    /// Whether `remaining` has dropped below the configured warning threshold
+    /// of the burst size
+    pub fn is_near_limit(&self, remaining: i64) -> bool {
+        let limit = self.capacity() as i64;
+        let threshold = self.config.warning_threshold_percent as i64;
+        remaining * 100 < limit * threshold
+    }
+
     pub async fn reset_rate_limit(&self, identifier: &str) -> ApiResult<()> {
         // Reset in Redis
-        let redis_key = format!("rate_limit:{}", identifier);
-        let _ = self.cache.delete(&redis_key).await;
+        let _ = self.cache.delete(&Self::redis_key(identifier)).await;
+        let today = Utc::now().date_naive();
+        let _ = self.cache.delete(&Self::day_key(identifier, today)).await;
 
         // Reset in local cache
         let mut cache = self.local_cache.write().await;
         cache.remove(identifier);
+        self.local_days.write().await.remove(identifier);
 
         Ok(())
     }
@@ -139,6 +315,32 @@ mod tests {
     use super::*;
     use crate::config::RedisConfig;
 
+    #[test]
+    fn test_burst_passes_then_rate_throttles() {
+        let (burst_size, per_sec) = (3.0, 1.0);
+        let start = Instant::now();
+        let mut bucket = TokenBucket::full(burst_size, start);
+
+        for _ in 0..3 {
+            assert!(bucket.try_take(start, burst_size, per_sec));
+        }
+        assert!(!bucket.try_take(start, burst_size, per_sec));
+
+        // Sustained rate: one more token a second, not a new burst
+        let half = start + Duration::from_millis(500);
+        assert!(!bucket.try_take(half, burst_size, per_sec));
+        let later = start + Duration::from_secs(1);
+        assert!(bucket.try_take(later, burst_size, per_sec));
+        assert!(!bucket.try_take(later, burst_size, per_sec));
+
+        // Idle time refills up to the burst size only
+        let idle = later + Duration::from_secs(60);
+        for _ in 0..3 {
+            assert!(bucket.try_take(idle, burst_size, per_sec));
+        }
+        assert!(!bucket.try_take(idle, burst_size, per_sec));
+    }
+
     #[tokio::test]
     async fn test_local_rate_limiter() {
         let redis_config = RedisConfig {
@@ -158,7 +360,10 @@ mod tests {
         let rate_limit_config = RateLimitConfig {
             requests_per_minute: 5,
             burst_size: 2,
+            requests_per_day: None,
             cleanup_interval: 60,
+            warning_threshold_percent: 10,
+            trusted_proxies: String::new(),
         };
 
         let rate_limiter = SimpleRateLimiter::new(cache, rate_limit_config);
@@ -167,4 +372,51 @@ mod tests {
         // This test would need proper mocking to work
         // For now, it's just a structure example
     }
+
+    #[tokio::test]
+    async fn test_daily_cap_blocks_with_minute_budget_left() {
+        let Ok(cache) = Cache::new(&RedisConfig {
+            url: "redis://localhost:6379".to_string(),
+            pool_max_open: 10,
+            pool_max_idle: 5,
+            pool_timeout: 30,
+            pool_expire: 300,
+        })
+        .await
+        else {
+            return;
+        };
+        let limiter = SimpleRateLimiter::new(
+            cache,
+            RateLimitConfig {
+                requests_per_minute: 1000,
+                burst_size: 100,
+                requests_per_day: Some(3),
+                cleanup_interval: 60,
+                warning_threshold_percent: 10,
+                trusted_proxies: String::new(),
+            },
+        );
+        let client = "203.0.113.252";
+        limiter.reset_rate_limit(client).await.unwrap();
+
+        for _ in 0..3 {
+            assert!(limiter.check_rate_limit(client).await.unwrap());
+        }
+        let limited = limiter.check(client).await.unwrap();
+        assert!(
+            matches!(limited, RateDecision::Limited { retry_after_secs } if (1..=86400).contains(&retry_after_secs)),
+            "{:?}",
+            limited
+        );
+        assert!(limiter.get_remaining_requests(client).await.unwrap() > 90);
+
+        // Another client has its own quota
+        assert!(limiter.check_rate_limit("203.0.113.253").await.unwrap());
+
+        limiter.reset_rate_limit(client).await.unwrap();
+        limiter.reset_rate_limit("203.0.113.253").await.unwrap();
+        assert!(limiter.check_rate_limit(client).await.unwrap());
+        limiter.reset_rate_limit(client).await.unwrap();
+    }
 }
@@ -0,0 +1,102 @@
+// One timestamp format for every response: RFC 3339 in UTC with a `Z`
+// suffix and millisecond precision, e.g. `2024-03-01T12:30:00.000Z`.
+// `to_rfc3339` writes `+00:00` and chrono's serde default writes nanoseconds,
+// so fields go through these helpers instead.
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::{Deserialize, Deserializer, Serializer};
+
+pub fn format_rfc3339(time: &DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+/// The current time, formatted for a response
+pub fn now_rfc3339() -> String {
+    format_rfc3339(&Utc::now())
+}
+
+/// `#[serde(with = "shared::time::rfc3339")]` for `DateTime<Utc>` fields.
+/// Any RFC 3339 offset is accepted when deserializing.
+pub mod rfc3339 {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(
+        time: &DateTime<Utc>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format_rfc3339(time))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<DateTime<Utc>, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        DateTime::parse_from_rfc3339(&value)
+            .map(|time| time.with_timezone(&Utc))
+            .map_err(serde::de::Error::custom)
+    }
+
+    /// The same for `Option<DateTime<Utc>>`
+    pub mod option {
+        use super::*;
+
+        pub fn serialize<S: Serializer>(
+            time: &Option<DateTime<Utc>>,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            match time {
+                Some(time) => serializer.serialize_some(&format_rfc3339(time)),
+                None => serializer.serialize_none(),
+            }
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Option<DateTime<Utc>>, D::Error> {
+            Option::<String>::deserialize(deserializer)?
+                .map(|value| {
+                    DateTime::parse_from_rfc3339(&value)
+                        .map(|time| time.with_timezone(&Utc))
+                        .map_err(serde::de::Error::custom)
+                })
+                .transpose()
+        }
+    }
+}
+
+/// Whether `value` is an RFC 3339 timestamp in the format above
+pub fn is_rfc3339_utc(value: &str) -> bool {
+    DateTime::parse_from_rfc3339(value).is_ok() && value.ends_with('Z') && value.len() == 24
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use serde::Serialize;
+
+    #[derive(Serialize, Deserialize)]
+    struct Stamped {
+        #[serde(with = "rfc3339")]
+        at: DateTime<Utc>,
+        #[serde(with = "rfc3339::option")]
+        done: Option<DateTime<Utc>>,
+    }
+
+    #[test]
+    fn test_timestamps_use_z_and_millis() {
+        let at = Utc.with_ymd_and_hms(2024, 3, 1, 12, 30, 0).unwrap();
+        let json = serde_json::to_value(Stamped { at, done: None }).unwrap();
+        assert_eq!(json["at"], "2024-03-01T12:30:00.000Z");
+        assert!(json["done"].is_null());
+        assert!(is_rfc3339_utc(&now_rfc3339()));
+
+        let parsed: Stamped = serde_json::from_value(serde_json::json!({
+            "at": "2024-03-01T15:30:00+03:00",
+            "done": "2024-03-01T12:30:00Z"
+        }))
+        .unwrap();
+        assert_eq!(parsed.at, at);
+        assert_eq!(parsed.done, Some(at));
+    }
+}
@@ -28,6 +28,18 @@ pub fn validate_latitude(lat: f64) -> Result<(), ApiError> {
     Ok(())
 }
 
+/// Prayer times and bearings are undefined at the poles themselves
+pub fn validate_not_pole(lat: f64) -> Result<(), ApiError> {
+    if lat.abs() >= 90.0 {
+        return Err(ApiError::Validation(
+            "Latitude must be strictly between -90 and 90 degrees; \
+             prayer times and the qibla are undefined at the poles"
+                .to_string(),
+        ));
+    }
+    Ok(())
+}
+
 pub fn validate_longitude(lng: f64) -> Result<(), ApiError> {
     if lng < -180.0 || lng > 180.0 {
         return Err(ApiError::Validation(
@@ -1,54 +1,192 @@
 use chrono::Utc;
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
-use shared::error::{ApiError, ApiResult};
+use shared::{
+    config::ZakatConfig,
+    error::{ApiError, ApiResult},
+};
+use tracing::warn;
 use uuid::Uuid;
 
+use crate::i18n::{Locale, LocalizedFigures, Message};
 use crate::models::{
-    BusinessZakatDetails, CropsZakatDetails, Currency, IrrigationMethod, IslamicReference,
-    LivestockZakatDetails, MetalZakatDetails, WealthZakatDetails, ZakatCalculationRequest,
+    BusinessZakatDetails, CropsZakatDetails, Currency, CurrencyCode, FitrZakatDetails, GoldItem,
+    IrrigationMethod, IslamicReference, LivestockDue, LivestockType, LivestockZakatDetails,
+    MetalItemDetails, MetalZakatDetails, NisabRate, WealthZakatDetails, ZakatCalculationRequest,
     ZakatCalculationResponse, ZakatDetails, ZakatType,
 };
+use crate::rates::{builtin_currency_rates, MetalPrices, RateProvider};
+
+/// Exchange rates (units per USD) keyed by currency code, as loaded from the
+/// `currency_rates` table at startup.
+pub type CurrencyRateTable = std::sync::Arc<std::collections::HashMap<String, Decimal>>;
 
 pub struct ZakatCalculator {
-    // Current market rates (would normally be fetched from external API)
+    // Market rates, the built-in samples unless a `RateProvider` supplies them
     gold_price_per_gram_usd: Decimal,
     silver_price_per_gram_usd: Decimal,
-    currency_rates: std::collections::HashMap<Currency, Decimal>,
+    currency_rates: std::collections::HashMap<CurrencyCode, Decimal>,
+    // Weight of one sa' per staple food, used for Zakat al-Fitr
+    fitr_sa_weights_kg: std::collections::HashMap<String, Decimal>,
+    fitr_default_sa_weight_kg: Decimal,
+    // Estimated value of one animal given, for the livestock cash alternative
+    livestock_values_usd: std::collections::HashMap<LivestockType, Decimal>,
 }
 
 impl ZakatCalculator {
     pub fn new() -> Self {
-        let mut currency_rates = std::collections::HashMap::new();
-
-        // Sample exchange rates (in production, fetch from live API)
-        currency_rates.insert(Currency::USD, dec!(1.0));
-        currency_rates.insert(Currency::EUR, dec!(0.85));
-        currency_rates.insert(Currency::GBP, dec!(0.73));
-        currency_rates.insert(Currency::SAR, dec!(3.75));
-        currency_rates.insert(Currency::AED, dec!(3.67));
-        currency_rates.insert(Currency::PKR, dec!(280.0));
-        currency_rates.insert(Currency::INR, dec!(83.0));
-        currency_rates.insert(Currency::BDT, dec!(110.0));
-        currency_rates.insert(Currency::MYR, dec!(4.7));
-        currency_rates.insert(Currency::IDR, dec!(15500.0));
-        currency_rates.insert(Currency::TRY, dec!(27.0));
-        currency_rates.insert(Currency::EGP, dec!(31.0));
-
+        let mut fitr_sa_weights_kg = std::collections::HashMap::new();
+        fitr_sa_weights_kg.insert("wheat".to_string(), dec!(2.04));
+        fitr_sa_weights_kg.insert("barley".to_string(), dec!(2.04));
+        fitr_sa_weights_kg.insert("dates".to_string(), dec!(2.5));
+        fitr_sa_weights_kg.insert("raisins".to_string(), dec!(2.5));
+        fitr_sa_weights_kg.insert("rice".to_string(), dec!(3.0));
+
+        let mut livestock_values_usd = std::collections::HashMap::new();
+        livestock_values_usd.insert(LivestockType::Sheep, dec!(250.0));
+        livestock_values_usd.insert(LivestockType::Cattle, dec!(800.0));
+        livestock_values_usd.insert(LivestockType::Camel, dec!(1500.0));
+
+        let prices = MetalPrices::builtin();
         Self {
-            gold_price_per_gram_usd: dec!(65.0),   // ~$65 per gram
-            silver_price_per_gram_usd: dec!(0.80), // ~$0.80 per gram
-            currency_rates,
+            gold_price_per_gram_usd: prices.gold_per_gram_usd,
+            silver_price_per_gram_usd: prices.silver_per_gram_usd,
+            currency_rates: builtin_currency_rates(),
+            fitr_sa_weights_kg,
+            fitr_default_sa_weight_kg: dec!(2.5),
+            livestock_values_usd,
+        }
+    }
+
+    /// Overrides the sa' weights with the configured values.
+    pub fn with_config(mut self, config: &ZakatConfig) -> Self {
+        for (staple, weight) in &config.fitr_sa_weights_kg {
+            if let Ok(weight) = Decimal::try_from(*weight) {
+                self.fitr_sa_weights_kg.insert(staple.to_lowercase(), weight);
+            }
+        }
+        if let Ok(weight) = Decimal::try_from(config.fitr_default_sa_weight_kg) {
+            self.fitr_default_sa_weight_kg = weight;
+        }
+        for (animal, value) in &config.livestock_values_usd {
+            match (LivestockType::from_name(animal), Decimal::try_from(*value)) {
+                (Some(animal), Ok(value)) if value >= Decimal::ZERO => {
+                    self.livestock_values_usd.insert(animal, value);
+                }
+                _ => tracing::warn!("Ignoring livestock value for '{}': {}", animal, value),
+            }
+        }
+        self
+    }
+
+    /// Adds or overrides exchange rates (units per USD) from the
+    /// `currency_rates` table. Malformed codes and non-positive rates are skipped.
+    pub fn with_currency_rates(
+        mut self,
+        rates: &std::collections::HashMap<String, Decimal>,
+    ) -> Self {
+        for (code, rate) in rates {
+            match CurrencyCode::parse(code) {
+                Ok(code) if *rate > Decimal::ZERO => {
+                    self.currency_rates.insert(code, *rate);
+                }
+                _ => warn!("Ignoring currency rate {} for '{}'", rate, code),
+            }
+        }
+        self
+    }
+
+    /// Takes metal prices and exchange rates from `provider`. Whatever it
+    /// fails to supply keeps its current value.
+    pub async fn with_rates_from(self, provider: &dyn RateProvider) -> Self {
+        let calculator = match provider.gold_silver().await {
+            Ok(prices) => self.with_metal_prices(prices),
+            Err(e) => {
+                warn!("Failed to get metal prices, keeping current prices: {}", e);
+                self
+            }
+        };
+        calculator.with_exchange_rates_from(provider).await
+    }
+
+    /// Takes only exchange rates from `provider`, for when metal prices come
+    /// from elsewhere. Keeps the current rates if it fails.
+    pub async fn with_exchange_rates_from(self, provider: &dyn RateProvider) -> Self {
+        match provider.fx_rates().await {
+            Ok(rates) => self.with_currency_rates(&rates),
+            Err(e) => {
+                warn!("Failed to get exchange rates, keeping current rates: {}", e);
+                self
+            }
+        }
+    }
+
+    /// Overrides the gold and silver prices. Non-positive prices are skipped.
+    pub fn with_metal_prices(mut self, prices: MetalPrices) -> Self {
+        for (metal, price, current) in [
+            (
+                "gold",
+                prices.gold_per_gram_usd,
+                &mut self.gold_price_per_gram_usd,
+            ),
+            (
+                "silver",
+                prices.silver_per_gram_usd,
+                &mut self.silver_price_per_gram_usd,
+            ),
+        ] {
+            if price > Decimal::ZERO {
+                *current = price;
+            } else {
+                warn!("Ignoring {} price {} per gram", metal, price);
+            }
         }
+        self
+    }
+
+    /// Overrides the built-in gold and silver prices with the `nisab_rates`
+    /// table, so calculations agree with what `/nisab` reports. Unknown metals
+    /// and non-positive prices are skipped.
+    pub fn with_nisab_rates(mut self, rates: &[NisabRate]) -> Self {
+        for rate in rates {
+            if rate.price_per_gram_usd <= Decimal::ZERO {
+                warn!(
+                    "Ignoring {} price {} per gram",
+                    rate.metal_type, rate.price_per_gram_usd
+                );
+                continue;
+            }
+            match rate.metal_type.to_lowercase().as_str() {
+                "gold" => self.gold_price_per_gram_usd = rate.price_per_gram_usd,
+                "silver" => self.silver_price_per_gram_usd = rate.price_per_gram_usd,
+                other => warn!("Ignoring nisab rate for unknown metal '{}'", other),
+            }
+        }
+        self
+    }
+
+    pub fn supports_currency(&self, currency: &CurrencyCode) -> bool {
+        self.currency_rates.contains_key(currency)
     }
 
     pub async fn calculate_zakat(
         &self,
         request: ZakatCalculationRequest,
     ) -> ApiResult<ZakatCalculationResponse> {
+        if !self.supports_currency(&request.currency) {
+            return Err(ApiError::invalid_input(format!(
+                "Unsupported currency: {}",
+                request.currency
+            )));
+        }
+
         let calculation_id = Uuid::new_v4();
+        let requested_locale = request
+            .lang
+            .as_deref()
+            .map(|lang| Locale::from_code(Some(lang)));
 
-        match request.calculation_type {
+        let mut response = match request.calculation_type {
             ZakatType::Wealth => self.calculate_wealth_zakat(calculation_id, request).await,
             ZakatType::Gold => self.calculate_gold_zakat(calculation_id, request).await,
             ZakatType::Silver => self.calculate_silver_zakat(calculation_id, request).await,
@@ -58,7 +196,13 @@ impl ZakatCalculator {
                     .await
             }
             ZakatType::Crops => self.calculate_crops_zakat(calculation_id, request).await,
-        }
+            ZakatType::Fitr => self.calculate_fitr_zakat(calculation_id, request).await,
+        }?;
+
+        // Display strings are only added when a language was asked for
+        response.formatted =
+            requested_locale.map(|locale| LocalizedFigures::new(&response, locale));
+        Ok(response)
     }
 
     async fn calculate_wealth_zakat(
@@ -66,7 +210,7 @@ impl ZakatCalculator {
         calculation_id: Uuid,
         request: ZakatCalculationRequest,
     ) -> ApiResult<ZakatCalculationResponse> {
-        let amount_in_usd = self.convert_to_usd(request.amount, request.currency)?;
+        let amount_in_usd = self.convert_to_usd(request.amount, &request.currency)?;
 
         // Nisab for wealth is equivalent to 85g of gold or 595g of silver (whichever is lower)
         let gold_nisab_usd = dec!(85.0) * self.gold_price_per_gram_usd;
@@ -90,22 +234,31 @@ impl ZakatCalculator {
             years_held: None, // Could be added as input
         };
 
-        let recommendations = self.get_wealth_recommendations(amount_in_usd, nisab_threshold);
+        let locale = Locale::from_code(request.lang.as_deref());
+        let recommendations =
+            self.get_wealth_recommendations(amount_in_usd, nisab_threshold, locale);
         let islamic_references = self.get_wealth_references();
 
+        let nisab_threshold = self.convert_from_usd(nisab_threshold, &request.currency)?;
+        let (amount_below_nisab, amount_above_nisab) =
+            nisab_distance(request.amount, nisab_threshold);
+
         Ok(ZakatCalculationResponse {
             calculation_id,
             calculation_type: ZakatType::Wealth,
             input_amount: request.amount,
-            currency: request.currency,
-            nisab_threshold: self.convert_from_usd(nisab_threshold, request.currency)?,
-            zakat_due: self.convert_from_usd(zakat_due, request.currency)?,
+            nisab_threshold,
+            zakat_due: self.convert_from_usd(zakat_due, &request.currency)?,
+            currency: request.currency.into(),
             zakat_percentage: zakat_rate,
             is_zakat_applicable,
+            amount_below_nisab,
+            amount_above_nisab,
             calculation_details: ZakatDetails::Wealth(details),
             recommendations,
             islamic_references,
             calculation_time: Utc::now(),
+            formatted: None,
         })
     }
 
@@ -114,13 +267,42 @@ impl ZakatCalculator {
         calculation_id: Uuid,
         request: ZakatCalculationRequest,
     ) -> ApiResult<ZakatCalculationResponse> {
-        let weight_grams = request
-            .gold_weight_grams
-            .ok_or_else(|| ApiError::invalid_input("Gold weight in grams is required"))?;
+        let (pieces, itemized) = match request.gold_items {
+            Some(ref items) if !items.is_empty() => (items.clone(), true),
+            _ => {
+                let weight_grams = request
+                    .gold_weight_grams
+                    .ok_or_else(|| ApiError::invalid_input("Gold weight in grams is required"))?;
+                let karats = request.gold_purity_karats.unwrap_or(24);
+                (
+                    vec![GoldItem {
+                        weight_grams,
+                        karats,
+                    }],
+                    false,
+                )
+            }
+        };
 
-        let purity_karats = request.gold_purity_karats.unwrap_or(24);
-        let purity_percentage = self.karat_to_purity_percentage(purity_karats)?;
-        let pure_weight_grams = weight_grams * purity_percentage / dec!(100.0);
+        let items = pieces
+            .iter()
+            .map(|piece| {
+                let purity_percentage = self.karat_to_purity_percentage(piece.karats)?;
+                let pure_weight_grams = piece.weight_grams * purity_percentage / dec!(100.0);
+                Ok(MetalItemDetails {
+                    weight_grams: piece.weight_grams,
+                    karats: piece.karats,
+                    purity_percentage,
+                    pure_weight_grams,
+                    value: pure_weight_grams * self.gold_price_per_gram_usd,
+                })
+            })
+            .collect::<ApiResult<Vec<_>>>()?;
+
+        let weight_grams: Decimal = items.iter().map(|item| item.weight_grams).sum();
+        let pure_weight_grams: Decimal = items.iter().map(|item| item.pure_weight_grams).sum();
+        // Weighted average over the pieces
+        let purity_percentage = (pure_weight_grams * dec!(100.0) / weight_grams).round_dp(2);
 
         let nisab_weight_grams = dec!(85.0); // 85 grams of pure gold
         let is_zakat_applicable = pure_weight_grams >= nisab_weight_grams;
@@ -140,24 +322,38 @@ impl ZakatCalculator {
             current_price_per_gram: self.gold_price_per_gram_usd,
             total_value: total_value_usd,
             nisab_weight_grams,
+            items: if itemized { items } else { Vec::new() },
         };
 
-        let recommendations = self.get_gold_recommendations(pure_weight_grams, nisab_weight_grams);
+        let locale = Locale::from_code(request.lang.as_deref());
+        let recommendations =
+            self.get_gold_recommendations(pure_weight_grams, nisab_weight_grams, locale);
         let islamic_references = self.get_gold_references();
 
+        let (amount_below_nisab, amount_above_nisab) = nisab_distance(
+            self.convert_from_usd(total_value_usd, &request.currency)?,
+            self.convert_from_usd(
+                nisab_weight_grams * self.gold_price_per_gram_usd,
+                &request.currency,
+            )?,
+        );
+
         Ok(ZakatCalculationResponse {
             calculation_id,
             calculation_type: ZakatType::Gold,
             input_amount: weight_grams,
-            currency: Currency::USD, // Gold calculations in USD
+            currency: Currency::USD.into(), // Gold calculations in USD
             nisab_threshold: nisab_weight_grams,
-            zakat_due: self.convert_from_usd(zakat_due_usd, request.currency)?,
+            zakat_due: self.convert_from_usd(zakat_due_usd, &request.currency)?,
             zakat_percentage: zakat_rate,
             is_zakat_applicable,
+            amount_below_nisab,
+            amount_above_nisab,
             calculation_details: ZakatDetails::Gold(details),
             recommendations,
             islamic_references,
             calculation_time: Utc::now(),
+            formatted: None,
         })
     }
 
@@ -191,25 +387,38 @@ impl ZakatCalculator {
             current_price_per_gram: self.silver_price_per_gram_usd,
             total_value: total_value_usd,
             nisab_weight_grams,
+            items: Vec::new(),
         };
 
+        let locale = Locale::from_code(request.lang.as_deref());
         let recommendations =
-            self.get_silver_recommendations(pure_weight_grams, nisab_weight_grams);
+            self.get_silver_recommendations(pure_weight_grams, nisab_weight_grams, locale);
         let islamic_references = self.get_silver_references();
 
+        let (amount_below_nisab, amount_above_nisab) = nisab_distance(
+            self.convert_from_usd(total_value_usd, &request.currency)?,
+            self.convert_from_usd(
+                nisab_weight_grams * self.silver_price_per_gram_usd,
+                &request.currency,
+            )?,
+        );
+
         Ok(ZakatCalculationResponse {
             calculation_id,
             calculation_type: ZakatType::Silver,
             input_amount: weight_grams,
-            currency: Currency::USD,
+            currency: Currency::USD.into(),
             nisab_threshold: nisab_weight_grams,
-            zakat_due: self.convert_from_usd(zakat_due_usd, request.currency)?,
+            zakat_due: self.convert_from_usd(zakat_due_usd, &request.currency)?,
             zakat_percentage: zakat_rate,
             is_zakat_applicable,
+            amount_below_nisab,
+            amount_above_nisab,
             calculation_details: ZakatDetails::Silver(details),
             recommendations,
             islamic_references,
             calculation_time: Utc::now(),
+            formatted: None,
         })
     }
 
@@ -226,7 +435,7 @@ impl ZakatCalculator {
         let zakatable_amount = net_assets + inventory + request.amount; // Cash + net assets + inventory
 
         let nisab_usd = dec!(85.0) * self.gold_price_per_gram_usd;
-        let zakatable_amount_usd = self.convert_to_usd(zakatable_amount, request.currency)?;
+        let zakatable_amount_usd = self.convert_to_usd(zakatable_amount, &request.currency)?;
 
         let is_zakat_applicable = zakatable_amount_usd >= nisab_usd;
         let zakat_rate = dec!(2.5);
@@ -245,22 +454,30 @@ impl ZakatCalculator {
             zakatable_amount,
         };
 
-        let recommendations = self.get_business_recommendations(&details);
+        let locale = Locale::from_code(request.lang.as_deref());
+        let recommendations = self.get_business_recommendations(&details, locale);
         let islamic_references = self.get_business_references();
 
+        let nisab_threshold = self.convert_from_usd(nisab_usd, &request.currency)?;
+        let (amount_below_nisab, amount_above_nisab) =
+            nisab_distance(zakatable_amount, nisab_threshold);
+
         Ok(ZakatCalculationResponse {
             calculation_id,
             calculation_type: ZakatType::Business,
             input_amount: request.amount,
-            currency: request.currency,
-            nisab_threshold: self.convert_from_usd(nisab_usd, request.currency)?,
-            zakat_due: self.convert_from_usd(zakat_due_usd, request.currency)?,
+            nisab_threshold,
+            zakat_due: self.convert_from_usd(zakat_due_usd, &request.currency)?,
+            currency: request.currency.into(),
             zakat_percentage: zakat_rate,
             is_zakat_applicable,
+            amount_below_nisab,
+            amount_above_nisab,
             calculation_details: ZakatDetails::Business(details),
             recommendations,
             islamic_references,
             calculation_time: Utc::now(),
+            formatted: None,
         })
     }
 
@@ -276,13 +493,36 @@ impl ZakatCalculator {
         let total_animals = cattle + sheep_goats + camels;
 
         // Simplified livestock zakat calculation
-        let zakat_animals_due = self.calculate_livestock_zakat_animals(cattle, sheep_goats, camels);
+        let client_values = request.livestock_values.clone().unwrap_or_default();
+        let dues = self
+            .calculate_livestock_zakat_animals(cattle, sheep_goats, camels)
+            .into_iter()
+            .map(|(animal, count)| {
+                // Client values are in the request currency, estimates in USD
+                let value_per_animal = match client_values.get(animal) {
+                    Some(value) => self.convert_to_usd(value, &request.currency)?,
+                    None => self
+                        .livestock_values_usd
+                        .get(&animal)
+                        .copied()
+                        .unwrap_or_default(),
+                };
+                Ok(LivestockDue {
+                    animal,
+                    count,
+                    value_per_animal,
+                })
+            })
+            .collect::<ApiResult<Vec<_>>>()?;
+        let zakat_animals_due = dues.iter().map(|due| due.count).sum::<u32>();
         let is_zakat_applicable = zakat_animals_due > 0;
 
-        // Estimate cash value (simplified)
-        let estimated_value_per_animal = dec!(500.0); // USD
         let alternative_cash_value = if zakat_animals_due > 0 {
-            Some(Decimal::from(zakat_animals_due) * estimated_value_per_animal)
+            Some(
+                dues.iter()
+                    .map(|due| Decimal::from(due.count) * due.value_per_animal)
+                    .sum(),
+            )
         } else {
             None
         };
@@ -294,24 +534,29 @@ impl ZakatCalculator {
             total_animals,
             zakat_animals_due,
             alternative_cash_value,
+            dues,
         };
 
-        let recommendations = self.get_livestock_recommendations(&details);
+        let locale = Locale::from_code(request.lang.as_deref());
+        let recommendations = self.get_livestock_recommendations(&details, locale);
         let islamic_references = self.get_livestock_references();
 
         Ok(ZakatCalculationResponse {
             calculation_id,
             calculation_type: ZakatType::Livestock,
             input_amount: Decimal::from(total_animals),
-            currency: Currency::USD,
+            currency: Currency::USD.into(),
             nisab_threshold: dec!(40.0), // Minimum threshold varies by animal type
             zakat_due: alternative_cash_value.unwrap_or(dec!(0.0)),
             zakat_percentage: dec!(0.0), // Variable based on count
             is_zakat_applicable,
+            amount_below_nisab: None,
+            amount_above_nisab: None,
             calculation_details: ZakatDetails::Livestock(details),
             recommendations,
             islamic_references,
             calculation_time: Utc::now(),
+            formatted: None,
         })
     }
 
@@ -330,7 +575,7 @@ impl ZakatCalculator {
             IrrigationMethod::Manual => dec!(5.0),   // 5% for irrigated crops
         };
 
-        let harvest_value_usd = self.convert_to_usd(request.amount, request.currency)?;
+        let harvest_value_usd = self.convert_to_usd(request.amount, &request.currency)?;
         let nisab_usd = dec!(653.0); // Approximately 5 Awsuq (about 653 kg of dates/wheat value)
 
         let is_zakat_applicable = harvest_value_usd >= nisab_usd;
@@ -349,36 +594,100 @@ impl ZakatCalculator {
             net_harvest_value: request.amount,
         };
 
-        let recommendations = self.get_crops_recommendations(&details);
+        let locale = Locale::from_code(request.lang.as_deref());
+        let recommendations = self.get_crops_recommendations(&details, locale);
         let islamic_references = self.get_crops_references();
 
+        let nisab_threshold = self.convert_from_usd(nisab_usd, &request.currency)?;
+        let (amount_below_nisab, amount_above_nisab) =
+            nisab_distance(request.amount, nisab_threshold);
+
         Ok(ZakatCalculationResponse {
             calculation_id,
             calculation_type: ZakatType::Crops,
             input_amount: request.amount,
-            currency: request.currency,
-            nisab_threshold: self.convert_from_usd(nisab_usd, request.currency)?,
-            zakat_due: self.convert_from_usd(zakat_due_usd, request.currency)?,
+            nisab_threshold,
+            zakat_due: self.convert_from_usd(zakat_due_usd, &request.currency)?,
+            currency: request.currency.into(),
             zakat_percentage,
             is_zakat_applicable,
+            amount_below_nisab,
+            amount_above_nisab,
             calculation_details: ZakatDetails::Crops(details),
             recommendations,
             islamic_references,
             calculation_time: Utc::now(),
+            formatted: None,
+        })
+    }
+
+    async fn calculate_fitr_zakat(
+        &self,
+        calculation_id: Uuid,
+        request: ZakatCalculationRequest,
+    ) -> ApiResult<ZakatCalculationResponse> {
+        let household_members = request.household_members.unwrap_or(1);
+        let staple_price_per_kg = request
+            .staple_price_per_kg
+            .ok_or_else(|| ApiError::invalid_input("Staple price per kg is required"))?;
+        let staple_food = request
+            .staple_food
+            .map(|staple| staple.to_lowercase())
+            .unwrap_or_else(|| "wheat".to_string());
+
+        let sa_weight_kg = self.sa_weight_kg(&staple_food);
+        let total_weight_kg = sa_weight_kg * Decimal::from(household_members);
+        let zakat_due = total_weight_kg * staple_price_per_kg;
+
+        let details = FitrZakatDetails {
+            household_members,
+            staple_food,
+            sa_weight_kg,
+            total_weight_kg,
+            staple_price_per_kg,
+        };
+
+        let locale = Locale::from_code(request.lang.as_deref());
+        let recommendations = self.get_fitr_recommendations(&details, locale);
+        let islamic_references = self.get_fitr_references();
+
+        Ok(ZakatCalculationResponse {
+            calculation_id,
+            calculation_type: ZakatType::Fitr,
+            input_amount: Decimal::from(household_members),
+            currency: request.currency.into(),
+            nisab_threshold: dec!(0.0), // Due on anyone with food beyond the day's needs
+            zakat_due,
+            zakat_percentage: dec!(0.0), // Fixed measure per person
+            is_zakat_applicable: household_members > 0,
+            amount_below_nisab: None,
+            amount_above_nisab: None,
+            calculation_details: ZakatDetails::Fitr(details),
+            recommendations,
+            islamic_references,
+            calculation_time: Utc::now(),
+            formatted: None,
         })
     }
 
     // Helper methods
-    fn convert_to_usd(&self, amount: Decimal, currency: Currency) -> ApiResult<Decimal> {
-        let rate = self.currency_rates.get(&currency).ok_or_else(|| {
-            ApiError::invalid_input(format!("Unsupported currency: {:?}", currency))
+    fn sa_weight_kg(&self, staple_food: &str) -> Decimal {
+        self.fitr_sa_weights_kg
+            .get(staple_food)
+            .copied()
+            .unwrap_or(self.fitr_default_sa_weight_kg)
+    }
+
+    fn convert_to_usd(&self, amount: Decimal, currency: &CurrencyCode) -> ApiResult<Decimal> {
+        let rate = self.currency_rates.get(currency).ok_or_else(|| {
+            ApiError::invalid_input(format!("Unsupported currency: {}", currency))
         })?;
         Ok(amount / rate)
     }
 
-    fn convert_from_usd(&self, amount_usd: Decimal, currency: Currency) -> ApiResult<Decimal> {
-        let rate = self.currency_rates.get(&currency).ok_or_else(|| {
-            ApiError::invalid_input(format!("Unsupported currency: {:?}", currency))
+    fn convert_from_usd(&self, amount_usd: Decimal, currency: &CurrencyCode) -> ApiResult<Decimal> {
+        let rate = self.currency_rates.get(currency).ok_or_else(|| {
+            ApiError::invalid_input(format!("Unsupported currency: {}", currency))
         })?;
         Ok(amount_usd * rate)
     }
@@ -396,187 +705,558 @@ impl ZakatCalculator {
         }
     }
 
-    fn calculate_livestock_zakat_animals(&self, cattle: u32, sheep_goats: u32, camels: u32) -> u32 {
-        let mut total_due = 0;
+    /// Animals due by the type they're given in
+    fn calculate_livestock_zakat_animals(
+        &self,
+        cattle: u32,
+        sheep_goats: u32,
+        camels: u32,
+    ) -> Vec<(LivestockType, u32)> {
+        let mut dues = Vec::new();
 
         // Simplified calculation - in reality, each type has specific thresholds
         if sheep_goats >= 40 {
-            total_due += 1; // 1 sheep for 40-120 sheep
+            dues.push((LivestockType::Sheep, 1)); // 1 sheep for 40-120 sheep
         }
         if cattle >= 30 {
-            total_due += 1; // 1 calf for 30-39 cattle
-        }
-        if camels >= 5 {
-            total_due += 1; // 1 sheep for 5-9 camels
+            dues.push((LivestockType::Cattle, 1)); // 1 calf for 30-39 cattle
         }
+        dues.extend(camel_due(camels));
 
-        total_due
+        dues
     }
 
     // Recommendation methods
-    fn get_wealth_recommendations(&self, amount_usd: Decimal, nisab_usd: Decimal) -> Vec<String> {
+    fn get_wealth_recommendations(
+        &self,
+        amount_usd: Decimal,
+        nisab_usd: Decimal,
+        locale: Locale,
+    ) -> Vec<String> {
         let mut recommendations = Vec::new();
 
         if amount_usd < nisab_usd {
-            recommendations.push(
-                "Your wealth is below the nisab threshold, so Zakat is not required.".to_string(),
-            );
+            recommendations.push(locale.text(Message::WealthBelowNisab));
         } else {
-            recommendations.push("Zakat is due on your wealth. Pay 2.5% annually.".to_string());
-            recommendations
-                .push("Ensure you've held this wealth for a full lunar year (Hawl).".to_string());
+            recommendations.push(locale.text(Message::WealthDue));
+            recommendations.push(locale.text(Message::WealthHawl));
         }
 
-        recommendations.push(
-            "Consider consulting with a qualified Islamic scholar for complex cases.".to_string(),
-        );
+        recommendations.push(locale.text(Message::ConsultScholar));
         recommendations
     }
 
-    fn get_gold_recommendations(&self, weight: Decimal, nisab: Decimal) -> Vec<String> {
+    fn get_gold_recommendations(
+        &self,
+        weight: Decimal,
+        nisab: Decimal,
+        locale: Locale,
+    ) -> Vec<String> {
         let mut recommendations = Vec::new();
 
         if weight < nisab {
-            recommendations
-                .push("Your gold is below the nisab threshold (85g of pure gold).".to_string());
+            recommendations.push(locale.text(Message::GoldBelowNisab));
         } else {
-            recommendations.push(
-                "Zakat is due on your gold. Consider the purity when calculating.".to_string(),
-            );
+            recommendations.push(locale.text(Message::GoldDue));
         }
 
-        recommendations.push(
-            "Jewelry worn regularly may have different rulings - consult a scholar.".to_string(),
-        );
+        recommendations.push(locale.text(Message::JewelryRulings));
         recommendations
     }
 
-    fn get_silver_recommendations(&self, weight: Decimal, nisab: Decimal) -> Vec<String> {
+    fn get_silver_recommendations(
+        &self,
+        weight: Decimal,
+        nisab: Decimal,
+        locale: Locale,
+    ) -> Vec<String> {
         let mut recommendations = Vec::new();
 
         if weight < nisab {
-            recommendations.push(
-                "Your silver is below the nisab threshold (595g of pure silver).".to_string(),
-            );
+            recommendations.push(locale.text(Message::SilverBelowNisab));
         } else {
-            recommendations.push("Zakat is due on your silver holdings.".to_string());
+            recommendations.push(locale.text(Message::SilverDue));
         }
 
         recommendations
     }
 
-    fn get_business_recommendations(&self, _details: &BusinessZakatDetails) -> Vec<String> {
+    fn get_business_recommendations(
+        &self,
+        _details: &BusinessZakatDetails,
+        locale: Locale,
+    ) -> Vec<String> {
         let mut recommendations = Vec::new();
 
-        recommendations.push(
-            "Include all business assets, inventory, and cash in Zakat calculation.".to_string(),
-        );
-        recommendations.push("Deduct legitimate business liabilities and debts.".to_string());
-        recommendations
-            .push("Calculate Zakat annually on your business lunar year-end.".to_string());
+        recommendations.push(locale.text(Message::BusinessIncludeAssets));
+        recommendations.push(locale.text(Message::BusinessDeductLiabilities));
+        recommendations.push(locale.text(Message::BusinessAnnual));
 
         recommendations
     }
 
-    fn get_livestock_recommendations(&self, details: &LivestockZakatDetails) -> Vec<String> {
+    fn get_livestock_recommendations(
+        &self,
+        details: &LivestockZakatDetails,
+        locale: Locale,
+    ) -> Vec<String> {
         let mut recommendations = Vec::new();
 
         if details.total_animals == 0 {
-            recommendations.push("No livestock for Zakat calculation.".to_string());
+            recommendations.push(locale.text(Message::LivestockNone));
         } else {
-            recommendations
-                .push("Livestock Zakat has specific thresholds for each animal type.".to_string());
-            recommendations
-                .push("Animals must be grazing freely for most of the year.".to_string());
-            recommendations
-                .push("Consult detailed Islamic texts for precise calculations.".to_string());
+            recommendations.push(locale.text(Message::LivestockThresholds));
+            recommendations.push(locale.text(Message::LivestockGrazing));
+            recommendations.push(locale.text(Message::LivestockConsultTexts));
         }
 
         recommendations
     }
 
-    fn get_crops_recommendations(&self, details: &CropsZakatDetails) -> Vec<String> {
+    fn get_crops_recommendations(
+        &self,
+        details: &CropsZakatDetails,
+        locale: Locale,
+    ) -> Vec<String> {
         let mut recommendations = Vec::new();
 
         match details.irrigation_method {
             IrrigationMethod::Natural => {
-                recommendations.push("Rain-fed crops: 10% Zakat rate applies.".to_string());
+                recommendations.push(locale.text(Message::CropsRainFed));
             }
             IrrigationMethod::Manual => {
-                recommendations.push("Irrigated crops: 5% Zakat rate applies.".to_string());
+                recommendations.push(locale.text(Message::CropsIrrigated));
             }
         }
 
-        recommendations.push("Zakat is due at harvest time, not annually.".to_string());
-        recommendations
-            .push("Only staple crops like wheat, rice, dates typically require Zakat.".to_string());
+        recommendations.push(locale.text(Message::CropsAtHarvest));
+        recommendations.push(locale.text(Message::CropsStaples));
 
         recommendations
     }
 
+    fn get_fitr_recommendations(&self, details: &FitrZakatDetails, locale: Locale) -> Vec<String> {
+        vec![
+            locale.render(
+                Message::FitrSaWeight,
+                &[
+                    ("staple", &details.staple_food),
+                    ("kg", &details.sa_weight_kg.to_string()),
+                ],
+            ),
+            locale.text(Message::FitrBeforeEid),
+            locale.text(Message::FitrScholarsDiffer),
+        ]
+    }
+
     // Islamic reference methods
     fn get_wealth_references(&self) -> Vec<IslamicReference> {
-        vec![IslamicReference {
+        verified_references(vec![IslamicReference {
             source: "Quran".to_string(),
             reference: "Surah At-Tawbah 9:103".to_string(),
             arabic_text: Some("خُذْ مِنْ أَمْوَالِهِمْ صَدَقَةً تُطَهِّرُهُمْ وَتُزَكِّيهِمْ بِهَا".to_string()),
             translation:
                 "Take from their wealth a charity by which you purify them and cause them increase"
                     .to_string(),
-        }]
+            collection: Some("Quran".to_string()),
+            book_number: Some(9),
+            hadith_number: None,
+            external_url: Some("https://quran.com/9/103".to_string()),
+        }])
     }
 
     fn get_gold_references(&self) -> Vec<IslamicReference> {
-        vec![IslamicReference {
+        verified_references(vec![IslamicReference {
             source: "Hadith".to_string(),
             reference: "Sunan Abu Dawood".to_string(),
             arabic_text: None,
             translation:
                 "No Zakat is due on gold until it reaches 20 dinars (approximately 85 grams)"
                     .to_string(),
-        }]
+            collection: Some("Sunan Abi Dawud".to_string()),
+            book_number: Some(9),
+            hadith_number: Some(1573),
+            external_url: Some("https://sunnah.com/abudawud:1573".to_string()),
+        }])
     }
 
     fn get_silver_references(&self) -> Vec<IslamicReference> {
-        vec![IslamicReference {
+        verified_references(vec![IslamicReference {
             source: "Hadith".to_string(),
             reference: "Sahih Bukhari".to_string(),
             arabic_text: None,
             translation:
                 "No Zakat is due on silver until it reaches 200 dirhams (approximately 595 grams)"
                     .to_string(),
-        }]
+            collection: Some("Sahih al-Bukhari".to_string()),
+            book_number: Some(24),
+            hadith_number: Some(1447),
+            external_url: Some("https://sunnah.com/bukhari:1447".to_string()),
+        }])
     }
 
     fn get_business_references(&self) -> Vec<IslamicReference> {
-        vec![IslamicReference {
+        verified_references(vec![IslamicReference {
             source: "Islamic Jurisprudence".to_string(),
             reference: "Fiqh al-Zakat".to_string(),
             arabic_text: None,
             translation: "Business assets and inventory are subject to Zakat if held for trade"
                 .to_string(),
-        }]
+            collection: None,
+            book_number: None,
+            hadith_number: None,
+            external_url: None,
+        }])
     }
 
     fn get_livestock_references(&self) -> Vec<IslamicReference> {
-        vec![IslamicReference {
+        verified_references(vec![IslamicReference {
             source: "Hadith".to_string(),
             reference: "Sahih Bukhari".to_string(),
             arabic_text: None,
             translation:
                 "On grazing livestock, specific Zakat rates apply based on numbers and type"
                     .to_string(),
-        }]
+            collection: Some("Sahih al-Bukhari".to_string()),
+            book_number: Some(24),
+            hadith_number: Some(1454),
+            external_url: Some("https://sunnah.com/bukhari:1454".to_string()),
+        }])
     }
 
     fn get_crops_references(&self) -> Vec<IslamicReference> {
-        vec![
+        verified_references(vec![
             IslamicReference {
                 source: "Hadith".to_string(),
                 reference: "Sahih Bukhari".to_string(),
                 arabic_text: None,
                 translation: "On crops watered by rain or springs: one-tenth. On crops watered by irrigation: one-twentieth".to_string(),
+                collection: Some("Sahih al-Bukhari".to_string()),
+                book_number: Some(24),
+                hadith_number: Some(1483),
+                external_url: Some("https://sunnah.com/bukhari:1483".to_string()),
             },
-        ]
+        ])
+    }
+
+    fn get_fitr_references(&self) -> Vec<IslamicReference> {
+        verified_references(vec![IslamicReference {
+            source: "Hadith".to_string(),
+            reference: "Sahih Bukhari".to_string(),
+            arabic_text: None,
+            translation: "The Prophet enjoined Zakat al-Fitr of one sa' of dates or one sa' of barley on every Muslim"
+                .to_string(),
+            collection: Some("Sahih al-Bukhari".to_string()),
+            book_number: Some(24),
+            hadith_number: Some(1503),
+            external_url: Some("https://sunnah.com/bukhari:1503".to_string()),
+        }])
+    }
+}
+
+/// Shortfall below, or surplus above, the nisab for a value in the same
+/// currency; exactly one of the pair is set.
+fn nisab_distance(value: Decimal, nisab: Decimal) -> (Option<Decimal>, Option<Decimal>) {
+    if value < nisab {
+        (Some(nisab - value), None)
+    } else {
+        (None, Some(value - nisab))
+    }
+}
+
+/// Drops any external link that is not a well-formed https URL so that
+/// clients never receive a broken reference link.
+fn verified_references(references: Vec<IslamicReference>) -> Vec<IslamicReference> {
+    references
+        .into_iter()
+        .map(|mut reference| {
+            if !reference.has_valid_external_url() {
+                tracing::warn!(
+                    "Dropping invalid reference URL for {}: {:?}",
+                    reference.reference,
+                    reference.external_url
+                );
+                reference.external_url = None;
+            }
+            reference
+        })
+        .collect()
+}
+
+/// Animals due on a herd of camels. Below 25 a sheep is given per 5 camels;
+/// from 25 the due is a young she-camel whose age rises with the herd (bint
+/// makhad, bint labun, hiqqah, jadha'ah), then two; above 120 it is a bint
+/// labun per 40 and a hiqqah per 50, combined to cover as many camels as
+/// possible. All she-camels are valued as camels.
+fn camel_due(camels: u32) -> Option<(LivestockType, u32)> {
+    let she_camels = match camels {
+        0..=4 => return None,
+        5..=24 => return Some((LivestockType::Sheep, camels / 5)),
+        25..=35 => 1,  // bint makhad
+        36..=45 => 1,  // bint labun
+        46..=60 => 1,  // hiqqah
+        61..=75 => 1,  // jadha'ah
+        76..=90 => 2,  // two bint labun
+        91..=120 => 2, // two hiqqah
+        _ => {
+            // Prefer more hiqqahs when two splits cover the same count
+            let (forties, fifties) = (0..=camels / 50)
+                .map(|fifties| ((camels - fifties * 50) / 40, fifties))
+                .max_by_key(|&(forties, fifties)| (forties * 40 + fifties * 50, fifties))
+                .unwrap_or_default();
+            forties + fifties
+        }
+    };
+    Some((LivestockType::Camel, she_camels))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::LivestockValues;
+    use shared::config::NisabSource;
+
+    #[test]
+    fn test_gold_reference_has_structured_hadith_number() {
+        let calculator = ZakatCalculator::new();
+        let references = calculator.get_gold_references();
+
+        assert_eq!(references[0].collection.as_deref(), Some("Sunan Abi Dawud"));
+        assert_eq!(references[0].hadith_number, Some(1573));
+        assert!(references[0].external_url.is_some());
+    }
+
+    fn fitr_request(staple_food: &str) -> ZakatCalculationRequest {
+        ZakatCalculationRequest {
+            calculation_type: ZakatType::Fitr,
+            amount: dec!(0.0),
+            currency: Currency::USD.into(),
+            gold_weight_grams: None,
+            silver_weight_grams: None,
+            gold_purity_karats: None,
+            gold_items: None,
+            cattle_count: None,
+            sheep_goat_count: None,
+            camel_count: None,
+            livestock_values: None,
+            business_assets: None,
+            business_liabilities: None,
+            inventory_value: None,
+            crop_type: None,
+            irrigation_method: None,
+            household_members: Some(4),
+            staple_food: Some(staple_food.to_string()),
+            staple_price_per_kg: Some(dec!(1.5)),
+            user_id: None,
+            save_calculation: None,
+            dry_run: false,
+            lang: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fitr_total_scales_with_sa_weight() {
+        let mut config = ZakatConfig {
+            fitr_sa_weights_kg: std::collections::HashMap::new(),
+            fitr_default_sa_weight_kg: 2.5,
+            nisab_cache_soft_ttl_secs: 3600,
+            nisab_cache_hard_ttl_secs: 86400,
+            nisab_source: NisabSource::Builtin,
+            rate_provider_url: None,
+            livestock_values_usd: std::collections::HashMap::new(),
+        };
+        config.fitr_sa_weights_kg.insert("wheat".to_string(), 2.0);
+        let base = ZakatCalculator::new()
+            .with_config(&config)
+            .calculate_zakat(fitr_request("wheat"))
+            .await
+            .unwrap();
+
+        config.fitr_sa_weights_kg.insert("wheat".to_string(), 3.0);
+        let heavier = ZakatCalculator::new()
+            .with_config(&config)
+            .calculate_zakat(fitr_request("wheat"))
+            .await
+            .unwrap();
+
+        assert_eq!(base.zakat_due, dec!(12.0)); // 4 people * 2kg * 1.5
+        assert_eq!(heavier.zakat_due, base.zakat_due * dec!(1.5));
+    }
+
+    #[tokio::test]
+    async fn test_amount_just_below_nisab_shows_shortfall() {
+        // Builtin silver nisab: 595g * $0.80 = $476
+        let mut request = fitr_request("wheat");
+        request.calculation_type = ZakatType::Wealth;
+        request.amount = dec!(475.50);
+        let below = ZakatCalculator::new()
+            .calculate_zakat(request)
+            .await
+            .unwrap();
+
+        assert!(!below.is_zakat_applicable);
+        assert_eq!(below.amount_below_nisab, Some(dec!(0.50)));
+        assert_eq!(below.amount_above_nisab, None);
+
+        let mut request = fitr_request("wheat");
+        request.calculation_type = ZakatType::Wealth;
+        request.amount = dec!(500.0);
+        let above = ZakatCalculator::new()
+            .calculate_zakat(request)
+            .await
+            .unwrap();
+        assert_eq!(above.amount_below_nisab, None);
+        assert_eq!(above.amount_above_nisab, Some(dec!(24.0)));
+
+        let json = serde_json::to_value(&above).unwrap();
+        assert!(shared::time::is_rfc3339_utc(
+            json["calculation_time"].as_str().unwrap()
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_camel_due_uses_camel_value() {
+        let mut request = fitr_request("wheat");
+        request.calculation_type = ZakatType::Livestock;
+        request.camel_count = Some(30);
+        let response = ZakatCalculator::new()
+            .calculate_zakat(request.clone())
+            .await
+            .unwrap();
+
+        let ZakatDetails::Livestock(details) = response.calculation_details else {
+            panic!("expected livestock details");
+        };
+        assert_eq!(details.dues.len(), 1);
+        assert_eq!(details.dues[0].animal, LivestockType::Camel);
+        assert_eq!(details.alternative_cash_value, Some(dec!(1500.0)));
+        assert_eq!(response.zakat_due, dec!(1500.0));
+
+        // A client's own estimate, in SAR, replaces the configured one
+        request.currency = Currency::SAR.into();
+        request.livestock_values = Some(LivestockValues {
+            camel: Some(dec!(7500.0)),
+            ..Default::default()
+        });
+        let response = ZakatCalculator::new()
+            .calculate_zakat(request)
+            .await
+            .unwrap();
+        assert_eq!(response.zakat_due, dec!(2000.0));
+    }
+
+    #[tokio::test]
+    async fn test_mixed_karat_gold_is_itemized_and_summed() {
+        let mut request = fitr_request("wheat");
+        request.calculation_type = ZakatType::Gold;
+        request.gold_items = Some(vec![
+            GoldItem {
+                weight_grams: dec!(40.0),
+                karats: 22,
+            },
+            GoldItem {
+                weight_grams: dec!(70.0),
+                karats: 18,
+            },
+        ]);
+        let response = ZakatCalculator::new()
+            .calculate_zakat(request)
+            .await
+            .unwrap();
+
+        let ZakatDetails::Gold(details) = response.calculation_details else {
+            panic!("expected gold details");
+        };
+        assert_eq!(details.items.len(), 2);
+        assert_eq!(details.items[0].pure_weight_grams, dec!(36.68)); // 40g * 91.7%
+        assert_eq!(details.items[1].pure_weight_grams, dec!(52.5)); // 70g * 75%
+        assert_eq!(details.items[1].value, dec!(3412.5)); // 52.5g * $65
+        assert_eq!(details.weight_grams, dec!(110.0));
+        assert_eq!(details.pure_weight_grams, dec!(89.18));
+        // Neither piece reaches 85g of pure gold alone, together they do
+        assert!(response.is_zakat_applicable);
+        assert_eq!(response.zakat_due, dec!(89.18) * dec!(65.0) * dec!(0.025));
+    }
+
+    #[test]
+    fn test_invalid_reference_urls_are_dropped() {
+        let reference = IslamicReference {
+            source: "Hadith".to_string(),
+            reference: "Test".to_string(),
+            arabic_text: None,
+            translation: "Test".to_string(),
+            collection: None,
+            book_number: None,
+            hadith_number: None,
+            external_url: Some("javascript:alert(1)".to_string()),
+        };
+
+        let references = verified_references(vec![reference]);
+        assert!(references[0].external_url.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_arabic_recommendations() {
+        let mut request = fitr_request("dates");
+        request.lang = Some("ar".to_string());
+        let response = ZakatCalculator::new()
+            .calculate_zakat(request)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            response.recommendations[1],
+            "أخرج زكاة الفطر قبل صلاة العيد."
+        );
+        assert!(response.recommendations[0].contains("dates"));
+        let formatted = response.formatted.expect("formatted figures");
+        assert_eq!(formatted.locale, Locale::Ar);
+        assert_eq!(formatted.zakat_due, "١٥٫٠٠"); // 4 people * 2.5kg * 1.5
+
+        let english = ZakatCalculator::new()
+            .calculate_zakat(fitr_request("dates"))
+            .await
+            .unwrap();
+        assert_eq!(
+            english.recommendations[1],
+            "Pay Zakat al-Fitr before the Eid prayer."
+        );
+        assert!(english.formatted.is_none());
+    }
+
+    #[test]
+    fn test_camel_due_follows_each_bracket() {
+        let sheep = |count| Some((LivestockType::Sheep, count));
+        let camels = |count| Some((LivestockType::Camel, count));
+        for (herd, due) in [
+            (4, None),
+            (5, sheep(1)),
+            (9, sheep(1)),
+            (10, sheep(2)),
+            (14, sheep(2)),
+            (15, sheep(3)),
+            (19, sheep(3)),
+            (20, sheep(4)),
+            (24, sheep(4)),
+            (25, camels(1)),
+            (35, camels(1)),
+            (36, camels(1)),
+            (45, camels(1)),
+            (46, camels(1)),
+            (60, camels(1)),
+            (61, camels(1)),
+            (75, camels(1)),
+            (76, camels(2)),
+            (90, camels(2)),
+            (91, camels(2)),
+            (120, camels(2)),
+            (121, camels(3)), // three bint labun
+            (130, camels(3)), // a hiqqah and two bint labun
+            (150, camels(3)), // three hiqqah
+            (160, camels(4)), // four bint labun
+            (200, camels(4)), // four hiqqah
+        ] {
+            assert_eq!(camel_due(herd), due, "{} camels", herd);
+        }
     }
 }
@@ -0,0 +1,219 @@
+// ISO 4217 currency metadata, so clients can format amounts without
+// hardcoding symbols or decimal places per currency.
+
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct CurrencyMetadata {
+    pub code: &'static str,
+    pub symbol: &'static str,
+    /// Decimal places used in everyday prices
+    pub decimal_digits: u8,
+    pub name: &'static str,
+}
+
+const fn currency(
+    code: &'static str,
+    symbol: &'static str,
+    decimal_digits: u8,
+    name: &'static str,
+) -> CurrencyMetadata {
+    CurrencyMetadata {
+        code,
+        symbol,
+        decimal_digits,
+        name,
+    }
+}
+
+/// Active ISO 4217 currencies, sorted by code. Decimal places follow the
+/// ISO minor unit, except for currencies whose minor unit has no coins or
+/// notes in circulation (IDR, IQD, IRR, LBP, SYP), which are shown whole.
+const ISO_4217: &[CurrencyMetadata] = &[
+    currency("AED", "د.إ", 2, "UAE Dirham"),
+    currency("AFN", "؋", 2, "Afghan Afghani"),
+    currency("ALL", "L", 2, "Albanian Lek"),
+    currency("AMD", "֏", 2, "Armenian Dram"),
+    currency("ANG", "ƒ", 2, "Netherlands Antillean Guilder"),
+    currency("AOA", "Kz", 2, "Angolan Kwanza"),
+    currency("ARS", "$", 2, "Argentine Peso"),
+    currency("AUD", "A$", 2, "Australian Dollar"),
+    currency("AWG", "ƒ", 2, "Aruban Florin"),
+    currency("AZN", "₼", 2, "Azerbaijani Manat"),
+    currency("BAM", "KM", 2, "Bosnia-Herzegovina Convertible Mark"),
+    currency("BBD", "Bds$", 2, "Barbadian Dollar"),
+    currency("BDT", "৳", 2, "Bangladeshi Taka"),
+    currency("BGN", "лв", 2, "Bulgarian Lev"),
+    currency("BHD", ".د.ب", 3, "Bahraini Dinar"),
+    currency("BIF", "FBu", 0, "Burundian Franc"),
+    currency("BMD", "$", 2, "Bermudian Dollar"),
+    currency("BND", "B$", 2, "Brunei Dollar"),
+    currency("BOB", "Bs", 2, "Bolivian Boliviano"),
+    currency("BRL", "R$", 2, "Brazilian Real"),
+    currency("BSD", "$", 2, "Bahamian Dollar"),
+    currency("BTN", "Nu.", 2, "Bhutanese Ngultrum"),
+    currency("BWP", "P", 2, "Botswana Pula"),
+    currency("BYN", "Br", 2, "Belarusian Ruble"),
+    currency("BZD", "BZ$", 2, "Belize Dollar"),
+    currency("CAD", "C$", 2, "Canadian Dollar"),
+    currency("CDF", "FC", 2, "Congolese Franc"),
+    currency("CHF", "CHF", 2, "Swiss Franc"),
+    currency("CLP", "$", 0, "Chilean Peso"),
+    currency("CNY", "¥", 2, "Chinese Yuan"),
+    currency("COP", "$", 2, "Colombian Peso"),
+    currency("CRC", "₡", 2, "Costa Rican Colón"),
+    currency("CUP", "$", 2, "Cuban Peso"),
+    currency("CVE", "$", 2, "Cape Verdean Escudo"),
+    currency("CZK", "Kč", 2, "Czech Koruna"),
+    currency("DJF", "Fdj", 0, "Djiboutian Franc"),
+    currency("DKK", "kr", 2, "Danish Krone"),
+    currency("DOP", "RD$", 2, "Dominican Peso"),
+    currency("DZD", "د.ج", 2, "Algerian Dinar"),
+    currency("EGP", "E£", 2, "Egyptian Pound"),
+    currency("ERN", "Nfk", 2, "Eritrean Nakfa"),
+    currency("ETB", "Br", 2, "Ethiopian Birr"),
+    currency("EUR", "€", 2, "Euro"),
+    currency("FJD", "FJ$", 2, "Fijian Dollar"),
+    currency("FKP", "£", 2, "Falkland Islands Pound"),
+    currency("GBP", "£", 2, "British Pound"),
+    currency("GEL", "₾", 2, "Georgian Lari"),
+    currency("GHS", "GH₵", 2, "Ghanaian Cedi"),
+    currency("GIP", "£", 2, "Gibraltar Pound"),
+    currency("GMD", "D", 2, "Gambian Dalasi"),
+    currency("GNF", "FG", 0, "Guinean Franc"),
+    currency("GTQ", "Q", 2, "Guatemalan Quetzal"),
+    currency("GYD", "G$", 2, "Guyanese Dollar"),
+    currency("HKD", "HK$", 2, "Hong Kong Dollar"),
+    currency("HNL", "L", 2, "Honduran Lempira"),
+    currency("HTG", "G", 2, "Haitian Gourde"),
+    currency("HUF", "Ft", 2, "Hungarian Forint"),
+    currency("IDR", "Rp", 0, "Indonesian Rupiah"),
+    currency("ILS", "₪", 2, "Israeli New Shekel"),
+    currency("INR", "₹", 2, "Indian Rupee"),
+    currency("IQD", "ع.د", 0, "Iraqi Dinar"),
+    currency("IRR", "﷼", 0, "Iranian Rial"),
+    currency("ISK", "kr", 0, "Icelandic Króna"),
+    currency("JMD", "J$", 2, "Jamaican Dollar"),
+    currency("JOD", "د.ا", 3, "Jordanian Dinar"),
+    currency("JPY", "¥", 0, "Japanese Yen"),
+    currency("KES", "KSh", 2, "Kenyan Shilling"),
+    currency("KGS", "с", 2, "Kyrgyzstani Som"),
+    currency("KHR", "៛", 2, "Cambodian Riel"),
+    currency("KMF", "CF", 0, "Comorian Franc"),
+    currency("KPW", "₩", 2, "North Korean Won"),
+    currency("KRW", "₩", 0, "South Korean Won"),
+    currency("KWD", "د.ك", 3, "Kuwaiti Dinar"),
+    currency("KYD", "CI$", 2, "Cayman Islands Dollar"),
+    currency("KZT", "₸", 2, "Kazakhstani Tenge"),
+    currency("LAK", "₭", 2, "Lao Kip"),
+    currency("LBP", "ل.ل", 0, "Lebanese Pound"),
+    currency("LKR", "Rs", 2, "Sri Lankan Rupee"),
+    currency("LRD", "L$", 2, "Liberian Dollar"),
+    currency("LSL", "L", 2, "Lesotho Loti"),
+    currency("LYD", "ل.د", 3, "Libyan Dinar"),
+    currency("MAD", "د.م.", 2, "Moroccan Dirham"),
+    currency("MDL", "L", 2, "Moldovan Leu"),
+    currency("MGA", "Ar", 2, "Malagasy Ariary"),
+    currency("MKD", "ден", 2, "Macedonian Denar"),
+    currency("MMK", "K", 2, "Myanmar Kyat"),
+    currency("MNT", "₮", 2, "Mongolian Tögrög"),
+    currency("MOP", "MOP$", 2, "Macanese Pataca"),
+    currency("MRU", "UM", 2, "Mauritanian Ouguiya"),
+    currency("MUR", "₨", 2, "Mauritian Rupee"),
+    currency("MVR", "Rf", 2, "Maldivian Rufiyaa"),
+    currency("MWK", "MK", 2, "Malawian Kwacha"),
+    currency("MXN", "$", 2, "Mexican Peso"),
+    currency("MYR", "RM", 2, "Malaysian Ringgit"),
+    currency("MZN", "MT", 2, "Mozambican Metical"),
+    currency("NAD", "N$", 2, "Namibian Dollar"),
+    currency("NGN", "₦", 2, "Nigerian Naira"),
+    currency("NIO", "C$", 2, "Nicaraguan Córdoba"),
+    currency("NOK", "kr", 2, "Norwegian Krone"),
+    currency("NPR", "Rs", 2, "Nepalese Rupee"),
+    currency("NZD", "NZ$", 2, "New Zealand Dollar"),
+    currency("OMR", "ر.ع.", 3, "Omani Rial"),
+    currency("PAB", "B/.", 2, "Panamanian Balboa"),
+    currency("PEN", "S/", 2, "Peruvian Sol"),
+    currency("PGK", "K", 2, "Papua New Guinean Kina"),
+    currency("PHP", "₱", 2, "Philippine Peso"),
+    currency("PKR", "Rs", 2, "Pakistani Rupee"),
+    currency("PLN", "zł", 2, "Polish Złoty"),
+    currency("PYG", "₲", 0, "Paraguayan Guaraní"),
+    currency("QAR", "ر.ق", 2, "Qatari Riyal"),
+    currency("RON", "lei", 2, "Romanian Leu"),
+    currency("RSD", "дин.", 2, "Serbian Dinar"),
+    currency("RUB", "₽", 2, "Russian Ruble"),
+    currency("RWF", "FRw", 0, "Rwandan Franc"),
+    currency("SAR", "ر.س", 2, "Saudi Riyal"),
+    currency("SBD", "SI$", 2, "Solomon Islands Dollar"),
+    currency("SCR", "₨", 2, "Seychellois Rupee"),
+    currency("SDG", "ج.س.", 2, "Sudanese Pound"),
+    currency("SEK", "kr", 2, "Swedish Krona"),
+    currency("SGD", "S$", 2, "Singapore Dollar"),
+    currency("SHP", "£", 2, "Saint Helena Pound"),
+    currency("SLE", "Le", 2, "Sierra Leonean Leone"),
+    currency("SOS", "Sh", 2, "Somali Shilling"),
+    currency("SRD", "$", 2, "Surinamese Dollar"),
+    currency("SSP", "£", 2, "South Sudanese Pound"),
+    currency("STN", "Db", 2, "São Tomé and Príncipe Dobra"),
+    currency("SVC", "₡", 2, "Salvadoran Colón"),
+    currency("SYP", "£S", 0, "Syrian Pound"),
+    currency("SZL", "L", 2, "Swazi Lilangeni"),
+    currency("THB", "฿", 2, "Thai Baht"),
+    currency("TJS", "SM", 2, "Tajikistani Somoni"),
+    currency("TMT", "m", 2, "Turkmenistani Manat"),
+    currency("TND", "د.ت", 3, "Tunisian Dinar"),
+    currency("TOP", "T$", 2, "Tongan Paʻanga"),
+    currency("TRY", "₺", 2, "Turkish Lira"),
+    currency("TTD", "TT$", 2, "Trinidad and Tobago Dollar"),
+    currency("TWD", "NT$", 2, "New Taiwan Dollar"),
+    currency("TZS", "TSh", 2, "Tanzanian Shilling"),
+    currency("UAH", "₴", 2, "Ukrainian Hryvnia"),
+    currency("UGX", "USh", 0, "Ugandan Shilling"),
+    currency("USD", "$", 2, "US Dollar"),
+    currency("UYU", "$U", 2, "Uruguayan Peso"),
+    currency("UZS", "soʻm", 2, "Uzbekistani Som"),
+    currency("VES", "Bs.", 2, "Venezuelan Bolívar"),
+    currency("VND", "₫", 0, "Vietnamese Đồng"),
+    currency("VUV", "VT", 0, "Vanuatu Vatu"),
+    currency("WST", "WS$", 2, "Samoan Tālā"),
+    currency("XAF", "FCFA", 0, "Central African CFA Franc"),
+    currency("XCD", "EC$", 2, "East Caribbean Dollar"),
+    currency("XOF", "CFA", 0, "West African CFA Franc"),
+    currency("XPF", "₣", 0, "CFP Franc"),
+    currency("YER", "﷼", 2, "Yemeni Rial"),
+    currency("ZAR", "R", 2, "South African Rand"),
+    currency("ZMW", "ZK", 2, "Zambian Kwacha"),
+    currency("ZWG", "ZiG", 2, "Zimbabwe Gold"),
+];
+
+/// Looks up an ISO 4217 code, case-insensitively
+pub fn currency_metadata(code: &str) -> Option<&'static CurrencyMetadata> {
+    let code = code.trim().to_ascii_uppercase();
+    ISO_4217
+        .binary_search_by(|currency| currency.code.cmp(code.as_str()))
+        .ok()
+        .map(|index| &ISO_4217[index])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_symbol_and_decimal_digits() {
+        let usd = currency_metadata("USD").unwrap();
+        assert_eq!((usd.symbol, usd.decimal_digits), ("$", 2));
+
+        let idr = currency_metadata("idr").unwrap();
+        assert_eq!((idr.symbol, idr.decimal_digits), ("Rp", 0));
+
+        assert_eq!(currency_metadata("BHD").unwrap().decimal_digits, 3);
+        assert!(currency_metadata("XYZ").is_none());
+    }
+
+    #[test]
+    fn test_table_is_sorted_for_lookup() {
+        assert!(ISO_4217.windows(2).all(|pair| pair[0].code < pair[1].code));
+    }
+}
@@ -1,16 +1,69 @@
 use axum::{Extension, Json};
-use shared::{cache::Cache, database::Database, error::ApiResult};
-use tracing::info;
+use shared::{
+    cache::Cache,
+    config::{NisabSource, ZakatConfig},
+    database::Database,
+    error::ApiResult,
+};
+use tracing::{info, warn};
 use validator::Validate;
 
 use crate::{
-    calculations::ZakatCalculator,
-    models::{ZakatCalculationRequest, ZakatInfoResponse},
+    calculations::{CurrencyRateTable, ZakatCalculator},
+    models::{
+        SavedCalculation, ZakatCalculationRequest, ZakatInfoResponse, ZakatTypeSummary,
+        ZakatTypesResponse,
+    },
+    rates::{RateProvider, SharedRateProvider},
     repository::ZakatRepository,
     services::ZakatService,
 };
 
+/// A calculator with the configured sa' weights, the rate provider's
+/// exchange rates and the `currency_rates` table on top. With
+/// `NisabSource::Builtin` metal prices come from the rate provider. With
+/// `NisabSource::Database` they come from the cached nisab rates instead,
+/// which an update through the service invalidates, so it applies to the
+/// next calculation; the provider's prices aren't fetched, and if the nisab
+/// rates can't be read the built-in prices are used.
+async fn build_calculator(
+    zakat_config: &ZakatConfig,
+    service: &ZakatService,
+    currency_rates: &CurrencyRateTable,
+    rate_provider: &dyn RateProvider,
+) -> ZakatCalculator {
+    let calculator = ZakatCalculator::new().with_config(zakat_config);
+
+    match zakat_config.nisab_source {
+        NisabSource::Builtin => calculator
+            .with_rates_from(rate_provider)
+            .await
+            .with_currency_rates(currency_rates),
+        NisabSource::Database => {
+            let calculator = calculator
+                .with_exchange_rates_from(rate_provider)
+                .await
+                .with_currency_rates(currency_rates);
+            match service
+                .get_nisab_rates(zakat_config.nisab_cache_policy())
+                .await
+            {
+                Ok(rates) => calculator.with_nisab_rates(&[rates.gold, rates.silver]),
+                Err(e) => {
+                    warn!("Failed to load nisab rates, using built-in prices: {}", e);
+                    calculator
+                }
+            }
+        }
+    }
+}
+
 pub async fn calculate_zakat(
+    Extension(database): Extension<Database>,
+    Extension(cache): Extension<Cache>,
+    Extension(zakat_config): Extension<ZakatConfig>,
+    Extension(currency_rates): Extension<CurrencyRateTable>,
+    Extension(rate_provider): Extension<SharedRateProvider>,
     Json(request): Json<ZakatCalculationRequest>,
 ) -> ApiResult<Json<serde_json::Value>> {
     info!("Calculating zakat for type: {:?}", request.calculation_type);
@@ -18,7 +71,14 @@ pub async fn calculate_zakat(
     // Validate request - ValidationErrors automatically converts to ApiError
     request.validate()?;
 
-    let calculator = ZakatCalculator::new();
+    let service = ZakatService::new(ZakatRepository::new(database), cache);
+    let calculator = build_calculator(
+        &zakat_config,
+        &service,
+        &currency_rates,
+        rate_provider.as_ref(),
+    )
+    .await;
     let response = calculator.calculate_zakat(request).await?;
 
     Ok(Json(serde_json::to_value(response)?))
@@ -27,6 +87,9 @@ pub async fn calculate_zakat(
 pub async fn save_calculation(
     Extension(database): Extension<Database>,
     Extension(cache): Extension<Cache>,
+    Extension(zakat_config): Extension<ZakatConfig>,
+    Extension(currency_rates): Extension<CurrencyRateTable>,
+    Extension(rate_provider): Extension<SharedRateProvider>,
     Json(request): Json<ZakatCalculationRequest>,
 ) -> ApiResult<Json<serde_json::Value>> {
     info!("Saving zakat calculation for user: {:?}", request.user_id);
@@ -38,11 +101,27 @@ pub async fn save_calculation(
     let service = ZakatService::new(repository, cache);
 
     // Calculate first
-    let calculator = ZakatCalculator::new();
+    let calculator = build_calculator(
+        &zakat_config,
+        &service,
+        &currency_rates,
+        rate_provider.as_ref(),
+    )
+    .await;
     let calculation_result = calculator.calculate_zakat(request.clone()).await?;
 
     // Save if user_id is provided - fix partial move by using reference
     if let Some(ref user_id) = request.user_id {
+        if request.dry_run {
+            // Nothing is written, so cached history and stats stay valid
+            let preview = SavedCalculation::new(user_id.clone(), &request, &calculation_result)?;
+            return Ok(Json(serde_json::json!({
+                "calculation": calculation_result,
+                "saved": preview,
+                "dry_run": true
+            })));
+        }
+
         let saved_calculation = service
             .save_calculation(user_id.clone(), request, calculation_result.clone())
             .await?;
@@ -77,13 +156,16 @@ pub async fn get_calculation_history(
 pub async fn get_nisab_rates(
     Extension(database): Extension<Database>,
     Extension(cache): Extension<Cache>,
+    Extension(zakat_config): Extension<ZakatConfig>,
 ) -> ApiResult<Json<serde_json::Value>> {
     info!("Fetching current nisab rates");
 
     let repository = ZakatRepository::new(database);
     let service = ZakatService::new(repository, cache);
 
-    let response = service.get_nisab_rates().await?;
+    let response = service
+        .get_nisab_rates(zakat_config.nisab_cache_policy())
+        .await?;
     Ok(Json(serde_json::to_value(response)?))
 }
 
@@ -94,6 +176,18 @@ pub async fn get_zakat_info() -> ApiResult<Json<ZakatInfoResponse>> {
     Ok(Json(info))
 }
 
+/// Machine-readable catalog of zakat types for building calculation forms
+pub async fn get_zakat_types() -> ApiResult<Json<ZakatTypesResponse>> {
+    info!("Fetching zakat type catalog");
+
+    let info = create_zakat_info_response();
+    Ok(Json(ZakatTypesResponse {
+        types: info.types.iter().map(ZakatTypeSummary::from).collect(),
+        common_required_fields: vec!["calculation_type", "amount", "currency"],
+        common_optional_fields: vec!["user_id", "save_calculation", "lang"],
+    }))
+}
+
 pub async fn health_check(
     Extension(database): Extension<Database>,
     Extension(cache): Extension<Cache>,
@@ -108,7 +202,7 @@ pub async fn health_check(
         "status": "OK",
         "database": "connected",
         "cache": "connected",
-        "timestamp": chrono::Utc::now().to_rfc3339()
+        "timestamp": shared::time::now_rfc3339()
     })))
 }
 
@@ -221,6 +315,23 @@ fn create_zakat_info_response() -> ZakatInfoResponse {
                 "Wheat, rice, dates, raisins".to_string(),
             ],
         },
+        ZakatTypeInfo {
+            zakat_type: ZakatType::Fitr,
+            title: "Zakat al-Fitr".to_string(),
+            description: "Charity paid at the end of Ramadan for each household member".to_string(),
+            rate_percentage: dec!(0.0),
+            nisab_criteria: "Food beyond the needs of the day of Eid".to_string(),
+            conditions: vec![
+                "One sa' of staple food per person".to_string(),
+                "Paid before the Eid prayer".to_string(),
+                "The head of household pays for dependants".to_string(),
+            ],
+            examples: vec![
+                "Wheat: about 2.04 kg per person".to_string(),
+                "Rice: about 3 kg per person".to_string(),
+                "Dates or raisins: about 2.5 kg per person".to_string(),
+            ],
+        },
     ];
 
     let general_info = GeneralZakatInfo {
@@ -311,3 +422,178 @@ fn create_zakat_info_response() -> ZakatInfoResponse {
         calculation_guidelines,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{models::ZakatType, rates::StaticRateProvider};
+    use rust_decimal_macros::dec;
+    use std::sync::Arc;
+
+    /// The database and cache, or `None` when either isn't reachable
+    async fn connect() -> Option<(Database, Cache)> {
+        let url = std::env::var("DATABASE_URL").ok()?;
+        let cache = Cache::new(&shared::config::RedisConfig {
+            url: "redis://localhost:6379".to_string(),
+            pool_max_open: 10,
+            pool_max_idle: 5,
+            pool_timeout: 30,
+            pool_expire: 300,
+        })
+        .await
+        .ok()?;
+        let database = Database::new(&shared::config::DatabaseConfig {
+            url,
+            max_connections: 5,
+            min_connections: 1,
+            connect_timeout: 30,
+            acquire_timeout_ms: 5000,
+            idle_timeout: 600,
+            slow_query_threshold_ms: 500,
+        })
+        .await
+        .unwrap();
+        Some((database, cache))
+    }
+
+    fn test_config(nisab_source: NisabSource) -> ZakatConfig {
+        ZakatConfig {
+            fitr_sa_weights_kg: std::collections::HashMap::new(),
+            fitr_default_sa_weight_kg: 2.5,
+            nisab_cache_soft_ttl_secs: 3600,
+            nisab_cache_hard_ttl_secs: 86400,
+            nisab_source,
+            rate_provider_url: None,
+            livestock_values_usd: std::collections::HashMap::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_zakat_types_lists_every_type() {
+        let Json(response) = get_zakat_types().await.unwrap();
+
+        let types: Vec<ZakatType> = response
+            .types
+            .iter()
+            .map(|t| t.zakat_type.clone())
+            .collect();
+        assert_eq!(
+            types,
+            vec![
+                ZakatType::Wealth,
+                ZakatType::Gold,
+                ZakatType::Silver,
+                ZakatType::Business,
+                ZakatType::Livestock,
+                ZakatType::Crops,
+                ZakatType::Fitr,
+            ]
+        );
+
+        let fitr = response.types.last().unwrap();
+        assert_eq!(fitr.required_fields, vec!["staple_price_per_kg"]);
+        assert!(fitr.optional_fields.contains(&"household_members"));
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_save_inserts_nothing() {
+        let Some((database, cache)) = connect().await else {
+            return;
+        };
+        let count = || async {
+            sqlx::query_scalar::<_, i64>(
+                "SELECT COUNT(*) FROM zakat_calculations WHERE user_id = 'dry_run_user'",
+            )
+            .fetch_one(&database.pool)
+            .await
+            .unwrap()
+        };
+        let before = count().await;
+
+        let request: ZakatCalculationRequest = serde_json::from_value(serde_json::json!({
+            "calculation_type": "wealth",
+            "amount": "10000",
+            "currency": "USD",
+            "user_id": "dry_run_user",
+            "dry_run": true
+        }))
+        .unwrap();
+        let Json(response) = save_calculation(
+            Extension(database.clone()),
+            Extension(cache),
+            Extension(test_config(NisabSource::Builtin)),
+            Extension(CurrencyRateTable::default()),
+            Extension(Arc::new(StaticRateProvider) as SharedRateProvider),
+            Json(request),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(count().await, before);
+        assert_eq!(response["dry_run"], true);
+        assert_eq!(response["saved"]["user_id"], "dry_run_user");
+        assert_eq!(response["saved"]["zakat_amount"], "250.0");
+    }
+
+    #[tokio::test]
+    async fn test_nisab_price_change_is_reflected_in_next_calculation() {
+        let Some((database, cache)) = connect().await else {
+            return;
+        };
+        let repo = ZakatRepository::new(database.clone());
+        let service = ZakatService::new(repo.clone(), cache.clone());
+        let config = test_config(NisabSource::Database);
+        let currency_rates = CurrencyRateTable::default();
+
+        let gold_price = || async {
+            let request: ZakatCalculationRequest = serde_json::from_value(serde_json::json!({
+                "calculation_type": "gold",
+                "amount": "0",
+                "currency": "USD",
+                "gold_weight_grams": "100"
+            }))
+            .unwrap();
+            let Json(response) = calculate_zakat(
+                Extension(database.clone()),
+                Extension(cache.clone()),
+                Extension(config.clone()),
+                Extension(currency_rates.clone()),
+                Extension(Arc::new(StaticRateProvider) as SharedRateProvider),
+                Json(request),
+            )
+            .await
+            .unwrap();
+            serde_json::from_value::<rust_decimal::Decimal>(
+                response["calculation_details"]["current_price_per_gram"].clone(),
+            )
+            .unwrap()
+        };
+
+        let original = repo
+            .get_nisab_rates()
+            .await
+            .unwrap()
+            .into_iter()
+            .find(|rate| rate.metal_type == "gold")
+            .unwrap()
+            .price_per_gram_usd;
+
+        service
+            .update_nisab_rates(Some(dec!(70.25)), None)
+            .await
+            .unwrap();
+        let first = gold_price().await;
+        service
+            .update_nisab_rates(Some(dec!(71.5)), None)
+            .await
+            .unwrap();
+        let second = gold_price().await;
+        service
+            .update_nisab_rates(Some(original), None)
+            .await
+            .unwrap();
+
+        assert_eq!(first, dec!(70.25));
+        assert_eq!(second, dec!(71.5));
+    }
+}
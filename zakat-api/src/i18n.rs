@@ -0,0 +1,331 @@
+use chrono::{DateTime, Datelike, Timelike, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::models::ZakatCalculationResponse;
+
+/// Languages the recommendation catalog is translated into.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    #[default]
+    En,
+    Ar,
+    Ur,
+}
+
+impl Locale {
+    /// Resolves a language tag such as `ar` or `ur-PK`; anything unsupported
+    /// falls back to English.
+    pub fn from_code(code: Option<&str>) -> Self {
+        let primary = code
+            .and_then(|c| c.split(['-', '_']).next())
+            .map(|c| c.trim().to_lowercase());
+        match primary.as_deref() {
+            Some("ar") => Locale::Ar,
+            Some("ur") => Locale::Ur,
+            _ => Locale::En,
+        }
+    }
+
+    fn index(self) -> usize {
+        match self {
+            Locale::En => 0,
+            Locale::Ar => 1,
+            Locale::Ur => 2,
+        }
+    }
+
+    pub fn text(self, message: Message) -> String {
+        message.templates()[self.index()].to_string()
+    }
+
+    /// Renders a template, replacing `{name}` placeholders with `args`
+    pub fn render(self, message: Message, args: &[(&str, &str)]) -> String {
+        args.iter().fold(self.text(message), |text, (name, value)| {
+            text.replace(&format!("{{{}}}", name), value)
+        })
+    }
+
+    fn digits(self) -> Option<[char; 10]> {
+        match self {
+            Locale::En => None,
+            Locale::Ar => Some(['٠', '١', '٢', '٣', '٤', '٥', '٦', '٧', '٨', '٩']),
+            Locale::Ur => Some(['۰', '۱', '۲', '۳', '۴', '۵', '۶', '۷', '۸', '۹']),
+        }
+    }
+
+    fn separators(self) -> (char, char) {
+        match self {
+            // Arabic thousands and decimal separators
+            Locale::Ar => ('٬', '٫'),
+            Locale::En | Locale::Ur => (',', '.'),
+        }
+    }
+
+    fn localize_digits(self, text: &str) -> String {
+        match self.digits() {
+            Some(digits) => text
+                .chars()
+                .map(|c| c.to_digit(10).map_or(c, |d| digits[d as usize]))
+                .collect(),
+            None => text.to_string(),
+        }
+    }
+
+    /// Formats an amount with two decimals, digit grouping and the locale's numerals
+    pub fn format_amount(self, amount: Decimal) -> String {
+        let (group_sep, decimal_sep) = self.separators();
+        let rounded = format!("{:.2}", amount.round_dp(2).abs());
+        let (whole, fraction) = rounded.split_once('.').unwrap_or((&rounded, "00"));
+
+        let mut grouped = String::new();
+        for (i, c) in whole.chars().enumerate() {
+            if i > 0 && (whole.len() - i) % 3 == 0 {
+                grouped.push(group_sep);
+            }
+            grouped.push(c);
+        }
+
+        let sign = if amount.is_sign_negative() && !amount.round_dp(2).is_zero() {
+            "-"
+        } else {
+            ""
+        };
+        self.localize_digits(&format!("{}{}{}{}", sign, grouped, decimal_sep, fraction))
+    }
+
+    /// Formats a timestamp as e.g. `16 October 2026, 14:05 UTC` in the locale's language
+    pub fn format_time(self, time: DateTime<Utc>) -> String {
+        let month = MONTHS[time.month0() as usize][self.index()];
+        let text = format!(
+            "{} {} {}{} {:02}:{:02} UTC",
+            time.day(),
+            month,
+            time.year(),
+            if self == Locale::En { "," } else { "،" },
+            time.hour(),
+            time.minute()
+        );
+        self.localize_digits(&text)
+    }
+}
+
+const MONTHS: [[&str; 3]; 12] = [
+    ["January", "يناير", "جنوری"],
+    ["February", "فبراير", "فروری"],
+    ["March", "مارس", "مارچ"],
+    ["April", "أبريل", "اپریل"],
+    ["May", "مايو", "مئی"],
+    ["June", "يونيو", "جون"],
+    ["July", "يوليو", "جولائی"],
+    ["August", "أغسطس", "اگست"],
+    ["September", "سبتمبر", "ستمبر"],
+    ["October", "أكتوبر", "اکتوبر"],
+    ["November", "نوفمبر", "نومبر"],
+    ["December", "ديسمبر", "دسمبر"],
+];
+
+/// Keys into the recommendation catalog.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Message {
+    WealthBelowNisab,
+    WealthDue,
+    WealthHawl,
+    ConsultScholar,
+    GoldBelowNisab,
+    GoldDue,
+    JewelryRulings,
+    SilverBelowNisab,
+    SilverDue,
+    BusinessIncludeAssets,
+    BusinessDeductLiabilities,
+    BusinessAnnual,
+    LivestockNone,
+    LivestockThresholds,
+    LivestockGrazing,
+    LivestockConsultTexts,
+    CropsRainFed,
+    CropsIrrigated,
+    CropsAtHarvest,
+    CropsStaples,
+    /// Placeholders: `{staple}`, `{kg}`
+    FitrSaWeight,
+    FitrBeforeEid,
+    FitrScholarsDiffer,
+}
+
+impl Message {
+    /// English, Arabic and Urdu text, in `Locale` order
+    fn templates(self) -> [&'static str; 3] {
+        match self {
+            Message::WealthBelowNisab => [
+                "Your wealth is below the nisab threshold, so Zakat is not required.",
+                "ثروتك أقل من حد النصاب، لذا لا تجب عليك الزكاة.",
+                "آپ کی دولت نصاب سے کم ہے، اس لیے زکوٰة واجب نہیں۔",
+            ],
+            Message::WealthDue => [
+                "Zakat is due on your wealth. Pay 2.5% annually.",
+                "تجب الزكاة في مالك. أخرج 2.5% سنوياً.",
+                "آپ کے مال پر زکوٰة واجب ہے۔ سالانہ 2.5% ادا کریں۔",
+            ],
+            Message::WealthHawl => [
+                "Ensure you've held this wealth for a full lunar year (Hawl).",
+                "تأكد من مرور حول قمري كامل على هذا المال.",
+                "یقینی بنائیں کہ اس مال پر پورا قمری سال (حول) گزر چکا ہو۔",
+            ],
+            Message::ConsultScholar => [
+                "Consider consulting with a qualified Islamic scholar for complex cases.",
+                "يُستحسن استشارة عالم شرعي مؤهل في الحالات المعقدة.",
+                "پیچیدہ معاملات میں کسی مستند عالمِ دین سے مشورہ کریں۔",
+            ],
+            Message::GoldBelowNisab => [
+                "Your gold is below the nisab threshold (85g of pure gold).",
+                "ذهبك أقل من النصاب (85 غراماً من الذهب الخالص).",
+                "آپ کا سونا نصاب (85 گرام خالص سونا) سے کم ہے۔",
+            ],
+            Message::GoldDue => [
+                "Zakat is due on your gold. Consider the purity when calculating.",
+                "تجب الزكاة في ذهبك. راعِ درجة النقاء عند الحساب.",
+                "آپ کے سونے پر زکوٰة واجب ہے۔ حساب کرتے وقت خالص پن کا خیال رکھیں۔",
+            ],
+            Message::JewelryRulings => [
+                "Jewelry worn regularly may have different rulings - consult a scholar.",
+                "قد تختلف أحكام الحلي المستعمل بانتظام - استشر عالماً.",
+                "روزمرہ پہنے جانے والے زیورات کے احکام مختلف ہو سکتے ہیں - کسی عالم سے رجوع کریں۔",
+            ],
+            Message::SilverBelowNisab => [
+                "Your silver is below the nisab threshold (595g of pure silver).",
+                "فضتك أقل من النصاب (595 غراماً من الفضة الخالصة).",
+                "آپ کی چاندی نصاب (595 گرام خالص چاندی) سے کم ہے۔",
+            ],
+            Message::SilverDue => [
+                "Zakat is due on your silver holdings.",
+                "تجب الزكاة في ما تملكه من الفضة.",
+                "آپ کی چاندی پر زکوٰة واجب ہے۔",
+            ],
+            Message::BusinessIncludeAssets => [
+                "Include all business assets, inventory, and cash in Zakat calculation.",
+                "أدرج جميع أصول التجارة والمخزون والنقد في حساب الزكاة.",
+                "زکوٰة کے حساب میں تمام کاروباری اثاثے، مالِ تجارت اور نقدی شامل کریں۔",
+            ],
+            Message::BusinessDeductLiabilities => [
+                "Deduct legitimate business liabilities and debts.",
+                "اخصم الالتزامات والديون التجارية المشروعة.",
+                "جائز کاروباری واجبات اور قرضے منہا کریں۔",
+            ],
+            Message::BusinessAnnual => [
+                "Calculate Zakat annually on your business lunar year-end.",
+                "احسب الزكاة سنوياً في نهاية السنة القمرية لتجارتك.",
+                "اپنے کاروبار کے قمری سال کے اختتام پر سالانہ زکوٰة کا حساب کریں۔",
+            ],
+            Message::LivestockNone => [
+                "No livestock for Zakat calculation.",
+                "لا توجد مواشٍ لحساب الزكاة.",
+                "زکوٰة کے حساب کے لیے کوئی مویشی نہیں۔",
+            ],
+            Message::LivestockThresholds => [
+                "Livestock Zakat has specific thresholds for each animal type.",
+                "لزكاة المواشي أنصبة محددة لكل نوع من الأنعام.",
+                "مویشیوں کی زکوٰة میں ہر جانور کی قسم کا الگ نصاب ہے۔",
+            ],
+            Message::LivestockGrazing => [
+                "Animals must be grazing freely for most of the year.",
+                "يشترط أن تكون الأنعام سائمة ترعى أكثر الحول.",
+                "جانوروں کا سال کے زیادہ حصے میں چرنے والا (سائمہ) ہونا ضروری ہے۔",
+            ],
+            Message::LivestockConsultTexts => [
+                "Consult detailed Islamic texts for precise calculations.",
+                "ارجع إلى كتب الفقه المفصلة للحساب الدقيق.",
+                "درست حساب کے لیے تفصیلی فقہی کتب سے رجوع کریں۔",
+            ],
+            Message::CropsRainFed => [
+                "Rain-fed crops: 10% Zakat rate applies.",
+                "المحاصيل المسقية بالمطر: نسبة الزكاة 10%.",
+                "بارانی فصلیں: زکوٰة (عشر) کی شرح 10% ہے۔",
+            ],
+            Message::CropsIrrigated => [
+                "Irrigated crops: 5% Zakat rate applies.",
+                "المحاصيل المسقية بالري: نسبة الزكاة 5%.",
+                "مصنوعی آبپاشی والی فصلیں: زکوٰة کی شرح 5% ہے۔",
+            ],
+            Message::CropsAtHarvest => [
+                "Zakat is due at harvest time, not annually.",
+                "تجب الزكاة يوم الحصاد، لا سنوياً.",
+                "زکوٰة فصل کی کٹائی کے وقت واجب ہے، سالانہ نہیں۔",
+            ],
+            Message::CropsStaples => [
+                "Only staple crops like wheat, rice, dates typically require Zakat.",
+                "تجب الزكاة عادةً في المحاصيل الأساسية كالقمح والأرز والتمر.",
+                "عام طور پر صرف گندم، چاول اور کھجور جیسی بنیادی فصلوں پر زکوٰة واجب ہے۔",
+            ],
+            Message::FitrSaWeight => [
+                "One sa' of {staple} is taken as {kg} kg per person.",
+                "يُقدَّر الصاع من {staple} بـ {kg} كغ للفرد.",
+                "{staple} کا ایک صاع فی کس {kg} کلو گرام شمار کیا گیا ہے۔",
+            ],
+            Message::FitrBeforeEid => [
+                "Pay Zakat al-Fitr before the Eid prayer.",
+                "أخرج زكاة الفطر قبل صلاة العيد.",
+                "صدقۂ فطر عید کی نماز سے پہلے ادا کریں۔",
+            ],
+            Message::FitrScholarsDiffer => [
+                "Scholars differ on the weight of a sa' - follow your local authority.",
+                "اختلف العلماء في وزن الصاع - اتبع الجهة المعتمدة في بلدك.",
+                "صاع کے وزن میں علماء کا اختلاف ہے - اپنے مقامی ادارے کی پیروی کریں۔",
+            ],
+        }
+    }
+}
+
+/// Display strings for a calculation, formatted for the requested locale.
+/// The numeric fields of the response stay machine-readable.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LocalizedFigures {
+    pub locale: Locale,
+    pub input_amount: String,
+    pub nisab_threshold: String,
+    pub zakat_due: String,
+    pub calculation_time: String,
+}
+
+impl LocalizedFigures {
+    pub fn new(response: &ZakatCalculationResponse, locale: Locale) -> Self {
+        Self {
+            locale,
+            input_amount: locale.format_amount(response.input_amount),
+            nisab_threshold: locale.format_amount(response.nisab_threshold),
+            zakat_due: locale.format_amount(response.zakat_due),
+            calculation_time: locale.format_time(response.calculation_time),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_locale_from_code_falls_back_to_english() {
+        assert_eq!(Locale::from_code(Some("ar")), Locale::Ar);
+        assert_eq!(Locale::from_code(Some("ur-PK")), Locale::Ur);
+        assert_eq!(Locale::from_code(Some("fr")), Locale::En);
+        assert_eq!(Locale::from_code(None), Locale::En);
+    }
+
+    #[test]
+    fn test_format_amount_per_locale() {
+        assert_eq!(Locale::En.format_amount(dec!(1234567.891)), "1,234,567.89");
+        assert_eq!(Locale::Ar.format_amount(dec!(1234.5)), "١٬٢٣٤٫٥٠");
+        assert_eq!(Locale::Ur.format_amount(dec!(250)), "۲۵۰.۰۰");
+    }
+
+    #[test]
+    fn test_format_time_per_locale() {
+        let time = Utc.with_ymd_and_hms(2026, 10, 16, 14, 5, 0).unwrap();
+        assert_eq!(Locale::En.format_time(time), "16 October 2026, 14:05 UTC");
+        assert_eq!(Locale::Ar.format_time(time), "١٦ أكتوبر ٢٠٢٦، ١٤:٠٥ UTC");
+    }
+}
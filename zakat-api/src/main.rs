@@ -5,27 +5,38 @@ use axum::{
 };
 use dotenv::dotenv;
 use shared::{
+    admin::{admin_router, AdminToken},
     cache::Cache,
     config::AppConfig,
     database::Database,
     error::ApiResult,
-    middleware::{cors_layer, rate_limit_middleware, timeout_layer, trace_layer},
+    latency::{latency_middleware, LatencyTracker},
+    middleware::{
+        cors_layer, rate_limit_middleware, read_only_middleware, security_headers_middleware,
+        timeout_middleware, trace_layer, ReadOnlyMode, RouteTimeouts, SecurityHeaders,
+    },
     SimpleRateLimiter,
 };
-use std::net::SocketAddr;
-use tracing::{info, level_filters::LevelFilter};
+use std::{net::SocketAddr, sync::Arc, time::Duration};
+use tracing::{info, level_filters::LevelFilter, warn};
 use tracing_subscriber::{EnvFilter, FmtSubscriber};
 
 mod calculations;
+mod currencies;
 mod handlers;
+mod i18n;
 mod models;
+mod rates;
 mod repository;
 mod services;
 
+use calculations::CurrencyRateTable;
 use handlers::{
-    calculate_zakat, get_calculation_history, get_nisab_rates, get_zakat_info, health_check,
-    save_calculation,
+    calculate_zakat, get_calculation_history, get_nisab_rates, get_zakat_info, get_zakat_types,
+    health_check, save_calculation,
 };
+use rates::{HttpRateProvider, SharedRateProvider, StaticRateProvider};
+use repository::ZakatRepository;
 
 #[tokio::main]
 async fn main() -> ApiResult<()> {
@@ -57,6 +68,27 @@ async fn main() -> ApiResult<()> {
     let database = Database::new(&config.database).await?;
     info!("Database connected successfully");
 
+    // Currencies beyond the built-in set come from the currency_rates table;
+    // a failed load falls back to the built-in rates
+    let currency_rates: CurrencyRateTable = Arc::new(
+        ZakatRepository::new(database.clone())
+            .get_currency_rates()
+            .await
+            .unwrap_or_else(|e| {
+                warn!("Failed to load currency rates, using built-in rates: {}", e);
+                Default::default()
+            }),
+    );
+    info!("Loaded {} currency rates", currency_rates.len());
+
+    let rate_provider: SharedRateProvider = match config.zakat.rate_provider_url {
+        Some(ref url) => {
+            info!("Reading metal prices and exchange rates from {}", url);
+            Arc::new(HttpRateProvider::new(url.clone())?)
+        }
+        None => Arc::new(StaticRateProvider),
+    };
+
     // Initialize cache (Redis)
     let cache = Cache::new(&config.redis).await?;
     info!("Cache connected successfully");
@@ -64,6 +96,18 @@ async fn main() -> ApiResult<()> {
     // Initialize rate limiter
     let rate_limiter = SimpleRateLimiter::new(cache.clone(), config.rate_limit.clone());
 
+    // Maintenance switch; calculating is a POST but only saving writes
+    let read_only = ReadOnlyMode::new(config.server.read_only).exempt("/api/v1/zakat/calculate");
+    if read_only.is_enabled() {
+        info!("Read-only mode enabled: write requests will be rejected");
+    }
+
+    let timeouts = RouteTimeouts::from_config(&config.server).with_override(
+        "/health",
+        Duration::from_secs(config.server.short_timeout_secs),
+    );
+    let latency = LatencyTracker::default();
+
     // Build the application
     let app = Router::new()
         .route("/api/v1/zakat/calculate", post(calculate_zakat))
@@ -71,16 +115,35 @@ async fn main() -> ApiResult<()> {
         .route("/api/v1/zakat/history", get(get_calculation_history))
         .route("/api/v1/zakat/nisab", get(get_nisab_rates))
         .route("/api/v1/zakat/info", get(get_zakat_info))
+        .route("/api/v1/zakat/types", get(get_zakat_types))
         .route("/health", get(health_check))
+        .merge(admin_router(
+            rate_limiter.clone(),
+            latency.clone(),
+            read_only.clone(),
+            AdminToken::new(config.server.admin_token.clone()),
+        ))
+        .layer(middleware::from_fn_with_state(
+            read_only.clone(),
+            read_only_middleware,
+        ))
         .layer(middleware::from_fn_with_state(
             rate_limiter.clone(),
             rate_limit_middleware,
         ))
-        .layer(timeout_layer())
-        .layer(cors_layer())
+        .layer(middleware::from_fn_with_state(timeouts, timeout_middleware))
+        .layer(middleware::from_fn_with_state(latency, latency_middleware))
+        .layer(middleware::from_fn_with_state(
+            SecurityHeaders::from_config(&config.server),
+            security_headers_middleware,
+        ))
+        .layer(cors_layer(&config.cors))
         .layer(trace_layer())
         .layer(Extension(database))
-        .layer(Extension(cache));
+        .layer(Extension(cache))
+        .layer(Extension(config.zakat.clone()))
+        .layer(Extension(currency_rates))
+        .layer(Extension(rate_provider));
 
     // Start the server - using axum 0.6 syntax
     let addr: SocketAddr = config.bind_address().parse()?;
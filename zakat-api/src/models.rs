@@ -1,10 +1,13 @@
 use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
+use shared::error::ApiResult;
 use sqlx::FromRow;
 use uuid::Uuid;
 use validator::{Validate, ValidationError};
 
+use crate::{currencies::currency_metadata, i18n::LocalizedFigures};
+
 #[derive(Debug, Clone, Serialize, Deserialize, Hash, Eq, PartialEq)]
 #[serde(rename_all = "lowercase")]
 pub enum ZakatType {
@@ -14,6 +17,7 @@ pub enum ZakatType {
     Business,  // Business assets and inventory
     Livestock, // Cattle, sheep, goats, camels
     Crops,     // Agricultural produce
+    Fitr,      // Zakat al-Fitr paid per household member
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, Hash, Eq, PartialEq)]
@@ -33,46 +37,284 @@ pub enum Currency {
     EGP,
 }
 
+/// An ISO 4217 currency code. Any code in the ISO list is accepted here;
+/// whether a rate exists for it is checked against the loaded rate table at
+/// calculation time, so currencies added to `currency_rates` work without a
+/// rebuild.
+///
+/// Stored uppercase, serialized lowercase like `Currency` so existing clients
+/// see the same format.
+#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+pub struct CurrencyCode(String);
+
+impl CurrencyCode {
+    pub fn parse(code: &str) -> Result<Self, String> {
+        let code = code.trim();
+        if code.len() != 3 || !code.chars().all(|c| c.is_ascii_alphabetic()) {
+            return Err(format!(
+                "Invalid currency code '{}': expected three letters",
+                code
+            ));
+        }
+        if currency_metadata(code).is_none() {
+            return Err(format!(
+                "Unknown currency code '{}': not an ISO 4217 currency",
+                code
+            ));
+        }
+        Ok(Self(code.to_ascii_uppercase()))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Default for CurrencyCode {
+    fn default() -> Self {
+        Currency::USD.into()
+    }
+}
+
+impl From<Currency> for CurrencyCode {
+    fn from(currency: Currency) -> Self {
+        Self(currency.to_string().to_string())
+    }
+}
+
+impl std::fmt::Display for CurrencyCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl Serialize for CurrencyCode {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0.to_ascii_lowercase())
+    }
+}
+
+impl<'de> Deserialize<'de> for CurrencyCode {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let code = String::deserialize(deserializer)?;
+        CurrencyCode::parse(&code).map_err(serde::de::Error::custom)
+    }
+}
+
+/// A currency code with the details clients need to format amounts in it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CurrencyInfo {
+    pub code: CurrencyCode,
+    pub symbol: String,
+    pub decimal_digits: u8,
+    pub name: String,
+}
+
+impl From<CurrencyCode> for CurrencyInfo {
+    fn from(code: CurrencyCode) -> Self {
+        // Codes are validated against the ISO list on parse, so the fallback
+        // only covers a code built some other way
+        let (symbol, decimal_digits, name) = match currency_metadata(code.as_str()) {
+            Some(metadata) => (metadata.symbol, metadata.decimal_digits, metadata.name),
+            None => (code.as_str(), 2, code.as_str()),
+        };
+        Self {
+            symbol: symbol.to_string(),
+            decimal_digits,
+            name: name.to_string(),
+            code,
+        }
+    }
+}
+
+impl From<Currency> for CurrencyInfo {
+    fn from(currency: Currency) -> Self {
+        CurrencyCode::from(currency).into()
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+#[validate(schema(function = "validate_type_requirements"))]
 pub struct ZakatCalculationRequest {
     pub calculation_type: ZakatType,
 
     #[validate(custom = "validate_amount")]
     pub amount: Decimal,
 
-    pub currency: Currency,
+    #[validate(custom = "validate_currency_code")]
+    pub currency: CurrencyCode,
 
     // Optional fields for different calculation types
+    #[validate(custom = "validate_weight_grams")]
     pub gold_weight_grams: Option<Decimal>,
+    #[validate(custom = "validate_weight_grams")]
     pub silver_weight_grams: Option<Decimal>,
+    #[validate(range(min = 1, max = 24))]
     pub gold_purity_karats: Option<u8>, // 14, 18, 22, 24
+    /// Pieces of mixed purity, valued separately and summed for the nisab.
+    /// Takes precedence over `gold_weight_grams`/`gold_purity_karats`.
+    #[serde(default)]
+    #[validate(length(max = 100))]
+    #[validate]
+    pub gold_items: Option<Vec<GoldItem>>,
 
     // Livestock specific
+    #[validate(range(max = 1_000_000))]
     pub cattle_count: Option<u32>,
+    #[validate(range(max = 1_000_000))]
     pub sheep_goat_count: Option<u32>,
+    #[validate(range(max = 1_000_000))]
     pub camel_count: Option<u32>,
+    /// Per-animal values in the request currency, overriding the configured
+    /// estimates for the cash alternative
+    #[serde(default)]
+    #[validate]
+    pub livestock_values: Option<LivestockValues>,
 
     // Business specific
+    #[validate(custom = "validate_amount")]
     pub business_assets: Option<Decimal>,
+    #[validate(custom = "validate_amount")]
     pub business_liabilities: Option<Decimal>,
+    #[validate(custom = "validate_amount")]
     pub inventory_value: Option<Decimal>,
 
     // Crops specific
+    #[validate(length(max = 100))]
     pub crop_type: Option<String>,
     pub irrigation_method: Option<IrrigationMethod>,
 
+    // Fitr specific
+    #[validate(range(min = 1, max = 1000))]
+    pub household_members: Option<u32>,
+    #[validate(length(max = 100))]
+    pub staple_food: Option<String>,
+    #[validate(custom = "validate_amount")]
+    pub staple_price_per_kg: Option<Decimal>,
+
     // Optional user info for saving calculation
     pub user_id: Option<String>,
     pub save_calculation: Option<bool>,
+    /// On save, return what would be stored without storing it
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub dry_run: bool,
+
+    /// Language for recommendations and display strings (`en`, `ar`, `ur`);
+    /// unsupported languages fall back to English
+    #[validate(length(max = 35))]
+    pub lang: Option<String>,
 }
 
+// Generous ceilings that still catch typos such as 1e18
+const MAX_AMOUNT: i64 = 1_000_000_000_000_000;
+const MAX_WEIGHT_GRAMS: i64 = 10_000_000;
+
 fn validate_amount(amount: &Decimal) -> Result<(), ValidationError> {
     if *amount < Decimal::ZERO {
         return Err(ValidationError::new("Amount must be non-negative"));
     }
+    if *amount > Decimal::from(MAX_AMOUNT) {
+        return Err(ValidationError::new("Amount must not exceed 10^15"));
+    }
     Ok(())
 }
 
+fn validate_currency_code(code: &CurrencyCode) -> Result<(), ValidationError> {
+    CurrencyCode::parse(code.as_str())
+        .map(|_| ())
+        .map_err(|_| ValidationError::new("Currency must be an ISO 4217 code"))
+}
+
+fn validate_weight_grams(weight: &Decimal) -> Result<(), ValidationError> {
+    if *weight <= Decimal::ZERO {
+        return Err(ValidationError::new("Weight must be greater than zero"));
+    }
+    if *weight > Decimal::from(MAX_WEIGHT_GRAMS) {
+        return Err(ValidationError::new(
+            "Weight must not exceed 10,000,000 grams",
+        ));
+    }
+    Ok(())
+}
+
+/// Animal a livestock due is given in
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Hash, Eq, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum LivestockType {
+    Sheep,
+    Cattle,
+    Camel,
+}
+
+impl LivestockType {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "sheep" => Some(LivestockType::Sheep),
+            "cattle" => Some(LivestockType::Cattle),
+            "camel" => Some(LivestockType::Camel),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Validate)]
+pub struct LivestockValues {
+    #[validate(custom = "validate_amount")]
+    pub sheep: Option<Decimal>,
+    #[validate(custom = "validate_amount")]
+    pub cattle: Option<Decimal>,
+    #[validate(custom = "validate_amount")]
+    pub camel: Option<Decimal>,
+}
+
+impl LivestockValues {
+    pub fn get(&self, animal: LivestockType) -> Option<Decimal> {
+        match animal {
+            LivestockType::Sheep => self.sheep,
+            LivestockType::Cattle => self.cattle,
+            LivestockType::Camel => self.camel,
+        }
+    }
+}
+
+/// One piece of gold in a mixed-karat holding
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct GoldItem {
+    #[validate(custom = "validate_weight_grams")]
+    pub weight_grams: Decimal,
+    #[validate(range(min = 1, max = 24))]
+    pub karats: u8,
+}
+
+fn validate_type_requirements(request: &ZakatCalculationRequest) -> Result<(), ValidationError> {
+    match request.calculation_type {
+        ZakatType::Wealth if request.amount.is_zero() => Err(ValidationError::new(
+            "amount must be greater than zero for wealth calculations",
+        )),
+        ZakatType::Crops if request.amount.is_zero() => Err(ValidationError::new(
+            "amount (harvest value) must be greater than zero for crops calculations",
+        )),
+        ZakatType::Crops if request.irrigation_method.is_none() => Err(ValidationError::new(
+            "irrigation_method is required for crops calculations",
+        )),
+        ZakatType::Gold
+            if request.gold_weight_grams.is_none()
+                && request.gold_items.as_ref().is_none_or(Vec::is_empty) =>
+        {
+            Err(ValidationError::new(
+                "gold_weight_grams or gold_items is required for gold calculations",
+            ))
+        }
+        ZakatType::Silver if request.silver_weight_grams.is_none() => Err(ValidationError::new(
+            "silver_weight_grams is required for silver calculations",
+        )),
+        ZakatType::Fitr if request.staple_price_per_kg.is_none() => Err(ValidationError::new(
+            "staple_price_per_kg is required for fitr calculations",
+        )),
+        _ => Ok(()),
+    }
+}
+
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, Hash, Eq, PartialEq)]
 #[serde(rename_all = "lowercase")]
 pub enum IrrigationMethod {
@@ -85,15 +327,26 @@ pub struct ZakatCalculationResponse {
     pub calculation_id: Uuid,
     pub calculation_type: ZakatType,
     pub input_amount: Decimal,
-    pub currency: Currency,
+    pub currency: CurrencyInfo,
     pub nisab_threshold: Decimal,
     pub zakat_due: Decimal,
     pub zakat_percentage: Decimal,
     pub is_zakat_applicable: bool,
+    /// How much more is needed to reach the nisab, in the request currency.
+    /// Absent once it's reached, and for livestock and fitr, which have no
+    /// monetary nisab.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub amount_below_nisab: Option<Decimal>,
+    /// How far the nisab is exceeded, in the request currency
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub amount_above_nisab: Option<Decimal>,
     pub calculation_details: ZakatDetails,
     pub recommendations: Vec<String>,
     pub islamic_references: Vec<IslamicReference>,
+    #[serde(with = "shared::time::rfc3339")]
     pub calculation_time: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub formatted: Option<LocalizedFigures>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -105,6 +358,7 @@ pub enum ZakatDetails {
     Business(BusinessZakatDetails),
     Livestock(LivestockZakatDetails),
     Crops(CropsZakatDetails),
+    Fitr(FitrZakatDetails),
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -125,6 +379,18 @@ pub struct MetalZakatDetails {
     pub current_price_per_gram: Decimal,
     pub total_value: Decimal,
     pub nisab_weight_grams: Decimal,
+    /// Per-piece breakdown when the request listed `gold_items`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub items: Vec<MetalItemDetails>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetalItemDetails {
+    pub weight_grams: Decimal,
+    pub karats: u8,
+    pub purity_percentage: Decimal,
+    pub pure_weight_grams: Decimal,
+    pub value: Decimal,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -145,6 +411,16 @@ pub struct LivestockZakatDetails {
     pub total_animals: u32,
     pub zakat_animals_due: u32,
     pub alternative_cash_value: Option<Decimal>,
+    /// Animals due by type and the USD value each was counted at
+    #[serde(default)]
+    pub dues: Vec<LivestockDue>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LivestockDue {
+    pub animal: LivestockType,
+    pub count: u32,
+    pub value_per_animal: Decimal,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -157,12 +433,36 @@ pub struct CropsZakatDetails {
     pub net_harvest_value: Decimal,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FitrZakatDetails {
+    pub household_members: u32,
+    pub staple_food: String,
+    pub sa_weight_kg: Decimal,
+    pub total_weight_kg: Decimal,
+    pub staple_price_per_kg: Decimal,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct IslamicReference {
     pub source: String,
     pub reference: String,
     pub arabic_text: Option<String>,
     pub translation: String,
+    // Structured identifiers so clients can link to a hadith/Quran database
+    pub collection: Option<String>,
+    pub book_number: Option<u32>,
+    pub hadith_number: Option<u32>,
+    pub external_url: Option<String>,
+}
+
+impl IslamicReference {
+    /// Returns true when `external_url` is absent or a well-formed https URL.
+    pub fn has_valid_external_url(&self) -> bool {
+        match &self.external_url {
+            Some(url) => url.starts_with("https://") && validator::validate_url(url.as_str()),
+            None => true,
+        }
+    }
 }
 
 // Updated SavedCalculation without FromRow derive - we'll handle conversion manually
@@ -175,9 +475,30 @@ pub struct SavedCalculation {
     pub result_data: serde_json::Value,
     pub zakat_amount: Decimal,
     pub currency: String,
+    #[serde(with = "shared::time::rfc3339")]
     pub created_at: DateTime<Utc>,
 }
 
+impl SavedCalculation {
+    /// The row saving `result` for `user_id` would insert
+    pub fn new(
+        user_id: String,
+        request: &ZakatCalculationRequest,
+        result: &ZakatCalculationResponse,
+    ) -> ApiResult<Self> {
+        Ok(Self {
+            id: Uuid::new_v4(),
+            user_id,
+            calculation_type: request.calculation_type.to_string().to_owned(),
+            input_data: serde_json::to_value(request)?,
+            result_data: serde_json::to_value(result)?,
+            zakat_amount: result.zakat_due,
+            currency: request.currency.to_string(),
+            created_at: Utc::now(),
+        })
+    }
+}
+
 // Database representation using strings for Decimal fields
 #[derive(Debug, Clone, FromRow)]
 pub struct SavedCalculationRow {
@@ -240,6 +561,7 @@ pub struct NisabRate {
     pub price_per_gram_usd: Decimal,
     pub nisab_grams: Decimal,
     pub nisab_value_usd: Decimal,
+    #[serde(with = "shared::time::rfc3339")]
     pub last_updated: DateTime<Utc>,
     pub source: String,
 }
@@ -262,7 +584,8 @@ impl From<NisabRateRow> for NisabRate {
 pub struct NisabRatesResponse {
     pub gold: NisabRate,
     pub silver: NisabRate,
-    pub currency_rates: std::collections::HashMap<Currency, Decimal>,
+    pub currency_rates: std::collections::HashMap<CurrencyCode, Decimal>,
+    #[serde(with = "shared::time::rfc3339")]
     pub last_updated: DateTime<Utc>,
 }
 
@@ -285,6 +608,41 @@ pub struct ZakatTypeInfo {
     pub examples: Vec<String>,
 }
 
+/// Compact, form-friendly description of one zakat type
+#[derive(Debug, Serialize)]
+pub struct ZakatTypeSummary {
+    pub zakat_type: ZakatType,
+    pub title: String,
+    pub rate_percentage: Decimal,
+    pub nisab_basis: String,
+    /// Type-specific request fields that must be supplied
+    pub required_fields: Vec<&'static str>,
+    /// Type-specific request fields that refine the calculation
+    pub optional_fields: Vec<&'static str>,
+}
+
+impl From<&ZakatTypeInfo> for ZakatTypeSummary {
+    fn from(info: &ZakatTypeInfo) -> Self {
+        Self {
+            zakat_type: info.zakat_type.clone(),
+            title: info.title.clone(),
+            rate_percentage: info.rate_percentage,
+            nisab_basis: info.nisab_criteria.clone(),
+            required_fields: info.zakat_type.required_fields().to_vec(),
+            optional_fields: info.zakat_type.optional_fields().to_vec(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ZakatTypesResponse {
+    pub types: Vec<ZakatTypeSummary>,
+    /// Fields every calculation request must include
+    pub common_required_fields: Vec<&'static str>,
+    /// Fields any calculation request may include
+    pub common_optional_fields: Vec<&'static str>,
+}
+
 #[derive(Debug, Serialize)]
 pub struct GeneralZakatInfo {
     pub definition: String,
@@ -345,6 +703,37 @@ impl ZakatType {
             ZakatType::Business => "business",
             ZakatType::Livestock => "livestock",
             ZakatType::Crops => "crops",
+            ZakatType::Fitr => "fitr",
+        }
+    }
+
+    /// Mirrors `validate_type_requirements`; `amount` is always present but
+    /// only has to be positive for these types.
+    pub fn required_fields(&self) -> &'static [&'static str] {
+        match self {
+            ZakatType::Wealth => &["amount"],
+            ZakatType::Gold => &["gold_weight_grams"],
+            ZakatType::Silver => &["silver_weight_grams"],
+            ZakatType::Business => &[],
+            ZakatType::Livestock => &[],
+            ZakatType::Crops => &["amount", "irrigation_method"],
+            ZakatType::Fitr => &["staple_price_per_kg"],
+        }
+    }
+
+    pub fn optional_fields(&self) -> &'static [&'static str] {
+        match self {
+            ZakatType::Wealth | ZakatType::Silver => &[],
+            ZakatType::Gold => &["gold_purity_karats", "gold_items"],
+            ZakatType::Business => &["business_assets", "business_liabilities", "inventory_value"],
+            ZakatType::Livestock => &[
+                "cattle_count",
+                "sheep_goat_count",
+                "camel_count",
+                "livestock_values",
+            ],
+            ZakatType::Crops => &["crop_type"],
+            ZakatType::Fitr => &["household_members", "staple_food"],
         }
     }
 
@@ -355,6 +744,109 @@ impl ZakatType {
             }
             ZakatType::Livestock => Decimal::new(0, 0), // Variable based on count
             ZakatType::Crops => Decimal::new(5, 0),     // 5% or 10% based on irrigation
+            ZakatType::Fitr => Decimal::new(0, 0),      // Fixed measure per person
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn request(calculation_type: ZakatType, amount: Decimal) -> ZakatCalculationRequest {
+        ZakatCalculationRequest {
+            calculation_type,
+            amount,
+            currency: Currency::USD.into(),
+            gold_weight_grams: None,
+            silver_weight_grams: None,
+            gold_purity_karats: None,
+            gold_items: None,
+            cattle_count: None,
+            sheep_goat_count: None,
+            camel_count: None,
+            livestock_values: None,
+            business_assets: None,
+            business_liabilities: None,
+            inventory_value: None,
+            crop_type: None,
+            irrigation_method: None,
+            household_members: None,
+            staple_food: None,
+            staple_price_per_kg: None,
+            user_id: None,
+            save_calculation: None,
+            dry_run: false,
+            lang: None,
         }
     }
+
+    #[test]
+    fn test_valid_wealth_request() {
+        assert!(request(ZakatType::Wealth, dec!(10000)).validate().is_ok());
+    }
+
+    #[test]
+    fn test_rejects_absurd_amount() {
+        let errors = request(ZakatType::Wealth, dec!(1e18))
+            .validate()
+            .unwrap_err();
+        assert!(errors.field_errors().contains_key("amount"));
+    }
+
+    #[test]
+    fn test_rejects_absurd_gold_weight() {
+        let mut gold = request(ZakatType::Gold, dec!(0));
+        gold.gold_weight_grams = Some(dec!(50_000_000));
+        let errors = gold.validate().unwrap_err();
+        assert!(errors.field_errors().contains_key("gold_weight_grams"));
+    }
+
+    #[test]
+    fn test_rejects_unreasonable_cattle_count() {
+        let mut livestock = request(ZakatType::Livestock, dec!(0));
+        livestock.cattle_count = Some(5_000_000);
+        let errors = livestock.validate().unwrap_err();
+        assert!(errors.field_errors().contains_key("cattle_count"));
+    }
+
+    #[test]
+    fn test_crops_requires_irrigation_method() {
+        let crops = request(ZakatType::Crops, dec!(5000));
+        let errors = crops.validate().unwrap_err();
+        assert!(errors.to_string().contains("irrigation_method is required"));
+    }
+
+    #[test]
+    fn test_rejects_zero_amount_for_wealth() {
+        let errors = request(ZakatType::Wealth, dec!(0)).validate().unwrap_err();
+        assert!(errors.to_string().contains("wealth calculations"));
+    }
+
+    #[test]
+    fn test_currency_code_accepts_any_iso_code() {
+        let code: CurrencyCode = serde_json::from_str("\"qar\"").unwrap();
+        assert_eq!(code.as_str(), "QAR");
+        assert_eq!(serde_json::to_string(&code).unwrap(), "\"qar\"");
+        assert_eq!(CurrencyCode::from(Currency::EUR).as_str(), "EUR");
+
+        assert!(serde_json::from_str::<CurrencyCode>("\"dollars\"").is_err());
+        assert!(CurrencyCode::parse("U$D").is_err());
+        assert!(CurrencyCode::parse("XYZ").is_err());
+    }
+
+    #[test]
+    fn test_currency_info_serializes_metadata() {
+        let info = CurrencyInfo::from(CurrencyCode::parse("BHD").unwrap());
+        assert_eq!(
+            serde_json::to_value(&info).unwrap(),
+            serde_json::json!({
+                "code": "bhd",
+                "symbol": ".د.ب",
+                "decimal_digits": 3,
+                "name": "Bahraini Dinar"
+            })
+        );
+    }
 }
@@ -0,0 +1,355 @@
+use axum::async_trait;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::Deserialize;
+use shared::error::{ApiError, ApiResult};
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::sync::Mutex;
+use tracing::warn;
+
+use crate::models::{Currency, CurrencyCode};
+
+const HTTP_TIMEOUT: Duration = Duration::from_secs(10);
+/// How long a fetched rate document is reused before fetching it again
+const RATE_CACHE_TTL: Duration = Duration::from_secs(300);
+
+/// Gold and silver prices nisab is computed from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub struct MetalPrices {
+    pub gold_per_gram_usd: Decimal,
+    pub silver_per_gram_usd: Decimal,
+}
+
+impl MetalPrices {
+    /// Sample prices used when nothing live is configured
+    pub fn builtin() -> Self {
+        Self {
+            gold_per_gram_usd: dec!(65.0),   // ~$65 per gram
+            silver_per_gram_usd: dec!(0.80), // ~$0.80 per gram
+        }
+    }
+}
+
+/// Sample exchange rates (units per USD) used when nothing live is configured
+pub fn builtin_currency_rates() -> HashMap<CurrencyCode, Decimal> {
+    [
+        (Currency::USD, dec!(1.0)),
+        (Currency::EUR, dec!(0.85)),
+        (Currency::GBP, dec!(0.73)),
+        (Currency::SAR, dec!(3.75)),
+        (Currency::AED, dec!(3.67)),
+        (Currency::PKR, dec!(280.0)),
+        (Currency::INR, dec!(83.0)),
+        (Currency::BDT, dec!(110.0)),
+        (Currency::MYR, dec!(4.7)),
+        (Currency::IDR, dec!(15500.0)),
+        (Currency::TRY, dec!(27.0)),
+        (Currency::EGP, dec!(31.0)),
+    ]
+    .into_iter()
+    .map(|(currency, rate)| (currency.into(), rate))
+    .collect()
+}
+
+/// Where calculations get metal prices and exchange rates from
+#[async_trait]
+pub trait RateProvider: Send + Sync {
+    async fn gold_silver(&self) -> ApiResult<MetalPrices>;
+
+    /// Units per USD, keyed by currency code
+    async fn fx_rates(&self) -> ApiResult<HashMap<String, Decimal>>;
+}
+
+/// The provider handlers are given, chosen at startup from `ZakatConfig`
+pub type SharedRateProvider = Arc<dyn RateProvider>;
+
+/// The built-in sample prices and rates
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StaticRateProvider;
+
+#[async_trait]
+impl RateProvider for StaticRateProvider {
+    async fn gold_silver(&self) -> ApiResult<MetalPrices> {
+        Ok(MetalPrices::builtin())
+    }
+
+    async fn fx_rates(&self) -> ApiResult<HashMap<String, Decimal>> {
+        Ok(builtin_currency_rates()
+            .into_iter()
+            .map(|(code, rate)| (code.to_string(), rate))
+            .collect())
+    }
+}
+
+/// Reads prices and rates from a JSON document at `url`, shaped like
+/// `{"gold_per_gram_usd": 65.0, "silver_per_gram_usd": 0.8, "rates": {"EUR": 0.85}}`.
+/// The document is fetched at most once per `RATE_CACHE_TTL`, whether or not
+/// the fetch succeeds, and shared by both lookups. Once it's due again the
+/// last document keeps being served while one background fetch replaces it;
+/// only a provider with no document yet makes callers wait, on one fetch.
+#[derive(Clone)]
+pub struct HttpRateProvider {
+    client: reqwest::Client,
+    url: String,
+    state: Arc<std::sync::Mutex<RateState>>,
+    /// Held by the caller fetching the first document
+    first_fetch: Arc<Mutex<()>>,
+}
+
+#[derive(Default)]
+struct RateState {
+    document: Option<Arc<RateDocument>>,
+    /// When the last fetch finished, successful or not
+    checked_at: Option<Instant>,
+    /// Why the last fetch failed, if it did
+    last_error: Option<String>,
+    refreshing: bool,
+}
+
+impl RateState {
+    /// The cached answer, unless the last fetch is older than the TTL
+    fn current(&self) -> Option<ApiResult<Arc<RateDocument>>> {
+        if self.checked_at?.elapsed() >= RATE_CACHE_TTL {
+            return None;
+        }
+        Some(match (&self.document, &self.last_error) {
+            (Some(document), _) => Ok(Arc::clone(document)),
+            (None, error) => Err(ApiError::network(error.as_deref().unwrap_or_default())),
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct RateDocument {
+    #[serde(flatten)]
+    prices: MetalPrices,
+    #[serde(default)]
+    rates: HashMap<String, Decimal>,
+}
+
+impl HttpRateProvider {
+    pub fn new(url: impl Into<String>) -> ApiResult<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(HTTP_TIMEOUT)
+            .build()
+            .map_err(|e| ApiError::internal(format!("Failed to build HTTP client: {}", e)))?;
+
+        Ok(Self {
+            client,
+            url: url.into(),
+            state: Arc::default(),
+            first_fetch: Arc::default(),
+        })
+    }
+
+    async fn document(&self) -> ApiResult<Arc<RateDocument>> {
+        {
+            let mut state = self.state.lock().unwrap();
+            if let Some(current) = state.current() {
+                return current;
+            }
+            if let Some(ref stale) = state.document {
+                let stale = Arc::clone(stale);
+                if !state.refreshing {
+                    state.refreshing = true;
+                    let provider = self.clone();
+                    tokio::spawn(async move {
+                        let _ = provider.refresh().await;
+                    });
+                }
+                return Ok(stale);
+            }
+        }
+
+        let _first_fetch = self.first_fetch.lock().await;
+        if let Some(current) = self.state.lock().unwrap().current() {
+            return current;
+        }
+        self.refresh().await
+    }
+
+    /// Fetches the document and records the outcome, keeping the last
+    /// document if the fetch fails
+    async fn refresh(&self) -> ApiResult<Arc<RateDocument>> {
+        let fetched = self.fetch().await.map(Arc::new);
+
+        let mut state = self.state.lock().unwrap();
+        state.refreshing = false;
+        state.checked_at = Some(Instant::now());
+        match fetched {
+            Ok(document) => {
+                state.document = Some(Arc::clone(&document));
+                state.last_error = None;
+                Ok(document)
+            }
+            Err(e) => {
+                let result = match state.document {
+                    Some(ref stale) => {
+                        warn!("{}; keeping the last rates", e);
+                        Ok(Arc::clone(stale))
+                    }
+                    None => Err(ApiError::network(&e)),
+                };
+                state.last_error = Some(e);
+                result
+            }
+        }
+    }
+
+    async fn fetch(&self) -> Result<RateDocument, String> {
+        let failed = |e: reqwest::Error| format!("Failed to fetch rates from {}: {}", self.url, e);
+        self.client
+            .get(&self.url)
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .map_err(failed)?
+            .json()
+            .await
+            .map_err(failed)
+    }
+}
+
+#[async_trait]
+impl RateProvider for HttpRateProvider {
+    async fn gold_silver(&self) -> ApiResult<MetalPrices> {
+        Ok(self.document().await?.prices)
+    }
+
+    async fn fx_rates(&self) -> ApiResult<HashMap<String, Decimal>> {
+        Ok(self.document().await?.rates.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{calculations::ZakatCalculator, models::ZakatCalculationRequest};
+
+    struct MockRateProvider {
+        prices: MetalPrices,
+        rates: HashMap<String, Decimal>,
+    }
+
+    #[async_trait]
+    impl RateProvider for MockRateProvider {
+        async fn gold_silver(&self) -> ApiResult<MetalPrices> {
+            Ok(self.prices)
+        }
+
+        async fn fx_rates(&self) -> ApiResult<HashMap<String, Decimal>> {
+            Ok(self.rates.clone())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_provider_prices_drive_the_calculation() {
+        let provider = MockRateProvider {
+            prices: MetalPrices {
+                gold_per_gram_usd: dec!(100.0),
+                silver_per_gram_usd: dec!(1.00),
+            },
+            rates: HashMap::from([("CHF".to_string(), dec!(0.9))]),
+        };
+        let calculator = ZakatCalculator::new().with_rates_from(&provider).await;
+        assert!(calculator.supports_currency(&CurrencyCode::parse("CHF").unwrap()));
+
+        // Silver nisab is now 595g * $1.00 = $595 rather than the built-in $476
+        let request: ZakatCalculationRequest = serde_json::from_value(serde_json::json!({
+            "calculation_type": "wealth",
+            "amount": "500",
+            "currency": "USD"
+        }))
+        .unwrap();
+        let response = calculator.calculate_zakat(request).await.unwrap();
+
+        assert!(!response.is_zakat_applicable);
+        assert_eq!(response.amount_below_nisab, Some(dec!(95.0)));
+    }
+
+    #[tokio::test]
+    async fn test_static_provider_matches_builtin_calculator() {
+        let request = || -> ZakatCalculationRequest {
+            serde_json::from_value(serde_json::json!({
+                "calculation_type": "wealth",
+                "amount": "1000",
+                "currency": "EUR"
+            }))
+            .unwrap()
+        };
+        let builtin = ZakatCalculator::new()
+            .calculate_zakat(request())
+            .await
+            .unwrap();
+        let provided = ZakatCalculator::new()
+            .with_rates_from(&StaticRateProvider)
+            .await
+            .calculate_zakat(request())
+            .await
+            .unwrap();
+
+        assert_eq!(provided.zakat_due, builtin.zakat_due);
+        assert_eq!(provided.nisab_threshold, builtin.nisab_threshold);
+    }
+
+    /// Serves the rate document over HTTP with `status`, counting requests
+    async fn serve_rates(status: &'static str) -> (String, Arc<std::sync::atomic::AtomicUsize>) {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/rates.json", listener.local_addr().unwrap());
+        let hits = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&hits);
+        tokio::spawn(async move {
+            let body =
+                r#"{"gold_per_gram_usd": 70.0, "silver_per_gram_usd": 0.9, "rates": {"CHF": 0.9}}"#;
+            while let Ok((mut stream, _)) = listener.accept().await {
+                counter.fetch_add(1, Ordering::SeqCst);
+                let mut request = [0u8; 1024];
+                let _ = stream.read(&mut request).await;
+                let response = format!(
+                    "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+                let _ = stream.write_all(response.as_bytes()).await;
+            }
+        });
+        (url, hits)
+    }
+
+    #[tokio::test]
+    async fn test_http_provider_fetches_once_per_ttl() {
+        let (url, hits) = serve_rates("200 OK").await;
+        let provider = HttpRateProvider::new(url).unwrap();
+
+        for _ in 0..3 {
+            let calculator = ZakatCalculator::new().with_rates_from(&provider).await;
+            assert!(calculator.supports_currency(&CurrencyCode::parse("CHF").unwrap()));
+        }
+        assert_eq!(
+            provider.gold_silver().await.unwrap().gold_per_gram_usd,
+            dec!(70.0)
+        );
+        assert_eq!(hits.load(std::sync::atomic::Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_http_provider_backs_off_after_failed_fetch() {
+        let (url, hits) = serve_rates("503 Service Unavailable").await;
+        let provider = HttpRateProvider::new(url).unwrap();
+
+        let first = provider.gold_silver().await;
+        let second = provider.fx_rates().await;
+
+        assert!(matches!(first, Err(ApiError::Network(_))));
+        assert!(matches!(second, Err(ApiError::Network(ref msg)) if msg.contains("503")));
+        assert_eq!(hits.load(std::sync::atomic::Ordering::SeqCst), 1);
+    }
+}
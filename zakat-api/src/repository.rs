@@ -5,6 +5,11 @@ use tracing::{debug, info};
 
 use crate::models::{NisabRate, NisabRateRow, SavedCalculation, SavedCalculationRow};
 
+/// `nisab_rates` columns, with the NUMERIC ones cast to text for `NisabRateRow`
+const NISAB_RATE_COLUMNS: &str = "id, metal_type, price_per_gram_usd::text AS price_per_gram_usd, \
+     nisab_grams::text AS nisab_grams, nisab_value_usd::text AS nisab_value_usd, last_updated, source";
+
+#[derive(Clone)]
 pub struct ZakatRepository {
     db: Database,
 }
@@ -110,10 +115,12 @@ impl ZakatRepository {
     pub async fn get_nisab_rates(&self) -> ApiResult<Vec<NisabRate>> {
         debug!("Fetching current nisab rates");
 
-        let rates =
-            sqlx::query_as::<_, NisabRateRow>("SELECT * FROM nisab_rates ORDER BY metal_type")
-                .fetch_all(&self.db.pool)
-                .await?;
+        let rates = sqlx::query_as::<_, NisabRateRow>(&format!(
+            "SELECT {} FROM nisab_rates ORDER BY metal_type",
+            NISAB_RATE_COLUMNS
+        ))
+        .fetch_all(&self.db.pool)
+        .await?;
 
         let result: Vec<NisabRate> = rates.into_iter().map(NisabRate::from).collect();
         debug!("Found {} nisab rates", result.len());
@@ -127,14 +134,15 @@ impl ZakatRepository {
     ) -> ApiResult<NisabRate> {
         debug!("Updating nisab rate for {}: {}", metal_type, price_per_gram);
 
-        let result = sqlx::query_as::<_, NisabRateRow>(
+        let result = sqlx::query_as::<_, NisabRateRow>(&format!(
             r#"
             UPDATE nisab_rates 
-            SET price_per_gram_usd = $1, last_updated = NOW() 
+            SET price_per_gram_usd = $1::numeric, last_updated = NOW() 
             WHERE metal_type = $2 
-            RETURNING *
+            RETURNING {}
             "#,
-        )
+            NISAB_RATE_COLUMNS
+        ))
         .bind(price_per_gram.to_string()) // Convert to string
         .bind(metal_type)
         .fetch_one(&self.db.pool)
@@ -150,9 +158,11 @@ impl ZakatRepository {
     ) -> ApiResult<std::collections::HashMap<String, Decimal>> {
         debug!("Fetching currency exchange rates");
 
-        let rates = sqlx::query("SELECT currency_code, rate_to_usd FROM currency_rates")
-            .fetch_all(&self.db.pool)
-            .await?;
+        let rates = sqlx::query(
+            "SELECT currency_code, rate_to_usd::text AS rate_to_usd FROM currency_rates",
+        )
+        .fetch_all(&self.db.pool)
+        .await?;
 
         let mut currency_map = std::collections::HashMap::new();
         for rate in rates {
@@ -172,9 +182,9 @@ impl ZakatRepository {
         sqlx::query(
             r#"
             INSERT INTO currency_rates (currency_code, rate_to_usd, source)
-            VALUES ($1, $2, 'API Update')
+            VALUES ($1, $2::numeric, 'API Update')
             ON CONFLICT (currency_code)
-            DO UPDATE SET rate_to_usd = $2, last_updated = NOW()
+            DO UPDATE SET rate_to_usd = $2::numeric, last_updated = NOW()
             "#,
         )
         .bind(currency)
@@ -247,7 +257,9 @@ mod tests {
             max_connections: 5,
             min_connections: 1,
             connect_timeout: 30,
+            acquire_timeout_ms: 5000,
             idle_timeout: 600,
+            slow_query_threshold_ms: 500,
         })
         .await
         .unwrap();
@@ -282,4 +294,56 @@ mod tests {
             .await
             .unwrap();
     }
+
+    #[tokio::test]
+    async fn test_db_added_currency_is_usable_in_calculation() {
+        use crate::calculations::ZakatCalculator;
+        use crate::models::ZakatCalculationRequest;
+        use rust_decimal_macros::dec;
+
+        if std::env::var("DATABASE_URL").is_err() {
+            return;
+        }
+
+        let db = Database::new(&shared::config::DatabaseConfig {
+            url: std::env::var("DATABASE_URL").unwrap(),
+            max_connections: 5,
+            min_connections: 1,
+            connect_timeout: 30,
+            acquire_timeout_ms: 5000,
+            idle_timeout: 600,
+            slow_query_threshold_ms: 500,
+        })
+        .await
+        .unwrap();
+        let repo = ZakatRepository::new(db);
+
+        repo.update_currency_rate("QAR", dec!(3.64)).await.unwrap();
+        let rates = repo.get_currency_rates().await;
+        sqlx::query("DELETE FROM currency_rates WHERE currency_code = 'QAR'")
+            .execute(&repo.db.pool)
+            .await
+            .unwrap();
+
+        let request: ZakatCalculationRequest = serde_json::from_value(serde_json::json!({
+            "calculation_type": "wealth",
+            "amount": "36400",
+            "currency": "qar"
+        }))
+        .unwrap();
+
+        assert!(ZakatCalculator::new()
+            .calculate_zakat(request.clone())
+            .await
+            .is_err());
+
+        let response = ZakatCalculator::new()
+            .with_currency_rates(&rates.unwrap())
+            .calculate_zakat(request)
+            .await
+            .unwrap();
+        assert_eq!(response.currency.code.as_str(), "QAR");
+        assert_eq!(response.currency.symbol, "ر.ق");
+        assert_eq!(response.zakat_due, dec!(910)); // 2.5% of 36,400
+    }
 }
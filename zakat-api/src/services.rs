@@ -1,18 +1,24 @@
 use chrono::Utc;
 use rust_decimal::Decimal;
-use shared::{cache::Cache, error::ApiResult};
+use shared::{
+    cache::{Cache, StalePolicy},
+    error::ApiResult,
+};
 use std::time::Duration;
 use tracing::{debug, warn};
 use uuid::Uuid;
 
 use crate::{
     models::{
-        CalculationHistoryResponse, CalculationSummary, Currency, NisabRatesResponse,
+        CalculationHistoryResponse, CalculationSummary, CurrencyCode, NisabRatesResponse,
         SavedCalculation, ZakatCalculationRequest, ZakatCalculationResponse,
     },
     repository::ZakatRepository,
 };
 
+const NISAB_RATES_KEY: &str = "nisab_rates";
+
+#[derive(Clone)]
 pub struct ZakatService {
     repository: ZakatRepository,
     cache: Cache,
@@ -29,16 +35,7 @@ impl ZakatService {
         request: ZakatCalculationRequest,
         result: ZakatCalculationResponse,
     ) -> ApiResult<SavedCalculation> {
-        let calculation = SavedCalculation {
-            id: Uuid::new_v4(),
-            user_id: user_id.clone(),
-            calculation_type: request.calculation_type.to_string().to_string(),
-            input_data: serde_json::to_value(&request)?,
-            result_data: serde_json::to_value(&result)?,
-            zakat_amount: result.zakat_due,
-            currency: request.currency.to_string().to_string(),
-            created_at: Utc::now(),
-        };
+        let calculation = SavedCalculation::new(user_id.clone(), &request, &result)?;
 
         let saved = self.repository.save_calculation(calculation).await?;
 
@@ -109,24 +106,29 @@ impl ZakatService {
         Ok(response)
     }
 
-    pub async fn get_nisab_rates(&self) -> ApiResult<NisabRatesResponse> {
-        let cache_key = "nisab_rates";
-
-        // Try cache first
-        if let Ok(Some(cached_rates)) = self.cache.get::<NisabRatesResponse>(&cache_key).await {
-            debug!("Returning cached nisab rates");
-            return Ok(cached_rates);
-        }
+    /// Nisab rates, served stale past the policy's soft TTL while a
+    /// background task refreshes them
+    pub async fn get_nisab_rates(&self, policy: StalePolicy) -> ApiResult<NisabRatesResponse> {
+        let service = self.clone();
+        self.cache
+            .get_or_compute_stale(NISAB_RATES_KEY, policy, move || async move {
+                service.load_nisab_rates().await
+            })
+            .await
+    }
 
-        // Fetch from database
+    async fn load_nisab_rates(&self) -> ApiResult<NisabRatesResponse> {
         let rates = self.repository.get_nisab_rates().await?;
         let currency_rates_map = self.repository.get_currency_rates().await?;
 
-        // Convert to Currency enum map
+        // Key by currency code, skipping malformed rows
         let mut currency_rates = std::collections::HashMap::new();
         for (code, rate) in currency_rates_map {
-            if let Some(currency) = parse_currency(&code) {
-                currency_rates.insert(currency, rate);
+            match CurrencyCode::parse(&code) {
+                Ok(currency) => {
+                    currency_rates.insert(currency, rate);
+                }
+                Err(e) => warn!("Skipping currency rate: {}", e),
             }
         }
 
@@ -148,15 +150,6 @@ impl ZakatService {
             last_updated: Utc::now(),
         };
 
-        // Cache for 1 hour
-        if let Err(e) = self
-            .cache
-            .set(&cache_key, &response, Some(Duration::from_secs(3600)))
-            .await
-        {
-            warn!("Failed to cache nisab rates: {}", e);
-        }
-
         Ok(response)
     }
 
@@ -174,7 +167,7 @@ impl ZakatService {
         }
 
         // Invalidate cache
-        if let Err(e) = self.cache.delete("nisab_rates").await {
+        if let Err(e) = self.cache.delete(NISAB_RATES_KEY).await {
             warn!("Failed to invalidate nisab rates cache: {}", e);
         }
 
@@ -192,7 +185,7 @@ impl ZakatService {
         }
 
         // Invalidate cache
-        if let Err(e) = self.cache.delete("nisab_rates").await {
+        if let Err(e) = self.cache.delete(NISAB_RATES_KEY).await {
             warn!("Failed to invalidate currency rates cache: {}", e);
         }
 
@@ -221,7 +214,7 @@ impl ZakatService {
             } else {
                 Decimal::ZERO
             },
-            "last_updated": Utc::now()
+            "last_updated": shared::time::now_rfc3339()
         });
 
         // Cache for 15 minutes
@@ -295,7 +288,7 @@ impl ZakatService {
                 Decimal::ZERO
             },
             "last_calculation_date": calculations.first().map(|c| c.created_at),
-            "generated_at": Utc::now()
+            "generated_at": shared::time::now_rfc3339()
         });
 
         // Cache for 1 hour
@@ -320,24 +313,7 @@ fn parse_zakat_type(type_str: &str) -> Option<crate::models::ZakatType> {
         "business" => Some(crate::models::ZakatType::Business),
         "livestock" => Some(crate::models::ZakatType::Livestock),
         "crops" => Some(crate::models::ZakatType::Crops),
-        _ => None,
-    }
-}
-
-fn parse_currency(code: &str) -> Option<Currency> {
-    match code.to_uppercase().as_str() {
-        "USD" => Some(Currency::USD),
-        "EUR" => Some(Currency::EUR),
-        "GBP" => Some(Currency::GBP),
-        "SAR" => Some(Currency::SAR),
-        "AED" => Some(Currency::AED),
-        "PKR" => Some(Currency::PKR),
-        "INR" => Some(Currency::INR),
-        "BDT" => Some(Currency::BDT),
-        "MYR" => Some(Currency::MYR),
-        "IDR" => Some(Currency::IDR),
-        "TRY" => Some(Currency::TRY),
-        "EGP" => Some(Currency::EGP),
+        "fitr" => Some(crate::models::ZakatType::Fitr),
         _ => None,
     }
 }
@@ -359,6 +335,7 @@ fn calculate_monthly_totals(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::Currency;
 
     #[test]
     fn test_parse_zakat_type() {
@@ -379,10 +356,10 @@ mod tests {
 
     #[test]
     fn test_parse_currency() {
-        assert!(matches!(parse_currency("USD"), Some(Currency::USD)));
-        assert!(matches!(parse_currency("eur"), Some(Currency::EUR)));
-        assert!(matches!(parse_currency("PKR"), Some(Currency::PKR)));
-        assert!(parse_currency("INVALID").is_none());
+        assert_eq!(CurrencyCode::parse("USD"), Ok(Currency::USD.into()));
+        assert_eq!(CurrencyCode::parse("eur"), Ok(Currency::EUR.into()));
+        assert_eq!(CurrencyCode::parse("PKR"), Ok(Currency::PKR.into()));
+        assert!(CurrencyCode::parse("INVALID").is_err());
     }
 
     #[test]